@@ -3,6 +3,7 @@ use clap::{Arg, Command};
 pub struct Config {
     pub host: String,
     pub port: i32,
+    pub database: Option<String>,
 }
 
 impl Config {
@@ -27,6 +28,13 @@ impl Config {
                     .value_name("PORT")
                     .default_value("3307"),
             )
+            .arg(
+                Arg::new("dbname")
+                    .help("The database to use, server chooses its default database when it's missing")
+                    .short('d')
+                    .long("dbname")
+                    .value_name("DBNAME"),
+            )
             .get_matches();
 
         let host = matches
@@ -39,6 +47,12 @@ impl Config {
             .parse::<i32>()
             .unwrap();
 
-        Ok(Config { host, port })
+        let database = matches.get_one::<String>("dbname").cloned();
+
+        Ok(Config {
+            host,
+            port,
+            database,
+        })
     }
 }
@@ -18,6 +18,12 @@ fn main() {
         eprintln!("Failed to connect to the server: {e}");
         std::process::exit(1);
     });
+    if let Some(database) = &config.database {
+        if let Err(e) = server.send_command(&format!("USE {};", database)) {
+            eprintln!("Failed to choose database: {e}");
+            std::process::exit(1);
+        }
+    }
     loop {
         print!("sSQL> ");
         if let Err(e) = io::stdout().flush() {
@@ -0,0 +1,156 @@
+use std::{fmt::Debug, ops::Bound};
+
+use super::BTree;
+
+/*
+    sorted set of keys used by table indexes, keys are kept in the B+tree of this module without values,
+    index entry is the whole key, so entries of equal values differ by their tuple pointer
+*/
+#[derive(Debug)]
+pub struct BPlusTree<K: Ord + Clone + Debug> {
+    tree: BTree<K, ()>,
+    len: usize,
+}
+
+impl<K: Ord + Clone + Debug> BPlusTree<K> {
+    // order is the maximal number of children of internal node and keys in leaf
+    pub fn new(order: usize) -> Self {
+        BPlusTree {
+            tree: BTree::new(order),
+            len: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.get(key).is_some()
+    }
+
+    // returns false when the key is already present
+    pub fn insert(&mut self, key: K) -> bool {
+        let inserted = self.tree.insert(key, ()).is_ok();
+        self.len += inserted as usize;
+        inserted
+    }
+
+    // returns false when the key isn't present
+    pub fn remove(&mut self, key: &K) -> bool {
+        let removed = self.tree.delete(key).is_ok();
+        self.len -= removed as usize;
+        removed
+    }
+
+    // keys inside of bounds in ascending order
+    #[allow(dead_code)]
+    pub fn range(&self, start: Bound<&K>, end: Bound<&K>) -> Vec<K> {
+        self.scan_from(start, |key| match end {
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+            Bound::Unbounded => true,
+        })
+    }
+
+    // keys after start in ascending order until keep returns false
+    pub fn scan_from(&self, start: Bound<&K>, keep: impl FnMut(&K) -> bool) -> Vec<K> {
+        self.tree
+            .scan_from(start, keep)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_many_keys_are_inserted_tree_should_split_and_keep_them_sorted() {
+        let mut tree = BPlusTree::new(3);
+        for key in [50, 10, 40, 20, 30, 70, 60, 90, 80, 0, 15, 35, 55] {
+            assert!(tree.insert(key));
+        }
+
+        assert!(!tree.insert(40));
+        assert_eq!(tree.len(), 13);
+        assert!(tree.contains(&55));
+        assert!(!tree.contains(&56));
+        assert_eq!(
+            tree.range(Bound::Unbounded, Bound::Unbounded),
+            vec![0, 10, 15, 20, 30, 35, 40, 50, 55, 60, 70, 80, 90]
+        );
+    }
+
+    #[test]
+    fn when_range_is_scanned_it_should_respect_bounds_across_leaves() {
+        let mut tree = BPlusTree::new(4);
+        for key in 0..100 {
+            tree.insert(key * 2);
+        }
+
+        assert_eq!(
+            tree.range(Bound::Excluded(&10), Bound::Included(&20)),
+            vec![12, 14, 16, 18, 20]
+        );
+        assert_eq!(
+            tree.range(Bound::Included(&191), Bound::Unbounded),
+            vec![192, 194, 196, 198]
+        );
+        assert!(tree
+            .range(Bound::Included(&7), Bound::Excluded(&8))
+            .is_empty());
+    }
+
+    #[test]
+    fn when_keys_are_removed_they_should_not_be_found_in_lookups_and_ranges() {
+        let mut tree = BPlusTree::new(3);
+        for key in 0..20 {
+            tree.insert(key);
+        }
+        for key in (0..20).filter(|key| key % 3 != 0) {
+            assert!(tree.remove(&key));
+        }
+
+        assert!(!tree.remove(&1));
+        assert!(!tree.contains(&4));
+        assert_eq!(tree.len(), 7);
+        assert_eq!(
+            tree.range(Bound::Included(&2), Bound::Excluded(&16)),
+            vec![3, 6, 9, 12, 15]
+        );
+    }
+
+    #[test]
+    fn when_most_keys_are_removed_tree_should_rebalance_and_keep_the_rest() {
+        let mut tree = BPlusTree::new(4);
+        for key in (0..301).map(|key| key * 11 % 301) {
+            assert!(tree.insert(key));
+        }
+        for key in (0..301).filter(|key| key % 10 != 0) {
+            assert!(tree.remove(&key));
+        }
+
+        assert_eq!(tree.len(), 31);
+        assert_eq!(
+            tree.range(Bound::Excluded(&250), Bound::Unbounded),
+            vec![260, 270, 280, 290, 300]
+        );
+        for key in (0..301).step_by(10) {
+            assert!(tree.remove(&key));
+        }
+        assert!(tree.is_empty());
+        assert!(tree.range(Bound::Unbounded, Bound::Unbounded).is_empty());
+        assert!(tree.insert(5));
+        assert!(tree.contains(&5));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod index;
 mod tree;
 pub mod page; 
 use core::fmt::Debug;
@@ -9,7 +10,7 @@ struct BTree<K: Ord + Clone + Debug, V: Clone + Debug> {
 }
 
 #[derive(Debug)]
-pub enum BTreeNode<K: Ord + Clone + Debug, V: Clone + Debug> {
+enum BTreeNode<K: Ord + Clone + Debug, V: Clone + Debug> {
     Leaf(LeafNode<K, V>),
     Internal(InternalNode<K, V>),
 }
@@ -20,6 +21,8 @@ struct LeafNode<K: Ord + Clone + Debug, V: Clone + Debug> {
     values: Vec<V>,
 }
 
+// children are boxed so a node is small whether it is a leaf or not
+#[allow(clippy::vec_box)]
 #[derive(Debug)]
 struct InternalNode<K: Ord + Clone + Debug, V: Clone + Debug> {
     keys: Vec<K>,
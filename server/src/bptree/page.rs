@@ -1,27 +1,64 @@
-use crate::{
-    information_schema::{SData, SType},
-    new_page::NewPage,
-    page::{self, Page},
-};
+use crate::{information_schema::SData, page::Page};
 use byteorder::{ByteOrder, LittleEndian};
-use core::{fmt::Debug, num};
+use core::fmt::Debug;
+use std::cmp::Ordering;
 
+// paged nodes aren't used yet, indexes are kept in memory
+#[allow(dead_code)]
 pub struct NewLeafNode {
     pub keys: Vec<SData>,
     pub values: Vec<TuplePointer>,
 }
 
+#[allow(dead_code)]
 pub struct NewInnerNode {
     keys: Vec<SData>,
     pointers: Vec<TuplePointer>,
 }
 
-#[derive(Clone)]
+// in heap files offset is number of the item on the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TuplePointer {
     pub page: u32,
     pub offset: u16,
 }
 
+/*
+    key of multi column index, compared column by column and key which is prefix of another key is smaller,
+    so keys starting with the same values are next to each other and can be found by their leading prefix,
+    NULL is greater than any value, like in ascending order of postgres
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TupleKey(pub Vec<SData>);
+
+impl TupleKey {
+    pub fn starts_with(&self, prefix: &[SData]) -> bool {
+        self.0.starts_with(prefix)
+    }
+
+    pub fn has_null(&self) -> bool {
+        self.0.contains(&SData::NULL)
+    }
+}
+
+impl Ord for TupleKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (left, right) in self.0.iter().zip(other.0.iter()) {
+            let ordering = left.cmp(right);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        self.0.len().cmp(&other.0.len())
+    }
+}
+
+impl PartialOrd for TupleKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 /*
     node file structure:
 
@@ -49,59 +86,7 @@ update:
     next_record_pointer: izracunat
 */
 
-impl NewPage {
-    fn get_leaf_node_pointer(
-        page: &NewPage,
-        key: &SData,
-        key_type: &SType,
-    ) -> Result<TuplePointer, String> {
-        if !page.is_page_leaf_type() {
-            return Err("Page is not leaf".into());
-        }
-        match key {
-            SData::INT(key_value) => {
-                if key_type != &SType::INT {
-                    return Err("Key type doesn't match with type of given key".into());
-                }
-                let mut slice_to_parse = &page.data[..];
-                slice_to_parse = &slice_to_parse[page.get_header_size_in_bytes() as usize..];
-                let number_of_keys = u32::from_le_bytes(
-                    slice_to_parse[0..4]
-                        .try_into()
-                        .map_err(|_| "Invalid slice length".to_string())?,
-                );
-                slice_to_parse = &slice_to_parse[4..];
-                let mut left = 0;
-                let mut right = number_of_keys;
-                while left <= right {                  
-                    let mid = (left + right) / 2;
-                    let key_position = (mid as usize) * std::mem::size_of::<i32>();
-                    let mid_key: i32 = i32::from_le_bytes(slice_to_parse[key_position..key_position + 4].try_into().unwrap());
-                    if mid_key == *key_value {
-                        let pointer_start = (number_of_keys as usize * std::mem::size_of::<i32>()) + mid_key; // Offset for values
-                        let mid_value_position = values_start + (mid as usize * std::mem::size_of::<TuplePointer>());
-                        let found_value: TuplePointer = TuplePointer(u32::from_le_bytes(slice_to_parse[mid_value_position..mid_value_position + 4].try_into().unwrap()));
-                        return Ok(found_value);
-                    } else if mid_key < *search_key {
-                        left = mid + 1;
-                    } else {
-                        right = mid - 1;
-                    }
-                }
-                let last_key_position = (right as usize) * std::mem::size_of::<i32>();
-                let last_key: i32 = i32::from_le_bytes(slice_to_parse[last_key_position..last_key_position + 4].try_into().unwrap());
-                println!("Key not found. Last key before the search key was: {}", last_key);
-                return Err("".into());
-            }
-            SData::STRING(_) => todo!(),
-        }
-    }
-
-    fn is_page_leaf_type(&self) -> bool {
-        return !self.data.is_empty() && self.data[0] == 0x02;
-    }
-}
-
+#[allow(dead_code)]
 impl NewLeafNode {
     pub fn buffer_fits_type(buffer: &mut &[u8]) -> bool {
         !buffer.is_empty() && buffer[0] == 0x01
@@ -115,7 +100,7 @@ impl NewLeafNode {
             return Err("No values in leaf".to_string());
         }
         let serilized = self.serialize()?;
-        let insert_pos = page.get_new_insert_pos(serilized.len().try_into().unwrap());
+        let _insert_pos = page.get_new_insert_pos(serilized.len().try_into().unwrap());
         Ok(page)
     }
 
@@ -127,6 +112,9 @@ impl NewLeafNode {
         let key_type: u8 = match self.keys.first().unwrap() {
             SData::INT(_) => 0x00,
             SData::STRING(_) => 0x01,
+            SData::BOOL(_) | SData::NULL => {
+                return Err("Only integers and strings can be used as node keys".to_string())
+            }
         };
         let number_of_entries = self.keys.len() as u32;
         let serialized_keys_size: u32 = self.keys.iter().map(|key| key.serialized_size()).sum();
@@ -157,13 +145,10 @@ impl NewLeafNode {
             return Err("Given buffer dosent contain leaf node".to_string());
         }
         let key_type: u8 = buffer[0];
-        let number_of_enteries = {
-            let number_of_keys_serilized: &[u8] = &buffer[1..3];
-            LittleEndian::read_u16(&buffer)
-        };
+        let number_of_enteries = LittleEndian::read_u16(buffer);
         *buffer = &buffer[1..];
         let keys = {
-            let mut keys_deserilized = Vec::with_capacity(number_of_enteries.try_into().unwrap());
+            let mut keys_deserilized = Vec::with_capacity(number_of_enteries.into());
             match key_type {
                 0x00 => {
                     let value = i32::from_le_bytes(
@@ -215,6 +200,7 @@ impl NewLeafNode {
     }
 }
 
+#[allow(dead_code)]
 impl NewInnerNode {
     pub fn buffer_fits_type(buffer: &mut &[u8]) -> bool {
         !buffer.is_empty() && buffer[0] == 0x00
@@ -228,6 +214,9 @@ impl NewInnerNode {
         let key_type: u8 = match self.keys.first().unwrap() {
             SData::INT(_) => 0x00,
             SData::STRING(_) => 0x01,
+            SData::BOOL(_) | SData::NULL => {
+                return Err("Only integers and strings can be used as node keys".to_string())
+            }
         };
         let number_of_keys = self.keys.len() as u32;
         let serialized_keys_size: u32 = self.keys.iter().map(|key| key.serialized_size()).sum();
@@ -258,13 +247,10 @@ impl NewInnerNode {
             return Err("Given buffer dosent contain leaf node".to_string());
         }
         let key_type: u8 = buffer[0];
-        let number_of_keys = {
-            let number_of_keys_serilized: &[u8] = &buffer[1..3];
-            LittleEndian::read_u16(&buffer)
-        };
+        let number_of_keys = LittleEndian::read_u16(buffer);
         *buffer = &buffer[1..];
         let keys = {
-            let mut keys_deserilized = Vec::with_capacity(number_of_keys.try_into().unwrap());
+            let mut keys_deserilized = Vec::with_capacity(number_of_keys.into());
             match key_type {
                 0x00 => {
                     let value = i32::from_le_bytes(
@@ -313,6 +299,7 @@ impl NewInnerNode {
     }
 }
 
+#[allow(dead_code)]
 impl NewInnerNode {
     pub fn get_node_pointer_for_key(&self, key: &SData) -> Result<TuplePointer, String> {
         match self.keys.binary_search(key) {
@@ -329,3 +316,66 @@ impl NewInnerNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_tuple_keys_are_sorted_they_should_compare_column_by_column_with_prefix_first() {
+        let key = |values: &[SData]| TupleKey(values.to_vec());
+        let mut keys = vec![
+            key(&[SData::INT(2), SData::STRING("a".to_string())]),
+            key(&[SData::INT(1), SData::NULL]),
+            key(&[SData::INT(1), SData::STRING("b".to_string())]),
+            key(&[SData::INT(1)]),
+            key(&[SData::INT(1), SData::STRING("a".to_string())]),
+        ];
+
+        keys.sort();
+
+        assert_eq!(
+            keys,
+            vec![
+                key(&[SData::INT(1)]),
+                key(&[SData::INT(1), SData::STRING("a".to_string())]),
+                key(&[SData::INT(1), SData::STRING("b".to_string())]),
+                key(&[SData::INT(1), SData::NULL]),
+                key(&[SData::INT(2), SData::STRING("a".to_string())]),
+            ]
+        );
+        assert!(keys[3].starts_with(&[SData::INT(1)]));
+        assert!(keys[3].has_null());
+        assert!(!keys[4].starts_with(&[SData::INT(1)]));
+    }
+
+    #[test]
+    fn when_values_of_different_types_are_sorted_null_should_be_greatest() {
+        let mut values = vec![
+            SData::NULL,
+            SData::STRING("a".to_string()),
+            SData::INT(2),
+            SData::BOOL(true),
+            SData::INT(1),
+        ];
+
+        values.sort();
+
+        assert_eq!(
+            values,
+            vec![
+                SData::BOOL(true),
+                SData::INT(1),
+                SData::INT(2),
+                SData::STRING("a".to_string()),
+                SData::NULL,
+            ]
+        );
+        assert_eq!(
+            SData::INT(1).partial_cmp(&SData::NULL),
+            Some(Ordering::Less)
+        );
+        assert_eq!(SData::INT(1).compare(&SData::STRING("a".to_string())), None);
+        assert_eq!(SData::NULL.compare(&SData::NULL), None);
+    }
+}
@@ -1,342 +1,286 @@
 use core::fmt::Debug;
-use crate::bptree::InternalNode;
+use std::ops::Bound;
 
-use super::{BTree, BTreeNode, LeafNode};
+use super::{BTree, BTreeNode, InternalNode, LeafNode};
 
+// separator and the new right sibling of a node which was split
+type Split<K, V> = Option<(K, BTreeNode<K, V>)>;
+
+/*
+    B+tree with values in leaves, keys[i] of internal node is the smallest key of children[i + 1],
+    node splits when it gets order keys, its upper half moves into a new right sibling,
+    node which has too few keys after delete borrows from its sibling or is merged with it,
+    root which lost its last key is replaced by its only child
+*/
 impl<K: Ord + Clone + Debug, V: Clone + Debug> BTree<K, V> {
-    fn new(order: usize) -> Self {
+    pub fn new(order: usize) -> Self {
+        assert!(order >= 3, "B+tree order has to be at least 3");
         BTree {
             root: BTreeNode::Leaf(LeafNode::new(order)),
             order,
         }
     }
 
-    fn insert(&mut self, key: K, value: V) -> Result<(), String> {
-        let mut current = &mut self.root as *mut BTreeNode<K, V>;
-        let mut stack = vec![];
-        println!("NEW INSERT {:?}, {:?}", key, value);
-        unsafe {
-            loop {
-                match &mut *current {
-                    BTreeNode::Internal(ref mut internal) => {
-                        let child_pos = match internal.keys.binary_search(&key) {
-                            Ok(_) => return Err(format!("Value with given key already exists")),
-                            Err(pos) => pos,
-                        };
-                        stack.push((current, child_pos));
-                        println!(
-                            "Pushed node: {:?}, child index is {:?}",
-                            internal, child_pos
-                        );
-                        current = &mut *(internal.children[child_pos]) as *mut BTreeNode<K, V>;
-                    }
-                    BTreeNode::Leaf(leaf) => {
-                        let pos_to_insert_value = match leaf.keys.binary_search(&key) {
-                            Ok(pos) => pos + 1,
-                            Err(pos) => pos,
-                        };
-                        println!(
-                            "Pushed node: {:?}, value index is {:?}",
-                            leaf, pos_to_insert_value
-                        );
-                        stack.push((current, pos_to_insert_value));
-                        break;
-                    }
-                }
-            }
-        }
-
-        let mut pushed_up: Option<(K, BTreeNode<K, V>)> = None;
-        unsafe {
-            while let Some((entry, child_index)) = stack.pop() {
-                match &mut *entry {
-                    BTreeNode::Leaf(leaf) => {
-                        println!("Stack is {:?}", stack);
-                        if leaf.keys.len() + 1 >= self.order {
-                            println!("SPLITTING LEAF {:?}", leaf);
-                            let split_off = if leaf.keys.len() / 2 < child_index {
-                                (leaf.keys.len() + 1) / 2
-                            } else {
-                                leaf.keys.len() / 2
-                            };
-                            let mut new_leaf_keys = leaf.keys.split_off(split_off);
-                            let mut new_leaf_values = leaf.values.split_off(split_off);
-                            if child_index > leaf.keys.len() / 2 {
-                                println!("Inserting into right node");
-                                println!("Position to place gotten from stack: {:?}", child_index);
-                                println!(
-                                    "Position to place new key: {:?}",
-                                    child_index - leaf.keys.len()
-                                );
-                                new_leaf_keys.insert(child_index - leaf.keys.len(), key.clone());
-                                new_leaf_values
-                                    .insert(child_index - leaf.values.len(), value.clone());
-                            } else {
-                                println!("Inserting into left node");
-                                println!("Position to place new key {:?}", child_index);
-                                leaf.keys.insert(child_index, key.clone());
-                                leaf.values.insert(child_index, value.clone());
-                            }
-                            println!("Left leaf is {:?}", leaf);
-                            pushed_up = Some((
-                                new_leaf_keys[0].clone(),
-                                BTreeNode::Leaf(LeafNode {
-                                    keys: new_leaf_keys,
-                                    values: new_leaf_values,
-                                }),
-                            ));
-                            println!("Right leaf is {:?}", pushed_up);
-                        } else {
-                            leaf.keys.insert(child_index, key.clone());
-                            leaf.values.insert(child_index, value.clone());
-                        }
-                    }
-                    BTreeNode::Internal(internal) => {
-                        println!("Stack is {:?}", stack);
-                        if let Some((new_key, new_child)) = pushed_up.take() {
-                            if internal.keys.len() + 1 >= self.order {
-                                println!(
-                                    "Tree is {:?}, pushed key is {:?}, new child is {:?}",
-                                    self, new_key, new_child
-                                );
-                                println!("SPLITTIG INTERNAL NODE: {:?}", internal);
-                                let split_off = if internal.keys.len() % 2 == 1 {
-                                    (internal.keys.len() + 1) / 2
-                                } else {
-                                    internal.keys.len() / 2
-                                };
-                                println!("After splitting internal node:");
-                                let mut new_internal_keys = internal.keys.split_off(split_off);
-                                let mut new_internal_children =
-                                    internal.children.split_off(split_off + 1);
-                                if child_index >= split_off {
-                                    println!("Inserting into right node");
-                                    println!(
-                                        "Inserting into place {:?}",
-                                        child_index - internal.keys.len()
-                                    );
-                                    println!(
-                                        "Children in right node before inserting are {:?}",
-                                        new_internal_children,
-                                    );
-                                    println!("Child to be inserted is {:?}", new_child);
-                                    new_internal_keys
-                                        .insert(child_index - internal.keys.len(), new_key.clone());
-                                    new_internal_children.insert(
-                                        child_index - internal.keys.len(),
-                                        Box::new(new_child),
-                                    );
-                                    // println!("After splitting internal node:");
-                                    //          println!("Left node is {:?}", internal);
-                                    println!(
-                                        "Right node keys are {:?}, Roght children are {:?}",
-                                        new_internal_keys, new_internal_children
-                                    );
-                                } else {
-                                    println!("Inserting into left node");
-                                    internal
-                                        .children
-                                        .insert(child_index + 1, Box::new(new_child));
-                                }
-                                let middle_key = new_internal_keys.remove(0);
-                                pushed_up = Some((
-                                    middle_key.clone(),
-                                    BTreeNode::Internal(InternalNode {
-                                        keys: new_internal_keys,
-                                        children: new_internal_children,
-                                    }),
-                                ));
-                                println!("Right iner node is {:?}", pushed_up);
-                                println!("Tree is now {:?}", self);
-                            } else {
-                                internal.keys.insert(child_index, new_key.clone());
-                                internal
-                                    .children
-                                    .insert(child_index + 1, Box::new(new_child));
-                                pushed_up = None;
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-        println!("Last push is {:?}", pushed_up);
-        if let Some((new_key, new_child)) = pushed_up {
-            let old_root = std::mem::replace(
-                &mut self.root,
-                BTreeNode::Leaf(LeafNode {
-                    keys: vec![],
-                    values: vec![],
-                }),
-            );
+    pub fn insert(&mut self, key: K, value: V) -> Result<(), String> {
+        if let Some((separator, right)) = Self::insert_into(&mut self.root, key, value, self.order)?
+        {
+            let left =
+                std::mem::replace(&mut self.root, BTreeNode::Leaf(LeafNode::new(self.order)));
             self.root = BTreeNode::Internal(InternalNode {
-                keys: vec![new_key],
-                children: vec![Box::new(old_root), Box::new(new_child)],
+                keys: vec![separator],
+                children: vec![Box::new(left), Box::new(right)],
             });
         }
-        println!("Tree is now: {:?}", self);
         Ok(())
     }
 
-    pub fn delete(&mut self, key: K) -> Result<(), String> {
-        let mut current = &mut self.root as *mut BTreeNode<K, V>;
-        let mut stack: Vec<(*mut BTreeNode<K, V>, usize)> = vec![];
-        unsafe {
-            loop {
-                match &mut *current {
-                    BTreeNode::Internal(ref mut internal) => {
-                        let child_pos = internal
-                            .keys
-                            .binary_search(&key)
-                            .unwrap_or_else(|position| position - 1);
-                        stack.push((current, child_pos));
-                        println!(
-                            "Pushed node: {:?}, child index is {:?}",
-                            internal, child_pos
-                        );
-                        current = &mut *(internal.children[child_pos]) as *mut BTreeNode<K, V>;
-                    }
-                    BTreeNode::Leaf(leaf) => {
-                        println!("Searching keys {:?} for key {:?}", leaf.keys, key);
-                        let value_pos = leaf.keys.binary_search(&key).map_err(|_| "Unknown key")?;
-                        println!(
-                            "Want to delete value for leaf {:?}, position {:?}",
-                            leaf, value_pos
-                        );
-                        stack.push((current, value_pos));
-                        break;
-                    }
+    fn insert_into(
+        node: &mut BTreeNode<K, V>,
+        key: K,
+        value: V,
+        order: usize,
+    ) -> Result<Split<K, V>, String> {
+        match node {
+            BTreeNode::Leaf(leaf) => {
+                let position = match leaf.keys.binary_search(&key) {
+                    Ok(_) => return Err("Value with given key already exists".to_string()),
+                    Err(position) => position,
+                };
+                leaf.keys.insert(position, key);
+                leaf.values.insert(position, value);
+                if leaf.keys.len() < order {
+                    return Ok(None);
                 }
+                let middle = leaf.keys.len() / 2;
+                let right = LeafNode {
+                    keys: leaf.keys.split_off(middle),
+                    values: leaf.values.split_off(middle),
+                };
+                Ok(Some((right.keys[0].clone(), BTreeNode::Leaf(right))))
             }
-        }
-
-        println!("Calculating underflow!");
-        let underflow = self.order / 2;
-        unsafe {
-            while let Some((entry, child_index)) = stack.pop() {
-                println!("Enrty is {:?}", *entry);
-                match &mut *entry {
-                    BTreeNode::Leaf(ref mut leaf) => {
-                        leaf.keys.remove(child_index);
-                        leaf.values.remove(child_index);
-                        if stack.last().is_none() {
-                            continue;
-                        } else if leaf.keys.len() >= underflow {
-                            println!("There isnt any underflow!");
-                            if child_index == 0 {
-                                if let Some((parent_pointer, leaf_pos)) = stack.last() {
-                                    if let BTreeNode::Internal(parent) = &mut **parent_pointer {
-                                        parent.keys[*leaf_pos] = leaf.keys.first().unwrap().clone();
-                                    }
-                                }
-                            }
-                            continue;
-                        } else {
-                            if let Some((parent_pointer, leaf_pos)) = stack.last() {
-                                if let BTreeNode::Internal(parent) = &mut **parent_pointer {
-                                    if *leaf_pos > 0 {
-                                        println!("Trying to borrow from left");
-                                        println!("Child pos is {:?}", leaf_pos);
-                                        if let BTreeNode::Leaf(left_sibling) =
-                                            &mut *parent.children[leaf_pos - 1]
-                                        {
-                                            if left_sibling.values.len() > underflow {
-                                                println!("Borrowing from left");
-                                                let borrow_key = left_sibling.keys.pop().unwrap();
-                                                let borrow_value =
-                                                    left_sibling.values.pop().unwrap();
-                                                parent.keys[*leaf_pos] = borrow_key.clone();
-                                                leaf.keys.insert(0, borrow_key);
-                                                leaf.values.insert(0, borrow_value);
-                                                println!("Ive gotten here!");
-                                                continue;
-                                            }
-                                        }
-                                    }
-                                    if *leaf_pos != parent.keys.len() {
-                                        println!("Trying to borrow from the right!");
-                                        if let BTreeNode::Leaf(right_sibling) =
-                                            &mut *parent.children[leaf_pos + 1]
-                                        {
-                                            if right_sibling.values.len() > underflow {
-                                                println!("Borrowing from the right");
-                                                let borrow_key = right_sibling.keys.remove(0);
-                                                let borrow_value = right_sibling.values.remove(0);
-                                                println!("After removing key/value for borrow, sibling is {:?}",right_sibling );
-                                                parent.keys[*leaf_pos] =
-                                                    leaf.keys.first().unwrap().clone();
-                                                println!(
-                                                    "Keys of the right node are {:?}",
-                                                    right_sibling.keys
-                                                );
-                                                parent.keys[*leaf_pos + 1] =
-                                                    right_sibling.keys.first().unwrap().clone();
-                                                leaf.keys.push(borrow_key);
-                                                leaf.values.push(borrow_value);
-                                                continue;
-                                            }
-                                        }
-                                    }
-                                    if *leaf_pos > 0 {
-                                        println!("Trying to merge with left node");
-                                        if let BTreeNode::Leaf(left_sibling) =
-                                            &mut *parent.children[leaf_pos - 1]
-                                        {
-                                            println!("Merging with right node");
-                                            left_sibling.keys.append(&mut leaf.keys);
-                                            left_sibling.values.append(&mut leaf.values);
-                                            parent.children.remove(*leaf_pos);
-                                            parent.keys.remove(*leaf_pos);
-                                            continue;
-                                        }
-                                    }
-                                    if *leaf_pos != parent.keys.len() {
-                                        println!("Trying to merge with right node");
-                                        if let BTreeNode::Leaf(right_sibling) =
-                                            &mut *parent.children[leaf_pos + 1]
-                                        {
-                                            println!("Merging with right node");
-                                            right_sibling.keys.splice(0..0, leaf.keys.clone());
-                                            right_sibling.values.splice(0..0, leaf.values.clone());
-                                            parent.keys[*leaf_pos + 1] =
-                                                right_sibling.keys.first().unwrap().clone();
-                                            parent.keys.remove(*leaf_pos);
-                                            parent.children.remove(*leaf_pos);
-                                            continue;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    BTreeNode::Internal(internal) => {}
+            BTreeNode::Internal(internal) => {
+                let index = child_index(&internal.keys, &key);
+                let Some((separator, right)) =
+                    Self::insert_into(&mut internal.children[index], key, value, order)?
+                else {
+                    return Ok(None);
+                };
+                internal.keys.insert(index, separator);
+                internal.children.insert(index + 1, Box::new(right));
+                if internal.keys.len() < order {
+                    return Ok(None);
                 }
+                let middle = internal.keys.len() / 2;
+                let mut right_keys = internal.keys.split_off(middle);
+                let separator = right_keys.remove(0);
+                let right = InternalNode {
+                    keys: right_keys,
+                    children: internal.children.split_off(middle + 1),
+                };
+                Ok(Some((separator, BTreeNode::Internal(right))))
             }
         }
+    }
+
+    pub fn delete(&mut self, key: &K) -> Result<(), String> {
+        Self::delete_from(&mut self.root, key, self.order)?;
+        let only_child = match &mut self.root {
+            BTreeNode::Internal(internal) if internal.keys.is_empty() => internal.children.pop(),
+            _ => None,
+        };
+        if let Some(child) = only_child {
+            self.root = *child;
+        }
         Ok(())
     }
 
+    fn delete_from(node: &mut BTreeNode<K, V>, key: &K, order: usize) -> Result<(), String> {
+        match node {
+            BTreeNode::Leaf(leaf) => {
+                let position = leaf
+                    .keys
+                    .binary_search(key)
+                    .map_err(|_| "Unknown key".to_string())?;
+                leaf.keys.remove(position);
+                leaf.values.remove(position);
+                Ok(())
+            }
+            BTreeNode::Internal(internal) => {
+                let index = child_index(&internal.keys, key);
+                Self::delete_from(&mut internal.children[index], key, order)?;
+                internal.rebalance(index, order);
+                Ok(())
+            }
+        }
+    }
+
+    #[allow(dead_code)]
     pub fn get(&self, key: &K) -> Option<&V> {
         let mut current = &self.root;
-
         loop {
             match current {
-                BTreeNode::Internal(internal_node) => {
-                    let child_pos = match internal_node.keys.binary_search(key) {
-                        Ok(pos) => pos,
-                        Err(pos) => pos + 1,
-                    };
-                    current = &internal_node.children[child_pos];
+                BTreeNode::Internal(internal) => {
+                    current = &internal.children[child_index(&internal.keys, key)];
+                }
+                BTreeNode::Leaf(leaf) => {
+                    return leaf
+                        .keys
+                        .binary_search(key)
+                        .ok()
+                        .map(|position| &leaf.values[position]);
                 }
-                BTreeNode::Leaf(leaf_node) => {
-                    return match leaf_node.keys.binary_search(key) {
-                        Ok(pos) => Some(&leaf_node.values[pos]),
-                        Err(_) => None,
+            }
+        }
+    }
+
+    // entries after start in ascending order until keep returns false, subtrees before start aren't visited
+    pub fn scan_from(&self, start: Bound<&K>, mut keep: impl FnMut(&K) -> bool) -> Vec<(K, V)> {
+        let mut result = vec![];
+        Self::scan_node(&self.root, start, &mut keep, &mut result);
+        result
+    }
+
+    // returns false when keep stopped the scan
+    fn scan_node(
+        node: &BTreeNode<K, V>,
+        start: Bound<&K>,
+        keep: &mut impl FnMut(&K) -> bool,
+        result: &mut Vec<(K, V)>,
+    ) -> bool {
+        match node {
+            BTreeNode::Internal(internal) => {
+                let first = match start {
+                    Bound::Included(key) | Bound::Excluded(key) => child_index(&internal.keys, key),
+                    Bound::Unbounded => 0,
+                };
+                internal.children[first..]
+                    .iter()
+                    .all(|child| Self::scan_node(child, start, keep, result))
+            }
+            BTreeNode::Leaf(leaf) => {
+                for (key, value) in leaf.keys.iter().zip(&leaf.values) {
+                    let after_start = match start {
+                        Bound::Included(start) => key >= start,
+                        Bound::Excluded(start) => key > start,
+                        Bound::Unbounded => true,
                     };
+                    if !after_start {
+                        continue;
+                    }
+                    if !keep(key) {
+                        return false;
+                    }
+                    result.push((key.clone(), value.clone()));
                 }
+                true
+            }
+        }
+    }
+}
+
+// child whose keys can contain the key
+fn child_index<K: Ord>(separators: &[K], key: &K) -> usize {
+    separators.partition_point(|separator| separator <= key)
+}
+
+impl<K: Ord + Clone + Debug, V: Clone + Debug> BTreeNode<K, V> {
+    fn len(&self) -> usize {
+        match self {
+            BTreeNode::Leaf(leaf) => leaf.keys.len(),
+            BTreeNode::Internal(internal) => internal.keys.len(),
+        }
+    }
+}
+
+impl<K: Ord + Clone + Debug, V: Clone + Debug> InternalNode<K, V> {
+    /*
+        child at index lost a key, separator of leaf is kept equal to its smallest key,
+        leaf needs at least half of order keys, internal node half of its maximum of order - 1 keys
+    */
+    fn rebalance(&mut self, index: usize, order: usize) {
+        if let (true, BTreeNode::Leaf(leaf)) = (index > 0, &*self.children[index]) {
+            if let Some(first) = leaf.keys.first() {
+                self.keys[index - 1] = first.clone();
+            }
+        }
+        let min_keys = match &*self.children[index] {
+            BTreeNode::Leaf(_) => order / 2,
+            BTreeNode::Internal(_) => (order - 1) / 2,
+        };
+        if self.children[index].len() >= min_keys {
+            return;
+        }
+        if index > 0 && self.children[index - 1].len() > min_keys {
+            self.borrow_from_left(index);
+        } else if index + 1 < self.children.len() && self.children[index + 1].len() > min_keys {
+            self.borrow_from_right(index);
+        } else if index > 0 {
+            self.merge(index - 1);
+        } else if index + 1 < self.children.len() {
+            self.merge(index);
+        }
+    }
+
+    fn borrow_from_left(&mut self, index: usize) {
+        let (left, right) = self.children.split_at_mut(index);
+        match (&mut *left[index - 1], &mut *right[0]) {
+            (BTreeNode::Leaf(sibling), BTreeNode::Leaf(node)) => {
+                let key = sibling
+                    .keys
+                    .pop()
+                    .expect("sibling has more than minimum keys");
+                let value = sibling.values.pop().expect("leaf has value for every key");
+                self.keys[index - 1] = key.clone();
+                node.keys.insert(0, key);
+                node.values.insert(0, value);
+            }
+            (BTreeNode::Internal(sibling), BTreeNode::Internal(node)) => {
+                let key = sibling
+                    .keys
+                    .pop()
+                    .expect("sibling has more than minimum keys");
+                let child = sibling.children.pop().expect("internal node has children");
+                node.keys
+                    .insert(0, std::mem::replace(&mut self.keys[index - 1], key));
+                node.children.insert(0, child);
+            }
+            _ => unreachable!("siblings are on the same level"),
+        }
+    }
+
+    fn borrow_from_right(&mut self, index: usize) {
+        let (left, right) = self.children.split_at_mut(index + 1);
+        match (&mut *left[index], &mut *right[0]) {
+            (BTreeNode::Leaf(node), BTreeNode::Leaf(sibling)) => {
+                node.keys.push(sibling.keys.remove(0));
+                node.values.push(sibling.values.remove(0));
+                self.keys[index] = sibling.keys[0].clone();
+            }
+            (BTreeNode::Internal(node), BTreeNode::Internal(sibling)) => {
+                let key = sibling.keys.remove(0);
+                node.keys
+                    .push(std::mem::replace(&mut self.keys[index], key));
+                node.children.push(sibling.children.remove(0));
+            }
+            _ => unreachable!("siblings are on the same level"),
+        }
+    }
+
+    // right sibling of the child at index is moved into it, internal nodes take their separator too
+    fn merge(&mut self, index: usize) {
+        let right = self.children.remove(index + 1);
+        let separator = self.keys.remove(index);
+        match (&mut *self.children[index], *right) {
+            (BTreeNode::Leaf(left), BTreeNode::Leaf(mut right)) => {
+                left.keys.append(&mut right.keys);
+                left.values.append(&mut right.values);
+            }
+            (BTreeNode::Internal(left), BTreeNode::Internal(mut right)) => {
+                left.keys.push(separator);
+                left.keys.append(&mut right.keys);
+                left.children.append(&mut right.children);
             }
+            _ => unreachable!("siblings are on the same level"),
         }
     }
 }
@@ -472,7 +416,7 @@ mod tests {
         let mut tree = BTree::create_from(
             4,
             InternalNode {
-                keys: vec![1, 50, 70],
+                keys: vec![50, 70],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -492,7 +436,7 @@ mod tests {
         let expected = BTree {
             order: 4,
             root: BTreeNode::Internal(InternalNode {
-                keys: vec![1, 50, 80],
+                keys: vec![50, 80],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -510,7 +454,7 @@ mod tests {
             }),
         };
 
-        tree.delete(70).unwrap();
+        tree.delete(&70).unwrap();
 
         assert_eq!(tree, expected);
     }
@@ -520,7 +464,7 @@ mod tests {
         let mut tree = BTree::create_from(
             4,
             InternalNode {
-                keys: vec![1, 50],
+                keys: vec![50],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10, 20],
@@ -536,7 +480,7 @@ mod tests {
         let expected = BTree {
             order: 4,
             root: BTreeNode::Internal(InternalNode {
-                keys: vec![1, 20],
+                keys: vec![20],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -550,7 +494,7 @@ mod tests {
             }),
         };
 
-        tree.delete(50).unwrap();
+        tree.delete(&50).unwrap();
 
         assert_eq!(tree, expected);
     }
@@ -560,7 +504,7 @@ mod tests {
         let mut tree = BTree::create_from(
             4,
             InternalNode {
-                keys: vec![1, 50, 70],
+                keys: vec![50, 70],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -580,7 +524,7 @@ mod tests {
         let expected = BTree {
             order: 4,
             root: BTreeNode::Internal(InternalNode {
-                keys: vec![1, 60, 80],
+                keys: vec![60, 80],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -598,7 +542,7 @@ mod tests {
             }),
         };
 
-        tree.delete(50).unwrap();
+        tree.delete(&50).unwrap();
 
         assert_eq!(tree, expected);
     }
@@ -608,7 +552,7 @@ mod tests {
         let mut tree = BTree::create_from(
             4,
             InternalNode {
-                keys: vec![1, 50, 70],
+                keys: vec![50, 70],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -628,7 +572,7 @@ mod tests {
         let expected = BTree {
             order: 4,
             root: BTreeNode::Internal(InternalNode {
-                keys: vec![1, 70],
+                keys: vec![70],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10, 60],
@@ -642,7 +586,7 @@ mod tests {
             }),
         };
 
-        tree.delete(50).unwrap();
+        tree.delete(&50).unwrap();
 
         assert_eq!(tree, expected);
     }
@@ -652,7 +596,7 @@ mod tests {
         let mut tree = BTree::create_from(
             4,
             InternalNode {
-                keys: vec![1, 50, 70],
+                keys: vec![50, 70],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 10],
@@ -672,7 +616,7 @@ mod tests {
         let expected = BTree {
             order: 4,
             root: BTreeNode::Internal(InternalNode {
-                keys: vec![1, 70],
+                keys: vec![70],
                 children: vec![
                     Box::from(BTreeNode::Leaf(LeafNode {
                         keys: vec![1, 50, 60],
@@ -686,7 +630,7 @@ mod tests {
             }),
         };
 
-        tree.delete(10).unwrap();
+        tree.delete(&10).unwrap();
 
         assert_eq!(tree, expected);
     }
@@ -1,5 +1,5 @@
-use std::{collections::VecDeque, sync::{Arc, Condvar, Mutex}, thread::{self, ThreadId}};
-use crate::{constants::PAGE_SIZE_IN_BYTES, new_page::NewPage, page::Page};
+use std::{collections::VecDeque, sync::{Arc, Condvar, Mutex}, thread::{self, ThreadId}, time::{Duration, Instant}};
+use crate::{constants::PAGE_SIZE_IN_BYTES, error::SqlState, new_page::NewPage};
 use super::spin_lock::SpinLock;
 
 pub struct BufferDesc {
@@ -11,6 +11,7 @@ pub struct BufferDesc {
     buff: NewPage,
     wait_q: VecDeque<WaitQueueEntry>,
 }
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
 pub enum LockMode {
     UNLOCKED,
@@ -39,7 +40,7 @@ impl BufferDesc {
     }
 
     pub fn get_buff(&self) -> &NewPage {
-        return &self.buff
+        &self.buff
     }
 
     pub fn write_buff(&mut self, new_buff: NewPage) -> Result<(), String> {
@@ -55,7 +56,9 @@ impl BufferDesc {
         Ok(())
     }
 
-    pub fn lock_shared(&mut self) -> Result<(), String> {
+    // waiting longer than timeout fails with lock timeout, like waiting for a lock of a row
+    pub fn lock_shared(&mut self, timeout: Option<Duration>) -> Result<(), String> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
         self.s_lock.lock();
         match &self.mode {
             LockMode::UNLOCKED => {  self.mode = LockMode::SHARED(1); },
@@ -71,7 +74,7 @@ impl BufferDesc {
                     };
                     self.wait_q.push_back(entry);
                     self.s_lock.unlock();
-                    mut_conv.1.wait(mut_conv.0.lock().unwrap()).unwrap();
+                    self.wait_in_queue(&mut_conv, deadline)?;
                 }
                 self.s_lock.lock();
                 match &self.mode {
@@ -99,7 +102,8 @@ impl BufferDesc {
         self.s_lock.unlock();
     }
 
-    pub fn lock_exclusive(&mut self) -> Result<(), String> {
+    pub fn lock_exclusive(&mut self, timeout: Option<Duration>) -> Result<(), String> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
         self.s_lock.lock();
         match self.mode {
             LockMode::UNLOCKED => { self.mode = LockMode::EXCLUSIVE( thread::current().id()); },
@@ -114,7 +118,7 @@ impl BufferDesc {
                     };
                     self.wait_q.push_back(entry);
                     self.s_lock.unlock();
-                    mut_conv.1.wait(mut_conv.0.lock().unwrap()).unwrap();
+                    self.wait_in_queue(&mut_conv, deadline)?;
                 }
                 self.s_lock.lock();
                 match self.mode {
@@ -141,8 +145,30 @@ impl BufferDesc {
         Ok(())
     }
 
+    // thread which isn't woken up before the deadline leaves the wait queue, its statement is canceled
+    fn wait_in_queue(&mut self, mut_conv: &Arc<(Mutex<()>, Condvar)>, deadline: Option<Instant>) -> Result<(), String> {
+        let guard = mut_conv.0.lock().map_err(|e| e.to_string())?;
+        let Some(deadline) = deadline else {
+            let _guard = mut_conv.1.wait(guard).map_err(|e| e.to_string())?;
+            return Ok(());
+        };
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let result = mut_conv.1.wait_timeout(guard, timeout).map_err(|e| e.to_string())?.1;
+        if result.timed_out() {
+            let current_thread_id = thread::current().id();
+            self.s_lock.lock();
+            self.wait_q.retain(|entry| entry.thread_id != current_thread_id);
+            self.s_lock.unlock();
+            return Err(SqlState::LockNotAvailable.error(&format!(
+                "Canceling statement due to lock timeout on buffer of page {} of '{}'",
+                self.page, self.file_name
+            )));
+        }
+        Ok(())
+    }
+
     pub fn get_lock_type(&self) -> LockMode {
-        return self.mode.clone();     
+        self.mode.clone()
     }
 
     fn notify_waiting_threads_change_in_lock(&mut self) {
@@ -167,7 +193,7 @@ impl BufferDesc {
         };
         let threads_to_notify: Vec<WaitQueueEntry> = self.wait_q.drain(0..last_index_to_notify).collect(); 
         for entry in threads_to_notify {
-            entry.mut_conv.0.lock();
+            let _guard = entry.mut_conv.0.lock();
             entry.mut_conv.1.notify_one();
         }   
     }
@@ -33,7 +33,7 @@ impl BuffPool {
         &mut self,
         from_file: &str,
         page_number: u32,
-    ) -> Result<&Box<BufferDesc>, String> {
+    ) -> Result<&BufferDesc, String> {
         self.s_lock.lock();
         if let Some(index) = self
             .descriptors
@@ -49,7 +49,7 @@ impl BuffPool {
             let buff_description = Box::from(BufferDesc::new(from_file, page_number, page_content));
             self.descriptors.push_front(buff_description);
             self.s_lock.unlock();
-            return Ok(self.descriptors.front().unwrap());
+            Ok(self.descriptors.front().unwrap())
         } else {
             loop {
                 if let Some(index) = self.descriptors.iter().position(|desc| {
@@ -75,14 +75,14 @@ fn load_page_to_memory(
     let mut file = File::options()
         .read(true)
         .open(file_name)
-        .map_err(|e| format!("Error opening file '{}': {}", file_name, e.to_string()))?;
+        .map_err(|e| format!("Error opening file '{}': {}", file_name, e))?;
     file.seek(SeekFrom::Start((PAGE_SIZE_IN_BYTES * page_number).into()))
         .map_err(|e| {
             format!(
                 "Error seeking page {} in file '{}': {}",
                 page_number,
                 file_name,
-                e.to_string()
+                e
             )
         })?;
     let page_content = {
@@ -92,10 +92,10 @@ fn load_page_to_memory(
                 "Error reading page {} from file '{}': {}",
                 page_number,
                 file_name,
-                e.to_string()
+                e
             )
         })?;
         content
     };
-    return Ok(page_content);
+    Ok(page_content)
 }
@@ -1,3 +1,476 @@
-mod tables;
+pub mod tables;
 
-struct Oid(i32);
\ No newline at end of file
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::Path,
+};
+
+use crate::constants::{
+    FIRST_USER_OID, PG_ATTRIBUTE_FILE, PG_CLASS_FILE, PG_CONSTRAINT_FILE, PG_FOREIGN_KEY_FILE,
+    PG_INDEX_FILE, PG_NAMESPACE_FILE, PG_SEQUENCE_FILE, PG_VIEW_FILE, PUBLIC_NAMESPACE,
+    PUBLIC_NAMESPACE_OID,
+};
+use tables::{
+    CatalogRecord, Kind, PgAttribute, PgClass, PgConstraint, PgForeignKey, PgIndex, PgNamespace,
+    PgSequence, PgView,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Oid(pub i32);
+
+/*
+    system catalog of one database, every relation is kept in memory and saved to its own file,
+    pg_class file starts with next oid which will be allocated,
+    relations are looked up by name in schemas of search path given by the session
+*/
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    next_oid: i32,
+    pub namespaces: Vec<PgNamespace>,
+    pub classes: Vec<PgClass>,
+    pub attributes: Vec<PgAttribute>,
+    pub indexes: Vec<PgIndex>,
+    pub constraints: Vec<PgConstraint>,
+    pub foreign_keys: Vec<PgForeignKey>,
+    pub sequences: Vec<PgSequence>,
+    pub views: Vec<PgView>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog {
+            next_oid: FIRST_USER_OID,
+            namespaces: vec![PgNamespace {
+                oid: PUBLIC_NAMESPACE_OID,
+                name: PUBLIC_NAMESPACE.to_string(),
+            }],
+            classes: vec![],
+            attributes: vec![],
+            indexes: vec![],
+            constraints: vec![],
+            foreign_keys: vec![],
+            sequences: vec![],
+            views: vec![],
+        }
+    }
+
+    // missing files mean empty catalog with public schema
+    pub fn load(directory: &Path) -> Result<Self, String> {
+        let mut catalog = Catalog::new();
+        if let Some(content) = read_file(&directory.join(PG_NAMESPACE_FILE))? {
+            catalog.namespaces = read_records(&mut content.as_slice())?;
+        }
+        if let Some(content) = read_file(&directory.join(PG_CLASS_FILE))? {
+            let mut buffer = content.as_slice();
+            catalog.next_oid = read_i32(&mut buffer)?;
+            catalog.classes = read_records(&mut buffer)?;
+        }
+        catalog.attributes = load_relation(&directory.join(PG_ATTRIBUTE_FILE))?;
+        catalog.indexes = load_relation(&directory.join(PG_INDEX_FILE))?;
+        catalog.constraints = load_relation(&directory.join(PG_CONSTRAINT_FILE))?;
+        catalog.foreign_keys = load_relation(&directory.join(PG_FOREIGN_KEY_FILE))?;
+        catalog.sequences = load_relation(&directory.join(PG_SEQUENCE_FILE))?;
+        catalog.views = load_relation(&directory.join(PG_VIEW_FILE))?;
+        Ok(catalog)
+    }
+
+    pub fn save(&self, directory: &Path) -> Result<(), String> {
+        let mut buffer = vec![];
+        buffer.extend(self.next_oid.to_le_bytes());
+        write_records(&mut buffer, &self.classes);
+        write_file(&directory.join(PG_CLASS_FILE), &buffer)?;
+        save_relation(&directory.join(PG_NAMESPACE_FILE), &self.namespaces)?;
+        save_relation(&directory.join(PG_ATTRIBUTE_FILE), &self.attributes)?;
+        save_relation(&directory.join(PG_INDEX_FILE), &self.indexes)?;
+        save_relation(&directory.join(PG_CONSTRAINT_FILE), &self.constraints)?;
+        save_relation(&directory.join(PG_FOREIGN_KEY_FILE), &self.foreign_keys)?;
+        save_relation(&directory.join(PG_SEQUENCE_FILE), &self.sequences)?;
+        save_relation(&directory.join(PG_VIEW_FILE), &self.views)
+    }
+
+    pub fn allocate_oid(&mut self) -> Oid {
+        let oid = Oid(self.next_oid);
+        self.next_oid += 1;
+        oid
+    }
+
+    // "schema.name" is looked up only in the schema, unqualified name in schemas of search path in their order
+    pub fn find_relation(&self, name: &str, search_path: &[String]) -> Option<&PgClass> {
+        match split_name(name) {
+            (Some(schema), name) => self
+                .namespace(schema)
+                .ok()
+                .and_then(|namespace| self.find_in_namespace(namespace.oid, name)),
+            (None, name) => search_path
+                .iter()
+                .filter_map(|schema| self.namespace(schema).ok())
+                .find_map(|namespace| self.find_in_namespace(namespace.oid, name)),
+        }
+    }
+
+    pub fn find_in_namespace(&self, namespace: Oid, name: &str) -> Option<&PgClass> {
+        self.classes
+            .iter()
+            .find(|class| class.name == name && class.namespace == namespace)
+    }
+
+    pub fn namespace(&self, name: &str) -> Result<&PgNamespace, String> {
+        self.namespaces
+            .iter()
+            .find(|namespace| namespace.name == name)
+            .ok_or_else(|| format!("Schema \"{}\" does not exist", name))
+    }
+
+    // schema of new relation and its unqualified name, unqualified name goes to the first existing schema of search path
+    pub fn creation_namespace<'a>(
+        &self,
+        name: &'a str,
+        search_path: &[String],
+    ) -> Result<(Oid, &'a str), String> {
+        match split_name(name) {
+            (Some(schema), name) => Ok((self.namespace(schema)?.oid, name)),
+            (None, name) => search_path
+                .iter()
+                .find_map(|schema| self.namespace(schema).ok())
+                .map(|namespace| (namespace.oid, name))
+                .ok_or_else(|| "No schema has been selected to create in".to_string()),
+        }
+    }
+
+    // name which finds the relation with any search path
+    pub fn qualified_name(&self, class: &PgClass) -> String {
+        match self.namespaces.iter().find(|ns| ns.oid == class.namespace) {
+            Some(namespace) => format!("{}.{}", namespace.name, class.name),
+            None => class.name.clone(),
+        }
+    }
+
+    pub fn table(&self, name: &str, search_path: &[String]) -> Result<&PgClass, String> {
+        match self.find_relation(name, search_path) {
+            Some(class) if class.kind == Kind::NormalTable => Ok(class),
+            Some(_) => Err(format!("\"{}\" is not a table", name)),
+            None => Err(format!("Relation \"{}\" does not exist", name)),
+        }
+    }
+
+    // table or materialized view, which can be read
+    pub fn relation_with_heap(
+        &self,
+        name: &str,
+        search_path: &[String],
+    ) -> Result<&PgClass, String> {
+        match self.find_relation(name, search_path) {
+            Some(class) if class.kind.has_heap() => Ok(class),
+            _ => Err(format!("Relation \"{}\" does not exist", name)),
+        }
+    }
+
+    pub fn view(&self, oid: Oid) -> Result<&PgView, String> {
+        self.views
+            .iter()
+            .find(|view| view.oid == oid)
+            .ok_or_else(|| format!("Definition of view with oid {} is missing", oid.0))
+    }
+
+    pub fn sequence(&self, name: &str, search_path: &[String]) -> Result<&PgSequence, String> {
+        let oid = match self.find_relation(name, search_path) {
+            Some(class) if class.kind == Kind::Sequence => class.oid,
+            _ => return Err(format!("Relation \"{}\" does not exist", name)),
+        };
+        self.sequences
+            .iter()
+            .find(|sequence| sequence.oid == oid)
+            .ok_or_else(|| format!("State of sequence \"{}\" is missing", name))
+    }
+
+    // columns of the table ordered by their number
+    pub fn attributes_of(&self, table: Oid) -> Vec<&PgAttribute> {
+        let mut attributes: Vec<&PgAttribute> = self
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.owner_id == table)
+            .collect();
+        attributes.sort_by_key(|attribute| attribute.num);
+        attributes
+    }
+
+    pub fn indexes_of(&self, table: Oid) -> Vec<&PgIndex> {
+        self.indexes
+            .iter()
+            .filter(|index| index.table == table)
+            .collect()
+    }
+
+    pub fn constraints_of(&self, table: Oid) -> Vec<&PgConstraint> {
+        self.constraints
+            .iter()
+            .filter(|constraint| constraint.table == table)
+            .collect()
+    }
+
+    pub fn constraint(&self, oid: Oid) -> Result<&PgConstraint, String> {
+        self.constraints
+            .iter()
+            .find(|constraint| constraint.oid == oid)
+            .ok_or_else(|| format!("Constraint with oid {} does not exist", oid.0))
+    }
+
+    pub fn class(&self, oid: Oid) -> Result<&PgClass, String> {
+        self.classes
+            .iter()
+            .find(|class| class.oid == oid)
+            .ok_or_else(|| format!("Relation with oid {} does not exist", oid.0))
+    }
+
+    // foreign keys of the table, which reference other tables
+    pub fn foreign_keys_of(&self, table: Oid) -> Vec<&PgForeignKey> {
+        self.foreign_keys
+            .iter()
+            .filter(|foreign_key| foreign_key.table == table)
+            .collect()
+    }
+
+    // foreign keys of other tables, which reference the table
+    pub fn foreign_keys_referencing(&self, table: Oid) -> Vec<&PgForeignKey> {
+        self.foreign_keys
+            .iter()
+            .filter(|foreign_key| foreign_key.referenced_table == table)
+            .collect()
+    }
+
+    /*
+        removes relations with everything owned by them: columns, indexes of tables, constraints and states,
+        foreign keys of other tables which reference removed table are removed too,
+        returns oids of all removed relations
+    */
+    pub fn remove_relations(&mut self, relations: &[Oid]) -> Vec<Oid> {
+        let mut removed: Vec<Oid> = relations.to_vec();
+        removed.extend(
+            self.indexes
+                .iter()
+                .filter(|index| relations.contains(&index.table))
+                .map(|index| index.oid),
+        );
+        let foreign_keys: Vec<Oid> = self
+            .foreign_keys
+            .iter()
+            .filter(|foreign_key| removed.contains(&foreign_key.referenced_table))
+            .map(|foreign_key| foreign_key.oid)
+            .collect();
+        self.classes.retain(|class| !removed.contains(&class.oid));
+        self.attributes
+            .retain(|attribute| !removed.contains(&attribute.owner_id));
+        self.indexes.retain(|index| !removed.contains(&index.oid));
+        self.constraints.retain(|constraint| {
+            !removed.contains(&constraint.table) && !foreign_keys.contains(&constraint.oid)
+        });
+        self.foreign_keys.retain(|foreign_key| {
+            !removed.contains(&foreign_key.table) && !foreign_keys.contains(&foreign_key.oid)
+        });
+        self.sequences
+            .retain(|sequence| !removed.contains(&sequence.oid));
+        self.views.retain(|view| !removed.contains(&view.oid));
+        removed
+    }
+}
+
+// "schema.name" is split to schema and name of relation
+pub fn split_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once('.') {
+        Some((schema, name)) => (Some(schema), name),
+        None => (None, name),
+    }
+}
+
+pub fn load_relation<T: CatalogRecord>(path: &Path) -> Result<Vec<T>, String> {
+    match read_file(path)? {
+        Some(content) => read_records(&mut content.as_slice()),
+        None => Ok(vec![]),
+    }
+}
+
+pub fn save_relation<T: CatalogRecord>(path: &Path, records: &[T]) -> Result<(), String> {
+    let mut buffer = vec![];
+    write_records(&mut buffer, records);
+    write_file(path, &buffer)
+}
+
+fn read_records<T: CatalogRecord>(buffer: &mut &[u8]) -> Result<Vec<T>, String> {
+    let count = read_i32(buffer)?;
+    (0..count).map(|_| T::read(buffer)).collect()
+}
+
+fn write_records<T: CatalogRecord>(buffer: &mut Vec<u8>, records: &[T]) {
+    buffer.extend((records.len() as i32).to_le_bytes());
+    for record in records {
+        record.write(buffer);
+    }
+}
+
+fn read_file(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Error opening file '{}': {}", path.display(), e)),
+    };
+    let mut content = vec![];
+    file.read_to_end(&mut content)
+        .map_err(|e| format!("Error reading file '{}': {}", path.display(), e))?;
+    Ok(Some(content))
+}
+
+// written to temporary file first, so the old content stays if writing fails
+fn write_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let temporary_path = path.with_extension("tmp");
+    let mut file = File::create(&temporary_path)
+        .map_err(|e| format!("Error creating file '{}': {}", temporary_path.display(), e))?;
+    file.write_all(content)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Error writing file '{}': {}", temporary_path.display(), e))?;
+    fs::rename(&temporary_path, path)
+        .map_err(|e| format!("Error replacing file '{}': {}", path.display(), e))
+}
+
+pub fn read_u8(buffer: &mut &[u8]) -> Result<u8, String> {
+    let (&value, rest) = buffer
+        .split_first()
+        .ok_or_else(|| "Unexpected end of catalog record".to_string())?;
+    *buffer = rest;
+    Ok(value)
+}
+
+pub fn read_i32(buffer: &mut &[u8]) -> Result<i32, String> {
+    let bytes = buffer
+        .get(0..4)
+        .ok_or_else(|| "Unexpected end of catalog record".to_string())?;
+    let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    *buffer = &buffer[4..];
+    Ok(value)
+}
+
+fn read_i32_list(buffer: &mut &[u8]) -> Result<Vec<i32>, String> {
+    let count = read_i32(buffer)?;
+    (0..count).map(|_| read_i32(buffer)).collect()
+}
+
+fn write_i32_list(buffer: &mut Vec<u8>, values: &[i32]) {
+    buffer.extend((values.len() as i32).to_le_bytes());
+    for value in values {
+        buffer.extend(value.to_le_bytes());
+    }
+}
+
+pub fn read_string(buffer: &mut &[u8]) -> Result<String, String> {
+    let length = u16::from_le_bytes([read_u8(buffer)?, read_u8(buffer)?]) as usize;
+    let bytes = buffer
+        .get(0..length)
+        .ok_or_else(|| "Unexpected end of catalog record".to_string())?;
+    let value = String::from_utf8(bytes.to_vec())
+        .map_err(|_| "String in catalog isn't valid UTF-8".to_string())?;
+    *buffer = &buffer[length..];
+    Ok(value)
+}
+
+pub fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend((value.len() as u16).to_le_bytes());
+    buffer.extend(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{information_schema::SType, parser::ast::ReferentialAction};
+
+    use super::{
+        tables::{ConstraintKind, Identity},
+        *,
+    };
+
+    #[test]
+    fn when_catalog_is_saved_it_should_be_loaded_with_same_relations() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut catalog = Catalog::new();
+        let table = catalog.allocate_oid();
+        let index = catalog.allocate_oid();
+        catalog.classes.push(PgClass {
+            oid: table,
+            name: "users".to_string(),
+            kind: Kind::NormalTable,
+            namespace: PUBLIC_NAMESPACE_OID,
+        });
+        catalog.attributes.push(PgAttribute {
+            owner_id: table,
+            name: "age".to_string(),
+            num: 1,
+            data_type: SType::INT,
+            default: Some("18".to_string()),
+            identity: Identity::ByDefault,
+        });
+        catalog.indexes.push(PgIndex {
+            oid: index,
+            table,
+            indkey: vec![1, 2],
+            unique: true,
+        });
+        let unique = catalog.allocate_oid();
+        let check = catalog.allocate_oid();
+        catalog.constraints.push(PgConstraint {
+            oid: unique,
+            name: "users_age_key".to_string(),
+            table,
+            kind: ConstraintKind::Unique { index },
+        });
+        catalog.constraints.push(PgConstraint {
+            oid: check,
+            name: "users_age_check".to_string(),
+            table,
+            kind: ConstraintKind::Check {
+                expression: "(age >= 18)".to_string(),
+            },
+        });
+
+        catalog.foreign_keys.push(PgForeignKey {
+            oid: check,
+            table,
+            columns: vec![2, 1],
+            referenced_table: table,
+            referenced_columns: vec![1, 2],
+            referenced_index: index,
+            on_delete: ReferentialAction::Cascade,
+            on_update: ReferentialAction::SetDefault,
+        });
+        catalog.sequences.push(PgSequence {
+            oid: table,
+            increment: -2,
+            min_value: -10,
+            max_value: 10,
+            start: 10,
+            cycle: true,
+            last_value: 4,
+            is_called: true,
+        });
+        let sales = catalog.allocate_oid();
+        catalog.namespaces.push(PgNamespace {
+            oid: sales,
+            name: "sales".to_string(),
+        });
+        catalog.views.push(PgView {
+            oid: index,
+            columns: vec!["a".to_string(), "b".to_string()],
+            definition: "SELECT * FROM users".to_string(),
+        });
+
+        catalog.save(directory.path()).unwrap();
+        let mut loaded = Catalog::load(directory.path()).unwrap();
+
+        assert_eq!(loaded.classes, catalog.classes);
+        assert_eq!(loaded.attributes, catalog.attributes);
+        assert_eq!(loaded.indexes, catalog.indexes);
+        assert_eq!(loaded.constraints, catalog.constraints);
+        assert_eq!(loaded.foreign_keys, catalog.foreign_keys);
+        assert_eq!(loaded.sequences, catalog.sequences);
+        assert_eq!(loaded.views, catalog.views);
+        assert_eq!(loaded.namespaces, catalog.namespaces);
+        assert_eq!(loaded.allocate_oid(), Oid(FIRST_USER_OID + 5));
+    }
+}
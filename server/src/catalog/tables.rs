@@ -1,32 +1,383 @@
-use super::Oid;
+use crate::{information_schema::SType, parser::ast::ReferentialAction};
 
-struct PgClass {
-    oid: Oid, // indexed, unique
-    name: String,  // indexed, unique
-    kind: Kind,
-    namespace: Oid,
+use super::{read_i32, read_i32_list, read_string, read_u8, write_i32_list, write_string, Oid};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgClass {
+    pub oid: Oid,     // indexed, unique
+    pub name: String, // indexed, unique in namespace
+    pub kind: Kind,
+    pub namespace: Oid,
+}
+
+// schema, relations with the same name can exist in different schemas
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgNamespace {
+    pub oid: Oid,
+    pub name: String,
 }
 
-enum Kind {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
     NormalTable,
     Index,
+    Sequence,
+    View,
+    MaterializedView,
+}
+
+impl Kind {
+    // relations whose rows are stored in heap file
+    pub fn has_heap(self) -> bool {
+        matches!(self, Kind::NormalTable | Kind::MaterializedView)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgIndex {
+    pub oid: Oid,
+    pub table: Oid,
+    pub indkey: Vec<i32>, // defines on what column numbers is index created, in order of the key
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgAttribute {
+    pub owner_id: Oid,
+    pub name: String,
+    pub num: i32, // defines column number
+    pub data_type: SType,
+    pub default: Option<String>, // text of DEFAULT expression
+    pub identity: Identity,
+}
+
+// identity column gets its value from sequence, like column with nextval DEFAULT
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Identity {
+    None,
+    Always,    // value can't be given by INSERT or UPDATE
+    ByDefault, // given value overrides the sequence
+}
+
+// query of view or materialized view, columns rename columns of the query result when they are given
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgView {
+    pub oid: Oid,
+    pub columns: Vec<String>,
+    pub definition: String, // text of the query
+}
+
+/*
+    state of sequence, its relation is in PgClass with the same oid,
+    last_value is saved ahead of values given by nextval, so values after restart are never reused
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgSequence {
+    pub oid: Oid,
+    pub increment: i32,
+    pub min_value: i32,
+    pub max_value: i32,
+    pub start: i32,
+    pub cycle: bool,
+    pub last_value: i32,
+    pub is_called: bool, // false when next value is last_value itself
 }
 
-struct PgIndex {
-    oid: Oid, 
-    table: Oid,
-    indkey: i32, // defines on what column number is index created
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgConstraint {
+    pub oid: Oid,
+    pub name: String,
+    pub table: Oid,
+    pub kind: ConstraintKind,
 }
 
-struct PgAttribute {
-    owner_id: Oid,
-    name: String,
-    num: i32, // defines column number   
-    // data_type: CollumnType, 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstraintKind {
+    PrimaryKey { index: Oid },
+    Unique { index: Oid },
+    Check { expression: String }, // text of CHECK expression
+    ForeignKey,                   // details are in PgForeignKey with the same oid
 }
-/*  
+
+// column of table references column of referenced table, which has unique index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgForeignKey {
+    pub oid: Oid, // oid of the constraint
+    pub table: Oid,
+    pub columns: Vec<i32>,
+    pub referenced_table: Oid,
+    pub referenced_columns: Vec<i32>, // paired with columns, in order of the referenced index key
+    pub referenced_index: Oid,        // probed when row of the table is inserted or updated
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+/*
     when searching for index in collumn:
         1. search OID od the table
         2. search PgIndex with OID of the table to get file name where index tree is saved
-        3. search PgAttribute where num == indkey and  owner_id == PgIndex.table to get data type
- */
\ No newline at end of file
+        3. search PgAttribute where num is in indkey and  owner_id == PgIndex.table to get data types
+*/
+
+/*
+    every catalog relation is saved as sequence of records, integers are little endian
+    and strings are prefixed with their length as u16
+*/
+pub trait CatalogRecord: Sized {
+    fn write(&self, buffer: &mut Vec<u8>);
+    fn read(buffer: &mut &[u8]) -> Result<Self, String>;
+}
+
+impl CatalogRecord for PgNamespace {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        write_string(buffer, &self.name);
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        Ok(PgNamespace {
+            oid: Oid(read_i32(buffer)?),
+            name: read_string(buffer)?,
+        })
+    }
+}
+
+impl CatalogRecord for PgClass {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        write_string(buffer, &self.name);
+        buffer.push(match self.kind {
+            Kind::NormalTable => 0,
+            Kind::Index => 1,
+            Kind::Sequence => 2,
+            Kind::View => 3,
+            Kind::MaterializedView => 4,
+        });
+        buffer.extend(self.namespace.0.to_le_bytes());
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        Ok(PgClass {
+            oid: Oid(read_i32(buffer)?),
+            name: read_string(buffer)?,
+            kind: match read_u8(buffer)? {
+                0 => Kind::NormalTable,
+                1 => Kind::Index,
+                2 => Kind::Sequence,
+                3 => Kind::View,
+                4 => Kind::MaterializedView,
+                other => return Err(format!("Unknown relation kind {}", other)),
+            },
+            namespace: Oid(read_i32(buffer)?),
+        })
+    }
+}
+
+impl CatalogRecord for PgIndex {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        buffer.extend(self.table.0.to_le_bytes());
+        write_i32_list(buffer, &self.indkey);
+        buffer.push(self.unique as u8);
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        Ok(PgIndex {
+            oid: Oid(read_i32(buffer)?),
+            table: Oid(read_i32(buffer)?),
+            indkey: read_i32_list(buffer)?,
+            unique: read_u8(buffer)? != 0,
+        })
+    }
+}
+
+impl CatalogRecord for PgAttribute {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.owner_id.0.to_le_bytes());
+        write_string(buffer, &self.name);
+        buffer.extend(self.num.to_le_bytes());
+        buffer.push(match self.data_type {
+            SType::INT => 0,
+            SType::STRING => 1,
+        });
+        match &self.default {
+            Some(default) => {
+                buffer.push(1);
+                write_string(buffer, default);
+            }
+            None => buffer.push(0),
+        }
+        buffer.push(match self.identity {
+            Identity::None => 0,
+            Identity::Always => 1,
+            Identity::ByDefault => 2,
+        });
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        Ok(PgAttribute {
+            owner_id: Oid(read_i32(buffer)?),
+            name: read_string(buffer)?,
+            num: read_i32(buffer)?,
+            data_type: match read_u8(buffer)? {
+                0 => SType::INT,
+                1 => SType::STRING,
+                other => return Err(format!("Unknown data type {}", other)),
+            },
+            default: match read_u8(buffer)? {
+                0 => None,
+                _ => Some(read_string(buffer)?),
+            },
+            identity: match read_u8(buffer)? {
+                0 => Identity::None,
+                1 => Identity::Always,
+                2 => Identity::ByDefault,
+                other => return Err(format!("Unknown identity {}", other)),
+            },
+        })
+    }
+}
+
+impl CatalogRecord for PgConstraint {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        write_string(buffer, &self.name);
+        buffer.extend(self.table.0.to_le_bytes());
+        match &self.kind {
+            ConstraintKind::PrimaryKey { index } => {
+                buffer.push(0);
+                buffer.extend(index.0.to_le_bytes());
+            }
+            ConstraintKind::Unique { index } => {
+                buffer.push(1);
+                buffer.extend(index.0.to_le_bytes());
+            }
+            ConstraintKind::Check { expression } => {
+                buffer.push(2);
+                write_string(buffer, expression);
+            }
+            ConstraintKind::ForeignKey => buffer.push(3),
+        }
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        let oid = Oid(read_i32(buffer)?);
+        let name = read_string(buffer)?;
+        let table = Oid(read_i32(buffer)?);
+        let kind = match read_u8(buffer)? {
+            0 => ConstraintKind::PrimaryKey {
+                index: Oid(read_i32(buffer)?),
+            },
+            1 => ConstraintKind::Unique {
+                index: Oid(read_i32(buffer)?),
+            },
+            2 => ConstraintKind::Check {
+                expression: read_string(buffer)?,
+            },
+            3 => ConstraintKind::ForeignKey,
+            other => return Err(format!("Unknown constraint kind {}", other)),
+        };
+        Ok(PgConstraint {
+            oid,
+            name,
+            table,
+            kind,
+        })
+    }
+}
+
+impl CatalogRecord for PgForeignKey {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        buffer.extend(self.table.0.to_le_bytes());
+        write_i32_list(buffer, &self.columns);
+        buffer.extend(self.referenced_table.0.to_le_bytes());
+        write_i32_list(buffer, &self.referenced_columns);
+        buffer.extend(self.referenced_index.0.to_le_bytes());
+        buffer.push(write_action(self.on_delete));
+        buffer.push(write_action(self.on_update));
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        Ok(PgForeignKey {
+            oid: Oid(read_i32(buffer)?),
+            table: Oid(read_i32(buffer)?),
+            columns: read_i32_list(buffer)?,
+            referenced_table: Oid(read_i32(buffer)?),
+            referenced_columns: read_i32_list(buffer)?,
+            referenced_index: Oid(read_i32(buffer)?),
+            on_delete: read_action(buffer)?,
+            on_update: read_action(buffer)?,
+        })
+    }
+}
+
+impl CatalogRecord for PgView {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        buffer.extend((self.columns.len() as i32).to_le_bytes());
+        for column in &self.columns {
+            write_string(buffer, column);
+        }
+        write_string(buffer, &self.definition);
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        let oid = Oid(read_i32(buffer)?);
+        let count = read_i32(buffer)?;
+        let columns = (0..count)
+            .map(|_| read_string(buffer))
+            .collect::<Result<Vec<String>, String>>()?;
+        Ok(PgView {
+            oid,
+            columns,
+            definition: read_string(buffer)?,
+        })
+    }
+}
+
+impl CatalogRecord for PgSequence {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.oid.0.to_le_bytes());
+        buffer.extend(self.increment.to_le_bytes());
+        buffer.extend(self.min_value.to_le_bytes());
+        buffer.extend(self.max_value.to_le_bytes());
+        buffer.extend(self.start.to_le_bytes());
+        buffer.push(self.cycle as u8);
+        buffer.extend(self.last_value.to_le_bytes());
+        buffer.push(self.is_called as u8);
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        Ok(PgSequence {
+            oid: Oid(read_i32(buffer)?),
+            increment: read_i32(buffer)?,
+            min_value: read_i32(buffer)?,
+            max_value: read_i32(buffer)?,
+            start: read_i32(buffer)?,
+            cycle: read_u8(buffer)? != 0,
+            last_value: read_i32(buffer)?,
+            is_called: read_u8(buffer)? != 0,
+        })
+    }
+}
+
+fn write_action(action: ReferentialAction) -> u8 {
+    match action {
+        ReferentialAction::NoAction => 0,
+        ReferentialAction::Restrict => 1,
+        ReferentialAction::Cascade => 2,
+        ReferentialAction::SetNull => 3,
+        ReferentialAction::SetDefault => 4,
+    }
+}
+
+fn read_action(buffer: &mut &[u8]) -> Result<ReferentialAction, String> {
+    match read_u8(buffer)? {
+        0 => Ok(ReferentialAction::NoAction),
+        1 => Ok(ReferentialAction::Restrict),
+        2 => Ok(ReferentialAction::Cascade),
+        3 => Ok(ReferentialAction::SetNull),
+        4 => Ok(ReferentialAction::SetDefault),
+        other => Err(format!("Unknown referential action {}", other)),
+    }
+}
@@ -1,7 +1,12 @@
+use std::path::PathBuf;
+
 use clap::{Arg, Command};
 
+use crate::constants::DEFAULT_DATA_DIRECTORY;
+
 pub struct Config {
     pub port: i32,
+    pub data_directory: PathBuf,
 }
 
 impl Config {
@@ -18,12 +23,27 @@ impl Config {
                     .value_name("PORT")
                     .default_value("3307"),
             )
+            .arg(
+                Arg::new("data-directory")
+                    .help("Directory with one directory of catalog and tables for every database")
+                    .short('d')
+                    .long("data-directory")
+                    .value_name("DIRECTORY")
+                    .default_value(DEFAULT_DATA_DIRECTORY),
+            )
             .get_matches();
         let port = matches
             .get_one::<String>("port")
             .ok_or("Port argument couldnt be parsed")?
             .parse::<i32>()
             .unwrap();
-        Ok(Config { port })
+        let data_directory = matches
+            .get_one::<String>("data-directory")
+            .ok_or("Data directory argument couldnt be parsed")?
+            .into();
+        Ok(Config {
+            port,
+            data_directory,
+        })
     }
 }
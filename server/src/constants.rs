@@ -1,3 +1,27 @@
+use crate::catalog::Oid;
+
 pub const PAGE_SIZE_IN_BYTES: u32 = 8 * 1024;
-pub const PG_CLASS_NAME_INDEX_FILE: &str = "pg_class_name_index.ss";
-pub const DEFAULT_BUFF_POOL_SIZE: u32 = PAGE_SIZE_IN_BYTES * 20; 
\ No newline at end of file
+#[allow(dead_code)]
+pub const DEFAULT_BUFF_POOL_SIZE: u32 = PAGE_SIZE_IN_BYTES * 20;
+pub const MAX_RECURSIVE_CTE_ITERATIONS: u32 = 10_000;
+pub const PG_CLASS_FILE: &str = "pg_class.ss";
+pub const PG_ATTRIBUTE_FILE: &str = "pg_attribute.ss";
+pub const PG_INDEX_FILE: &str = "pg_index.ss";
+pub const PG_CONSTRAINT_FILE: &str = "pg_constraint.ss";
+pub const PG_FOREIGN_KEY_FILE: &str = "pg_foreign_key.ss";
+pub const PG_SEQUENCE_FILE: &str = "pg_sequence.ss";
+pub const PG_VIEW_FILE: &str = "pg_view.ss";
+pub const PG_NAMESPACE_FILE: &str = "pg_namespace.ss";
+pub const PG_PREPARED_XACTS_FILE: &str = "pg_prepared_xacts.ss";
+pub const PG_PREPARED_XACTS: &str = "pg_prepared_xacts";
+pub const PG_XACT_FILE: &str = "pg_xact.ss";
+pub const FIRST_USER_OID: i32 = 16384;
+pub const PUBLIC_NAMESPACE_OID: Oid = Oid(2200);
+pub const PUBLIC_NAMESPACE: &str = "public";
+pub const DEFAULT_DATA_DIRECTORY: &str = "data";
+pub const DEFAULT_DATABASE: &str = "postgres";
+pub const INDEX_ORDER: usize = 64;
+pub const SEQUENCE_LOG_VALUES: i32 = 32;
+pub const DEADLOCK_TIMEOUT_MS: u64 = 1000;
+pub const AUTOVACUUM_NAPTIME_MS: u64 = 60_000;
+pub const AUTOVACUUM_VACUUM_THRESHOLD: usize = 50;
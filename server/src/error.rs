@@ -0,0 +1,59 @@
+/*
+    SQLSTATE of errors which the client has to tell apart, it can retry the whole transaction
+    after serialization failure or deadlock and the statement after it was canceled by a timeout,
+    errors are strings, so the code is put in front of the message, like "40P01: Deadlock detected"
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlState {
+    SerializationFailure,
+    DeadlockDetected,
+    LockNotAvailable,
+    QueryCanceled,
+}
+
+#[allow(dead_code)]
+const SQL_STATES: &[SqlState] = &[
+    SqlState::SerializationFailure,
+    SqlState::DeadlockDetected,
+    SqlState::LockNotAvailable,
+    SqlState::QueryCanceled,
+];
+
+impl SqlState {
+    pub fn code(self) -> &'static str {
+        match self {
+            SqlState::SerializationFailure => "40001",
+            SqlState::DeadlockDetected => "40P01",
+            SqlState::LockNotAvailable => "55P03",
+            SqlState::QueryCanceled => "57014",
+        }
+    }
+
+    pub fn error(self, message: &str) -> String {
+        format!("{}: {}", self.code(), message)
+    }
+
+    // code of the error, errors without a code are None
+    #[allow(dead_code)]
+    pub fn of(error: &str) -> Option<SqlState> {
+        let (code, _) = error.split_once(": ")?;
+        SQL_STATES
+            .iter()
+            .copied()
+            .find(|state| state.code() == code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_error_has_code_in_front_it_should_be_recognized() {
+        let error = SqlState::DeadlockDetected.error("Deadlock detected");
+
+        assert_eq!(error, "40P01: Deadlock detected");
+        assert_eq!(SqlState::of(&error), Some(SqlState::DeadlockDetected));
+        assert_eq!(SqlState::of("Deadlock detected: 40P01"), None);
+    }
+}
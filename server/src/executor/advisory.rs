@@ -0,0 +1,319 @@
+use crate::{
+    information_schema::SData, lock_manager::lock::AdvisoryKey, parser::ast::Expression,
+    storage::Database,
+};
+
+use super::expression::{evaluate, map_children};
+
+/*
+    advisory locks lock keys chosen by the application, they can be called only in the select list,
+    where they are called once for the statement:
+        pg_advisory_lock(key)           waits for the lock of the session
+        pg_try_advisory_lock(key)       takes the lock of the session only if it's free, returns whether it did
+        pg_advisory_unlock(key)         releases the lock of the session, false when the session doesn't hold it
+        pg_advisory_unlock_all()        releases all locks of the session
+        pg_advisory_xact_lock(key)      waits for the lock, which is held until the transaction ends
+        pg_try_advisory_xact_lock(key)  takes the lock of the transaction only if it's free
+    lock of the session is held until it's unlocked as many times as it was taken, or the session ends,
+    key is bigint given by INT or by text with 64-bit integer, or a pair of INTs,
+    pair is a key of its own keyspace, so (0, 42) is another lock than 42
+*/
+const ADVISORY_FUNCTIONS: &[&str] = &[
+    "pg_advisory_lock",
+    "pg_try_advisory_lock",
+    "pg_advisory_unlock",
+    "pg_advisory_unlock_all",
+    "pg_advisory_xact_lock",
+    "pg_try_advisory_xact_lock",
+];
+
+pub fn is_advisory_function(name: &str) -> bool {
+    ADVISORY_FUNCTIONS.contains(&name)
+}
+
+/*
+    every call of advisory function is replaced by reference to hidden column with its result,
+    arguments of the call can reference only hidden columns of earlier calls
+*/
+pub fn call_advisory_functions(
+    expression: &Expression,
+    columns: &mut Vec<String>,
+    results: &mut Vec<SData>,
+    database: &mut Database,
+) -> Result<Expression, String> {
+    match expression {
+        Expression::Function {
+            name, arguments, ..
+        } if is_advisory_function(name) => {
+            let mut values = Vec::with_capacity(arguments.len());
+            for argument in arguments {
+                let argument = call_advisory_functions(argument, columns, results, database)?;
+                values.push(evaluate(&argument, columns, results)?);
+            }
+            let value = match (name.as_str(), values.as_slice()) {
+                ("pg_advisory_unlock_all", []) => {
+                    database.advisory_unlock_all()?;
+                    SData::NULL
+                }
+                (_, [SData::NULL, ..]) | (_, [_, SData::NULL]) => SData::NULL,
+                (name, [key]) => call(name, AdvisoryKey::Single(bigint(key)?), database)?,
+                (name, [SData::INT(high), SData::INT(low)]) => {
+                    call(name, AdvisoryKey::Pair(*high, *low), database)?
+                }
+                (name, values) => {
+                    return Err(format!(
+                        "Function {} can't be called with arguments {:?}",
+                        name, values
+                    ))
+                }
+            };
+            columns.push(format!("#advisory{}", results.len()));
+            results.push(value);
+            Ok(Expression::Column(columns[columns.len() - 1].clone()))
+        }
+        other => map_children(other, &mut |child| {
+            call_advisory_functions(child, columns, results, database)
+        }),
+    }
+}
+
+// integer literals are 32-bit, so larger key is given as text
+fn bigint(key: &SData) -> Result<i64, String> {
+    match key {
+        SData::INT(key) => Ok(*key as i64),
+        SData::STRING(key) => key
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid input syntax for type bigint: \"{}\"", key)),
+        other => Err(format!("Advisory lock key can't be {:?}", other)),
+    }
+}
+
+fn call(name: &str, key: AdvisoryKey, database: &mut Database) -> Result<SData, String> {
+    Ok(match name {
+        "pg_advisory_lock" => {
+            database.advisory_lock(key, true, true)?;
+            SData::NULL
+        }
+        "pg_advisory_xact_lock" => {
+            database.advisory_lock(key, false, true)?;
+            SData::NULL
+        }
+        "pg_try_advisory_lock" => SData::BOOL(database.advisory_lock(key, true, false)?),
+        "pg_try_advisory_xact_lock" => SData::BOOL(database.advisory_lock(key, false, false)?),
+        "pg_advisory_unlock" => SData::BOOL(database.advisory_unlock(key)?),
+        _ => return Err(format!("Function {} can't be called with a key", name)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::end_session,
+        storage::Session,
+        test_support::{open_database, run_in, select_in},
+    };
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn when_session_holds_advisory_lock_other_sessions_should_not_get_it_until_unlocked() {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+
+        assert_eq!(
+            select_in(
+                &mut database,
+                first,
+                "SELECT pg_try_advisory_lock(42), pg_try_advisory_lock(\"42\") AS again;"
+            ),
+            vec![vec![SData::BOOL(true), SData::BOOL(true)]]
+        );
+        // lock of the session outlives the transaction of the statement which took it
+        assert_eq!(
+            select_in(&mut database, second, "SELECT pg_try_advisory_lock(42);"),
+            vec![vec![SData::BOOL(false)]]
+        );
+        // key given by two INTs is in its own keyspace
+        assert_eq!(
+            select_in(
+                &mut database,
+                second,
+                "SELECT pg_try_advisory_lock(0, 42), pg_advisory_unlock(0, 42);"
+            ),
+            vec![vec![SData::BOOL(true), SData::BOOL(true)]]
+        );
+        assert_eq!(
+            run_in(&mut database, second, "SELECT pg_advisory_lock(42);"),
+            Err("Could not obtain advisory lock 42".to_string())
+        );
+        assert!(database
+            .locks
+            .is_waiting(second.transaction.as_ref().unwrap().id)
+            .unwrap());
+        assert_eq!(
+            select_in(&mut database, first, "SELECT pg_advisory_unlock(42);"),
+            vec![vec![SData::BOOL(true)]]
+        );
+        assert!(database
+            .locks
+            .is_waiting(second.transaction.as_ref().unwrap().id)
+            .unwrap());
+        select_in(&mut database, first, "SELECT pg_advisory_unlock(42);");
+
+        assert_eq!(
+            select_in(&mut database, second, "SELECT pg_advisory_lock(42);"),
+            vec![vec![SData::NULL]]
+        );
+        assert_eq!(
+            second.advisory_locks.get(&AdvisoryKey::Single(42)),
+            Some(&1)
+        );
+        assert_eq!(
+            select_in(&mut database, first, "SELECT pg_advisory_unlock(42);"),
+            vec![vec![SData::BOOL(false)]]
+        );
+        let database = Mutex::new(database);
+        end_session(&database, second).unwrap();
+        let mut database = database.into_inner().unwrap();
+        assert_eq!(
+            select_in(
+                &mut database,
+                first,
+                "SELECT pg_try_advisory_lock(4 * 10 + 2);"
+            ),
+            vec![vec![SData::BOOL(true)]]
+        );
+    }
+
+    #[test]
+    fn when_key_is_larger_than_int_it_should_be_given_as_text_and_not_truncated() {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+
+        assert_eq!(
+            select_in(
+                &mut database,
+                first,
+                "SELECT pg_try_advisory_lock(\"9000000000\");"
+            ),
+            vec![vec![SData::BOOL(true)]]
+        );
+        // 9000000000 mod 2^32
+        assert_eq!(
+            select_in(
+                &mut database,
+                second,
+                "SELECT pg_try_advisory_lock(\"9000000000\"), pg_try_advisory_lock(410065408);"
+            ),
+            vec![vec![SData::BOOL(false), SData::BOOL(true)]]
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                second,
+                "SELECT pg_try_advisory_lock(\"key\");"
+            ),
+            Err("Invalid input syntax for type bigint: \"key\"".to_string())
+        );
+    }
+
+    #[test]
+    fn when_query_reads_many_rows_advisory_function_should_be_called_once() {
+        let (_directory, mut database) = open_database();
+        let session = &mut Session::default();
+        for sql in [
+            "CREATE TABLE jobs (id INT PRIMARY KEY);",
+            "INSERT INTO jobs (id) VALUES (1), (2), (3);",
+        ] {
+            run_in(&mut database, session, sql).unwrap();
+        }
+
+        assert_eq!(
+            select_in(
+                &mut database,
+                session,
+                "SELECT id, pg_try_advisory_lock(7) FROM jobs WHERE id > 1;"
+            )
+            .len(),
+            2
+        );
+        assert_eq!(
+            session.advisory_locks.get(&AdvisoryKey::Single(7)),
+            Some(&1)
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                session,
+                "SELECT pg_try_advisory_lock(id) FROM jobs;"
+            ),
+            Err("No column named 'id'".to_string())
+        );
+    }
+
+    #[test]
+    fn when_transaction_ends_its_advisory_locks_should_be_released() {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        run_in(&mut database, first, "BEGIN;").unwrap();
+
+        assert_eq!(
+            select_in(
+                &mut database,
+                first,
+                "SELECT pg_advisory_xact_lock(7), pg_try_advisory_xact_lock(8);"
+            ),
+            vec![vec![SData::NULL, SData::BOOL(true)]]
+        );
+        assert_eq!(
+            select_in(
+                &mut database,
+                second,
+                "SELECT pg_try_advisory_xact_lock(7), pg_try_advisory_lock(9);"
+            ),
+            vec![vec![SData::BOOL(false), SData::BOOL(true)]]
+        );
+        // transaction lock can't be unlocked, only lock of the session can
+        assert_eq!(
+            select_in(&mut database, first, "SELECT pg_advisory_unlock(8);"),
+            vec![vec![SData::BOOL(false)]]
+        );
+        assert_eq!(
+            run_in(&mut database, first, "SELECT pg_advisory_lock(9);"),
+            Err("Could not obtain advisory lock 9".to_string())
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                first,
+                "SELECT 1 WHERE pg_try_advisory_lock(1);"
+            ),
+            Err("Function pg_try_advisory_lock is allowed only in select list".to_string())
+        );
+        select_in(&mut database, second, "SELECT pg_advisory_unlock_all();");
+        assert_eq!(
+            select_in(&mut database, first, "SELECT pg_advisory_lock(9);"),
+            vec![vec![SData::NULL]]
+        );
+        assert_eq!(
+            run_in(&mut database, first, "PREPARE TRANSACTION \"worker\";"),
+            Err("Cannot PREPARE a transaction while the session holds advisory locks".to_string())
+        );
+        run_in(&mut database, first, "ROLLBACK;").unwrap();
+
+        assert_eq!(
+            select_in(
+                &mut database,
+                second,
+                "SELECT pg_try_advisory_lock(7), pg_try_advisory_lock(8), pg_try_advisory_lock(9);"
+            ),
+            vec![vec![
+                SData::BOOL(true),
+                SData::BOOL(true),
+                SData::BOOL(false)
+            ]]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+
+use crate::{
+    information_schema::SData,
+    parser::ast::{Expression, SelectItem},
+};
+
+use super::{
+    expression::{children, evaluate, map_children},
+    Relation,
+};
+
+pub fn is_aggregate(name: &str) -> bool {
+    matches!(name, "count" | "sum" | "avg" | "min" | "max")
+}
+
+pub fn contains_aggregate(expression: &Expression) -> bool {
+    match expression {
+        Expression::Function {
+            name, over: None, ..
+        } if is_aggregate(name) => true,
+        // arguments of window functions are evaluated per row
+        Expression::Function { over: Some(_), .. } => false,
+        other => children(other).into_iter().any(contains_aggregate),
+    }
+}
+
+/*
+    select list with aggregate function calls (without OVER) and no GROUP BY aggregates all rows into one:
+        1. aggregate calls are computed over all rows and replaced with references to hidden columns
+        2. select items are evaluated on the single row of aggregated values
+    columns referenced outside of aggregate calls aren't allowed
+*/
+pub fn aggregate_relation(relation: &Relation, items: &[SelectItem]) -> Result<Vec<SData>, String> {
+    let mut calls = vec![];
+    let expressions = items
+        .iter()
+        .map(|item| extract_aggregate_calls(&item.expression, &mut calls))
+        .collect::<Result<Vec<Expression>, String>>()?;
+    let rows: Vec<&[SData]> = relation.rows.iter().map(|row| row.as_slice()).collect();
+    let mut columns = Vec::with_capacity(calls.len());
+    let mut aggregated = Vec::with_capacity(calls.len());
+    for (index, (name, distinct, arguments)) in calls.iter().enumerate() {
+        columns.push(aggregate_column_name(index));
+        aggregated.push(aggregate(
+            name,
+            *distinct,
+            arguments,
+            &rows,
+            &relation.columns,
+        )?);
+    }
+    expressions
+        .iter()
+        .map(|expression| evaluate(expression, &columns, &aggregated))
+        .collect()
+}
+
+fn aggregate_column_name(index: usize) -> String {
+    format!("#aggregate{}", index)
+}
+
+fn extract_aggregate_calls(
+    expression: &Expression,
+    calls: &mut Vec<(String, bool, Vec<Expression>)>,
+) -> Result<Expression, String> {
+    match expression {
+        Expression::Function {
+            name,
+            distinct,
+            arguments,
+            over: None,
+        } if is_aggregate(name) => {
+            calls.push((name.clone(), *distinct, arguments.clone()));
+            Ok(Expression::Column(aggregate_column_name(calls.len() - 1)))
+        }
+        Expression::Column(name) => Err(format!(
+            "Column {} must be used in an aggregate function",
+            name
+        )),
+        Expression::Function {
+            name,
+            over: Some(_),
+            ..
+        } => Err(format!(
+            "Window function {} can't be used together with aggregates",
+            name
+        )),
+        other => map_children(other, &mut |child| extract_aggregate_calls(child, calls)),
+    }
+}
+
+// aggregates ignore NULL values, avg is integer average
+pub fn aggregate(
+    name: &str,
+    distinct: bool,
+    arguments: &[Expression],
+    rows: &[&[SData]],
+    columns: &[String],
+) -> Result<SData, String> {
+    let argument = match arguments.first() {
+        Some(argument) => argument,
+        None if name == "count" => return Ok(SData::INT(rows.len() as i32)),
+        None => return Err(format!("{} expects one argument", name)),
+    };
+    let mut values = Vec::with_capacity(rows.len());
+    let mut seen = HashSet::new();
+    for row in rows {
+        match evaluate(argument, columns, row)? {
+            SData::NULL => (),
+            value if distinct => {
+                if seen.insert(value.clone()) {
+                    values.push(value);
+                }
+            }
+            value => values.push(value),
+        }
+    }
+    match name {
+        "count" => Ok(SData::INT(values.len() as i32)),
+        "min" | "max" => {
+            let mut result: Option<SData> = None;
+            for value in values {
+                let replace = match &result {
+                    None => true,
+                    Some(current) => {
+                        let ordering = value
+                            .compare(current)
+                            .ok_or_else(|| format!("Can't compare values in {}", name))?;
+                        if name == "min" {
+                            ordering.is_lt()
+                        } else {
+                            ordering.is_gt()
+                        }
+                    }
+                };
+                if replace {
+                    result = Some(value);
+                }
+            }
+            Ok(result.unwrap_or(SData::NULL))
+        }
+        _ => {
+            if values.is_empty() {
+                return Ok(SData::NULL);
+            }
+            let mut sum: i32 = 0;
+            for value in &values {
+                match value {
+                    SData::INT(value) => {
+                        sum = sum
+                            .checked_add(*value)
+                            .ok_or_else(|| format!("Integer out of range in {}", name))?;
+                    }
+                    other => return Err(format!("{} can't be applied to {:?}", name, other)),
+                }
+            }
+            if name == "sum" {
+                Ok(SData::INT(sum))
+            } else {
+                Ok(SData::INT(sum / values.len() as i32))
+            }
+        }
+    }
+}
@@ -0,0 +1,499 @@
+use std::collections::HashSet;
+
+use crate::{
+    bptree::page::TuplePointer,
+    catalog::{
+        tables::{ConstraintKind, PgAttribute, PgClass},
+        Oid,
+    },
+    information_schema::{SData, SType},
+    lock_manager::lock::LockMode,
+    parser::{ast::Expression, parse_expression_text},
+    storage::Database,
+};
+
+use super::{
+    aggregate::contains_aggregate,
+    expression::{children, evaluate, resolve_column, unqualified_name},
+    functions::type_name,
+    sequences::evaluate_with_sequences,
+};
+
+pub const MAX_VARCHAR_LENGTH: usize = 256;
+
+/*
+    constraints of one table, loaded from the catalog once per statement:
+        primary key columns can't be NULL
+        PRIMARY KEY and UNIQUE keys are checked with the index backing the constraint,
+        key with any NULL value never conflicts with other keys
+        CHECK is violated only when it evaluates to FALSE, NULL passes
+*/
+pub struct TableConstraints {
+    table: Oid,
+    table_name: String,
+    // qualified with the table name, like columns of scanned relation
+    pub columns: Vec<String>,
+    types: Vec<SType>,
+    not_null: Vec<(usize, String)>,
+    unique: Vec<(Oid, Vec<usize>, String)>,
+    checks: Vec<(String, Expression)>,
+}
+
+impl TableConstraints {
+    pub fn load(database: &Database, table: &PgClass) -> Result<Self, String> {
+        let attributes = database.catalog.attributes_of(table.oid);
+        let mut constraints = TableConstraints {
+            table: table.oid,
+            table_name: table.name.clone(),
+            columns: attributes
+                .iter()
+                .map(|attribute| format!("{}.{}", table.name, attribute.name))
+                .collect(),
+            types: attributes
+                .iter()
+                .map(|attribute| attribute.data_type)
+                .collect(),
+            not_null: vec![],
+            unique: vec![],
+            checks: vec![],
+        };
+        for constraint in database.catalog.constraints_of(table.oid) {
+            match &constraint.kind {
+                ConstraintKind::PrimaryKey { index } | ConstraintKind::Unique { index } => {
+                    let columns = key_columns(database, *index)?;
+                    if let ConstraintKind::PrimaryKey { .. } = constraint.kind {
+                        for column in &columns {
+                            constraints
+                                .not_null
+                                .push((*column, constraint.name.clone()));
+                        }
+                    }
+                    constraints
+                        .unique
+                        .push((*index, columns, constraint.name.clone()));
+                }
+                ConstraintKind::Check { expression } => constraints.checks.push((
+                    constraint.name.clone(),
+                    parse_expression_text(expression.clone())?,
+                )),
+                // checked by foreign_keys after rows are written
+                ConstraintKind::ForeignKey => (),
+            }
+        }
+        // unique index created by CREATE UNIQUE INDEX is reported by its own name
+        for index in database.catalog.indexes_of(table.oid) {
+            if index.unique
+                && constraints
+                    .unique
+                    .iter()
+                    .all(|(oid, _, _)| *oid != index.oid)
+            {
+                constraints.unique.push((
+                    index.oid,
+                    key_columns(database, index.oid)?,
+                    database.catalog.class(index.oid)?.name.clone(),
+                ));
+            }
+        }
+        Ok(constraints)
+    }
+
+    // checks types of values, NOT NULL of primary key and CHECK constraints
+    pub fn check_row(&self, row: &[SData]) -> Result<(), String> {
+        for ((value, data_type), column) in row.iter().zip(&self.types).zip(&self.columns) {
+            check_type(value, data_type, column)?;
+        }
+        for (column, name) in &self.not_null {
+            if row[*column] == SData::NULL {
+                return Err(format!(
+                    "null value in column \"{}\" of relation \"{}\" violates not-null constraint \"{}\"",
+                    unqualified_name(&self.columns[*column]),
+                    self.table_name,
+                    name
+                ));
+            }
+        }
+        for (name, expression) in &self.checks {
+            if evaluate(expression, &self.columns, row)? == SData::BOOL(false) {
+                return Err(format!(
+                    "new row for relation \"{}\" violates check constraint \"{}\"",
+                    self.table_name, name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        rows are new versions of rows, replaced is pointer to the old version which is going to be deleted,
+        their keys have to be unique among themselves and among the rows which stay in the table,
+        conflicting row is locked first, so a row of uncommitted transaction is waited for, it may be rolled back
+    */
+    pub fn check_unique(
+        &self,
+        database: &Database,
+        rows: &[(Option<TuplePointer>, Vec<SData>)],
+    ) -> Result<(), String> {
+        let replaced: HashSet<TuplePointer> =
+            rows.iter().filter_map(|(pointer, _)| *pointer).collect();
+        for (index, columns, name) in &self.unique {
+            let mut seen = HashSet::new();
+            for (_, row) in rows {
+                let key: Vec<SData> = columns.iter().map(|column| row[*column].clone()).collect();
+                if key.contains(&SData::NULL) {
+                    continue;
+                }
+                let conflicting: Vec<TuplePointer> = database
+                    .index_conflicts(*index, &key)?
+                    .into_iter()
+                    .filter(|pointer| !replaced.contains(pointer))
+                    .collect();
+                for pointer in &conflicting {
+                    database.lock_row(self.table, *pointer, LockMode::Shared)?;
+                }
+                let duplicate = !conflicting.is_empty() || !seen.insert(key);
+                if duplicate {
+                    return Err(format!(
+                        "duplicate key value violates unique constraint \"{}\"",
+                        name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// positions of indexed columns in the row, in the order of the index key
+fn key_columns(database: &Database, index: Oid) -> Result<Vec<usize>, String> {
+    Ok(database
+        .catalog
+        .indexes
+        .iter()
+        .find(|pg_index| pg_index.oid == index)
+        .ok_or_else(|| format!("Index with oid {} is missing", index.0))?
+        .indkey
+        .iter()
+        .map(|num| *num as usize - 1)
+        .collect())
+}
+
+// value of DEFAULT expression of the column, NULL without default
+pub fn default_value(attribute: &PgAttribute, database: &mut Database) -> Result<SData, String> {
+    match &attribute.default {
+        Some(default) => {
+            evaluate_with_sequences(&parse_expression_text(default.clone())?, &[], &[], database)
+        }
+        None => Ok(SData::NULL),
+    }
+}
+
+fn check_type(value: &SData, data_type: &SType, column: &str) -> Result<(), String> {
+    match (value, data_type) {
+        (SData::NULL, _) | (SData::INT(_), SType::INT) => Ok(()),
+        (SData::STRING(value), SType::STRING) if value.len() > MAX_VARCHAR_LENGTH => Err(format!(
+            "value too long for column \"{}\" of type VARCHAR({})",
+            unqualified_name(column),
+            MAX_VARCHAR_LENGTH
+        )),
+        (SData::STRING(_), SType::STRING) => Ok(()),
+        (value, data_type) => Err(format!(
+            "column \"{}\" is of type {} but expression is of type {}",
+            unqualified_name(column),
+            match data_type {
+                SType::INT => "INT",
+                SType::STRING => "VARCHAR",
+            },
+            type_name(value)
+        )),
+    }
+}
+
+// CHECK and DEFAULT can't contain aggregates or window functions
+pub fn validate_constraint_expression(
+    expression: &Expression,
+    columns: &[String],
+    kind: &str,
+) -> Result<(), String> {
+    if contains_aggregate(expression) || contains_window_function(expression) {
+        return Err(format!(
+            "Aggregate and window functions are not allowed in {} expression",
+            kind
+        ));
+    }
+    for column in referenced_columns(expression) {
+        resolve_column(columns, column)?;
+    }
+    Ok(())
+}
+
+fn contains_window_function(expression: &Expression) -> bool {
+    match expression {
+        Expression::Function { over: Some(_), .. } => true,
+        other => children(other).into_iter().any(contains_window_function),
+    }
+}
+
+// column names in the order of their appearance in the expression
+pub fn referenced_columns(expression: &Expression) -> Vec<&str> {
+    match expression {
+        Expression::Column(name) => vec![name.as_str()],
+        other => children(other)
+            .into_iter()
+            .flat_map(referenced_columns)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::QueryResult,
+        test_support::{open_database, run, select},
+    };
+
+    use super::*;
+
+    fn users(database: &mut Database) {
+        run(
+            database,
+            "CREATE TABLE users (id INT PRIMARY KEY, email VARCHAR UNIQUE, \
+            age INT DEFAULT 18 CHECK (age >= 18), CONSTRAINT short_email CHECK (length(email) < 10));",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn when_insert_violates_constraint_error_should_name_the_constraint() {
+        let (_directory, mut database) = open_database();
+        users(&mut database);
+
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO users (id, email) VALUES (1, \"a@b\");"
+            ),
+            Ok(QueryResult::Command("INSERT 0 1".to_string()))
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO users (id, email) VALUES (2, \"a@b\");"
+            ),
+            Err("duplicate key value violates unique constraint \"users_email_key\"".to_string())
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO users (id, email) VALUES (1, \"c@d\");"
+            ),
+            Err("duplicate key value violates unique constraint \"users_pkey\"".to_string())
+        );
+        assert_eq!(
+            run(&mut database, "INSERT INTO users (id, age) VALUES (2, 17);"),
+            Err(
+                "new row for relation \"users\" violates check constraint \"users_age_check\""
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO users (id, email) VALUES (2, \"long@email.com\");"
+            ),
+            Err(
+                "new row for relation \"users\" violates check constraint \"short_email\""
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run(&mut database, "INSERT INTO users (email) VALUES (\"e@f\");"),
+            Err("null value in column \"id\" of relation \"users\" violates not-null constraint \"users_pkey\"".to_string())
+        );
+        assert!(run(
+            &mut database,
+            "INSERT INTO users (id, age) VALUES (2, \"old\");"
+        )
+        .is_err());
+        // NULL passes both UNIQUE and CHECK
+        run(
+            &mut database,
+            "INSERT INTO users (id, age) VALUES (3, NULL);",
+        )
+        .unwrap();
+        run(&mut database, "INSERT INTO users (id) VALUES (4);").unwrap();
+
+        assert_eq!(
+            select(&mut database, "SELECT id, email, age FROM users;"),
+            vec![
+                vec![
+                    SData::INT(1),
+                    SData::STRING("a@b".to_string()),
+                    SData::INT(18)
+                ],
+                vec![SData::INT(3), SData::NULL, SData::NULL],
+                vec![SData::INT(4), SData::NULL, SData::INT(18)],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_update_violates_constraint_no_row_should_be_changed() {
+        let (_directory, mut database) = open_database();
+        users(&mut database);
+        run(
+            &mut database,
+            "INSERT INTO users (id, email) VALUES (1, \"a\");",
+        )
+        .unwrap();
+        run(
+            &mut database,
+            "INSERT INTO users (id, email) VALUES (2, \"b\");",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(&mut database, "UPDATE users SET email = \"same\";"),
+            Err("duplicate key value violates unique constraint \"users_email_key\"".to_string())
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "UPDATE users SET age = age - 1 WHERE id = 2;"
+            ),
+            Err(
+                "new row for relation \"users\" violates check constraint \"users_age_check\""
+                    .to_string()
+            )
+        );
+        // keys can be swapped because rows are checked against the table after the update
+        assert_eq!(
+            run(
+                &mut database,
+                "UPDATE users SET id = 3 - id, age = age + id;"
+            ),
+            Ok(QueryResult::Command("UPDATE 2".to_string()))
+        );
+
+        assert_eq!(
+            select(
+                &mut database,
+                "SELECT id, email, age FROM users WHERE email = \"a\" OR id = 1;"
+            ),
+            vec![
+                vec![
+                    SData::INT(2),
+                    SData::STRING("a".to_string()),
+                    SData::INT(19)
+                ],
+                vec![
+                    SData::INT(1),
+                    SData::STRING("b".to_string()),
+                    SData::INT(20)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_database_is_reopened_constraints_should_still_be_enforced() {
+        let directory = tempfile::tempdir().unwrap();
+        {
+            let mut database = Database::open(directory.path()).unwrap();
+            users(&mut database);
+            run(
+                &mut database,
+                "INSERT INTO users (id, email) VALUES (1, \"a\");",
+            )
+            .unwrap();
+        }
+
+        let mut database = Database::open(directory.path()).unwrap();
+
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO users (id, email) VALUES (2, \"a\");"
+            ),
+            Err("duplicate key value violates unique constraint \"users_email_key\"".to_string())
+        );
+        assert_eq!(
+            run(&mut database, "INSERT INTO users (id, age) VALUES (2, 1);"),
+            Err(
+                "new row for relation \"users\" violates check constraint \"users_age_check\""
+                    .to_string()
+            )
+        );
+        assert!(run(&mut database, "CREATE TABLE users (id INT PRIMARY KEY);").is_err());
+        assert_eq!(
+            select(&mut database, "SELECT id, age FROM users;"),
+            vec![vec![SData::INT(1), SData::INT(18)]]
+        );
+    }
+
+    #[test]
+    fn when_key_has_multiple_columns_only_whole_key_should_be_unique() {
+        let (_directory, mut database) = open_database();
+        run(
+            &mut database,
+            "CREATE TABLE grades (student INT, course INT, grade INT, PRIMARY KEY (student, course));",
+        )
+        .unwrap();
+        for sql in [
+            "INSERT INTO grades (student, course, grade) VALUES (1, 2, 5);",
+            "INSERT INTO grades (student, course, grade) VALUES (1, 1, 4);",
+            "INSERT INTO grades (student, course, grade) VALUES (2, 1, 4);",
+        ] {
+            run(&mut database, sql).unwrap();
+        }
+
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO grades (student, course, grade) VALUES (1, 2, 3);"
+            ),
+            Err("duplicate key value violates unique constraint \"grades_pkey\"".to_string())
+        );
+        assert_eq!(
+            run(&mut database, "INSERT INTO grades (student) VALUES (3);"),
+            Err("null value in column \"course\" of relation \"grades\" violates not-null constraint \"grades_pkey\"".to_string())
+        );
+        let mut courses = select(
+            &mut database,
+            "SELECT course FROM grades WHERE student = 1;",
+        );
+        courses.sort();
+        assert_eq!(courses, vec![vec![SData::INT(1)], vec![SData::INT(2)]]);
+        assert_eq!(
+            run(
+                &mut database,
+                "CREATE UNIQUE INDEX course_grade ON grades (course, grade);"
+            ),
+            Err("Could not create unique index \"course_grade\"".to_string())
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "CREATE UNIQUE INDEX grade_course ON grades (grade, course);"
+            ),
+            Err("Could not create unique index \"grade_course\"".to_string())
+        );
+        run(
+            &mut database,
+            "CREATE UNIQUE INDEX student_grade ON grades (student, grade);",
+        )
+        .unwrap();
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO grades (student, course, grade) VALUES (2, 2, 4);"
+            ),
+            Err("duplicate key value violates unique constraint \"student_grade\"".to_string())
+        );
+        run(
+            &mut database,
+            "INSERT INTO grades (student, course, grade) VALUES (2, 2, NULL);",
+        )
+        .unwrap();
+    }
+}
@@ -1,53 +1,446 @@
-use std::{ops::Deref, rc::Rc};
-use crate::buff::buff_pool::BuffPool;
-
-// should i use data directly from page?
+use std::collections::HashSet;
 
 use crate::{
-    bptree::page::{NewInnerNode, NewLeafNode},
-    constants::PG_CLASS_NAME_INDEX_FILE,
-    information_schema::SData,
-    page::Page,
-    parser::ast::SqlStatement,
+    catalog::{
+        tables::{
+            ConstraintKind, Identity, Kind, PgAttribute, PgClass, PgConstraint, PgForeignKey,
+            PgIndex,
+        },
+        Catalog, Oid,
+    },
+    information_schema::SType,
+    lock_manager::lock::{LockMode, LockTarget},
+    parser::ast::{
+        ColumnDefinition, ColumnIdentity, DataType, SequenceOptions, TableConstraint,
+        TableConstraintKind,
+    },
+    storage::{index_key, Database},
+};
+
+use super::{
+    constraints::{referenced_columns, validate_constraint_expression},
+    sequences::{add_sequence, nextval_expression, sequence_name},
 };
 
-fn create(statemant: crate::SqlStatement, pool: &mut BuffPool) -> Result<(), String> {
-    if let SqlStatement::CreateTable {
-        table_name,
-        primary_key,
-        columns,
-    } = statemant
+/*
+    creates table with its columns and constraints in the catalog,
+    PRIMARY KEY and UNIQUE constraints get unique index named the same as the constraint,
+    unnamed constraints are named like in postgres: table_pkey, table_column_key, table_column_check,
+    SERIAL and identity columns get their own sequence named table_column_seq
+*/
+pub fn create_table(
+    table_name: &str,
+    columns: &[ColumnDefinition],
+    constraints: &[TableConstraint],
+    database: &mut Database,
+) -> Result<(), String> {
+    let search_path = database.session.search_path.clone();
+    let (namespace, table_name) = database
+        .catalog
+        .creation_namespace(table_name, &search_path)?;
+    if database
+        .catalog
+        .find_in_namespace(namespace, table_name)
+        .is_some()
     {
-        let descriptor = pool.get_descriptor(PG_CLASS_NAME_INDEX_FILE, 1)?;
-        descriptor.lock_shared();
-        let mut node = descriptor.get_buff().get_first_tuple()?;
-        loop {
-            if NewInnerNode::buffer_fits_type(node) {
-                let inner_node = NewInnerNode::deserialize(node)?;
-                let next_node_pointer =
-                    inner_node.get_node_pointer_for_key(&SData::STRING(table_name))?;
-                let descriptor_with_pointed_page =
-                    pool.get_descriptor(PG_CLASS_NAME_INDEX_FILE, next_node_pointer.page)?;
-                descriptor.unlock_shared();
-                descriptor_with_pointed_page.lock_shared();
-                descriptor = descriptor_with_pointed_page;
-                node = descriptor_with_pointed_page.get_buff().get_tuple(next_node_pointer.offset)?;
-            } else {
-                let leaf_node = NewLeafNode::deserialize(node)?;
-                if let Ok(_) = leaf_node
-                    .keys
-                    .binary_search(&SData::STRING(table_name.clone()))
-                {
-                    descriptor.unlock_shared();
-                    return Err("pg_class with the given table name already exists".to_string());
+        return Err(format!("Relation \"{}\" already exists", table_name));
+    }
+    if columns.is_empty() {
+        return Err("At least one column must be provided".to_string());
+    }
+    let mut names = HashSet::new();
+    for column in columns {
+        if !names.insert(column.name.as_str()) {
+            return Err(format!(
+                "Column \"{}\" specified more than once",
+                column.name
+            ));
+        }
+    }
+    let primary_keys = constraints
+        .iter()
+        .filter(|constraint| matches!(constraint.kind, TableConstraintKind::PrimaryKey(_)))
+        .count();
+    if primary_keys == 0 {
+        return Err(format!("Table \"{}\" must have a primary key", table_name));
+    }
+    if primary_keys > 1 {
+        return Err(format!(
+            "Multiple primary keys for table \"{}\" are not allowed",
+            table_name
+        ));
+    }
+    let qualified_columns: Vec<String> = columns
+        .iter()
+        .map(|column| format!("{}.{}", table_name, column.name))
+        .collect();
+
+    // catalog is changed only when the whole definition is valid
+    let mut catalog = database.catalog.clone();
+    let table = catalog.allocate_oid();
+    catalog.classes.push(PgClass {
+        oid: table,
+        name: table_name.to_string(),
+        kind: Kind::NormalTable,
+        namespace,
+    });
+    for (position, column) in columns.iter().enumerate() {
+        if let Some(default) = &column.default {
+            if !referenced_columns(default).is_empty() {
+                return Err(format!(
+                    "Cannot use column reference in DEFAULT expression of column \"{}\"",
+                    column.name
+                ));
+            }
+            validate_constraint_expression(default, &qualified_columns, "DEFAULT")?;
+        }
+        let mut default = column.default.as_ref().map(|default| default.to_string());
+        let mut identity = Identity::None;
+        if let Some(column_identity) = &column.identity {
+            if default.is_some() {
+                return Err(format!(
+                    "Both default and identity specified for column \"{}\"",
+                    column.name
+                ));
+            }
+            if column.data_type != DataType::Int32 {
+                return Err(format!(
+                    "Identity column \"{}\" must be of type INT",
+                    column.name
+                ));
+            }
+            let options = match column_identity {
+                ColumnIdentity::Serial => SequenceOptions::default(),
+                ColumnIdentity::Always(options) | ColumnIdentity::ByDefault(options) => {
+                    options.clone()
+                }
+            };
+            identity = match column_identity {
+                ColumnIdentity::Serial => Identity::None,
+                ColumnIdentity::Always(_) => Identity::Always,
+                ColumnIdentity::ByDefault(_) => Identity::ByDefault,
+            };
+            let sequence = sequence_name(&catalog, namespace, table_name, &column.name);
+            let sequence = add_sequence(&mut catalog, namespace, &sequence, &options)?;
+            // qualified name finds the sequence with search path of any session
+            let sequence = catalog.qualified_name(catalog.class(sequence)?);
+            default = Some(nextval_expression(&sequence).to_string());
+        }
+        catalog.attributes.push(PgAttribute {
+            owner_id: table,
+            name: column.name.clone(),
+            num: position as i32 + 1,
+            data_type: match column.data_type {
+                DataType::Int32 => SType::INT,
+                DataType::VarChar256 => SType::STRING,
+            },
+            default,
+            identity,
+        });
+    }
+    let mut indexes = vec![];
+    // foreign keys come last, so they can reference keys of the table itself
+    let is_foreign_key = |constraint: &&TableConstraint| {
+        matches!(constraint.kind, TableConstraintKind::ForeignKey(_))
+    };
+    let ordered = constraints
+        .iter()
+        .filter(|constraint| !is_foreign_key(constraint))
+        .chain(constraints.iter().filter(is_foreign_key));
+    for constraint in ordered {
+        let oid = catalog.allocate_oid();
+        let (kind, name) = match &constraint.kind {
+            TableConstraintKind::PrimaryKey(key) | TableConstraintKind::Unique(key) => {
+                let indkey = column_numbers(&catalog, table, key, "named in key")?;
+                let primary = matches!(constraint.kind, TableConstraintKind::PrimaryKey(_));
+                let name = constraint_name(
+                    &catalog,
+                    table,
+                    table_name,
+                    &constraint.name,
+                    if primary {
+                        format!("{}_pkey", table_name)
+                    } else {
+                        format!("{}_{}_key", table_name, key.join("_"))
+                    },
+                )?;
+                let pg_index = add_index(&mut catalog, table, &name, indkey, true)?;
+                let index = pg_index.oid;
+                indexes.push(pg_index);
+                if primary {
+                    (ConstraintKind::PrimaryKey { index }, name)
+                } else {
+                    (ConstraintKind::Unique { index }, name)
+                }
+            }
+            TableConstraintKind::Check(expression) => {
+                validate_constraint_expression(expression, &qualified_columns, "CHECK")?;
+                let default_name = match referenced_columns(expression).first() {
+                    Some(column) => format!(
+                        "{}_{}_check",
+                        table_name,
+                        super::expression::unqualified_name(column)
+                    ),
+                    None => format!("{}_check", table_name),
+                };
+                let name =
+                    constraint_name(&catalog, table, table_name, &constraint.name, default_name)?;
+                (
+                    ConstraintKind::Check {
+                        expression: expression.to_string(),
+                    },
+                    name,
+                )
+            }
+            TableConstraintKind::ForeignKey(foreign_key) => {
+                let referencing = column_numbers(
+                    &catalog,
+                    table,
+                    &foreign_key.columns,
+                    "referenced in foreign key constraint",
+                )?;
+                // table itself is already in the catalog, so it can be referenced
+                let referenced_table = catalog
+                    .table(&foreign_key.referenced_table, &search_path)?
+                    .oid;
+                let referenced_index = referenced_index(
+                    &catalog,
+                    referenced_table,
+                    &foreign_key.referenced_table,
+                    &foreign_key.referenced_columns,
+                )?;
+                let referenced = if foreign_key.referenced_columns.is_empty() {
+                    referenced_index.indkey.clone()
+                } else {
+                    column_numbers(
+                        &catalog,
+                        referenced_table,
+                        &foreign_key.referenced_columns,
+                        "referenced in foreign key constraint",
+                    )?
+                };
+                if referencing.len() != referenced.len() {
+                    return Err(
+                        "Number of referencing and referenced columns for foreign key disagree"
+                            .to_string(),
+                    );
+                }
+                // pairs of columns are reordered to the order of the referenced index key
+                let mut columns = Vec::with_capacity(referencing.len());
+                for num in &referenced_index.indkey {
+                    let position = referenced.iter().position(|other| other == num).unwrap();
+                    columns.push(referencing[position]);
                 }
-                break;
+                let types = |table: Oid, num: i32| {
+                    catalog
+                        .attributes_of(table)
+                        .into_iter()
+                        .find(|attribute| attribute.num == num)
+                        .map(|attribute| (attribute.name.clone(), attribute.data_type))
+                        .unwrap()
+                };
+                for (column, referenced_column) in columns.iter().zip(&referenced_index.indkey) {
+                    let (referencing_name, referencing_type) = types(table, *column);
+                    let (referenced_name, referenced_type) =
+                        types(referenced_table, *referenced_column);
+                    if referencing_type != referenced_type {
+                        return Err(format!(
+                            "Key columns \"{}\" and \"{}\" are of incompatible types",
+                            referencing_name, referenced_name
+                        ));
+                    }
+                }
+                let name = constraint_name(
+                    &catalog,
+                    table,
+                    table_name,
+                    &constraint.name,
+                    format!("{}_{}_fkey", table_name, foreign_key.columns.join("_")),
+                )?;
+                catalog.foreign_keys.push(PgForeignKey {
+                    oid,
+                    table,
+                    columns,
+                    referenced_table,
+                    referenced_columns: referenced_index.indkey.clone(),
+                    referenced_index: referenced_index.oid,
+                    on_delete: foreign_key.on_delete,
+                    on_update: foreign_key.on_update,
+                });
+                (ConstraintKind::ForeignKey, name)
+            }
+        };
+        catalog.constraints.push(PgConstraint {
+            oid,
+            name,
+            table,
+            kind,
+        });
+    }
+
+    database.catalog = catalog;
+    database.create_heap(table);
+    for index in &indexes {
+        database.build_index(index)?;
+    }
+    database.save_table(table)?;
+    database.save_catalog()
+}
+
+// unique index can't be created when the table already contains duplicate keys
+pub fn create_index(
+    name: &str,
+    table_name: &str,
+    columns: &[String],
+    unique: bool,
+    database: &mut Database,
+) -> Result<(), String> {
+    let mut catalog = database.catalog.clone();
+    let table = catalog
+        .table(table_name, &database.session.search_path)?
+        .oid;
+    // writers of the table are waited for, rows can't change while the index is built
+    database.lock(LockTarget::Table(table), LockMode::Shared)?;
+    let indkey = column_numbers(&catalog, table, columns, "named in key")?;
+    let index = add_index(&mut catalog, table, name, indkey, unique)?;
+    if unique {
+        let mut keys = HashSet::new();
+        for (_, row) in database.rows(table)? {
+            let key = index_key(&index, &row);
+            if !key.has_null() && !keys.insert(key) {
+                return Err(format!("Could not create unique index \"{}\"", name));
             }
         }
-        Ok(())
+    }
+    database.catalog = catalog;
+    database.build_index(&index)?;
+    database.save_catalog()
+}
+
+// foreign key references primary key, or columns with unique index
+fn referenced_index(
+    catalog: &Catalog,
+    table: Oid,
+    table_name: &str,
+    columns: &[String],
+) -> Result<PgIndex, String> {
+    let index = if columns.is_empty() {
+        catalog
+            .constraints_of(table)
+            .into_iter()
+            .find_map(|constraint| match constraint.kind {
+                ConstraintKind::PrimaryKey { index } => Some(index),
+                _ => None,
+            })
+            .and_then(|index| {
+                catalog
+                    .indexes
+                    .iter()
+                    .find(|pg_index| pg_index.oid == index)
+            })
     } else {
-        Err("Not given select statment to execute".to_string())
+        let mut nums = column_numbers(
+            catalog,
+            table,
+            columns,
+            "referenced in foreign key constraint",
+        )?;
+        nums.sort();
+        catalog.indexes_of(table).into_iter().find(|index| {
+            let mut indkey = index.indkey.clone();
+            indkey.sort();
+            index.unique && indkey == nums
+        })
+    };
+    index.cloned().ok_or_else(|| {
+        format!(
+            "There is no unique constraint matching given keys for referenced table \"{}\"",
+            table_name
+        )
+    })
+}
+
+// numbers of named columns of the table, in the given order
+fn column_numbers(
+    catalog: &Catalog,
+    table: Oid,
+    names: &[String],
+    usage: &str,
+) -> Result<Vec<i32>, String> {
+    let attributes = catalog.attributes_of(table);
+    let mut nums = Vec::with_capacity(names.len());
+    for name in names {
+        let num = attributes
+            .iter()
+            .find(|attribute| attribute.name == *name)
+            .ok_or_else(|| format!("Column \"{}\" {} does not exist", name, usage))?
+            .num;
+        if nums.contains(&num) {
+            return Err(format!("Column \"{}\" appears twice in key", name));
+        }
+        nums.push(num);
+    }
+    Ok(nums)
+}
+
+// index is added to the catalog as relation of its own in the schema of the table, it still has to be built
+fn add_index(
+    catalog: &mut Catalog,
+    table: Oid,
+    name: &str,
+    indkey: Vec<i32>,
+    unique: bool,
+) -> Result<PgIndex, String> {
+    let namespace = catalog.class(table)?.namespace;
+    if catalog.find_in_namespace(namespace, name).is_some() {
+        return Err(format!("Relation \"{}\" already exists", name));
+    }
+    let oid = catalog.allocate_oid();
+    catalog.classes.push(PgClass {
+        oid,
+        name: name.to_string(),
+        kind: Kind::Index,
+        namespace,
+    });
+    let index = PgIndex {
+        oid,
+        table,
+        indkey,
+        unique,
+    };
+    catalog.indexes.push(index.clone());
+    Ok(index)
+}
+
+// explicit name has to be unique in the table, generated name gets number suffix when it's taken
+fn constraint_name(
+    catalog: &Catalog,
+    table: Oid,
+    table_name: &str,
+    name: &Option<String>,
+    default_name: String,
+) -> Result<String, String> {
+    let namespace = catalog.class(table)?.namespace;
+    let taken = |name: &str| {
+        catalog
+            .constraints_of(table)
+            .iter()
+            .any(|constraint| constraint.name == name)
+    };
+    match name {
+        Some(name) if taken(name) => Err(format!(
+            "Constraint \"{}\" for relation \"{}\" already exists",
+            name, table_name
+        )),
+        Some(name) => Ok(name.clone()),
+        None => {
+            let mut candidate = default_name.clone();
+            let mut suffix = 1;
+            while taken(&candidate) || catalog.find_in_namespace(namespace, &candidate).is_some() {
+                candidate = format!("{}{}", default_name, suffix);
+                suffix += 1;
+            }
+            Ok(candidate)
+        }
     }
 }
-// provjeri da li nesto s tim imenom vec postoji
-// load first page of pg_class.name, and go to leaf which would contain that name,
@@ -0,0 +1,29 @@
+use crate::{information_schema::SData, parser::ast::Expression, storage::Database};
+
+use super::{constraints::TableConstraints, expression::evaluate_predicate, modify::delete_rows};
+
+// returns deleted rows, rows deleted by ON DELETE CASCADE aren't included
+pub fn delete(
+    table_name: &str,
+    selection: &Option<Expression>,
+    database: &mut Database,
+) -> Result<Vec<Vec<SData>>, String> {
+    let table = database
+        .catalog
+        .table(table_name, &database.session.search_path)?
+        .clone();
+    let columns = TableConstraints::load(database, &table)?.columns;
+    let mut pointers = vec![];
+    let mut deleted = vec![];
+    for (pointer, row) in database.rows(table.oid)? {
+        if let Some(selection) = selection {
+            if !evaluate_predicate(selection, &columns, &row)? {
+                continue;
+            }
+        }
+        pointers.push(pointer);
+        deleted.push(row);
+    }
+    delete_rows(database, &table, pointers)?;
+    Ok(deleted)
+}
@@ -0,0 +1,420 @@
+use crate::{
+    information_schema::SData,
+    parser::ast::{BinaryOperator, Expression, Value},
+};
+
+use super::{
+    advisory::is_advisory_function,
+    aggregate::is_aggregate,
+    functions::{call_function, cast},
+    pattern::{like, regex, DEFAULT_LIKE_ESCAPE},
+    sequences::is_sequence_function,
+};
+
+pub fn evaluate(
+    expression: &Expression,
+    columns: &[String],
+    row: &[SData],
+) -> Result<SData, String> {
+    evaluate_with(expression, columns, row, &mut without_database)
+}
+
+fn without_database(name: &str, _: &[SData]) -> Result<SData, String> {
+    Err(format!(
+        "Function {} is allowed only in INSERT, UPDATE, DEFAULT and select list",
+        name
+    ))
+}
+
+/*
+    functions which change the database, like nextval, are called by the caller of the evaluation,
+    so they are called only when their value is needed, e.g. in the matching branch of CASE
+*/
+pub type DatabaseCall<'a> = dyn FnMut(&str, &[SData]) -> Result<SData, String> + 'a;
+
+pub fn evaluate_with(
+    expression: &Expression,
+    columns: &[String],
+    row: &[SData],
+    call: &mut DatabaseCall,
+) -> Result<SData, String> {
+    match expression {
+        Expression::Column(name) => {
+            let index = resolve_column(columns, name)?;
+            Ok(row[index].clone())
+        }
+        Expression::Value(value) => Ok(value_to_data(value)),
+        Expression::BinaryOperation {
+            left,
+            operator,
+            right,
+        } => {
+            let left = evaluate_with(left, columns, row, call)?;
+            let right = evaluate_with(right, columns, row, call)?;
+            evaluate_binary_operation(left, operator, right)
+        }
+        Expression::Function {
+            name,
+            over: Some(_),
+            ..
+        } => Err(format!(
+            "Window function {} is allowed only in select list",
+            name
+        )),
+        Expression::Function { name, .. } if is_aggregate(name) => {
+            Err(format!("Aggregate function {} is not allowed here", name))
+        }
+        Expression::Function {
+            name, arguments, ..
+        } if is_sequence_function(name) => {
+            let values = arguments
+                .iter()
+                .map(|argument| evaluate_with(argument, columns, row, call))
+                .collect::<Result<Vec<SData>, String>>()?;
+            call(name, &values)
+        }
+        Expression::Function { name, .. } if is_advisory_function(name) => {
+            Err(format!("Function {} is allowed only in select list", name))
+        }
+        Expression::Function {
+            name,
+            distinct: true,
+            ..
+        } => Err(format!(
+            "DISTINCT specified, but {} is not an aggregate function",
+            name
+        )),
+        Expression::Function {
+            name, arguments, ..
+        } => evaluate_function(name, arguments, columns, row, call),
+        Expression::Case {
+            operand,
+            conditions,
+            else_result,
+        } => {
+            let operand = match operand {
+                Some(operand) => Some(evaluate_with(operand, columns, row, call)?),
+                None => None,
+            };
+            for (condition, result) in conditions {
+                let matches = match &operand {
+                    Some(operand) => {
+                        let value = evaluate_with(condition, columns, row, call)?;
+                        evaluate_binary_operation(operand.clone(), &BinaryOperator::Equal, value)?
+                            == SData::BOOL(true)
+                    }
+                    None => evaluate_predicate_with(condition, columns, row, call)?,
+                };
+                if matches {
+                    return evaluate_with(result, columns, row, call);
+                }
+            }
+            match else_result {
+                Some(else_result) => evaluate_with(else_result, columns, row, call),
+                None => Ok(SData::NULL),
+            }
+        }
+        Expression::Cast {
+            expression,
+            data_type,
+        } => cast(evaluate_with(expression, columns, row, call)?, data_type),
+        Expression::Like {
+            expression,
+            pattern,
+            escape,
+            case_insensitive,
+        } => {
+            let escape = match escape {
+                Some(escape) => evaluate_with(escape, columns, row, call)?,
+                None => SData::STRING(DEFAULT_LIKE_ESCAPE.to_string()),
+            };
+            match (
+                evaluate_with(expression, columns, row, call)?,
+                evaluate_with(pattern, columns, row, call)?,
+                escape,
+            ) {
+                (SData::NULL, _, _) | (_, SData::NULL, _) | (_, _, SData::NULL) => Ok(SData::NULL),
+                (SData::STRING(value), SData::STRING(pattern), SData::STRING(escape)) => {
+                    let mut characters = escape.chars();
+                    let escape = match (characters.next(), characters.next()) {
+                        (escape, None) => escape,
+                        _ => return Err("ESCAPE has to be a single character".to_string()),
+                    };
+                    like(&value, &pattern, escape, *case_insensitive).map(SData::BOOL)
+                }
+                (value, pattern, _) => Err(format!(
+                    "LIKE is supported only on strings, got {:?} and {:?}",
+                    value, pattern
+                )),
+            }
+        }
+    }
+}
+
+fn evaluate_function(
+    name: &str,
+    arguments: &[Expression],
+    columns: &[String],
+    row: &[SData],
+    call: &mut DatabaseCall,
+) -> Result<SData, String> {
+    // arguments of coalesce are evaluated only until the first one which isn't NULL
+    if name == "coalesce" {
+        if arguments.is_empty() {
+            return Err("coalesce expects at least one argument".to_string());
+        }
+        for argument in arguments {
+            match evaluate_with(argument, columns, row, call)? {
+                SData::NULL => (),
+                value => return Ok(value),
+            }
+        }
+        return Ok(SData::NULL);
+    }
+    let values = arguments
+        .iter()
+        .map(|argument| evaluate_with(argument, columns, row, call))
+        .collect::<Result<Vec<SData>, String>>()?;
+    call_function(name, &values)
+}
+
+// direct subexpressions, arguments of window function calls included
+pub fn children(expression: &Expression) -> Vec<&Expression> {
+    match expression {
+        Expression::Column(_) | Expression::Value(_) => vec![],
+        Expression::BinaryOperation { left, right, .. } => vec![left, right],
+        Expression::Function { arguments, .. } => arguments.iter().collect(),
+        Expression::Case {
+            operand,
+            conditions,
+            else_result,
+        } => operand
+            .iter()
+            .map(|operand| operand.as_ref())
+            .chain(
+                conditions
+                    .iter()
+                    .flat_map(|(condition, result)| [condition, result]),
+            )
+            .chain(else_result.iter().map(|else_result| else_result.as_ref()))
+            .collect(),
+        Expression::Cast { expression, .. } => vec![expression],
+        Expression::Like {
+            expression,
+            pattern,
+            escape,
+            ..
+        } => [expression, pattern]
+            .into_iter()
+            .chain(escape.iter())
+            .map(|child| child.as_ref())
+            .collect(),
+    }
+}
+
+// rebuilds expression with every direct subexpression replaced by result of transform
+pub fn map_children(
+    expression: &Expression,
+    transform: &mut dyn FnMut(&Expression) -> Result<Expression, String>,
+) -> Result<Expression, String> {
+    Ok(match expression {
+        Expression::Column(_) | Expression::Value(_) => expression.clone(),
+        Expression::BinaryOperation {
+            left,
+            operator,
+            right,
+        } => Expression::BinaryOperation {
+            left: Box::new(transform(left)?),
+            operator: operator.clone(),
+            right: Box::new(transform(right)?),
+        },
+        Expression::Function {
+            name,
+            distinct,
+            arguments,
+            over,
+        } => Expression::Function {
+            name: name.clone(),
+            distinct: *distinct,
+            arguments: arguments
+                .iter()
+                .map(&mut *transform)
+                .collect::<Result<Vec<Expression>, String>>()?,
+            over: over.clone(),
+        },
+        Expression::Case {
+            operand,
+            conditions,
+            else_result,
+        } => Expression::Case {
+            operand: match operand {
+                Some(operand) => Some(Box::new(transform(operand)?)),
+                None => None,
+            },
+            conditions: conditions
+                .iter()
+                .map(|(condition, result)| Ok((transform(condition)?, transform(result)?)))
+                .collect::<Result<Vec<(Expression, Expression)>, String>>()?,
+            else_result: match else_result {
+                Some(else_result) => Some(Box::new(transform(else_result)?)),
+                None => None,
+            },
+        },
+        Expression::Cast {
+            expression,
+            data_type,
+        } => Expression::Cast {
+            expression: Box::new(transform(expression)?),
+            data_type: data_type.clone(),
+        },
+        Expression::Like {
+            expression,
+            pattern,
+            escape,
+            case_insensitive,
+        } => Expression::Like {
+            expression: Box::new(transform(expression)?),
+            pattern: Box::new(transform(pattern)?),
+            escape: match escape {
+                Some(escape) => Some(Box::new(transform(escape)?)),
+                None => None,
+            },
+            case_insensitive: *case_insensitive,
+        },
+    })
+}
+
+pub fn evaluate_predicate(
+    expression: &Expression,
+    columns: &[String],
+    row: &[SData],
+) -> Result<bool, String> {
+    evaluate_predicate_with(expression, columns, row, &mut without_database)
+}
+
+fn evaluate_predicate_with(
+    expression: &Expression,
+    columns: &[String],
+    row: &[SData],
+    call: &mut DatabaseCall,
+) -> Result<bool, String> {
+    match evaluate_with(expression, columns, row, call)? {
+        SData::BOOL(value) => Ok(value),
+        SData::NULL => Ok(false),
+        other => Err(format!("Expected boolean expression, got {:?}", other)),
+    }
+}
+
+/*
+    columns are named "table.column", unqualified name matches column part of the name,
+    qualified name has to match whole name
+*/
+pub fn resolve_column(columns: &[String], name: &str) -> Result<usize, String> {
+    let mut found = None;
+    for (index, column) in columns.iter().enumerate() {
+        let matches = if name.contains('.') {
+            column == name
+        } else {
+            unqualified_name(column) == name
+        };
+        if matches {
+            if found.is_some() {
+                return Err(format!("Column reference \"{}\" is ambiguous", name));
+            }
+            found = Some(index);
+        }
+    }
+    found.ok_or_else(|| format!("No column named '{}'", name))
+}
+
+pub fn unqualified_name(column: &str) -> &str {
+    match column.rfind('.') {
+        Some(index) => &column[index + 1..],
+        None => column,
+    }
+}
+
+pub fn value_to_data(value: &Value) -> SData {
+    match value {
+        Value::Int32(integer) => SData::INT(*integer),
+        Value::VarChar256(s) => SData::STRING(s.clone()),
+        Value::Null => SData::NULL,
+    }
+}
+
+fn evaluate_binary_operation(
+    left: SData,
+    operator: &BinaryOperator,
+    right: SData,
+) -> Result<SData, String> {
+    match operator {
+        // three-valued logic, NULL is unknown
+        BinaryOperator::And => match (left, right) {
+            (SData::BOOL(false), SData::BOOL(_) | SData::NULL)
+            | (SData::BOOL(_) | SData::NULL, SData::BOOL(false)) => Ok(SData::BOOL(false)),
+            (SData::BOOL(true), SData::BOOL(true)) => Ok(SData::BOOL(true)),
+            (SData::BOOL(_) | SData::NULL, SData::BOOL(_) | SData::NULL) => Ok(SData::NULL),
+            _ => Err("Operands of AND have to be boolean".to_string()),
+        },
+        BinaryOperator::Or => match (left, right) {
+            (SData::BOOL(true), SData::BOOL(_) | SData::NULL)
+            | (SData::BOOL(_) | SData::NULL, SData::BOOL(true)) => Ok(SData::BOOL(true)),
+            (SData::BOOL(false), SData::BOOL(false)) => Ok(SData::BOOL(false)),
+            (SData::BOOL(_) | SData::NULL, SData::BOOL(_) | SData::NULL) => Ok(SData::NULL),
+            _ => Err("Operands of OR have to be boolean".to_string()),
+        },
+        _ if left == SData::NULL || right == SData::NULL => Ok(SData::NULL),
+        BinaryOperator::Equal
+        | BinaryOperator::NotEqual
+        | BinaryOperator::Less
+        | BinaryOperator::LessOrEqual
+        | BinaryOperator::Greater
+        | BinaryOperator::GreaterOrEqual => {
+            let ordering = left
+                .compare(&right)
+                .ok_or_else(|| format!("Can't compare {:?} with {:?}", left, right))?;
+            Ok(SData::BOOL(match operator {
+                BinaryOperator::Equal => ordering.is_eq(),
+                BinaryOperator::NotEqual => ordering.is_ne(),
+                BinaryOperator::Less => ordering.is_lt(),
+                BinaryOperator::LessOrEqual => ordering.is_le(),
+                BinaryOperator::Greater => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        BinaryOperator::RegexMatch => match (left, right) {
+            (SData::STRING(value), SData::STRING(pattern)) => {
+                Ok(SData::BOOL(regex(&pattern, false)?.is_match(&value)))
+            }
+            (left, right) => Err(format!(
+                "~ is supported only on strings, got {:?} and {:?}",
+                left, right
+            )),
+        },
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide => match (left, right) {
+            (SData::INT(left), SData::INT(right)) => {
+                let result = match operator {
+                    BinaryOperator::Plus => left.checked_add(right),
+                    BinaryOperator::Minus => left.checked_sub(right),
+                    BinaryOperator::Multiply => left.checked_mul(right),
+                    _ => {
+                        if right == 0 {
+                            return Err("Division by zero".to_string());
+                        }
+                        left.checked_div(right)
+                    }
+                };
+                result
+                    .map(SData::INT)
+                    .ok_or_else(|| "Integer out of range".to_string())
+            }
+            (left, right) => Err(format!(
+                "Arithmetic is supported only on integers, got {:?} and {:?}",
+                left, right
+            )),
+        },
+    }
+}
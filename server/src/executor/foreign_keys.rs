@@ -0,0 +1,442 @@
+use crate::{
+    bptree::page::TuplePointer,
+    catalog::tables::{PgAttribute, PgClass, PgForeignKey},
+    information_schema::SData,
+    lock_manager::lock::LockMode,
+    parser::ast::ReferentialAction,
+    storage::Database,
+};
+
+use super::{
+    constraints::default_value,
+    modify::{delete_rows, update_rows},
+};
+
+/*
+    foreign key is checked when row of referencing table is inserted or updated,
+    and its action is applied when referenced row is deleted or its key is changed,
+    key with any NULL value doesn't reference anything,
+    columns and referenced columns are stored in the order of the referenced index key
+*/
+
+// every referencing key without NULL of rows written after the savepoint has to exist in the referenced index
+pub fn check_references(database: &Database, savepoint: usize) -> Result<(), String> {
+    for (table, pointer) in database.inserted_since(savepoint) {
+        let foreign_keys = database.catalog.foreign_keys_of(table);
+        if foreign_keys.is_empty() {
+            continue;
+        }
+        // row replaced later in the statement was checked as its new version
+        let row = match database.row(table, pointer)? {
+            Some(row) => row,
+            None => continue,
+        };
+        for foreign_key in foreign_keys {
+            let key = key_of(&row, &foreign_key.columns);
+            if key.contains(&SData::NULL) {
+                continue;
+            }
+            let referenced = database.index_lookup(foreign_key.referenced_index, &key)?;
+            if referenced.is_empty() {
+                return Err(format!(
+                    "insert or update on table \"{}\" violates foreign key constraint \"{}\"",
+                    database.catalog.class(table)?.name,
+                    database.catalog.constraint(foreign_key.oid)?.name
+                ));
+            }
+            // referenced row can't be deleted by another transaction until this one ends
+            for pointer in referenced {
+                database.lock_row(foreign_key.referenced_table, pointer, LockMode::Shared)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn on_parent_delete(
+    database: &mut Database,
+    table: &PgClass,
+    deleted: &[Vec<SData>],
+) -> Result<(), String> {
+    let foreign_keys: Vec<PgForeignKey> = database
+        .catalog
+        .foreign_keys_referencing(table.oid)
+        .into_iter()
+        .cloned()
+        .collect();
+    for foreign_key in foreign_keys {
+        for (_, children) in orphaned_children(database, &foreign_key, deleted)? {
+            apply_action(
+                database,
+                table,
+                &foreign_key,
+                foreign_key.on_delete,
+                children,
+                None,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// old_rows and new_rows are versions of the same rows, in the same order
+pub fn on_parent_update(
+    database: &mut Database,
+    table: &PgClass,
+    old_rows: &[Vec<SData>],
+    new_rows: &[Vec<SData>],
+) -> Result<(), String> {
+    let foreign_keys: Vec<PgForeignKey> = database
+        .catalog
+        .foreign_keys_referencing(table.oid)
+        .into_iter()
+        .cloned()
+        .collect();
+    for foreign_key in foreign_keys {
+        let columns = &foreign_key.referenced_columns;
+        for (key, children) in orphaned_children(database, &foreign_key, old_rows)? {
+            let new_key = old_rows
+                .iter()
+                .zip(new_rows)
+                .find(|(old_row, _)| key_of(old_row, columns) == key)
+                .map(|(_, new_row)| key_of(new_row, columns));
+            apply_action(
+                database,
+                table,
+                &foreign_key,
+                foreign_key.on_update,
+                children,
+                new_key,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// key which no longer exists in the referenced table with pointers to rows still referencing it
+type Orphans = (Vec<SData>, Vec<TuplePointer>);
+
+// rows referencing keys of the old rows which no longer exist in the referenced table
+fn orphaned_children(
+    database: &Database,
+    foreign_key: &PgForeignKey,
+    old_rows: &[Vec<SData>],
+) -> Result<Vec<Orphans>, String> {
+    let mut orphaned = vec![];
+    let mut keys: Vec<Vec<SData>> = old_rows
+        .iter()
+        .map(|row| key_of(row, &foreign_key.referenced_columns))
+        .filter(|key| !key.contains(&SData::NULL))
+        .collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        if !database
+            .index_lookup(foreign_key.referenced_index, &key)?
+            .is_empty()
+        {
+            continue;
+        }
+        let children = find_children(database, foreign_key, &key)?;
+        if !children.is_empty() {
+            orphaned.push((key, children));
+        }
+    }
+    Ok(orphaned)
+}
+
+// uses index whose key starts with referencing columns when there is one
+fn find_children(
+    database: &Database,
+    foreign_key: &PgForeignKey,
+    key: &[SData],
+) -> Result<Vec<TuplePointer>, String> {
+    let index = database
+        .catalog
+        .indexes_of(foreign_key.table)
+        .into_iter()
+        .find(|index| index.indkey.starts_with(&foreign_key.columns));
+    if let Some(index) = index {
+        return database.index_lookup(index.oid, key);
+    }
+    Ok(database
+        .rows(foreign_key.table)?
+        .into_iter()
+        .filter(|(_, row)| key_of(row, &foreign_key.columns) == key)
+        .map(|(pointer, _)| pointer)
+        .collect())
+}
+
+// new_key is Some when referenced key was changed by UPDATE
+fn apply_action(
+    database: &mut Database,
+    table: &PgClass,
+    foreign_key: &PgForeignKey,
+    action: ReferentialAction,
+    children: Vec<TuplePointer>,
+    new_key: Option<Vec<SData>>,
+) -> Result<(), String> {
+    let child_table = database.catalog.class(foreign_key.table)?.clone();
+    let new_values = match (action, new_key) {
+        (ReferentialAction::NoAction | ReferentialAction::Restrict, _) => {
+            return Err(format!(
+                "update or delete on table \"{}\" violates foreign key constraint \"{}\" on table \"{}\"",
+                table.name,
+                database.catalog.constraint(foreign_key.oid)?.name,
+                child_table.name
+            ))
+        }
+        (ReferentialAction::Cascade, None) => {
+            return delete_rows(database, &child_table, children)
+        }
+        (ReferentialAction::Cascade, Some(new_key)) => new_key,
+        (ReferentialAction::SetNull, _) => vec![SData::NULL; foreign_key.columns.len()],
+        (ReferentialAction::SetDefault, _) => {
+            let attributes: Vec<PgAttribute> = database
+                .catalog
+                .attributes_of(child_table.oid)
+                .into_iter()
+                .cloned()
+                .collect();
+            foreign_key
+                .columns
+                .iter()
+                .map(|num| default_value(&attributes[*num as usize - 1], database))
+                .collect::<Result<Vec<SData>, String>>()?
+        }
+    };
+    let mut updates = Vec::with_capacity(children.len());
+    for pointer in children {
+        if let Some(mut row) = database.row(child_table.oid, pointer)? {
+            for (num, value) in foreign_key.columns.iter().zip(&new_values) {
+                row[*num as usize - 1] = value.clone();
+            }
+            updates.push((pointer, row));
+        }
+    }
+    update_rows(database, &child_table, updates)
+}
+
+// values of columns with given numbers
+fn key_of(row: &[SData], columns: &[i32]) -> Vec<SData> {
+    columns
+        .iter()
+        .map(|num| row[*num as usize - 1].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::QueryResult,
+        test_support::{open_database, run, select},
+    };
+
+    use super::*;
+
+    fn ints(rows: &[&[i32]]) -> Vec<Vec<SData>> {
+        rows.iter()
+            .map(|row| row.iter().map(|value| SData::INT(*value)).collect())
+            .collect()
+    }
+
+    // authors <- books (cascade) <- reviews (restrict), books <- loans (set null, set default)
+    fn library(database: &mut Database) {
+        for sql in [
+            "CREATE TABLE authors (id INT PRIMARY KEY, code INT UNIQUE);",
+            "CREATE TABLE books (id INT PRIMARY KEY, \
+                author INT REFERENCES authors ON DELETE CASCADE ON UPDATE CASCADE);",
+            "CREATE TABLE reviews (id INT PRIMARY KEY, book INT, \
+                CONSTRAINT review_book FOREIGN KEY (book) REFERENCES books (id) ON DELETE RESTRICT);",
+            "CREATE TABLE loans (id INT PRIMARY KEY, book INT DEFAULT 0 REFERENCES books ON DELETE SET DEFAULT, \
+                author_code INT REFERENCES authors (code) ON DELETE SET NULL);",
+            "INSERT INTO authors (id, code) VALUES (1, 10);",
+            "INSERT INTO authors (id, code) VALUES (2, 20);",
+            "INSERT INTO books (id, author) VALUES (0, NULL);",
+            "INSERT INTO books (id, author) VALUES (1, 1);",
+            "INSERT INTO books (id, author) VALUES (2, 1);",
+            "INSERT INTO books (id, author) VALUES (3, 2);",
+        ] {
+            run(database, sql).unwrap();
+        }
+    }
+
+    #[test]
+    fn when_referenced_key_does_not_exist_insert_should_fail() {
+        let (_directory, mut database) = open_database();
+        library(&mut database);
+
+        assert_eq!(
+            run(&mut database, "INSERT INTO books (id, author) VALUES (4, 3);"),
+            Err("insert or update on table \"books\" violates foreign key constraint \"books_author_fkey\"".to_string())
+        );
+        assert_eq!(
+            run(&mut database, "UPDATE books SET author = 7 WHERE id = 1;"),
+            Err("insert or update on table \"books\" violates foreign key constraint \"books_author_fkey\"".to_string())
+        );
+        assert!(run(
+            &mut database,
+            "INSERT INTO loans (id, author_code) VALUES (1, 1);"
+        )
+        .is_err());
+        run(
+            &mut database,
+            "INSERT INTO loans (id, author_code) VALUES (1, 10);",
+        )
+        .unwrap();
+        assert!(run(
+            &mut database,
+            "CREATE TABLE bad (id INT PRIMARY KEY, author INT REFERENCES authors (id, code));"
+        )
+        .is_err());
+        assert_eq!(
+            run(
+                &mut database,
+                "CREATE TABLE bad (id INT PRIMARY KEY, book VARCHAR REFERENCES books);"
+            ),
+            Err("Key columns \"book\" and \"id\" are of incompatible types".to_string())
+        );
+    }
+
+    #[test]
+    fn when_referenced_row_is_deleted_action_should_be_applied_to_children() {
+        let (_directory, mut database) = open_database();
+        library(&mut database);
+        run(
+            &mut database,
+            "INSERT INTO loans (id, book, author_code) VALUES (1, 2, 10);",
+        )
+        .unwrap();
+        run(
+            &mut database,
+            "INSERT INTO loans (id, book, author_code) VALUES (2, 3, 20);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(&mut database, "DELETE FROM authors WHERE id = 1;"),
+            Ok(QueryResult::Command("DELETE 1".to_string()))
+        );
+
+        assert_eq!(
+            select(&mut database, "SELECT id, author FROM books;"),
+            vec![
+                vec![SData::INT(0), SData::NULL],
+                vec![SData::INT(3), SData::INT(2)]
+            ]
+        );
+        assert_eq!(
+            select(&mut database, "SELECT id, book, author_code FROM loans;"),
+            vec![
+                vec![SData::INT(2), SData::INT(3), SData::INT(20)],
+                vec![SData::INT(1), SData::INT(0), SData::NULL],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_delete_is_restricted_whole_statement_should_be_rolled_back() {
+        let (_directory, mut database) = open_database();
+        library(&mut database);
+        run(
+            &mut database,
+            "INSERT INTO reviews (id, book) VALUES (1, 3);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(&mut database, "DELETE FROM authors;"),
+            Err("update or delete on table \"books\" violates foreign key constraint \"review_book\" on table \"reviews\"".to_string())
+        );
+
+        assert_eq!(
+            select(&mut database, "SELECT id FROM authors;"),
+            ints(&[&[1], &[2]])
+        );
+        assert_eq!(
+            select(&mut database, "SELECT id FROM books;"),
+            ints(&[&[0], &[1], &[2], &[3]])
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO authors (id, code) VALUES (2, 30);"
+            ),
+            Err("duplicate key value violates unique constraint \"authors_pkey\"".to_string())
+        );
+    }
+
+    #[test]
+    fn when_referenced_key_is_updated_children_should_follow_it() {
+        let (_directory, mut database) = open_database();
+        library(&mut database);
+        run(
+            &mut database,
+            "INSERT INTO reviews (id, book) VALUES (1, 1);",
+        )
+        .unwrap();
+
+        run(&mut database, "UPDATE authors SET id = id + 10;").unwrap();
+
+        assert_eq!(
+            select(&mut database, "SELECT id, author FROM books WHERE id > 0;"),
+            ints(&[&[1, 11], &[2, 11], &[3, 12]])
+        );
+        assert_eq!(
+            run(&mut database, "UPDATE books SET id = 5 WHERE id = 1;"),
+            Err("update or delete on table \"books\" violates foreign key constraint \"review_book\" on table \"reviews\"".to_string())
+        );
+        // swapped keys are still referenced, so nothing is orphaned
+        run(&mut database, "UPDATE authors SET id = 23 - id;").unwrap();
+        assert_eq!(
+            select(&mut database, "SELECT id, author FROM books WHERE id > 0;"),
+            ints(&[&[1, 11], &[2, 11], &[3, 12]])
+        );
+    }
+
+    #[test]
+    fn when_foreign_key_has_multiple_columns_whole_key_should_be_referenced() {
+        let (_directory, mut database) = open_database();
+        for sql in [
+            "CREATE TABLE courses (year INT, code VARCHAR, PRIMARY KEY (year, code));",
+            "CREATE TABLE enrollments (id INT PRIMARY KEY, code VARCHAR, year INT, \
+                FOREIGN KEY (code, year) REFERENCES courses (code, year) ON UPDATE CASCADE ON DELETE SET NULL);",
+            "INSERT INTO courses (year, code) VALUES (2024, \"db\");",
+            "INSERT INTO courses (year, code) VALUES (2025, \"db\");",
+            "INSERT INTO enrollments (id, code, year) VALUES (1, \"db\", 2024);",
+            "INSERT INTO enrollments (id, code, year) VALUES (2, \"db\", 2025);",
+            // key with NULL doesn't reference anything
+            "INSERT INTO enrollments (id, code, year) VALUES (3, \"os\", NULL);",
+        ] {
+            run(&mut database, sql).unwrap();
+        }
+
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO enrollments (id, code, year) VALUES (4, \"os\", 2024);"
+            ),
+            Err("insert or update on table \"enrollments\" violates foreign key constraint \"enrollments_code_year_fkey\"".to_string())
+        );
+        run(
+            &mut database,
+            "UPDATE courses SET year = 2026 WHERE year = 2025;",
+        )
+        .unwrap();
+        run(&mut database, "DELETE FROM courses WHERE year = 2024;").unwrap();
+        let mut enrollments = select(&mut database, "SELECT id, code, year FROM enrollments;");
+        enrollments.sort();
+        assert_eq!(
+            enrollments,
+            vec![
+                vec![SData::INT(1), SData::NULL, SData::NULL],
+                vec![
+                    SData::INT(2),
+                    SData::STRING("db".to_string()),
+                    SData::INT(2026)
+                ],
+                vec![SData::INT(3), SData::STRING("os".to_string()), SData::NULL],
+            ]
+        );
+    }
+}
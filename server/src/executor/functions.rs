@@ -0,0 +1,372 @@
+use crate::{information_schema::SData, parser::ast::DataType};
+
+use super::pattern;
+
+/*
+    built-in scalar functions, overloads are resolved by types of evaluated arguments:
+        1. candidates with the same name are checked in order of registration
+        2. candidate matches if number of arguments fits and every argument has parameter type, NULL matches any type
+        3. strict functions return NULL without being called when any argument is NULL
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParameterType {
+    Int,
+    String,
+    Any,
+}
+
+struct ScalarFunction {
+    name: &'static str,
+    parameters: &'static [ParameterType],
+    // last parameter can be repeated any number of times
+    variadic: bool,
+    strict: bool,
+    implementation: fn(&[SData]) -> Result<SData, String>,
+}
+
+use ParameterType::{Any, Int, String as Str};
+
+const FUNCTIONS: &[ScalarFunction] = &[
+    strict("lower", &[Str], |arguments| {
+        Ok(SData::STRING(string(arguments, 0)?.to_lowercase()))
+    }),
+    strict("upper", &[Str], |arguments| {
+        Ok(SData::STRING(string(arguments, 0)?.to_uppercase()))
+    }),
+    strict("length", &[Str], |arguments| {
+        Ok(SData::INT(string(arguments, 0)?.chars().count() as i32))
+    }),
+    strict("substring", &[Str, Int], |arguments| {
+        substring(string(arguments, 0)?, int(arguments, 1)?, None)
+    }),
+    strict("substring", &[Str, Int, Int], |arguments| {
+        substring(
+            string(arguments, 0)?,
+            int(arguments, 1)?,
+            Some(int(arguments, 2)?),
+        )
+    }),
+    strict("trim", &[Str], |arguments| {
+        Ok(SData::STRING(
+            string(arguments, 0)?.trim_matches(' ').to_string(),
+        ))
+    }),
+    strict("trim", &[Str, Str], |arguments| {
+        let characters: Vec<char> = string(arguments, 1)?.chars().collect();
+        Ok(SData::STRING(
+            string(arguments, 0)?
+                .trim_matches(characters.as_slice())
+                .to_string(),
+        ))
+    }),
+    strict("replace", &[Str, Str, Str], |arguments| {
+        let (value, from, to) = (
+            string(arguments, 0)?,
+            string(arguments, 1)?,
+            string(arguments, 2)?,
+        );
+        if from.is_empty() {
+            return Ok(SData::STRING(value.to_string()));
+        }
+        Ok(SData::STRING(value.replace(from, to)))
+    }),
+    strict("regexp_replace", &[Str, Str, Str], |arguments| {
+        pattern::regexp_replace(
+            string(arguments, 0)?,
+            string(arguments, 1)?,
+            string(arguments, 2)?,
+            "",
+        )
+        .map(SData::STRING)
+    }),
+    strict("regexp_replace", &[Str, Str, Str, Str], |arguments| {
+        pattern::regexp_replace(
+            string(arguments, 0)?,
+            string(arguments, 1)?,
+            string(arguments, 2)?,
+            string(arguments, 3)?,
+        )
+        .map(SData::STRING)
+    }),
+    // NULL arguments are ignored
+    ScalarFunction {
+        name: "concat",
+        parameters: &[Any],
+        variadic: true,
+        strict: false,
+        implementation: |arguments| {
+            Ok(SData::STRING(
+                arguments
+                    .iter()
+                    .filter(|argument| **argument != SData::NULL)
+                    .map(to_text)
+                    .collect(),
+            ))
+        },
+    },
+    ScalarFunction {
+        name: "nullif",
+        parameters: &[Any, Any],
+        variadic: false,
+        strict: false,
+        implementation: |arguments| match (&arguments[0], &arguments[1]) {
+            (SData::NULL, _) => Ok(SData::NULL),
+            (value, SData::NULL) => Ok(value.clone()),
+            (left, right) => match left.compare(right) {
+                Some(ordering) if ordering.is_eq() => Ok(SData::NULL),
+                Some(_) => Ok(left.clone()),
+                None => Err(format!("Can't compare {:?} with {:?}", left, right)),
+            },
+        },
+    },
+    strict("abs", &[Int], |arguments| {
+        int(arguments, 0)?
+            .checked_abs()
+            .map(SData::INT)
+            .ok_or_else(|| "Integer out of range".to_string())
+    }),
+    strict("mod", &[Int, Int], |arguments| {
+        let (left, right) = (int(arguments, 0)?, int(arguments, 1)?);
+        if right == 0 {
+            return Err("Division by zero".to_string());
+        }
+        Ok(SData::INT(left.wrapping_rem(right)))
+    }),
+    // integers are already whole numbers, rounding only makes sense to negative number of decimal places
+    strict("round", &[Int], |arguments| {
+        Ok(SData::INT(int(arguments, 0)?))
+    }),
+    strict("round", &[Int, Int], |arguments| {
+        round(int(arguments, 0)?, int(arguments, 1)?)
+    }),
+    strict("floor", &[Int], |arguments| {
+        Ok(SData::INT(int(arguments, 0)?))
+    }),
+    strict("ceil", &[Int], |arguments| {
+        Ok(SData::INT(int(arguments, 0)?))
+    }),
+    strict("ceiling", &[Int], |arguments| {
+        Ok(SData::INT(int(arguments, 0)?))
+    }),
+];
+
+const fn strict(
+    name: &'static str,
+    parameters: &'static [ParameterType],
+    implementation: fn(&[SData]) -> Result<SData, String>,
+) -> ScalarFunction {
+    ScalarFunction {
+        name,
+        parameters,
+        variadic: false,
+        strict: true,
+        implementation,
+    }
+}
+
+pub fn call_function(name: &str, arguments: &[SData]) -> Result<SData, String> {
+    let mut candidates = FUNCTIONS
+        .iter()
+        .filter(|function| function.name == name)
+        .peekable();
+    if candidates.peek().is_none() {
+        return Err(format!("Unknown function {}", name));
+    }
+    let function = candidates
+        .find(|function| function.accepts(arguments))
+        .ok_or_else(|| {
+            format!(
+                "No function matches {}({})",
+                name,
+                arguments
+                    .iter()
+                    .map(type_name)
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        })?;
+    if function.strict && arguments.contains(&SData::NULL) {
+        return Ok(SData::NULL);
+    }
+    (function.implementation)(arguments)
+}
+
+impl ScalarFunction {
+    fn accepts(&self, arguments: &[SData]) -> bool {
+        let arity_fits = if self.variadic {
+            arguments.len() >= self.parameters.len()
+        } else {
+            arguments.len() == self.parameters.len()
+        };
+        arity_fits
+            && arguments.iter().enumerate().all(|(index, argument)| {
+                let parameter = self.parameters[index.min(self.parameters.len() - 1)];
+                parameter.matches(argument)
+            })
+    }
+}
+
+impl ParameterType {
+    fn matches(&self, argument: &SData) -> bool {
+        matches!(
+            (self, argument),
+            (_, SData::NULL)
+                | (ParameterType::Any, _)
+                | (ParameterType::Int, SData::INT(_))
+                | (ParameterType::String, SData::STRING(_))
+        )
+    }
+}
+
+pub fn cast(value: SData, data_type: &DataType) -> Result<SData, String> {
+    match (value, data_type) {
+        (SData::NULL, _) => Ok(SData::NULL),
+        (SData::INT(integer), DataType::Int32) => Ok(SData::INT(integer)),
+        (SData::STRING(s), DataType::Int32) => s
+            .trim()
+            .parse::<i32>()
+            .map(SData::INT)
+            .map_err(|_| format!("Invalid input for type INT: \"{}\"", s)),
+        (SData::BOOL(value), DataType::Int32) => Ok(SData::INT(value as i32)),
+        (value, DataType::VarChar256) => Ok(SData::STRING(to_text(&value))),
+    }
+}
+
+fn to_text(value: &SData) -> String {
+    match value {
+        SData::INT(integer) => integer.to_string(),
+        SData::STRING(s) => s.clone(),
+        SData::BOOL(value) => value.to_string(),
+        SData::NULL => String::new(),
+    }
+}
+
+pub fn type_name(value: &SData) -> &'static str {
+    match value {
+        SData::INT(_) => "INT",
+        SData::STRING(_) => "VARCHAR",
+        SData::BOOL(_) => "BOOLEAN",
+        SData::NULL => "NULL",
+    }
+}
+
+fn int(arguments: &[SData], index: usize) -> Result<i32, String> {
+    match arguments.get(index) {
+        Some(SData::INT(integer)) => Ok(*integer),
+        other => Err(format!("Expected integer argument, got {:?}", other)),
+    }
+}
+
+fn string(arguments: &[SData], index: usize) -> Result<&str, String> {
+    match arguments.get(index) {
+        Some(SData::STRING(s)) => Ok(s),
+        other => Err(format!("Expected string argument, got {:?}", other)),
+    }
+}
+
+// start is 1-based and can be before the first character, characters before it count towards length
+fn substring(value: &str, start: i32, length: Option<i32>) -> Result<SData, String> {
+    let end = match length {
+        Some(length) if length < 0 => {
+            return Err("Negative substring length not allowed".to_string())
+        }
+        Some(length) => start as i64 + length as i64,
+        None => i64::MAX,
+    };
+    Ok(SData::STRING(
+        value
+            .chars()
+            .enumerate()
+            .filter(|(index, _)| {
+                let position = *index as i64 + 1;
+                position >= start as i64 && position < end
+            })
+            .map(|(_, character)| character)
+            .collect(),
+    ))
+}
+
+// negative number of decimal places rounds to tens, hundreds..., halves are rounded away from zero
+fn round(value: i32, decimal_places: i32) -> Result<SData, String> {
+    if decimal_places >= 0 {
+        return Ok(SData::INT(value));
+    }
+    let factor = match 10_i64.checked_pow(decimal_places.unsigned_abs()) {
+        Some(factor) => factor,
+        None => return Ok(SData::INT(0)),
+    };
+    let magnitude = (value as i64).abs();
+    let rounded = (magnitude + factor / 2) / factor * factor * (value as i64).signum();
+    i32::try_from(rounded)
+        .map(SData::INT)
+        .map_err(|_| "Integer out of range".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> SData {
+        SData::STRING(s.to_string())
+    }
+
+    #[test]
+    fn when_function_has_overloads_it_should_resolve_them_by_argument_count_and_types() {
+        assert_eq!(
+            call_function("substring", &[text("database"), SData::INT(5)]),
+            Ok(text("base"))
+        );
+        assert_eq!(
+            call_function(
+                "substring",
+                &[text("database"), SData::INT(0), SData::INT(3)]
+            ),
+            Ok(text("da"))
+        );
+        assert_eq!(
+            call_function("trim", &[text("xxdbxx"), text("x")]),
+            Ok(text("db"))
+        );
+        assert!(call_function("substring", &[SData::INT(1), SData::INT(1)]).is_err());
+        assert!(call_function("nosuchfunction", &[]).is_err());
+    }
+
+    #[test]
+    fn when_strict_function_gets_null_it_should_return_null() {
+        assert_eq!(call_function("upper", &[SData::NULL]), Ok(SData::NULL));
+        assert_eq!(
+            call_function("mod", &[SData::INT(7), SData::NULL]),
+            Ok(SData::NULL)
+        );
+        assert_eq!(
+            call_function("concat", &[text("a"), SData::NULL, SData::INT(1)]),
+            Ok(text("a1"))
+        );
+    }
+
+    #[test]
+    fn when_math_functions_are_called_they_should_handle_signs_and_rounding() {
+        assert_eq!(call_function("abs", &[SData::INT(-4)]), Ok(SData::INT(4)));
+        assert_eq!(
+            call_function("mod", &[SData::INT(-7), SData::INT(3)]),
+            Ok(SData::INT(-1))
+        );
+        assert_eq!(
+            call_function("round", &[SData::INT(-1250), SData::INT(-2)]),
+            Ok(SData::INT(-1300))
+        );
+        assert_eq!(
+            call_function("round", &[SData::INT(1249), SData::INT(-2)]),
+            Ok(SData::INT(1200))
+        );
+        assert!(call_function("abs", &[SData::INT(i32::MIN)]).is_err());
+    }
+
+    #[test]
+    fn when_value_is_cast_it_should_convert_or_return_error() {
+        assert_eq!(cast(text(" 42 "), &DataType::Int32), Ok(SData::INT(42)));
+        assert_eq!(cast(SData::INT(7), &DataType::VarChar256), Ok(text("7")));
+        assert_eq!(cast(SData::NULL, &DataType::Int32), Ok(SData::NULL));
+        assert!(cast(text("abc"), &DataType::Int32).is_err());
+    }
+}
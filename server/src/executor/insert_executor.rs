@@ -0,0 +1,264 @@
+use crate::{
+    bptree::page::{TupleKey, TuplePointer},
+    catalog::{
+        tables::{Identity, PgAttribute, PgClass, PgIndex},
+        Oid,
+    },
+    information_schema::SData,
+    lock_manager::lock::LockMode,
+    parser::ast::{ConflictAction, Expression, OnConflict},
+    storage::{index_key, Database},
+};
+
+use super::{
+    constraints::{default_value, TableConstraints},
+    expression::{evaluate_predicate, unqualified_name},
+    modify::{insert_rows, update_rows},
+    sequences::evaluate_with_sequences,
+    update_executor::{assign, assignment_targets},
+};
+
+// unique index and key of a row in it
+type ArbiterKey = (Oid, TupleKey);
+
+/*
+    columns which aren't listed get their DEFAULT value, or NULL without default,
+    identity column GENERATED ALWAYS can't be listed, returns inserted rows,
+    with ON CONFLICT the row which would violate the arbiter unique index is skipped or updated instead,
+    rows of VALUES are inserted in order, so a later row conflicts with the earlier ones
+*/
+pub fn insert(
+    table_name: &str,
+    column_names: &[String],
+    rows: &[Vec<Expression>],
+    on_conflict: &Option<OnConflict>,
+    database: &mut Database,
+) -> Result<Vec<Vec<SData>>, String> {
+    let table = database
+        .catalog
+        .table(table_name, &database.session.search_path)?
+        .clone();
+    let arbiters = match on_conflict {
+        Some(on_conflict) => {
+            arbiter_indexes(&table, &on_conflict.columns, &on_conflict.action, database)?
+        }
+        None => vec![],
+    };
+    // arbiter keys of rows inserted or updated by this statement, DO UPDATE can't change them again
+    let mut written: Vec<ArbiterKey> = vec![];
+    let mut inserted = vec![];
+    for values in rows {
+        let row = new_row(&table, table_name, column_names, values, &[], &[], database)?;
+        if let Some(on_conflict) = on_conflict {
+            if let Some((pointer, key)) = find_conflict(&table, &arbiters, &row, database)? {
+                let updated = matches!(on_conflict.action, ConflictAction::Update { .. });
+                if updated && written.contains(&key) {
+                    return Err(
+                        "ON CONFLICT DO UPDATE command cannot affect row a second time".to_string(),
+                    );
+                }
+                let resolved = resolve_conflict(
+                    &table,
+                    table_name,
+                    pointer,
+                    row,
+                    &on_conflict.action,
+                    database,
+                )?;
+                for row in &resolved {
+                    written.extend(arbiter_keys(&arbiters, row));
+                }
+                inserted.extend(resolved);
+                continue;
+            }
+        }
+        insert_rows(database, &table, vec![row.clone()])?;
+        written.extend(arbiter_keys(&arbiters, &row));
+        inserted.push(row);
+    }
+    Ok(inserted)
+}
+
+// values are evaluated against the context, which is empty for INSERT and the source row for MERGE
+pub fn new_row(
+    table: &PgClass,
+    table_name: &str,
+    column_names: &[String],
+    values: &[Expression],
+    context_columns: &[String],
+    context_row: &[SData],
+    database: &mut Database,
+) -> Result<Vec<SData>, String> {
+    if column_names.len() != values.len() {
+        return Err(format!(
+            "INSERT has {} target columns but {} values",
+            column_names.len(),
+            values.len()
+        ));
+    }
+    let attributes: Vec<PgAttribute> = database
+        .catalog
+        .attributes_of(table.oid)
+        .into_iter()
+        .cloned()
+        .collect();
+    for (position, name) in column_names.iter().enumerate() {
+        let attribute = attributes
+            .iter()
+            .find(|attribute| attribute.name == *name)
+            .ok_or_else(|| {
+                format!(
+                    "Column \"{}\" of relation \"{}\" does not exist",
+                    name, table_name
+                )
+            })?;
+        if column_names[..position].contains(name) {
+            return Err(format!("Column \"{}\" specified more than once", name));
+        }
+        if attribute.identity == Identity::Always {
+            return Err(format!(
+                "cannot insert a non-DEFAULT value into column \"{}\"",
+                name
+            ));
+        }
+    }
+    let mut row = Vec::with_capacity(attributes.len());
+    for attribute in &attributes {
+        let value = match column_names.iter().position(|name| *name == attribute.name) {
+            Some(position) => {
+                evaluate_with_sequences(&values[position], context_columns, context_row, database)?
+            }
+            None => default_value(attribute, database)?,
+        };
+        row.push(value);
+    }
+    Ok(row)
+}
+
+/*
+    conflict is found in the B+tree of the arbiter index, the database is locked for the whole statement,
+    so no other row with the same key can be inserted between the lookup and the insert or update,
+    conflicting row is locked, so the statement waits for the transaction which wrote it,
+    returns the conflicting version with the arbiter key it was found by
+*/
+fn find_conflict(
+    table: &PgClass,
+    arbiters: &[PgIndex],
+    row: &[SData],
+    database: &Database,
+) -> Result<Option<(TuplePointer, ArbiterKey)>, String> {
+    for index in arbiters {
+        let key = index_key(index, row);
+        if key.has_null() {
+            continue;
+        }
+        if let Some(pointer) = database.index_conflicts(index.oid, &key.0)?.first() {
+            database.lock_row(table.oid, *pointer, LockMode::Shared)?;
+            return Ok(Some((*pointer, (index.oid, key))));
+        }
+    }
+    Ok(None)
+}
+
+fn arbiter_keys(arbiters: &[PgIndex], row: &[SData]) -> Vec<ArbiterKey> {
+    arbiters
+        .iter()
+        .map(|index| (index.oid, index_key(index, row)))
+        .collect()
+}
+
+// without listed columns DO NOTHING works with all unique indexes of the table
+fn arbiter_indexes(
+    table: &PgClass,
+    columns: &[String],
+    action: &ConflictAction,
+    database: &Database,
+) -> Result<Vec<PgIndex>, String> {
+    let unique_indexes = database
+        .catalog
+        .indexes_of(table.oid)
+        .into_iter()
+        .filter(|index| index.unique)
+        .cloned();
+    if columns.is_empty() {
+        return match action {
+            ConflictAction::Nothing => Ok(unique_indexes.collect()),
+            ConflictAction::Update { .. } => Err(
+                "ON CONFLICT DO UPDATE requires inference specification or constraint name"
+                    .to_string(),
+            ),
+        };
+    }
+    let attributes = database.catalog.attributes_of(table.oid);
+    let mut nums = Vec::with_capacity(columns.len());
+    for column in columns {
+        let attribute = attributes
+            .iter()
+            .find(|attribute| attribute.name == *column)
+            .ok_or_else(|| format!("Column \"{}\" does not exist", column))?;
+        nums.push(attribute.num);
+    }
+    nums.sort_unstable();
+    nums.dedup();
+    let arbiters: Vec<PgIndex> = unique_indexes
+        .filter(|index| {
+            let mut indkey = index.indkey.clone();
+            indkey.sort_unstable();
+            indkey == nums
+        })
+        .collect();
+    if arbiters.is_empty() {
+        return Err(
+            "There is no unique or exclusion constraint matching the ON CONFLICT specification"
+                .to_string(),
+        );
+    }
+    Ok(arbiters)
+}
+
+// proposed row is visible to DO UPDATE as excluded, next to the existing row
+fn resolve_conflict(
+    table: &PgClass,
+    table_name: &str,
+    pointer: TuplePointer,
+    proposed: Vec<SData>,
+    action: &ConflictAction,
+    database: &mut Database,
+) -> Result<Vec<Vec<SData>>, String> {
+    let (assignments, selection) = match action {
+        ConflictAction::Nothing => return Ok(vec![]),
+        ConflictAction::Update {
+            assignments,
+            selection,
+        } => (assignments, selection),
+    };
+    let existing = database
+        .row(table.oid, pointer)?
+        .ok_or_else(|| format!("Conflicting row of \"{}\" was not found", table_name))?;
+    let columns = TableConstraints::load(database, table)?.columns;
+    let context_columns: Vec<String> = columns
+        .iter()
+        .cloned()
+        .chain(
+            columns
+                .iter()
+                .map(|column| format!("excluded.{}", unqualified_name(column))),
+        )
+        .collect();
+    let context_row: Vec<SData> = existing.iter().chain(proposed.iter()).cloned().collect();
+    if let Some(selection) = selection {
+        if !evaluate_predicate(selection, &context_columns, &context_row)? {
+            return Ok(vec![]);
+        }
+    }
+    let targets = assignment_targets(table, table_name, &columns, assignments, database)?;
+    let updated = assign(
+        &targets,
+        &existing,
+        &context_columns,
+        &context_row,
+        database,
+    )?;
+    update_rows(database, table, vec![(pointer, updated.clone())])?;
+    Ok(vec![updated])
+}
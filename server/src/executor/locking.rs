@@ -0,0 +1,230 @@
+use crate::{
+    lock_manager::lock::LockMode,
+    parser::ast::{Columns, LockStrength, Locking, Query, QueryBody},
+    storage::Database,
+};
+
+use super::{
+    aggregate::contains_aggregate,
+    expression::evaluate_predicate,
+    select_executor::{project, qualify},
+    Relation,
+};
+
+/*
+    SELECT ... FOR UPDATE | FOR SHARE locks returned rows until the end of the transaction,
+    rows are filtered first, so only matching rows are locked, they have to come from one table,
+    so the query can't have joins, grouping or set operations,
+    NOWAIT fails on a row locked by another transaction, SKIP LOCKED leaves such row out of the result
+*/
+pub fn execute_locking_query(
+    query: &Query,
+    locking: &Locking,
+    database: &mut Database,
+) -> Result<Relation, String> {
+    let select = match &query.body {
+        _ if query.with.is_some() => {
+            return Err(format!(
+                "{} is not allowed with WITH queries",
+                locking.strength
+            ))
+        }
+        QueryBody::Select(select) => select,
+        QueryBody::SetOperation { .. } => {
+            return Err(format!(
+                "{} is not allowed with UNION/INTERSECT/EXCEPT",
+                locking.strength
+            ))
+        }
+    };
+    if select.distinct {
+        return Err(format!(
+            "{} is not allowed with DISTINCT clause",
+            locking.strength
+        ));
+    }
+    if !select.joins.is_empty() {
+        return Err(format!("{} is not allowed with joins", locking.strength));
+    }
+    let table_name = match &select.table {
+        Some(table_name) => table_name,
+        None => return Err(format!("{} requires a table in FROM", locking.strength)),
+    };
+    if let Columns::Specific(items) = &select.columns {
+        if items
+            .iter()
+            .any(|item| contains_aggregate(&item.expression))
+        {
+            return Err(format!(
+                "{} is not allowed with aggregate functions",
+                locking.strength
+            ));
+        }
+    }
+    let table = database
+        .catalog
+        .table(table_name, &database.session.search_path)?
+        .oid;
+    let columns = database
+        .catalog
+        .attributes_of(table)
+        .iter()
+        .map(|attribute| attribute.name.clone())
+        .collect();
+    let (pointers, rows): (Vec<_>, Vec<_>) = database.rows(table)?.into_iter().unzip();
+    let relation = qualify(Relation { columns, rows }, table_name, &select.alias);
+    let mode = match locking.strength {
+        LockStrength::Update => LockMode::Exclusive,
+        LockStrength::Share => LockMode::Shared,
+    };
+    let mut locked = vec![];
+    for (pointer, row) in pointers.into_iter().zip(relation.rows) {
+        if let Some(selection) = &select.selection {
+            if !evaluate_predicate(selection, &relation.columns, &row)? {
+                continue;
+            }
+        }
+        if database.lock_version(table, pointer, mode, locking.wait)? {
+            locked.push(row);
+        }
+    }
+    project(
+        Relation {
+            columns: relation.columns,
+            rows: locked,
+        },
+        &select.columns,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::SqlState,
+        executor::QueryResult,
+        information_schema::SData,
+        storage::Session,
+        test_support::{open_database, run_in, select_in, sorted},
+    };
+
+    use super::*;
+
+    fn jobs(database: &mut Database) {
+        let session = &mut Session::default();
+        for sql in [
+            "CREATE TABLE jobs (id INT PRIMARY KEY, done INT);",
+            "INSERT INTO jobs (id, done) VALUES (1, 0);",
+            "INSERT INTO jobs (id, done) VALUES (2, 0);",
+            "INSERT INTO jobs (id, done) VALUES (3, 0);",
+        ] {
+            run_in(database, session, sql).unwrap();
+        }
+    }
+
+    #[test]
+    fn when_rows_are_locked_by_other_worker_skip_locked_should_return_only_free_rows() {
+        let (_directory, mut database) = open_database();
+        jobs(&mut database);
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        run_in(&mut database, first, "BEGIN;").unwrap();
+        run_in(&mut database, second, "BEGIN;").unwrap();
+
+        assert_eq!(
+            sorted(select_in(
+                &mut database,
+                first,
+                "SELECT id FROM jobs j WHERE j.id < 3 FOR UPDATE;"
+            )),
+            vec![vec![SData::INT(1)], vec![SData::INT(2)]]
+        );
+        assert_eq!(
+            sorted(select_in(
+                &mut database,
+                second,
+                "SELECT id FROM jobs WHERE done = 0 FOR UPDATE SKIP LOCKED;"
+            )),
+            vec![vec![SData::INT(3)]]
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                second,
+                "SELECT id FROM jobs FOR SHARE NOWAIT;"
+            ),
+            Err("Could not obtain lock on row in relation \"jobs\"".to_string())
+        );
+        assert_eq!(
+            run_in(&mut database, second, "SELECT id FROM jobs FOR SHARE;"),
+            Err("Could not obtain lock on row in relation \"jobs\"".to_string())
+        );
+        assert!(database
+            .locks
+            .is_waiting(second.transaction.as_ref().unwrap().id)
+            .unwrap());
+        run_in(&mut database, first, "COMMIT;").unwrap();
+
+        assert_eq!(
+            sorted(select_in(
+                &mut database,
+                second,
+                "SELECT id FROM jobs FOR SHARE;"
+            )),
+            vec![
+                vec![SData::INT(1)],
+                vec![SData::INT(2)],
+                vec![SData::INT(3)]
+            ]
+        );
+    }
+
+    #[test]
+    fn when_locked_row_was_updated_after_snapshot_repeatable_read_should_fail() {
+        let (_directory, mut database) = open_database();
+        jobs(&mut database);
+        let (reader, writer) = (&mut Session::default(), &mut Session::default());
+        run_in(&mut database, reader, "BEGIN;").unwrap();
+        run_in(
+            &mut database,
+            reader,
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;",
+        )
+        .unwrap();
+        sorted(select_in(&mut database, reader, "SELECT id FROM jobs;"));
+
+        run_in(
+            &mut database,
+            writer,
+            "UPDATE jobs SET done = 1 WHERE id = 1;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run_in(
+                &mut database,
+                reader,
+                "SELECT id FROM jobs WHERE id = 2 FOR UPDATE;"
+            ),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["id".to_string()],
+                rows: vec![vec![SData::INT(2)]]
+            }))
+        );
+        assert_eq!(
+            run_in(&mut database, reader, "SELECT id FROM jobs FOR UPDATE;"),
+            Err(SqlState::SerializationFailure
+                .error("Could not serialize access due to concurrent update"))
+        );
+        assert_eq!(
+            run_in(&mut database, reader, "COMMIT;"),
+            Err("There is no transaction in progress".to_string())
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                writer,
+                "SELECT COUNT(*) FROM jobs FOR UPDATE;"
+            ),
+            Err("FOR UPDATE is not allowed with aggregate functions".to_string())
+        );
+    }
+}
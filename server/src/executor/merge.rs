@@ -0,0 +1,294 @@
+use std::collections::HashSet;
+
+use crate::{
+    catalog::split_name,
+    information_schema::SData,
+    parser::ast::{Expression, MergeAction, MergeClause},
+    storage::Database,
+};
+
+use super::{
+    expression::{evaluate_predicate, unqualified_name},
+    insert_executor::new_row,
+    modify::{delete_rows, insert_rows, update_rows},
+    select_executor::scan_relation,
+    update_executor::{assign, assignment_targets},
+};
+
+/*
+    every row of the source is joined with rows of the target which satisfy the condition,
+    matched pairs and unmatched source rows are handled by the first WHEN clause which applies,
+    all changes are computed from the rows before the statement and applied together at the end,
+    returns number of changed rows
+*/
+pub fn merge(
+    table_name: &str,
+    alias: &Option<String>,
+    source: &str,
+    source_alias: &Option<String>,
+    condition: &Expression,
+    clauses: &[MergeClause],
+    database: &mut Database,
+) -> Result<usize, String> {
+    let table = database
+        .catalog
+        .table(table_name, &database.session.search_path)?
+        .clone();
+    let source = scan_relation(source, source_alias, database)?;
+    let qualifier = alias.as_deref().unwrap_or(split_name(table_name).1);
+    let table_columns: Vec<String> = database
+        .catalog
+        .attributes_of(table.oid)
+        .iter()
+        .map(|attribute| format!("{}.{}", qualifier, attribute.name))
+        .collect();
+    let columns: Vec<String> = table_columns
+        .iter()
+        .chain(source.columns.iter())
+        .cloned()
+        .collect();
+    let targets = database.rows(table.oid)?;
+
+    let (mut updates, mut deletes, mut inserts) = (vec![], vec![], vec![]);
+    let mut changed = HashSet::new();
+    for source_row in &source.rows {
+        let mut matched = false;
+        for (pointer, target_row) in &targets {
+            let row: Vec<SData> = target_row.iter().chain(source_row).cloned().collect();
+            if !evaluate_predicate(condition, &columns, &row)? {
+                continue;
+            }
+            matched = true;
+            let action = match first_applying(clauses, true, &columns, &row)? {
+                Some(MergeAction::Nothing) | None => continue,
+                Some(action) => action,
+            };
+            if !changed.insert(*pointer) {
+                return Err("MERGE command cannot affect row a second time".to_string());
+            }
+            match action {
+                MergeAction::Update(assignments) => {
+                    let assigned = assignment_targets(
+                        &table,
+                        table_name,
+                        &table_columns,
+                        assignments,
+                        database,
+                    )?;
+                    updates.push((
+                        *pointer,
+                        assign(&assigned, target_row, &columns, &row, database)?,
+                    ));
+                }
+                _ => deletes.push(*pointer),
+            }
+        }
+        if matched {
+            continue;
+        }
+        let row: Vec<SData> = vec![SData::NULL; table_columns.len()]
+            .into_iter()
+            .chain(source_row.iter().cloned())
+            .collect();
+        if let Some(MergeAction::Insert {
+            columns: names,
+            values,
+        }) = first_applying(clauses, false, &columns, &row)?
+        {
+            let names = if names.is_empty() {
+                table_columns
+                    .iter()
+                    .map(|column| unqualified_name(column).to_string())
+                    .collect()
+            } else {
+                names.clone()
+            };
+            inserts.push(new_row(
+                &table, table_name, &names, values, &columns, &row, database,
+            )?);
+        }
+    }
+
+    let count = updates.len() + deletes.len() + inserts.len();
+    delete_rows(database, &table, deletes)?;
+    update_rows(database, &table, updates)?;
+    insert_rows(database, &table, inserts)?;
+    Ok(count)
+}
+
+fn first_applying<'a>(
+    clauses: &'a [MergeClause],
+    matched: bool,
+    columns: &[String],
+    row: &[SData],
+) -> Result<Option<&'a MergeAction>, String> {
+    for clause in clauses.iter().filter(|clause| clause.matched == matched) {
+        let applies = match &clause.condition {
+            Some(condition) => evaluate_predicate(condition, columns, row)?,
+            None => true,
+        };
+        if applies {
+            return Ok(Some(&clause.action));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{QueryResult, Relation},
+        test_support::{open_database, run, select, sorted},
+    };
+
+    use super::*;
+
+    fn stock(id: i32, quantity: i32) -> Vec<SData> {
+        vec![SData::INT(id), SData::INT(quantity)]
+    }
+
+    #[test]
+    fn when_rows_are_inserted_with_on_conflict_should_skip_or_update_existing_rows() {
+        let (_directory, mut database) = open_database();
+        run(
+            &mut database,
+            "CREATE TABLE stock (id INT PRIMARY KEY, quantity INT);",
+        )
+        .unwrap();
+        run(
+            &mut database,
+            "INSERT INTO stock (id, quantity) VALUES (1, 10);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (1, 99) ON CONFLICT (id) DO NOTHING;"
+            ),
+            Ok(QueryResult::Command("INSERT 0 0".to_string()))
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (1, 5) ON CONFLICT (id) DO UPDATE SET quantity = stock.quantity + excluded.quantity RETURNING quantity;"
+            ),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["quantity".to_string()],
+                rows: vec![vec![SData::INT(15)]],
+            }))
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (1, 1) ON CONFLICT (id) DO UPDATE SET quantity = excluded.quantity WHERE stock.quantity < 10;"
+            ),
+            Ok(QueryResult::Command("INSERT 0 0".to_string()))
+        );
+        run(
+            &mut database,
+            "INSERT INTO stock (id, quantity) VALUES (2, 7) ON CONFLICT (id) DO UPDATE SET quantity = 0;",
+        )
+        .unwrap();
+        // later row of VALUES conflicts with the row inserted before it
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (2, 1), (3, 3), (3, 4) ON CONFLICT (id) DO NOTHING;"
+            ),
+            Ok(QueryResult::Command("INSERT 0 1".to_string()))
+        );
+        assert_eq!(
+            sorted(select(&mut database, "SELECT id, quantity FROM stock;")),
+            vec![stock(1, 15), stock(2, 7), stock(3, 3)]
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (4, 1), (4, 2) ON CONFLICT (id) DO UPDATE SET quantity = excluded.quantity;"
+            ),
+            Err("ON CONFLICT DO UPDATE command cannot affect row a second time".to_string())
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (1, 1), (1, 2) ON CONFLICT (id) DO UPDATE SET quantity = excluded.quantity;"
+            ),
+            Err("ON CONFLICT DO UPDATE command cannot affect row a second time".to_string())
+        );
+        assert_eq!(
+            sorted(select(&mut database, "SELECT id, quantity FROM stock;")),
+            vec![stock(1, 15), stock(2, 7), stock(3, 3)]
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (1, 1) ON CONFLICT (quantity) DO NOTHING;"
+            ),
+            Err(
+                "There is no unique or exclusion constraint matching the ON CONFLICT specification"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO stock (id, quantity) VALUES (1, 1) ON CONFLICT DO UPDATE SET quantity = 0;"
+            ),
+            Err(
+                "ON CONFLICT DO UPDATE requires inference specification or constraint name"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn when_merge_is_executed_should_update_delete_and_insert_by_source_rows() {
+        let (_directory, mut database) = open_database();
+        for sql in [
+            "CREATE TABLE stock (id INT PRIMARY KEY, quantity INT);",
+            "CREATE TABLE delivery (no INT PRIMARY KEY, id INT, quantity INT);",
+            "INSERT INTO stock (id, quantity) VALUES (1, 10);",
+            "INSERT INTO stock (id, quantity) VALUES (2, 3);",
+            "INSERT INTO stock (id, quantity) VALUES (3, 8);",
+            "INSERT INTO delivery (no, id, quantity) VALUES (1, 1, 5);",
+            "INSERT INTO delivery (no, id, quantity) VALUES (2, 2, -3);",
+            "INSERT INTO delivery (no, id, quantity) VALUES (3, 4, 6);",
+        ] {
+            run(&mut database, sql).unwrap();
+        }
+
+        assert_eq!(
+            run(
+                &mut database,
+                "MERGE INTO stock AS s USING delivery AS d ON s.id = d.id \
+                 WHEN MATCHED AND s.quantity + d.quantity = 0 THEN DELETE \
+                 WHEN MATCHED THEN UPDATE SET quantity = s.quantity + d.quantity \
+                 WHEN NOT MATCHED THEN INSERT (id, quantity) VALUES (d.id, d.quantity);"
+            ),
+            Ok(QueryResult::Command("MERGE 3".to_string()))
+        );
+        assert_eq!(
+            sorted(select(&mut database, "SELECT id, quantity FROM stock;")),
+            vec![stock(1, 15), stock(3, 8), stock(4, 6)]
+        );
+
+        run(
+            &mut database,
+            "INSERT INTO delivery (no, id, quantity) VALUES (4, 1, 1);",
+        )
+        .unwrap();
+        assert_eq!(
+            run(
+                &mut database,
+                "MERGE INTO stock USING delivery ON stock.id = delivery.id \
+                 WHEN MATCHED THEN UPDATE SET quantity = 0;"
+            ),
+            Err("MERGE command cannot affect row a second time".to_string())
+        );
+        assert_eq!(
+            sorted(select(&mut database, "SELECT id, quantity FROM stock;")),
+            vec![stock(1, 15), stock(3, 8), stock(4, 6)]
+        );
+    }
+}
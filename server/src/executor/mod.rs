@@ -1,2 +1,421 @@
-mod select_executor;
+pub mod select_executor;
+mod advisory;
 mod create_executor;
+mod insert_executor;
+mod merge;
+mod update_executor;
+mod delete_executor;
+mod aggregate;
+mod constraints;
+mod foreign_keys;
+mod modify;
+mod expression;
+mod functions;
+mod locking;
+mod pattern;
+mod returning;
+mod schemas;
+mod sequences;
+mod views;
+mod set_operations;
+mod settings;
+mod transactions;
+mod vacuum;
+mod volatile;
+mod window;
+
+use std::{
+    ops::Bound,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::{
+    error::SqlState,
+    information_schema::SData,
+    parser::ast::{Query, SqlStatement},
+    storage::{cluster::Cluster, Database, Session},
+};
+
+// rows produced by a scan or by any operator of the executor, columns are in the same order as values in rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<SData>>,
+}
+
+// source of table rows for the executor, columns returned by scan are not qualified with the table name
+pub trait TableSource {
+    fn scan(&self, table_name: &str) -> Result<Relation, String>;
+
+    // rows whose value of indexed column is inside of key range, None when the column has no index
+    fn index_range_scan(
+        &self,
+        _table_name: &str,
+        _column: &str,
+        _range: (Bound<SData>, Bound<SData>),
+    ) -> Result<Option<Relation>, String> {
+        Ok(None)
+    }
+
+    // long running query stops when it fails, it's checked by loops over rows
+    fn check_timeout(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // query of the view with given name, None when it isn't a view
+    fn view(&self, _name: &str) -> Result<Option<View>, String> {
+        Ok(None)
+    }
+}
+
+// view is expanded into its query, given columns rename columns of the query result
+pub struct View {
+    pub columns: Vec<String>,
+    pub query: Query,
+}
+
+// SELECT returns rows, other statements return command tag like postgres
+#[derive(Debug, PartialEq)]
+pub enum QueryResult {
+    Rows(Relation),
+    Command(String),
+}
+
+/*
+    statements about databases are executed by the cluster, others by the database used by the connection,
+    session starts again when connection switches to another database
+*/
+pub fn execute_in_cluster(
+    statement: &SqlStatement,
+    cluster: &Cluster,
+    database: &mut Arc<Mutex<Database>>,
+    session: &mut Session,
+) -> Result<QueryResult, String> {
+    if let SqlStatement::CreateDatabase { .. }
+    | SqlStatement::DropDatabase { .. }
+    | SqlStatement::Use { .. } = statement
+    {
+        transactions::check_allowed_in_transaction(statement, session)?;
+    }
+    match statement {
+        SqlStatement::CreateDatabase { name } => {
+            cluster.create_database(name)?;
+            Ok(QueryResult::Command("CREATE DATABASE".to_string()))
+        }
+        SqlStatement::DropDatabase { name, if_exists } => {
+            cluster.drop_database(name, *if_exists, database)?;
+            Ok(QueryResult::Command("DROP DATABASE".to_string()))
+        }
+        SqlStatement::Use { name } => {
+            let next = cluster.database(name)?;
+            end_session(database, session)?;
+            *database = next;
+            *session = Session::default();
+            Ok(QueryResult::Command("USE".to_string()))
+        }
+        _ => loop {
+            let (locks, owner, explicit) = {
+                let mut database = database.lock().map_err(|e| e.to_string())?;
+                let result = execute_statement(statement, &mut database, session);
+                match &session.transaction {
+                    Some(transaction) if database.locks.is_waiting(transaction.id)? => (
+                        Arc::clone(&database.locks),
+                        transaction.id,
+                        transaction.explicit,
+                    ),
+                    _ => return result,
+                }
+            };
+            // other sessions can use the database while this one waits for their locks
+            let deadline = [session.lock_deadline(), session.statement_deadline()]
+                .into_iter()
+                .flatten()
+                .min();
+            match locks.wait(owner, deadline) {
+                Ok(true) => (),
+                // statement was already rolled back, transaction of a single statement ends with it
+                Ok(false) => {
+                    let error = match session.check_statement_timeout() {
+                        Err(e) => e,
+                        Ok(()) => SqlState::LockNotAvailable.error("Canceling statement due to lock timeout"),
+                    };
+                    session.statement_started = None;
+                    if !explicit {
+                        abort_transaction(database, session)?;
+                    }
+                    return Err(error);
+                }
+                // victim of a deadlock loses its whole transaction, so its locks are released
+                Err(e) => {
+                    session.statement_started = None;
+                    abort_transaction(database, session)?;
+                    return Err(e);
+                }
+            }
+        },
+    }
+}
+
+/*
+    statement is executed with state of the session moved into the database,
+    statement which waits for a lock keeps its start time for its retry
+*/
+pub fn execute_statement(
+    statement: &SqlStatement,
+    database: &mut Database,
+    session: &mut Session,
+) -> Result<QueryResult, String> {
+    session.statement_started.get_or_insert_with(Instant::now);
+    let advisory_locks = session.advisory_locks.clone();
+    std::mem::swap(&mut database.session, session);
+    let result = match statement {
+        SqlStatement::Begin => transactions::begin(database),
+        SqlStatement::Commit => transactions::commit(database),
+        SqlStatement::Rollback => transactions::rollback(database),
+        SqlStatement::Savepoint { name } => transactions::savepoint(name, database),
+        SqlStatement::RollbackToSavepoint { name } => {
+            transactions::rollback_to_savepoint(name, database)
+        }
+        SqlStatement::ReleaseSavepoint { name } => transactions::release_savepoint(name, database),
+        SqlStatement::PrepareTransaction { gid } => transactions::prepare(gid, database),
+        SqlStatement::CommitPrepared { gid } => transactions::finish_prepared(gid, true, database),
+        SqlStatement::RollbackPrepared { gid } => {
+            transactions::finish_prepared(gid, false, database)
+        }
+        SqlStatement::SetTransaction { isolation } => {
+            transactions::set_transaction(*isolation, database)
+        }
+        SqlStatement::Vacuum { table_name } => {
+            transactions::check_allowed_in_transaction(statement, &database.session)
+                .and_then(|_| vacuum::vacuum(table_name.as_deref(), database))
+        }
+        _ => execute_atomically(statement, database),
+    };
+    let result = match database.is_waiting() {
+        // advisory locks are counted again when the statement is retried, its transaction still holds them
+        Ok(true) => {
+            database.session.advisory_locks = advisory_locks;
+            result
+        }
+        Ok(false) => {
+            database.session.statement_started = None;
+            result
+        }
+        Err(e) => Err(e),
+    };
+    std::mem::swap(&mut database.session, session);
+    result
+}
+
+// open transaction of the closed connection or of the victim of a deadlock is rolled back
+pub fn abort_transaction(
+    database: &Mutex<Database>,
+    session: &mut Session,
+) -> Result<(), String> {
+    if session.transaction.is_none() {
+        return Ok(());
+    }
+    let mut database = database.lock().map_err(|e| e.to_string())?;
+    std::mem::swap(&mut database.session, session);
+    let result = database.rollback();
+    std::mem::swap(&mut database.session, session);
+    result
+}
+
+// session leaving the database rolls back its transaction and releases its advisory locks
+pub fn end_session(database: &Mutex<Database>, session: &mut Session) -> Result<(), String> {
+    abort_transaction(database, session)?;
+    let mut database = database.lock().map_err(|e| e.to_string())?;
+    std::mem::swap(&mut database.session, session);
+    let result = database.advisory_unlock_all();
+    std::mem::swap(&mut database.session, session);
+    result
+}
+
+/*
+    statement is atomic, changes of failed statement are rolled back,
+    statement outside of transaction block runs in its own transaction, which is committed right away,
+    failed statement inside of transaction block rolls back only itself,
+    unless it's a serialization failure, then the whole transaction is rolled back to be retried,
+    statement waiting for a lock keeps its transaction and locks, because it's going to be retried,
+    every statement reads rows committed before it started
+*/
+fn execute_atomically(
+    statement: &SqlStatement,
+    database: &mut Database,
+) -> Result<QueryResult, String> {
+    transactions::check_allowed_in_transaction(statement, &database.session)?;
+    if database.session.transaction.is_none() {
+        database.begin(false)?;
+    }
+    // settings don't read rows, isolation level can still be changed after them
+    if !matches!(statement, SqlStatement::Set { .. } | SqlStatement::Show { .. }) {
+        database.take_snapshot()?;
+    }
+    let explicit = database
+        .session
+        .transaction
+        .as_ref()
+        .is_some_and(|transaction| transaction.explicit);
+    let savepoint = database.savepoint();
+    let result = execute(statement, database)
+        .and_then(|result| foreign_keys::check_references(database, savepoint).map(|_| result));
+    match result {
+        Ok(result) => {
+            if !explicit {
+                database.commit()?;
+            }
+            Ok(result)
+        }
+        Err(e) => {
+            database.rollback_to(savepoint)?;
+            let failed = database
+                .session
+                .transaction
+                .as_ref()
+                .is_some_and(|transaction| transaction.failed);
+            if failed || (!explicit && !database.is_waiting()?) {
+                database.rollback()?;
+            }
+            Err(e)
+        }
+    }
+}
+
+fn execute(statement: &SqlStatement, database: &mut Database) -> Result<QueryResult, String> {
+    match statement {
+        SqlStatement::CreateTable {
+            table_name,
+            columns,
+            constraints,
+        } => {
+            create_executor::create_table(table_name, columns, constraints, database)?;
+            Ok(QueryResult::Command("CREATE TABLE".to_string()))
+        }
+        SqlStatement::CreateSequence { name, options } => {
+            sequences::create_sequence(name, options, database)?;
+            Ok(QueryResult::Command("CREATE SEQUENCE".to_string()))
+        }
+        SqlStatement::CreateView {
+            name,
+            columns,
+            query,
+            materialized,
+        } => {
+            views::create_view(name, columns, query, *materialized, database)?;
+            Ok(QueryResult::Command(if *materialized {
+                "CREATE MATERIALIZED VIEW".to_string()
+            } else {
+                "CREATE VIEW".to_string()
+            }))
+        }
+        SqlStatement::RefreshMaterializedView { name } => {
+            views::refresh_materialized_view(name, database)?;
+            Ok(QueryResult::Command("REFRESH MATERIALIZED VIEW".to_string()))
+        }
+        SqlStatement::CreateIndex {
+            name,
+            table_name,
+            columns,
+            unique,
+        } => {
+            create_executor::create_index(name, table_name, columns, *unique, database)?;
+            Ok(QueryResult::Command("CREATE INDEX".to_string()))
+        }
+        SqlStatement::InsertInto {
+            table_name,
+            column_names,
+            values,
+            on_conflict,
+            returning,
+        } => {
+            let inserted =
+                insert_executor::insert(table_name, column_names, values, on_conflict, database)?;
+            returning::changed_rows_result(table_name, returning, inserted, "INSERT 0", database)
+        }
+        SqlStatement::Update {
+            table_name,
+            assignments,
+            selection,
+            returning,
+        } => {
+            let updated = update_executor::update(table_name, assignments, selection, database)?;
+            returning::changed_rows_result(table_name, returning, updated, "UPDATE", database)
+        }
+        SqlStatement::Merge {
+            table_name,
+            alias,
+            source,
+            source_alias,
+            condition,
+            clauses,
+        } => {
+            let count = merge::merge(
+                table_name,
+                alias,
+                source,
+                source_alias,
+                condition,
+                clauses,
+                database,
+            )?;
+            Ok(QueryResult::Command(format!("MERGE {}", count)))
+        }
+        SqlStatement::Delete {
+            table_name,
+            selection,
+            returning,
+        } => {
+            let deleted = delete_executor::delete(table_name, selection, database)?;
+            returning::changed_rows_result(table_name, returning, deleted, "DELETE", database)
+        }
+        SqlStatement::Query(query) => match &query.locking {
+            Some(locking) => Ok(QueryResult::Rows(locking::execute_locking_query(
+                query, locking, database,
+            )?)),
+            None if volatile::calls_volatile_functions(query) => Ok(QueryResult::Rows(
+                volatile::execute_volatile_query(query, database)?,
+            )),
+            None => Ok(QueryResult::Rows(select_executor::execute_query(
+                query, database,
+            )?)),
+        },
+        SqlStatement::CreateSchema { name } => {
+            schemas::create_schema(name, database)?;
+            Ok(QueryResult::Command("CREATE SCHEMA".to_string()))
+        }
+        SqlStatement::DropSchema {
+            name,
+            if_exists,
+            cascade,
+        } => {
+            schemas::drop_schema(name, *if_exists, *cascade, database)?;
+            Ok(QueryResult::Command("DROP SCHEMA".to_string()))
+        }
+        SqlStatement::Set { name, values } => {
+            settings::set(name, values, database)?;
+            Ok(QueryResult::Command("SET".to_string()))
+        }
+        SqlStatement::Show { name } => Ok(QueryResult::Rows(settings::show(name, database)?)),
+        SqlStatement::CreateDatabase { .. }
+        | SqlStatement::DropDatabase { .. }
+        | SqlStatement::Use { .. } => {
+            Err("Statement about databases can't be executed inside of a database".to_string())
+        }
+        // they are handled by execute_statement, outside of the transaction of the statement
+        SqlStatement::Begin
+        | SqlStatement::Commit
+        | SqlStatement::Rollback
+        | SqlStatement::Savepoint { .. }
+        | SqlStatement::RollbackToSavepoint { .. }
+        | SqlStatement::ReleaseSavepoint { .. }
+        | SqlStatement::PrepareTransaction { .. }
+        | SqlStatement::CommitPrepared { .. }
+        | SqlStatement::RollbackPrepared { .. }
+        | SqlStatement::SetTransaction { .. } => {
+            Err("Transaction control statement can't be a part of transaction".to_string())
+        }
+        SqlStatement::Vacuum { .. } => Err("VACUUM can't be a part of transaction".to_string()),
+    }
+}
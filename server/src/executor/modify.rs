@@ -0,0 +1,77 @@
+use crate::{
+    bptree::page::TuplePointer, catalog::tables::PgClass, information_schema::SData,
+    storage::Database,
+};
+
+use super::{
+    constraints::TableConstraints,
+    foreign_keys::{on_parent_delete, on_parent_update},
+};
+
+/*
+    row changes shared by INSERT, UPDATE, DELETE and referential actions:
+        constraints of the table are checked before rows are changed
+        references of written rows are checked at the end of the statement, after all referential actions
+    failed change isn't undone here, statement is rolled back by its caller
+*/
+pub fn insert_rows(
+    database: &mut Database,
+    table: &PgClass,
+    rows: Vec<Vec<SData>>,
+) -> Result<(), String> {
+    let constraints = TableConstraints::load(database, table)?;
+    let rows: Vec<(Option<TuplePointer>, Vec<SData>)> =
+        rows.into_iter().map(|row| (None, row)).collect();
+    for (_, row) in &rows {
+        constraints.check_row(row)?;
+    }
+    constraints.check_unique(database, &rows)?;
+    for (_, row) in rows {
+        database.insert_row(table.oid, row)?;
+    }
+    Ok(())
+}
+
+/*
+    rows are replaced with their new versions, old versions are deleted first so unique keys can be swapped,
+    deleting them locks them and fails on concurrent updates before new version of the row is taken for a duplicate
+*/
+pub fn update_rows(
+    database: &mut Database,
+    table: &PgClass,
+    updates: Vec<(TuplePointer, Vec<SData>)>,
+) -> Result<(), String> {
+    let constraints = TableConstraints::load(database, table)?;
+    let updates: Vec<(Option<TuplePointer>, Vec<SData>)> = updates
+        .into_iter()
+        .map(|(pointer, row)| (Some(pointer), row))
+        .collect();
+    for (_, row) in &updates {
+        constraints.check_row(row)?;
+    }
+    let mut old_rows = Vec::with_capacity(updates.len());
+    for (pointer, _) in &updates {
+        if let Some(pointer) = pointer {
+            old_rows.push(database.delete_row(table.oid, *pointer)?);
+        }
+    }
+    constraints.check_unique(database, &updates)?;
+    let mut new_rows = Vec::with_capacity(updates.len());
+    for (_, row) in updates {
+        database.insert_row(table.oid, row.clone())?;
+        new_rows.push(row);
+    }
+    on_parent_update(database, table, &old_rows, &new_rows)
+}
+
+pub fn delete_rows(
+    database: &mut Database,
+    table: &PgClass,
+    pointers: Vec<TuplePointer>,
+) -> Result<(), String> {
+    let mut deleted = Vec::with_capacity(pointers.len());
+    for pointer in pointers {
+        deleted.push(database.delete_row(table.oid, pointer)?);
+    }
+    on_parent_delete(database, table, &deleted)
+}
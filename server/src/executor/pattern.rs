@@ -0,0 +1,236 @@
+use std::{cell::RefCell, ops::Bound};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::information_schema::SData;
+
+// escape character used by LIKE when ESCAPE clause isn't given
+pub const DEFAULT_LIKE_ESCAPE: char = '\\';
+
+#[derive(Debug, PartialEq)]
+enum LikeToken {
+    Literal(char),
+    // _
+    AnyCharacter,
+    // %
+    AnySequence,
+}
+
+fn tokenize_like(pattern: &str, escape: Option<char>) -> Result<Vec<LikeToken>, String> {
+    let mut tokens = vec![];
+    let mut characters = pattern.chars();
+    while let Some(character) = characters.next() {
+        let token = match character {
+            character if Some(character) == escape => match characters.next() {
+                Some(escaped) => LikeToken::Literal(escaped),
+                None => return Err("LIKE pattern must not end with escape character".to_string()),
+            },
+            '%' => LikeToken::AnySequence,
+            '_' => LikeToken::AnyCharacter,
+            character => LikeToken::Literal(character),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/*
+    wildcard matching with backtracking to the last %:
+        _ matches exactly one character, % matches any sequence of characters including empty one
+    pattern has to match whole value
+*/
+pub fn like(
+    value: &str,
+    pattern: &str,
+    escape: Option<char>,
+    case_insensitive: bool,
+) -> Result<bool, String> {
+    let (value, pattern) = if case_insensitive {
+        (value.to_lowercase(), pattern.to_lowercase())
+    } else {
+        (value.to_string(), pattern.to_string())
+    };
+    let value: Vec<char> = value.chars().collect();
+    let tokens = tokenize_like(&pattern, escape)?;
+    let (mut value_position, mut token_position) = (0, 0);
+    // position of the last % and value position it was tried with
+    let mut backtrack: Option<(usize, usize)> = None;
+    while value_position < value.len() {
+        match tokens.get(token_position) {
+            Some(LikeToken::AnySequence) => {
+                backtrack = Some((token_position, value_position));
+                token_position += 1;
+            }
+            Some(LikeToken::AnyCharacter) => {
+                value_position += 1;
+                token_position += 1;
+            }
+            Some(LikeToken::Literal(character)) if *character == value[value_position] => {
+                value_position += 1;
+                token_position += 1;
+            }
+            _ => match backtrack {
+                Some((sequence_position, matched_until)) => {
+                    backtrack = Some((sequence_position, matched_until + 1));
+                    token_position = sequence_position + 1;
+                    value_position = matched_until + 1;
+                }
+                None => return Ok(false),
+            },
+        }
+    }
+    Ok(tokens[token_position..]
+        .iter()
+        .all(|token| *token == LikeToken::AnySequence))
+}
+
+/*
+    literal part of the pattern before the first wildcard, values matching the pattern start with it,
+    for example "abc%" and "abc_d" have prefix "abc"
+*/
+pub fn like_prefix(pattern: &str, escape: Option<char>) -> Result<String, String> {
+    Ok(tokenize_like(pattern, escape)?
+        .into_iter()
+        .map_while(|token| match token {
+            LikeToken::Literal(character) => Some(character),
+            _ => None,
+        })
+        .collect())
+}
+
+// key range containing exactly the strings which start with prefix
+pub fn prefix_bounds(prefix: &str) -> (Bound<SData>, Bound<SData>) {
+    let start = Bound::Included(SData::STRING(prefix.to_string()));
+    let mut characters: Vec<char> = prefix.chars().collect();
+    while let Some(last) = characters.pop() {
+        if let Some(next) = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32) {
+            characters.push(next);
+            let end: String = characters.into_iter().collect();
+            return (start, Bound::Excluded(SData::STRING(end)));
+        }
+    }
+    (start, Bound::Unbounded)
+}
+
+thread_local! {
+    // last compiled pattern, constant pattern of an expression is compiled once instead of for every row
+    static LAST_REGEX: RefCell<Option<(String, bool, Regex)>> = const { RefCell::new(None) };
+}
+
+pub fn regex(pattern: &str, case_insensitive: bool) -> Result<Regex, String> {
+    LAST_REGEX.with(|last| {
+        if let Some((last_pattern, last_case_insensitive, regex)) = &*last.borrow() {
+            if last_pattern == pattern && *last_case_insensitive == case_insensitive {
+                return Ok(regex.clone());
+            }
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|err| format!("Invalid regular expression \"{}\": {}", pattern, err))?;
+        *last.borrow_mut() = Some((pattern.to_string(), case_insensitive, regex.clone()));
+        Ok(regex)
+    })
+}
+
+/*
+    replacement references groups as \1 to \9 and whole match as \&, flags:
+        g: replace all matches instead of only the first one
+        i: case insensitive matching
+*/
+pub fn regexp_replace(
+    value: &str,
+    pattern: &str,
+    replacement: &str,
+    flags: &str,
+) -> Result<String, String> {
+    if let Some(flag) = flags.chars().find(|flag| !matches!(flag, 'g' | 'i')) {
+        return Err(format!("Invalid regular expression flag {}", flag));
+    }
+    let regex = regex(pattern, flags.contains('i'))?;
+    let mut translated = String::with_capacity(replacement.len());
+    let mut characters = replacement.chars().peekable();
+    while let Some(character) = characters.next() {
+        match (character, characters.peek()) {
+            ('\\', Some(group @ '1'..='9')) => {
+                translated.push_str(&format!("${{{}}}", group));
+                characters.next();
+            }
+            ('\\', Some('&')) => {
+                translated.push_str("${0}");
+                characters.next();
+            }
+            ('\\', Some('\\')) => {
+                translated.push('\\');
+                characters.next();
+            }
+            ('$', _) => translated.push_str("$$"),
+            (character, _) => translated.push(character),
+        }
+    }
+    let limit = if flags.contains('g') { 0 } else { 1 };
+    Ok(regex
+        .replacen(value, limit, translated.as_str())
+        .into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_like_pattern_has_wildcards_it_should_match_whole_value() {
+        assert_eq!(like("database", "d%b_se", None, false), Ok(true));
+        assert_eq!(like("database", "%a%a%a%", None, false), Ok(true));
+        assert_eq!(like("database", "data", None, false), Ok(false));
+        assert_eq!(like("database", "_atabas", None, false), Ok(false));
+        assert_eq!(like("", "%", None, false), Ok(true));
+        assert_eq!(like("DataBase", "data%", None, true), Ok(true));
+    }
+
+    #[test]
+    fn when_like_pattern_has_escaped_wildcard_it_should_match_it_literally() {
+        assert_eq!(like("100%", "100!%", Some('!'), false), Ok(true));
+        assert_eq!(like("1000", "100!%", Some('!'), false), Ok(false));
+        assert_eq!(
+            like("a_b", "a\\_%", Some(DEFAULT_LIKE_ESCAPE), false),
+            Ok(true)
+        );
+        assert!(like("a", "a!", Some('!'), false).is_err());
+    }
+
+    #[test]
+    fn when_pattern_starts_with_literals_it_should_return_prefix_range() {
+        let prefix = like_prefix("ab!%c%d", Some('!')).unwrap();
+
+        assert_eq!(prefix, "ab%c");
+        assert_eq!(
+            prefix_bounds(&prefix),
+            (
+                Bound::Included(SData::STRING("ab%c".to_string())),
+                Bound::Excluded(SData::STRING("ab%d".to_string()))
+            )
+        );
+        assert_eq!(like_prefix("%abc", None), Ok("".to_string()));
+    }
+
+    #[test]
+    fn when_regexp_replace_is_called_it_should_replace_first_or_all_matches() {
+        assert_eq!(
+            regexp_replace("2024-01-31", "(\\d+)-(\\d+)-(\\d+)", "\\3.\\2.\\1", ""),
+            Ok("31.01.2024".to_string())
+        );
+        assert_eq!(regexp_replace("Aaa", "a", "$", "gi"), Ok("$$$".to_string()));
+        assert_eq!(regexp_replace("aaa", "a", "b", ""), Ok("baa".to_string()));
+        assert!(regexp_replace("a", "(", "b", "").is_err());
+    }
+
+    #[test]
+    fn when_pattern_is_compiled_again_it_should_respect_case_sensitivity() {
+        assert!(regex("^ab$", false).unwrap().is_match("ab"));
+        assert!(!regex("^ab$", false).unwrap().is_match("AB"));
+        assert!(regex("^ab$", true).unwrap().is_match("AB"));
+        assert!(regex("^ab$", false).is_ok());
+        assert!(regex("(", false).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{information_schema::SData, parser::ast::Columns, storage::Database};
+
+use super::{expression::evaluate, select_executor::output_name, QueryResult, Relation};
+
+/*
+    statement with RETURNING sends back rows which it wrote, or deleted, like result of SELECT,
+    the list is evaluated for every row like select list of a query over the table,
+    statement without RETURNING gives command tag with number of the rows
+*/
+pub fn changed_rows_result(
+    table_name: &str,
+    returning: &Option<Columns>,
+    rows: Vec<Vec<SData>>,
+    tag: &str,
+    database: &Database,
+) -> Result<QueryResult, String> {
+    let items = match returning {
+        Some(Columns::Specific(items)) => items,
+        Some(Columns::All) => {
+            return Ok(QueryResult::Rows(Relation {
+                columns: column_names(table_name, database, false)?,
+                rows,
+            }))
+        }
+        None => return Ok(QueryResult::Command(format!("{} {}", tag, rows.len()))),
+    };
+    let columns = column_names(table_name, database, true)?;
+    let mut returned = Vec::with_capacity(rows.len());
+    for row in &rows {
+        returned.push(
+            items
+                .iter()
+                .map(|item| evaluate(&item.expression, &columns, row))
+                .collect::<Result<Vec<SData>, String>>()?,
+        );
+    }
+    Ok(QueryResult::Rows(Relation {
+        columns: items.iter().map(output_name).collect(),
+        rows: returned,
+    }))
+}
+
+fn column_names(
+    table_name: &str,
+    database: &Database,
+    qualified: bool,
+) -> Result<Vec<String>, String> {
+    let table = database
+        .catalog
+        .table(table_name, &database.session.search_path)?;
+    Ok(database
+        .catalog
+        .attributes_of(table.oid)
+        .iter()
+        .map(|attribute| match qualified {
+            true => format!("{}.{}", table.name, attribute.name),
+            false => attribute.name.clone(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{open_database, run};
+
+    use super::*;
+
+    fn rows(columns: &[&str], rows: Vec<Vec<SData>>) -> Result<QueryResult, String> {
+        Ok(QueryResult::Rows(Relation {
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            rows,
+        }))
+    }
+
+    fn text(value: &str) -> SData {
+        SData::STRING(value.to_string())
+    }
+
+    #[test]
+    fn when_statement_has_returning_it_should_return_written_rows() {
+        let (_directory, mut database) = open_database();
+        run(
+            &mut database,
+            "CREATE TABLE items (id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY, name VARCHAR, stock INT DEFAULT 5);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO items (name) VALUES (\"pen\") RETURNING id, items.stock * 2 AS doubled;"
+            ),
+            rows(&["id", "doubled"], vec![vec![SData::INT(1), SData::INT(10)]])
+        );
+        assert_eq!(
+            run(&mut database, "INSERT INTO items (name) VALUES (\"ink\");"),
+            Ok(QueryResult::Command("INSERT 0 1".to_string()))
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "UPDATE items SET stock = stock - 1 WHERE name = \"ink\" RETURNING *;"
+            ),
+            rows(
+                &["id", "name", "stock"],
+                vec![vec![SData::INT(2), text("ink"), SData::INT(4)]]
+            )
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "DELETE FROM items WHERE id = 1 RETURNING name;"
+            ),
+            rows(&["name"], vec![vec![text("pen")]])
+        );
+        assert_eq!(
+            run(&mut database, "DELETE FROM items RETURNING COUNT(*);"),
+            Err("Aggregate function count is not allowed here".to_string())
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "DELETE FROM items WHERE id = 7 RETURNING id;"
+            ),
+            rows(&["id"], vec![])
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    catalog::{tables::PgNamespace, Oid},
+    lock_manager::lock::{LockMode, LockTarget},
+    storage::Database,
+};
+
+pub fn create_schema(name: &str, database: &mut Database) -> Result<(), String> {
+    if database.catalog.namespace(name).is_ok() {
+        return Err(format!("Schema \"{}\" already exists", name));
+    }
+    let mut catalog = database.catalog.clone();
+    let oid = catalog.allocate_oid();
+    catalog.namespaces.push(PgNamespace {
+        oid,
+        name: name.to_string(),
+    });
+    database.catalog = catalog;
+    database.save_catalog()
+}
+
+/*
+    schema with relations can be dropped only with CASCADE, which drops the relations too,
+    foreign keys of tables in other schemas, which reference dropped tables, are dropped with them,
+    files are deleted after the catalog is saved, so the catalog never points to missing file
+*/
+pub fn drop_schema(
+    name: &str,
+    if_exists: bool,
+    cascade: bool,
+    database: &mut Database,
+) -> Result<(), String> {
+    let namespace = match database.catalog.namespace(name) {
+        Ok(namespace) => namespace.oid,
+        Err(_) if if_exists => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let relations: Vec<Oid> = database
+        .catalog
+        .classes
+        .iter()
+        .filter(|class| class.namespace == namespace)
+        .map(|class| class.oid)
+        .collect();
+    if !relations.is_empty() && !cascade {
+        return Err(format!(
+            "Cannot drop schema \"{}\" because other objects depend on it",
+            name
+        ));
+    }
+    for relation in &relations {
+        database.lock(LockTarget::Table(*relation), LockMode::Exclusive)?;
+    }
+    let mut catalog = database.catalog.clone();
+    catalog
+        .namespaces
+        .retain(|pg_namespace| pg_namespace.oid != namespace);
+    let removed = catalog.remove_relations(&relations);
+    database.catalog = catalog;
+    database.save_catalog()?;
+    for relation in removed {
+        database.drop_storage(relation)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::QueryResult,
+        information_schema::SData,
+        storage::Session,
+        test_support::{open_database, run_in, select_in, sorted},
+    };
+
+    use super::*;
+
+    fn text(value: &str) -> SData {
+        SData::STRING(value.to_string())
+    }
+
+    #[test]
+    fn when_tables_have_same_name_in_different_schemas_search_path_should_choose_one() {
+        let (_directory, mut database) = open_database();
+        let (mut sales, mut hr) = (Session::default(), Session::default());
+        for sql in [
+            "CREATE SCHEMA sales;",
+            "CREATE SCHEMA hr;",
+            "CREATE TABLE sales.people (id SERIAL PRIMARY KEY, name VARCHAR);",
+            "CREATE TABLE hr.people (id INT PRIMARY KEY, name VARCHAR);",
+            "INSERT INTO hr.people (id, name) VALUES (1, \"hank\");",
+            "SET search_path TO sales, public;",
+            "INSERT INTO people (name) VALUES (\"sam\");",
+        ] {
+            run_in(&mut database, &mut sales, sql).unwrap();
+        }
+        run_in(&mut database, &mut hr, "SET search_path = hr;").unwrap();
+
+        assert_eq!(
+            sorted(select_in(
+                &mut database,
+                &mut sales,
+                "SELECT id, name FROM people;"
+            )),
+            vec![vec![SData::INT(1), text("sam")]]
+        );
+        assert_eq!(
+            sorted(select_in(
+                &mut database,
+                &mut hr,
+                "SELECT people.name FROM people;"
+            )),
+            vec![vec![text("hank")]]
+        );
+        assert_eq!(
+            sorted(select_in(
+                &mut database,
+                &mut hr,
+                "SELECT s.name, people.name FROM sales.people AS s JOIN people ON s.id = people.id;"
+            )),
+            vec![vec![text("sam"), text("hank")]]
+        );
+        assert_eq!(
+            sorted(select_in(&mut database, &mut sales, "SHOW search_path;")),
+            vec![vec![text("sales, public")]]
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut hr,
+                "CREATE TABLE hr.people (id INT PRIMARY KEY);"
+            ),
+            Err("Relation \"people\" already exists".to_string())
+        );
+        assert_eq!(
+            run_in(&mut database, &mut hr, "SELECT * FROM public.people;"),
+            Err("Relation \"public.people\" does not exist".to_string())
+        );
+    }
+
+    #[test]
+    fn when_schema_is_dropped_its_relations_should_be_dropped_only_with_cascade() {
+        let (directory, mut database) = open_database();
+        let mut session = Session::default();
+        for sql in [
+            "CREATE SCHEMA sales;",
+            "CREATE TABLE sales.orders (id SERIAL PRIMARY KEY);",
+            "CREATE TABLE invoices (id INT PRIMARY KEY, order_id INT REFERENCES sales.orders);",
+            "INSERT INTO sales.orders (id) VALUES (nextval(\"sales.orders_id_seq\"));",
+            "INSERT INTO invoices (id, order_id) VALUES (1, 1);",
+        ] {
+            run_in(&mut database, &mut session, sql).unwrap();
+        }
+
+        assert_eq!(
+            run_in(&mut database, &mut session, "DROP SCHEMA sales;"),
+            Err("Cannot drop schema \"sales\" because other objects depend on it".to_string())
+        );
+        run_in(&mut database, &mut session, "DROP SCHEMA sales CASCADE;").unwrap();
+        run_in(&mut database, &mut session, "DROP SCHEMA IF EXISTS sales;").unwrap();
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        assert!(database.catalog.namespace("sales").is_err());
+        assert!(database.catalog.foreign_keys.is_empty());
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut session,
+                "INSERT INTO invoices (id, order_id) VALUES (2, 7);"
+            ),
+            Ok(QueryResult::Command("INSERT 0 1".to_string()))
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut session,
+                "CREATE TABLE sales.orders (id INT PRIMARY KEY);"
+            ),
+            Err("Schema \"sales\" does not exist".to_string())
+        );
+    }
+}
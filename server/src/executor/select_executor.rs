@@ -1,7 +1,966 @@
-/* fn select(select: &SqlStatement::Select) {
-    
-    // search for appropriate index if there is one
-    // demand index tree with that index 
-    // get specic page gooten from that index 
-    // load and parse
-} */
\ No newline at end of file
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Bound,
+};
+
+use crate::{
+    catalog::split_name,
+    constants::MAX_RECURSIVE_CTE_ITERATIONS,
+    information_schema::SData,
+    parser::ast::{
+        BinaryOperator, Columns, Cte, Expression, Join, Query, QueryBody, Select, SelectItem,
+        SetOperator, Value, With,
+    },
+};
+
+use super::{
+    aggregate::{aggregate_relation, contains_aggregate},
+    expression::{evaluate, evaluate_predicate, unqualified_name},
+    pattern::{like_prefix, prefix_bounds, DEFAULT_LIKE_ESCAPE},
+    set_operations::{combine, distinct},
+    window::apply_window_functions,
+    Relation, TableSource,
+};
+
+// materialized CTEs, they shadow tables with the same name
+type CteScope = HashMap<String, Relation>;
+
+// column of the base table and range of its values which can be read through an index
+type IndexRange = (String, (Bound<SData>, Bound<SData>));
+
+pub fn execute_query(query: &Query, source: &dyn TableSource) -> Result<Relation, String> {
+    execute(query, source, &CteScope::new())
+}
+
+// CTEs are visible only inside of the query which defines them
+fn execute(query: &Query, source: &dyn TableSource, scope: &CteScope) -> Result<Relation, String> {
+    match &query.with {
+        Some(with) => {
+            let mut scope = scope.clone();
+            materialize_ctes(with, source, &mut scope)?;
+            execute_body(&query.body, source, &scope)
+        }
+        None => execute_body(&query.body, source, scope),
+    }
+}
+
+fn execute_body(
+    body: &QueryBody,
+    source: &dyn TableSource,
+    scope: &CteScope,
+) -> Result<Relation, String> {
+    match body {
+        QueryBody::Select(select) => execute_select(select, source, scope),
+        QueryBody::SetOperation {
+            operator,
+            all,
+            left,
+            right,
+        } => combine(
+            operator,
+            *all,
+            execute_body(left, source, scope)?,
+            execute_body(right, source, scope)?,
+        ),
+    }
+}
+
+fn execute_select(
+    select: &Select,
+    source: &dyn TableSource,
+    scope: &CteScope,
+) -> Result<Relation, String> {
+    let relation = selected_rows(select, source, scope)?;
+    let relation = project(relation, &select.columns)?;
+    if select.distinct {
+        Ok(distinct(relation))
+    } else {
+        Ok(relation)
+    }
+}
+
+// rows of FROM, joins and WHERE, before the select list is computed
+fn selected_rows(
+    select: &Select,
+    source: &dyn TableSource,
+    scope: &CteScope,
+) -> Result<Relation, String> {
+    let mut relation = scan_base_table(select, source, scope)?;
+    for join in &select.joins {
+        relation = nested_loop_join(relation, join, source, scope)?;
+    }
+    match &select.selection {
+        Some(selection) => filter(relation, selection),
+        None => Ok(relation),
+    }
+}
+
+// select outside of WITH, whose select list is computed by the caller
+pub(super) fn select_rows(select: &Select, source: &dyn TableSource) -> Result<Relation, String> {
+    selected_rows(select, source, &CteScope::new())
+}
+
+fn materialize_ctes(
+    with: &With,
+    source: &dyn TableSource,
+    scope: &mut CteScope,
+) -> Result<(), String> {
+    for cte in &with.ctes {
+        let relation = match &cte.query.body {
+            QueryBody::SetOperation {
+                operator: SetOperator::Union,
+                all,
+                left,
+                right,
+            } if with.recursive && cte.query.with.is_none() && references(right, &cte.name) => {
+                evaluate_recursive_cte(cte, *all, left, right, source, scope)?
+            }
+            _ => rename_columns(execute(&cte.query, source, scope)?, &cte.columns)?,
+        };
+        scope.insert(cte.name.clone(), relation);
+    }
+    Ok(())
+}
+
+fn references(body: &QueryBody, name: &str) -> bool {
+    match body {
+        QueryBody::Select(select) => {
+            select.table.as_deref() == Some(name)
+                || select.joins.iter().any(|join| join.table == name)
+        }
+        QueryBody::SetOperation { left, right, .. } => {
+            references(left, name) || references(right, name)
+        }
+    }
+}
+
+/*
+    iterative fixpoint:
+        1. result and working table are rows of the anchor query
+        2. recursive term is evaluated with cte name bound to the working table
+        3. rows it produced (without already seen rows in case of UNION) become new working table
+        4. repeat until working table is empty
+*/
+fn evaluate_recursive_cte(
+    cte: &Cte,
+    all: bool,
+    anchor: &QueryBody,
+    recursive_term: &QueryBody,
+    source: &dyn TableSource,
+    scope: &mut CteScope,
+) -> Result<Relation, String> {
+    let anchor = rename_columns(execute_body(anchor, source, scope)?, &cte.columns)?;
+    let columns = anchor.columns;
+    let mut seen = HashSet::new();
+    let mut rows = vec![];
+    let mut working = vec![];
+    for row in anchor.rows {
+        if all || seen.insert(row.clone()) {
+            rows.push(row.clone());
+            working.push(row);
+        }
+    }
+    let mut iteration = 0;
+    while !working.is_empty() {
+        if iteration == MAX_RECURSIVE_CTE_ITERATIONS {
+            return Err(format!(
+                "Recursive CTE {} didn't finish after {} iterations",
+                cte.name, MAX_RECURSIVE_CTE_ITERATIONS
+            ));
+        }
+        iteration += 1;
+        source.check_timeout()?;
+        scope.insert(
+            cte.name.clone(),
+            Relation {
+                columns: columns.clone(),
+                rows: working,
+            },
+        );
+        let produced = execute_body(recursive_term, source, scope)?;
+        if produced.columns.len() != columns.len() {
+            return Err(format!(
+                "Recursive term of CTE {} returns {} columns, expected {}",
+                cte.name,
+                produced.columns.len(),
+                columns.len()
+            ));
+        }
+        working = vec![];
+        for row in produced.rows {
+            if all || seen.insert(row.clone()) {
+                rows.push(row.clone());
+                working.push(row);
+            }
+        }
+    }
+    Ok(Relation { columns, rows })
+}
+
+pub(super) fn rename_columns(relation: Relation, names: &[String]) -> Result<Relation, String> {
+    if names.is_empty() {
+        return Ok(relation);
+    }
+    if names.len() != relation.columns.len() {
+        return Err(format!(
+            "Query returns {} columns, but {} column names were given",
+            relation.columns.len(),
+            names.len()
+        ));
+    }
+    Ok(Relation {
+        columns: names.to_vec(),
+        rows: relation.rows,
+    })
+}
+
+/*
+    table in FROM is read through an index when WHERE has top level conjunct column LIKE "prefix%"
+    or comparison of column with constant, where column belongs to the table,
+    only keys starting with the prefix or inside of the compared range are read,
+    conjuncts are tried in order until one has an index, whole WHERE is still evaluated on the returned rows,
+    ranges are read from the in-memory index tree, paged NewLeafNode keys aren't used because indexes aren't stored in pages
+*/
+fn scan_base_table(
+    select: &Select,
+    source: &dyn TableSource,
+    scope: &CteScope,
+) -> Result<Relation, String> {
+    let table = match &select.table {
+        Some(table) => table,
+        // SELECT without FROM computes its select list once, for one row without columns
+        None => {
+            return Ok(Relation {
+                columns: vec![],
+                rows: vec![vec![]],
+            })
+        }
+    };
+    let indexable = match &select.selection {
+        Some(selection) if !scope.contains_key(table) => indexable_ranges(selection, select),
+        _ => vec![],
+    };
+    for (column, range) in indexable {
+        if let Some(relation) = source.index_range_scan(table, &column, range)? {
+            return Ok(qualify(relation, table, &select.alias));
+        }
+    }
+    scan(table, &select.alias, source, scope)
+}
+
+// top level conjuncts which can be read through an index of the base table
+fn indexable_ranges(expression: &Expression, select: &Select) -> Vec<IndexRange> {
+    match expression {
+        Expression::BinaryOperation {
+            left,
+            operator: BinaryOperator::And,
+            right,
+        } => {
+            let mut ranges = indexable_ranges(left, select);
+            ranges.extend(indexable_ranges(right, select));
+            ranges
+        }
+        other => indexable_range(other, select).into_iter().collect(),
+    }
+}
+
+// column of the base table and range of its keys given by literal prefix of the LIKE pattern or by comparison
+fn indexable_range(expression: &Expression, select: &Select) -> Option<IndexRange> {
+    match expression {
+        Expression::BinaryOperation {
+            left,
+            operator,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (Expression::Column(name), Expression::Value(value)) => Some((
+                base_table_column(name, select)?,
+                comparison_range(operator, constant(value)?)?,
+            )),
+            (Expression::Value(value), Expression::Column(name)) => Some((
+                base_table_column(name, select)?,
+                comparison_range(&flipped(operator)?, constant(value)?)?,
+            )),
+            _ => None,
+        },
+        Expression::Like {
+            expression,
+            pattern,
+            escape,
+            case_insensitive: false,
+        } => {
+            let column = match expression.as_ref() {
+                Expression::Column(name) => base_table_column(name, select)?,
+                _ => return None,
+            };
+            let pattern = match pattern.as_ref() {
+                Expression::Value(Value::VarChar256(pattern)) => pattern,
+                _ => return None,
+            };
+            let escape = match escape.as_deref() {
+                None => Some(DEFAULT_LIKE_ESCAPE),
+                Some(Expression::Value(Value::VarChar256(escape)))
+                    if escape.chars().count() <= 1 =>
+                {
+                    escape.chars().next()
+                }
+                _ => return None,
+            };
+            let prefix = like_prefix(pattern, escape).ok()?;
+            if prefix.is_empty() {
+                None
+            } else {
+                Some((column, prefix_bounds(&prefix)))
+            }
+        }
+        _ => None,
+    }
+}
+
+// comparison with NULL matches nothing, so it isn't worth an index scan
+fn constant(value: &Value) -> Option<SData> {
+    match value {
+        Value::Int32(value) => Some(SData::INT(*value)),
+        Value::VarChar256(value) => Some(SData::STRING(value.clone())),
+        Value::Null => None,
+    }
+}
+
+// keys for which column operator value holds
+fn comparison_range(
+    operator: &BinaryOperator,
+    value: SData,
+) -> Option<(Bound<SData>, Bound<SData>)> {
+    match operator {
+        BinaryOperator::Equal => Some((Bound::Included(value.clone()), Bound::Included(value))),
+        BinaryOperator::Less => Some((Bound::Unbounded, Bound::Excluded(value))),
+        BinaryOperator::LessOrEqual => Some((Bound::Unbounded, Bound::Included(value))),
+        BinaryOperator::Greater => Some((Bound::Excluded(value), Bound::Unbounded)),
+        BinaryOperator::GreaterOrEqual => Some((Bound::Included(value), Bound::Unbounded)),
+        _ => None,
+    }
+}
+
+// value operator column is the same as column flipped operator value
+fn flipped(operator: &BinaryOperator) -> Option<BinaryOperator> {
+    match operator {
+        BinaryOperator::Equal => Some(BinaryOperator::Equal),
+        BinaryOperator::Less => Some(BinaryOperator::Greater),
+        BinaryOperator::LessOrEqual => Some(BinaryOperator::GreaterOrEqual),
+        BinaryOperator::Greater => Some(BinaryOperator::Less),
+        BinaryOperator::GreaterOrEqual => Some(BinaryOperator::LessOrEqual),
+        _ => None,
+    }
+}
+
+fn base_table_column(name: &str, select: &Select) -> Option<String> {
+    let table = select.table.as_deref()?;
+    match name.rsplit_once('.') {
+        Some((qualifier, column))
+            if qualifier == select.alias.as_deref().unwrap_or(split_name(table).1) =>
+        {
+            Some(column.to_string())
+        }
+        None if select.joins.is_empty() => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+fn scan(
+    table: &str,
+    alias: &Option<String>,
+    source: &dyn TableSource,
+    scope: &CteScope,
+) -> Result<Relation, String> {
+    let relation = match scope.get(table) {
+        Some(relation) => relation.clone(),
+        // CTEs of the outer query aren't visible inside of the view
+        None => match source.view(table)? {
+            Some(view) => rename_columns(
+                execute(&view.query, source, &CteScope::new())?,
+                &view.columns,
+            )?,
+            None => source.scan(table)?,
+        },
+    };
+    Ok(qualify(relation, table, alias))
+}
+
+// table, view or materialized view with columns qualified by alias, for statements which read other relations
+pub(super) fn scan_relation(
+    table: &str,
+    alias: &Option<String>,
+    source: &dyn TableSource,
+) -> Result<Relation, String> {
+    scan(table, alias, source, &CteScope::new())
+}
+
+// columns of relation from other schema are qualified only with its name, like in postgres
+pub(super) fn qualify(relation: Relation, table: &str, alias: &Option<String>) -> Relation {
+    let qualifier = alias.as_deref().unwrap_or(split_name(table).1);
+    Relation {
+        columns: relation
+            .columns
+            .iter()
+            .map(|column| format!("{}.{}", qualifier, unqualified_name(column)))
+            .collect(),
+        rows: relation.rows,
+    }
+}
+
+fn nested_loop_join(
+    left: Relation,
+    join: &Join,
+    source: &dyn TableSource,
+    scope: &CteScope,
+) -> Result<Relation, String> {
+    let right = scan(&join.table, &join.alias, source, scope)?;
+    let columns: Vec<String> = left
+        .columns
+        .iter()
+        .chain(right.columns.iter())
+        .cloned()
+        .collect();
+    let mut rows = vec![];
+    for left_row in &left.rows {
+        source.check_timeout()?;
+        for right_row in &right.rows {
+            let row: Vec<_> = left_row.iter().chain(right_row.iter()).cloned().collect();
+            if evaluate_predicate(&join.on, &columns, &row)? {
+                rows.push(row);
+            }
+        }
+    }
+    Ok(Relation { columns, rows })
+}
+
+fn filter(relation: Relation, selection: &Expression) -> Result<Relation, String> {
+    let mut rows = Vec::with_capacity(relation.rows.len());
+    for row in relation.rows {
+        if evaluate_predicate(selection, &relation.columns, &row)? {
+            rows.push(row);
+        }
+    }
+    Ok(Relation {
+        columns: relation.columns,
+        rows,
+    })
+}
+
+pub(super) fn project(relation: Relation, columns: &Columns) -> Result<Relation, String> {
+    match columns {
+        Columns::All => Ok(Relation {
+            columns: relation
+                .columns
+                .iter()
+                .map(|column| unqualified_name(column).to_string())
+                .collect(),
+            rows: relation.rows,
+        }),
+        Columns::Specific(items)
+            if items
+                .iter()
+                .any(|item| contains_aggregate(&item.expression)) =>
+        {
+            Ok(Relation {
+                columns: items.iter().map(output_name).collect(),
+                rows: vec![aggregate_relation(&relation, items)?],
+            })
+        }
+        Columns::Specific(items) => {
+            let names = items.iter().map(output_name).collect();
+            let expressions: Vec<Expression> =
+                items.iter().map(|item| item.expression.clone()).collect();
+            let (relation, expressions) = apply_window_functions(relation, &expressions)?;
+            let mut rows = Vec::with_capacity(relation.rows.len());
+            for row in &relation.rows {
+                rows.push(
+                    expressions
+                        .iter()
+                        .map(|expression| evaluate(expression, &relation.columns, row))
+                        .collect::<Result<Vec<SData>, String>>()?,
+                );
+            }
+            Ok(Relation {
+                columns: names,
+                rows,
+            })
+        }
+    }
+}
+
+pub(super) fn output_name(item: &SelectItem) -> String {
+    match &item.alias {
+        Some(alias) => alias.clone(),
+        None => expression_name(&item.expression),
+    }
+}
+
+fn expression_name(expression: &Expression) -> String {
+    match expression {
+        Expression::Column(name) => unqualified_name(name).to_string(),
+        Expression::Function { name, .. } => name.clone(),
+        Expression::Case { .. } => "case".to_string(),
+        Expression::Cast { expression, .. } => expression_name(expression),
+        _ => "?column?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bptree::{index::BPlusTree, page::TuplePointer},
+        parser::{ast::SqlStatement, parse},
+    };
+    use std::cell::Cell;
+
+    fn query(sql: &str) -> Query {
+        match parse(sql.to_string()).unwrap() {
+            SqlStatement::Query(query) => query,
+            _ => panic!("Expected query"),
+        }
+    }
+
+    struct TestTables(HashMap<String, Relation>);
+
+    impl TableSource for TestTables {
+        fn scan(&self, table_name: &str) -> Result<Relation, String> {
+            self.0
+                .get(table_name)
+                .cloned()
+                .ok_or_else(|| format!("No table named {}", table_name))
+        }
+    }
+
+    fn employees() -> TestTables {
+        let rows = [
+            (1, "ceo", 0),
+            (2, "cto", 1),
+            (3, "cfo", 1),
+            (4, "dev", 2),
+            (5, "intern", 4),
+        ]
+        .iter()
+        .map(|(id, name, manager_id)| {
+            vec![
+                SData::INT(*id),
+                SData::STRING(name.to_string()),
+                SData::INT(*manager_id),
+            ]
+        })
+        .collect();
+        TestTables(HashMap::from([(
+            "employees".to_string(),
+            Relation {
+                columns: vec![
+                    "id".to_string(),
+                    "name".to_string(),
+                    "manager_id".to_string(),
+                ],
+                rows,
+            },
+        )]))
+    }
+
+    #[test]
+    fn when_cte_is_not_recursive_it_should_be_materialized_and_used_as_table() {
+        let query = query(
+            "WITH managers AS (SELECT id, name FROM employees WHERE id < 4) \
+                SELECT name FROM managers WHERE id > 1;",
+        );
+
+        let result = execute_query(&query, &employees()).unwrap();
+
+        assert_eq!(result.columns, vec!["name".to_string()]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::STRING("cto".to_string())],
+                vec![SData::STRING("cfo".to_string())]
+            ]
+        );
+    }
+
+    #[test]
+    fn when_recursive_cte_is_executed_it_should_traverse_whole_hierarchy() {
+        let query = query(
+            "WITH RECURSIVE chart(id, name) AS (SELECT id, name FROM employees WHERE id = 2 \
+                UNION ALL SELECT e.id, e.name FROM employees e JOIN chart c ON e.manager_id = c.id) \
+                SELECT name FROM chart;",
+        );
+
+        let result = execute_query(&query, &employees()).unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::STRING("cto".to_string())],
+                vec![SData::STRING("dev".to_string())],
+                vec![SData::STRING("intern".to_string())]
+            ]
+        );
+    }
+
+    #[test]
+    fn when_recursive_cte_with_union_reaches_cycle_it_should_stop_on_already_seen_rows() {
+        let query = query(
+            "WITH RECURSIVE reachable(id) AS (SELECT id FROM employees WHERE id = 1 \
+                UNION SELECT e.manager_id FROM reachable r JOIN employees e ON e.id = r.id) \
+                SELECT * FROM reachable;",
+        );
+        let mut tables = employees();
+        tables.0.get_mut("employees").unwrap().rows.push(vec![
+            SData::INT(0),
+            SData::STRING("board".to_string()),
+            SData::INT(1),
+        ]);
+
+        let result = execute_query(&query, &tables).unwrap();
+
+        assert_eq!(result.columns, vec!["id".to_string()]);
+        assert_eq!(result.rows, vec![vec![SData::INT(1)], vec![SData::INT(0)]]);
+    }
+
+    fn sales() -> TestTables {
+        let rows = [
+            ("north", 1, 10),
+            ("south", 1, 7),
+            ("north", 2, 30),
+            ("north", 3, 30),
+            ("south", 2, 5),
+            ("north", 4, 20),
+        ]
+        .iter()
+        .map(|(region, day, amount)| {
+            vec![
+                SData::STRING(region.to_string()),
+                SData::INT(*day),
+                SData::INT(*amount),
+            ]
+        })
+        .collect();
+        TestTables(HashMap::from([(
+            "sales".to_string(),
+            Relation {
+                columns: vec![
+                    "region".to_string(),
+                    "day".to_string(),
+                    "amount".to_string(),
+                ],
+                rows,
+            },
+        )]))
+    }
+
+    #[test]
+    fn when_ranking_functions_are_executed_they_should_rank_rows_inside_of_partition() {
+        let query = query(
+            "SELECT day, ROW_NUMBER() OVER (PARTITION BY region ORDER BY amount DESC) AS position, \
+                RANK() OVER (PARTITION BY region ORDER BY amount DESC), \
+                DENSE_RANK() OVER (PARTITION BY region ORDER BY amount DESC) \
+                FROM sales WHERE region = \"north\";",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(
+            result.columns,
+            vec![
+                "day".to_string(),
+                "position".to_string(),
+                "rank".to_string(),
+                "dense_rank".to_string()
+            ]
+        );
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::INT(1), SData::INT(4), SData::INT(4), SData::INT(3)],
+                vec![SData::INT(2), SData::INT(1), SData::INT(1), SData::INT(1)],
+                vec![SData::INT(3), SData::INT(2), SData::INT(1), SData::INT(1)],
+                vec![SData::INT(4), SData::INT(3), SData::INT(3), SData::INT(2)],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_lag_and_lead_are_executed_they_should_return_neighbour_rows_or_default() {
+        let query = query(
+            "SELECT LAG(amount) OVER (PARTITION BY region ORDER BY day), \
+                LEAD(amount, 1, 0) OVER (PARTITION BY region ORDER BY day) FROM sales;",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::NULL, SData::INT(30)],
+                vec![SData::NULL, SData::INT(5)],
+                vec![SData::INT(10), SData::INT(30)],
+                vec![SData::INT(30), SData::INT(20)],
+                vec![SData::INT(7), SData::INT(0)],
+                vec![SData::INT(30), SData::INT(0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_aggregate_is_executed_over_frame_it_should_aggregate_only_rows_in_frame() {
+        let query = query(
+            "SELECT SUM(amount) OVER (PARTITION BY region ORDER BY day) AS running, \
+                SUM(amount) OVER (PARTITION BY region ORDER BY day ROWS BETWEEN 1 PRECEDING AND CURRENT ROW), \
+                FIRST_VALUE(amount) OVER (PARTITION BY region ORDER BY day ROWS BETWEEN 1 FOLLOWING AND UNBOUNDED FOLLOWING), \
+                COUNT(*) OVER (PARTITION BY region) FROM sales;",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![
+                    SData::INT(10),
+                    SData::INT(10),
+                    SData::INT(30),
+                    SData::INT(4)
+                ],
+                vec![SData::INT(7), SData::INT(7), SData::INT(5), SData::INT(2)],
+                vec![
+                    SData::INT(40),
+                    SData::INT(40),
+                    SData::INT(30),
+                    SData::INT(4)
+                ],
+                vec![
+                    SData::INT(70),
+                    SData::INT(60),
+                    SData::INT(20),
+                    SData::INT(4)
+                ],
+                vec![SData::INT(12), SData::INT(12), SData::NULL, SData::INT(2)],
+                vec![SData::INT(90), SData::INT(50), SData::NULL, SData::INT(4)],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_set_operations_are_combined_they_should_follow_precedence_and_all_semantics() {
+        let query = query(
+            "SELECT region FROM sales UNION ALL SELECT region FROM sales WHERE day = 1 \
+                EXCEPT SELECT region FROM sales WHERE amount > 25 INTERSECT SELECT region FROM sales;",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(result.columns, vec!["region".to_string()]);
+        assert_eq!(result.rows, vec![vec![SData::STRING("south".to_string())]]);
+    }
+
+    #[test]
+    fn when_intersect_all_is_executed_it_should_keep_common_duplicates() {
+        let query = query(
+            "SELECT region FROM sales INTERSECT ALL \
+                (SELECT region FROM sales WHERE day < 3 UNION ALL SELECT region FROM sales WHERE day = 1);",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::STRING("north".to_string())],
+                vec![SData::STRING("south".to_string())],
+                vec![SData::STRING("north".to_string())],
+                vec![SData::STRING("north".to_string())],
+                vec![SData::STRING("south".to_string())],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_select_is_distinct_it_should_remove_duplicate_rows() {
+        let query = query("SELECT DISTINCT region, amount FROM sales WHERE amount > 9;");
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::STRING("north".to_string()), SData::INT(10)],
+                vec![SData::STRING("north".to_string()), SData::INT(30)],
+                vec![SData::STRING("north".to_string()), SData::INT(20)],
+            ]
+        );
+    }
+
+    #[test]
+    fn when_count_distinct_is_executed_it_should_count_unique_values() {
+        let query = query(
+            "SELECT COUNT(DISTINCT amount), COUNT(amount), SUM(DISTINCT amount) + 1 FROM sales;",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![vec![SData::INT(5), SData::INT(6), SData::INT(73)]]
+        );
+    }
+
+    #[test]
+    fn when_column_is_selected_next_to_aggregate_it_should_return_error() {
+        let query = query("SELECT region, COUNT(*) FROM sales;");
+
+        let result = execute_query(&query, &sales());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn when_conditional_expressions_are_executed_they_should_pick_first_matching_value() {
+        let query = query(
+            "SELECT CASE region WHEN \"north\" THEN \"N\" ELSE upper(region) END, \
+                CASE WHEN amount >= 30 THEN \"high\" WHEN amount >= 10 THEN \"mid\" END AS level, \
+                COALESCE(NULLIF(amount, 30), -1), CAST(day AS VARCHAR) \
+                FROM sales WHERE day < 3;",
+        );
+
+        let result = execute_query(&query, &sales()).unwrap();
+
+        let text = |s: &str| SData::STRING(s.to_string());
+        assert_eq!(
+            result.columns,
+            vec![
+                "case".to_string(),
+                "level".to_string(),
+                "coalesce".to_string(),
+                "day".to_string()
+            ]
+        );
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![text("N"), text("mid"), SData::INT(10), text("1")],
+                vec![text("SOUTH"), SData::NULL, SData::INT(7), text("1")],
+                vec![text("N"), text("high"), SData::INT(-1), text("2")],
+                vec![text("SOUTH"), SData::NULL, SData::INT(5), text("2")],
+            ]
+        );
+    }
+
+    // index on name column of employees, offset of tuple pointer is position of the row in the table
+    struct IndexedEmployees {
+        tables: TestTables,
+        index: BPlusTree<(SData, TuplePointer)>,
+        index_scans: Cell<usize>,
+    }
+
+    impl TableSource for IndexedEmployees {
+        fn scan(&self, table_name: &str) -> Result<Relation, String> {
+            self.tables.scan(table_name)
+        }
+
+        fn index_range_scan(
+            &self,
+            table_name: &str,
+            column: &str,
+            range: (Bound<SData>, Bound<SData>),
+        ) -> Result<Option<Relation>, String> {
+            if table_name != "employees" || column != "name" {
+                return Ok(None);
+            }
+            self.index_scans.set(self.index_scans.get() + 1);
+            let mut relation = self.tables.scan(table_name)?;
+            let start = match &range.0 {
+                Bound::Included(key) | Bound::Excluded(key) => {
+                    Bound::Included((key.clone(), TuplePointer { page: 0, offset: 0 }))
+                }
+                Bound::Unbounded => Bound::Unbounded,
+            };
+            relation.rows = self
+                .index
+                .scan_from(start.as_ref(), |(key, _)| match &range.1 {
+                    Bound::Included(end) => key <= end,
+                    Bound::Excluded(end) => key < end,
+                    Bound::Unbounded => true,
+                })
+                .into_iter()
+                .filter(|(key, _)| !matches!(&range.0, Bound::Excluded(start) if key == start))
+                .map(|(_, pointer)| relation.rows[pointer.offset as usize].clone())
+                .collect();
+            Ok(Some(relation))
+        }
+    }
+
+    fn indexed_employees() -> IndexedEmployees {
+        let tables = employees();
+        let mut index = BPlusTree::new(3);
+        for (offset, row) in tables.0["employees"].rows.iter().enumerate() {
+            index.insert((
+                row[1].clone(),
+                TuplePointer {
+                    page: 0,
+                    offset: offset as u16,
+                },
+            ));
+        }
+        IndexedEmployees {
+            tables,
+            index,
+            index_scans: Cell::new(0),
+        }
+    }
+
+    #[test]
+    fn when_like_has_literal_prefix_on_indexed_column_it_should_use_index_range_scan() {
+        let query = query("SELECT id, name FROM employees WHERE id > 1 AND name LIKE \"c%o\";");
+        let tables = indexed_employees();
+
+        let result = execute_query(&query, &tables).unwrap();
+
+        assert_eq!(tables.index_scans.get(), 1);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::INT(3), SData::STRING("cfo".to_string())],
+                vec![SData::INT(2), SData::STRING("cto".to_string())]
+            ]
+        );
+    }
+
+    #[test]
+    fn when_indexed_column_is_compared_with_constant_it_should_use_index_range_scan() {
+        let query =
+            query("SELECT id FROM employees WHERE id < 5 AND \"cfo\" < name AND name <= \"dev\";");
+        let tables = indexed_employees();
+
+        let result = execute_query(&query, &tables).unwrap();
+
+        assert_eq!(tables.index_scans.get(), 1);
+        assert_eq!(result.rows, vec![vec![SData::INT(2)], vec![SData::INT(4)]]);
+    }
+
+    #[test]
+    fn when_pattern_has_no_prefix_or_ignores_case_it_should_scan_whole_table() {
+        let query = query(
+            "SELECT name FROM employees WHERE name ILIKE \"C%\" OR name ~ \"^d.v$\" \
+                OR name LIKE \"%tern\";",
+        );
+        let tables = indexed_employees();
+
+        let result = execute_query(&query, &tables).unwrap();
+
+        assert_eq!(tables.index_scans.get(), 0);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![SData::STRING("ceo".to_string())],
+                vec![SData::STRING("cto".to_string())],
+                vec![SData::STRING("cfo".to_string())],
+                vec![SData::STRING("dev".to_string())],
+                vec![SData::STRING("intern".to_string())]
+            ]
+        );
+    }
+}
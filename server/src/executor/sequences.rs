@@ -0,0 +1,505 @@
+use crate::{
+    catalog::{
+        tables::{Kind, PgClass, PgSequence},
+        Catalog, Oid,
+    },
+    constants::SEQUENCE_LOG_VALUES,
+    information_schema::SData,
+    parser::ast::{Expression, SequenceOptions, Value},
+    storage::Database,
+};
+
+use super::expression::evaluate_with;
+
+/*
+    sequence functions change state of the sequence, so they are called by the evaluation only where the database is at hand:
+        nextval(name)                   next value of the sequence, it's also remembered by the session
+        currval(name)                   value given by the last nextval of the sequence in this session
+        setval(name, value [, called])  next nextval returns value after given value, or value itself when not called
+    changes of sequences aren't undone when statement fails, so sessions never wait for each other's sequence values
+*/
+const SEQUENCE_FUNCTIONS: &[&str] = &["nextval", "currval", "setval"];
+
+pub fn is_sequence_function(name: &str) -> bool {
+    SEQUENCE_FUNCTIONS.contains(&name)
+}
+
+pub fn create_sequence(
+    name: &str,
+    options: &SequenceOptions,
+    database: &mut Database,
+) -> Result<(), String> {
+    let mut catalog = database.catalog.clone();
+    let (namespace, name) = catalog.creation_namespace(name, &database.session.search_path)?;
+    add_sequence(&mut catalog, namespace, name, options)?;
+    database.catalog = catalog;
+    database.save_catalog()
+}
+
+// sequence without given options counts up from 1
+pub fn add_sequence(
+    catalog: &mut Catalog,
+    namespace: Oid,
+    name: &str,
+    options: &SequenceOptions,
+) -> Result<Oid, String> {
+    if catalog.find_in_namespace(namespace, name).is_some() {
+        return Err(format!("Relation \"{}\" already exists", name));
+    }
+    let increment = options.increment.unwrap_or(1);
+    if increment == 0 {
+        return Err("INCREMENT must not be zero".to_string());
+    }
+    let ascending = increment > 0;
+    let min_value = options
+        .min_value
+        .unwrap_or(if ascending { 1 } else { i32::MIN });
+    let max_value = options
+        .max_value
+        .unwrap_or(if ascending { i32::MAX } else { -1 });
+    if min_value >= max_value {
+        return Err(format!(
+            "MINVALUE ({}) must be less than MAXVALUE ({})",
+            min_value, max_value
+        ));
+    }
+    let start = options
+        .start
+        .unwrap_or(if ascending { min_value } else { max_value });
+    if start < min_value {
+        return Err(format!(
+            "START value ({}) cannot be less than MINVALUE ({})",
+            start, min_value
+        ));
+    }
+    if start > max_value {
+        return Err(format!(
+            "START value ({}) cannot be greater than MAXVALUE ({})",
+            start, max_value
+        ));
+    }
+    let oid = catalog.allocate_oid();
+    catalog.classes.push(PgClass {
+        oid,
+        name: name.to_string(),
+        kind: Kind::Sequence,
+        namespace,
+    });
+    catalog.sequences.push(PgSequence {
+        oid,
+        increment,
+        min_value,
+        max_value,
+        start,
+        cycle: options.cycle.unwrap_or(false),
+        last_value: start,
+        is_called: false,
+    });
+    Ok(oid)
+}
+
+// name of sequence owned by column, like table_column_seq in postgres, it's in the schema of the table
+pub fn sequence_name(catalog: &Catalog, namespace: Oid, table_name: &str, column: &str) -> String {
+    let default_name = format!("{}_{}_seq", table_name, column);
+    let mut candidate = default_name.clone();
+    let mut suffix = 1;
+    while catalog.find_in_namespace(namespace, &candidate).is_some() {
+        candidate = format!("{}{}", default_name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+pub fn nextval_expression(sequence: &str) -> Expression {
+    Expression::Function {
+        name: "nextval".to_string(),
+        distinct: false,
+        arguments: vec![Expression::Value(Value::VarChar256(sequence.to_string()))],
+        over: None,
+    }
+}
+
+// evaluates expression which can call sequence functions, they are called only when their value is needed
+pub fn evaluate_with_sequences(
+    expression: &Expression,
+    columns: &[String],
+    row: &[SData],
+    database: &mut Database,
+) -> Result<SData, String> {
+    evaluate_with(expression, columns, row, &mut |name, values| {
+        call_sequence_function(name, values, database)
+    })
+}
+
+fn call_sequence_function(
+    name: &str,
+    values: &[SData],
+    database: &mut Database,
+) -> Result<SData, String> {
+    Ok(match (name, values) {
+        (_, [SData::NULL, ..]) => SData::NULL,
+        ("nextval", [SData::STRING(sequence)]) => SData::INT(nextval(database, sequence)?),
+        ("currval", [SData::STRING(sequence)]) => SData::INT(currval(database, sequence)?),
+        ("setval", [SData::STRING(sequence), SData::INT(value)]) => {
+            SData::INT(setval(database, sequence, *value, true)?)
+        }
+        ("setval", [SData::STRING(sequence), SData::INT(value), SData::BOOL(called)]) => {
+            SData::INT(setval(database, sequence, *value, *called)?)
+        }
+        (name, values) => {
+            return Err(format!(
+                "Function {} can't be called with arguments {:?}",
+                name, values
+            ))
+        }
+    })
+}
+
+fn nextval(database: &mut Database, name: &str) -> Result<i32, String> {
+    let sequence = database
+        .catalog
+        .sequence(name, &database.session.search_path)?
+        .clone();
+    let (last_value, is_called) = current_state(database, &sequence);
+    let stepped = last_value
+        .checked_add(sequence.increment)
+        .filter(|value| (sequence.min_value..=sequence.max_value).contains(value));
+    let (value, wrapped) = match stepped {
+        _ if !is_called => (last_value, false),
+        Some(value) => (value, false),
+        None if sequence.cycle && sequence.increment > 0 => (sequence.min_value, true),
+        None if sequence.cycle => (sequence.max_value, true),
+        None => {
+            let (limit, bound) = if sequence.increment > 0 {
+                ("maximum", sequence.max_value)
+            } else {
+                ("minimum", sequence.min_value)
+            };
+            return Err(format!(
+                "nextval: reached {} value of sequence \"{}\" ({})",
+                limit, name, bound
+            ));
+        }
+    };
+    database.sequence_states.insert(sequence.oid, (value, true));
+    database.session.sequence_values.insert(sequence.oid, value);
+    // saved value is ahead of given values, so file is written only once per SEQUENCE_LOG_VALUES calls
+    let saved = if sequence.increment > 0 {
+        value <= sequence.last_value
+    } else {
+        value >= sequence.last_value
+    };
+    if !sequence.is_called || !saved || wrapped {
+        let ahead = (value as i64 + sequence.increment as i64 * (SEQUENCE_LOG_VALUES - 1) as i64)
+            .clamp(sequence.min_value as i64, sequence.max_value as i64);
+        save_state(database, sequence.oid, ahead as i32, true)?;
+    }
+    Ok(value)
+}
+
+fn currval(database: &Database, name: &str) -> Result<i32, String> {
+    let oid = database
+        .catalog
+        .sequence(name, &database.session.search_path)?
+        .oid;
+    database
+        .session
+        .sequence_values
+        .get(&oid)
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "currval of sequence \"{}\" is not yet defined in this session",
+                name
+            )
+        })
+}
+
+fn setval(database: &mut Database, name: &str, value: i32, called: bool) -> Result<i32, String> {
+    let sequence = database
+        .catalog
+        .sequence(name, &database.session.search_path)?
+        .clone();
+    if value < sequence.min_value || value > sequence.max_value {
+        return Err(format!(
+            "setval: value {} is out of bounds for sequence \"{}\" ({}..{})",
+            value, name, sequence.min_value, sequence.max_value
+        ));
+    }
+    database
+        .sequence_states
+        .insert(sequence.oid, (value, called));
+    save_state(database, sequence.oid, value, called)?;
+    Ok(value)
+}
+
+fn current_state(database: &Database, sequence: &PgSequence) -> (i32, bool) {
+    database
+        .sequence_states
+        .get(&sequence.oid)
+        .copied()
+        .unwrap_or((sequence.last_value, sequence.is_called))
+}
+
+fn save_state(
+    database: &mut Database,
+    oid: Oid,
+    last_value: i32,
+    is_called: bool,
+) -> Result<(), String> {
+    if let Some(sequence) = database
+        .catalog
+        .sequences
+        .iter_mut()
+        .find(|sequence| sequence.oid == oid)
+    {
+        sequence.last_value = last_value;
+        sequence.is_called = is_called;
+    }
+    database.save_catalog()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{QueryResult, Relation},
+        storage::Session,
+        test_support::{open_database, run_in, select},
+    };
+
+    use super::*;
+
+    #[test]
+    fn when_nextval_is_called_sessions_should_share_sequence_but_not_currval() {
+        let (_directory, mut database) = open_database();
+        let (mut first, mut second) = (Session::default(), Session::default());
+        for sql in [
+            "CREATE SEQUENCE countdown INCREMENT BY -2 MINVALUE 1 MAXVALUE 5 CYCLE;",
+            "CREATE TABLE ticks (id INT PRIMARY KEY, value INT);",
+        ] {
+            run_in(&mut database, &mut first, sql).unwrap();
+        }
+
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut first,
+                "INSERT INTO ticks (id, value) VALUES (1, currval(\"countdown\"));"
+            ),
+            Err("currval of sequence \"countdown\" is not yet defined in this session".to_string())
+        );
+        for id in 1..=4 {
+            let session = if id % 2 == 1 { &mut first } else { &mut second };
+            run_in(
+                &mut database,
+                session,
+                &format!(
+                    "INSERT INTO ticks (id, value) VALUES ({}, nextval(\"countdown\") * 10 + currval(\"countdown\"));",
+                    id
+                ),
+            )
+            .unwrap();
+        }
+        run_in(
+            &mut database,
+            &mut first,
+            "UPDATE ticks SET value = currval(\"countdown\") WHERE id = 1;",
+        )
+        .unwrap();
+        run_in(
+            &mut database,
+            &mut second,
+            "UPDATE ticks SET value = setval(\"countdown\", 4, 1 = 0) WHERE id = 2;",
+        )
+        .unwrap();
+        run_in(
+            &mut database,
+            &mut second,
+            "UPDATE ticks SET value = nextval(\"countdown\") WHERE id = 3;",
+        )
+        .unwrap();
+
+        let mut ticks = select(&mut database, "SELECT id, value FROM ticks;");
+        ticks.sort();
+        assert_eq!(
+            ticks,
+            vec![
+                vec![SData::INT(1), SData::INT(1)],
+                vec![SData::INT(2), SData::INT(4)],
+                vec![SData::INT(3), SData::INT(4)],
+                vec![SData::INT(4), SData::INT(55)],
+            ]
+        );
+        assert!(run_in(
+            &mut database,
+            &mut first,
+            "UPDATE ticks SET value = setval(\"countdown\", 7) WHERE id = 1;"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn when_select_list_calls_sequence_function_it_should_be_called_only_when_needed() {
+        let (_directory, mut database) = open_database();
+        let mut session = Session::default();
+        for sql in [
+            "CREATE SEQUENCE tickets;",
+            "CREATE TABLE queue (id INT PRIMARY KEY);",
+            "INSERT INTO queue (id) VALUES (1), (2), (3);",
+        ] {
+            run_in(&mut database, &mut session, sql).unwrap();
+        }
+        let mut rows =
+            |database: &mut Database, sql: &str| match run_in(database, &mut session, sql) {
+                Ok(QueryResult::Rows(Relation { mut rows, .. })) => {
+                    rows.sort();
+                    rows
+                }
+                other => panic!("Expected rows, got {:?}", other),
+            };
+
+        assert_eq!(
+            rows(&mut database, "SELECT nextval(\"tickets\");"),
+            vec![vec![SData::INT(1)]]
+        );
+        assert_eq!(
+            rows(
+                &mut database,
+                "SELECT currval(\"tickets\"), CASE WHEN 1 = 0 THEN nextval(\"tickets\") END;"
+            ),
+            vec![vec![SData::INT(1), SData::NULL]]
+        );
+        assert_eq!(
+            rows(
+                &mut database,
+                "SELECT id, CASE WHEN id > 1 THEN nextval(\"tickets\") END FROM queue;"
+            ),
+            vec![
+                vec![SData::INT(1), SData::NULL],
+                vec![SData::INT(2), SData::INT(2)],
+                vec![SData::INT(3), SData::INT(3)],
+            ]
+        );
+        assert_eq!(
+            rows(&mut database, "SELECT currval(\"tickets\");"),
+            vec![vec![SData::INT(3)]]
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut session,
+                "SELECT id FROM queue WHERE nextval(\"tickets\") > 0;"
+            ),
+            Err(
+                "Function nextval is allowed only in INSERT, UPDATE, DEFAULT and select list"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn when_column_is_serial_or_identity_it_should_get_values_from_its_sequence() {
+        let (_directory, mut database) = open_database();
+        let mut session = Session::default();
+        run_in(
+            &mut database,
+            &mut session,
+            "CREATE TABLE orders (id SERIAL PRIMARY KEY, \
+                number INT GENERATED ALWAYS AS IDENTITY (START WITH 100 INCREMENT BY 10), \
+                code INT GENERATED BY DEFAULT AS IDENTITY UNIQUE);",
+        )
+        .unwrap();
+
+        run_in(
+            &mut database,
+            &mut session,
+            "INSERT INTO orders (code) VALUES (7);",
+        )
+        .unwrap();
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut session,
+                "INSERT INTO orders (id, code) VALUES (1, 8);"
+            ),
+            Err("duplicate key value violates unique constraint \"orders_pkey\"".to_string())
+        );
+        run_in(
+            &mut database,
+            &mut session,
+            "INSERT INTO orders (id) VALUES (5);",
+        )
+        .unwrap();
+        assert_eq!(
+            run_in(
+                &mut database,
+                &mut session,
+                "INSERT INTO orders (id, number) VALUES (6, 1);"
+            ),
+            Err("cannot insert a non-DEFAULT value into column \"number\"".to_string())
+        );
+        assert_eq!(
+            run_in(&mut database, &mut session, "UPDATE orders SET number = 1;"),
+            Err("column \"number\" can only be updated to DEFAULT".to_string())
+        );
+
+        // values given to failed statements aren't reused
+        let mut orders = select(&mut database, "SELECT id, number, code FROM orders;");
+        orders.sort();
+        assert_eq!(
+            orders,
+            vec![
+                vec![SData::INT(1), SData::INT(100), SData::INT(7)],
+                vec![SData::INT(5), SData::INT(120), SData::INT(1)],
+            ]
+        );
+        assert!(run_in(
+            &mut database,
+            &mut session,
+            "CREATE TABLE names (id SERIAL PRIMARY KEY, name VARCHAR GENERATED ALWAYS AS IDENTITY);"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn when_database_is_reopened_sequence_should_not_give_used_values_again() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut session = Session::default();
+        {
+            let mut database = Database::open(directory.path()).unwrap();
+            run_in(
+                &mut database,
+                &mut session,
+                "CREATE TABLE events (id SERIAL PRIMARY KEY, name VARCHAR);",
+            )
+            .unwrap();
+            for _ in 0..3 {
+                run_in(
+                    &mut database,
+                    &mut session,
+                    "INSERT INTO events (name) VALUES (\"a\");",
+                )
+                .unwrap();
+            }
+        }
+
+        let mut database = Database::open(directory.path()).unwrap();
+        let mut session = Session::default();
+        run_in(
+            &mut database,
+            &mut session,
+            "INSERT INTO events (name) VALUES (\"b\");",
+        )
+        .unwrap();
+
+        let ids: Vec<i32> = select(&mut database, "SELECT id FROM events WHERE name = \"b\";")
+            .into_iter()
+            .map(|row| match row[0] {
+                SData::INT(id) => id,
+                _ => panic!("Expected integer id"),
+            })
+            .collect();
+        assert_eq!(ids.len(), 1);
+        assert!(ids[0] > 3);
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{information_schema::SData, parser::ast::SetOperator};
+
+use super::Relation;
+
+/*
+    hash based set operations, rows are compared by all values and NULLs are equal to each other:
+        UNION:          rows from both inputs
+        INTERSECT:      rows present in both inputs, ALL keeps min(left count, right count) copies
+        EXCEPT:         rows of the left input not present in the right, ALL keeps left count - right count copies
+    without ALL duplicates are removed from the result, column names are taken from the left input
+*/
+pub fn combine(
+    operator: &SetOperator,
+    all: bool,
+    left: Relation,
+    right: Relation,
+) -> Result<Relation, String> {
+    if left.columns.len() != right.columns.len() {
+        return Err(format!(
+            "Each {:?} query must have the same number of columns, got {} and {}",
+            operator,
+            left.columns.len(),
+            right.columns.len()
+        ));
+    }
+    let rows = match operator {
+        SetOperator::Union => {
+            let rows: Vec<Vec<SData>> = left.rows.into_iter().chain(right.rows).collect();
+            if all {
+                rows
+            } else {
+                remove_duplicates(rows)
+            }
+        }
+        SetOperator::Intersect | SetOperator::Except => {
+            let mut right_counts = count_rows(right.rows);
+            let mut rows = vec![];
+            for row in left.rows {
+                let in_right = match right_counts.get_mut(&row) {
+                    Some(count) if *count > 0 => {
+                        if all {
+                            *count -= 1;
+                        }
+                        true
+                    }
+                    _ => false,
+                };
+                if in_right == matches!(operator, SetOperator::Intersect) {
+                    rows.push(row);
+                }
+            }
+            if all {
+                rows
+            } else {
+                remove_duplicates(rows)
+            }
+        }
+    };
+    Ok(Relation {
+        columns: left.columns,
+        rows,
+    })
+}
+
+pub fn distinct(relation: Relation) -> Relation {
+    Relation {
+        columns: relation.columns,
+        rows: remove_duplicates(relation.rows),
+    }
+}
+
+// keeps the first occurrence of each row
+fn remove_duplicates(rows: Vec<Vec<SData>>) -> Vec<Vec<SData>> {
+    let mut seen = HashSet::with_capacity(rows.len());
+    rows.into_iter()
+        .filter(|row| seen.insert(row.clone()))
+        .collect()
+}
+
+fn count_rows(rows: Vec<Vec<SData>>) -> HashMap<Vec<SData>, usize> {
+    let mut counts = HashMap::with_capacity(rows.len());
+    for row in rows {
+        *counts.entry(row).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relation(values: &[i32]) -> Relation {
+        Relation {
+            columns: vec!["a".to_string()],
+            rows: values
+                .iter()
+                .map(|value| vec![SData::INT(*value)])
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn when_union_is_not_all_it_should_remove_duplicates_from_both_inputs() {
+        let result = combine(
+            &SetOperator::Union,
+            false,
+            relation(&[1, 2, 2]),
+            relation(&[2, 3]),
+        )
+        .unwrap();
+
+        assert_eq!(result, relation(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn when_intersect_all_is_combined_it_should_keep_minimal_number_of_duplicates() {
+        let result = combine(
+            &SetOperator::Intersect,
+            true,
+            relation(&[1, 2, 2, 2, 3]),
+            relation(&[2, 2, 3, 4]),
+        )
+        .unwrap();
+
+        assert_eq!(result, relation(&[2, 2, 3]));
+    }
+
+    #[test]
+    fn when_except_is_combined_it_should_remove_rows_present_in_right_input() {
+        let left = relation(&[1, 1, 2, 3, 3]);
+        let right = relation(&[1, 3]);
+
+        let except = combine(&SetOperator::Except, false, left.clone(), right.clone()).unwrap();
+        let except_all = combine(&SetOperator::Except, true, left, right).unwrap();
+
+        assert_eq!(except, relation(&[2]));
+        assert_eq!(except_all, relation(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn when_inputs_have_different_number_of_columns_it_should_return_error() {
+        let mut right = relation(&[1]);
+        right.columns.push("b".to_string());
+        right.rows[0].push(SData::INT(2));
+
+        let result = combine(&SetOperator::Union, true, relation(&[1]), right);
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{information_schema::SData, parser::ast::IsolationLevel, storage::Database};
+
+use super::Relation;
+
+// units of time settings from the largest, value without unit is in milliseconds
+const TIME_UNITS: [(&str, u64); 5] = [
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("min", 60_000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+/*
+    settings of the session changed by SET and read by SHOW:
+        search_path                          schemas searched for unqualified names of relations
+        lock_timeout                         longest wait for one lock
+        statement_timeout                    longest run of one statement, including its waits for locks
+        idle_in_transaction_session_timeout  longest time of connection inside of transaction block
+                                             without any statement, then the connection is closed
+    time is given in milliseconds or with unit like "5s", 0 disables the timeout
+    SHOW reads also:
+        transaction_isolation  isolation level of the current transaction, set by SET TRANSACTION
+*/
+pub fn set(name: &str, values: &[String], database: &mut Database) -> Result<(), String> {
+    match name {
+        "search_path" => database.session.search_path = values.to_vec(),
+        "lock_timeout" => database.session.lock_timeout = parse_milliseconds(name, values)?,
+        "statement_timeout" => {
+            database.session.statement_timeout = parse_milliseconds(name, values)?
+        }
+        "idle_in_transaction_session_timeout" => {
+            database.session.idle_in_transaction_session_timeout = parse_milliseconds(name, values)?
+        }
+        _ => return Err(unknown_setting(name)),
+    }
+    Ok(())
+}
+
+pub fn show(name: &str, database: &Database) -> Result<Relation, String> {
+    let value = match name {
+        "search_path" => database.session.search_path.join(", "),
+        "lock_timeout" => format_milliseconds(database.session.lock_timeout),
+        "statement_timeout" => format_milliseconds(database.session.statement_timeout),
+        "idle_in_transaction_session_timeout" => {
+            format_milliseconds(database.session.idle_in_transaction_session_timeout)
+        }
+        "transaction_isolation" => match database
+            .session
+            .transaction
+            .as_ref()
+            .map(|transaction| transaction.isolation)
+        {
+            Some(IsolationLevel::RepeatableRead) => "repeatable read",
+            Some(IsolationLevel::Serializable) => "serializable",
+            _ => "read committed",
+        }
+        .to_string(),
+        _ => return Err(unknown_setting(name)),
+    };
+    Ok(Relation {
+        columns: vec![name.to_string()],
+        rows: vec![vec![SData::STRING(value)]],
+    })
+}
+
+fn parse_milliseconds(name: &str, values: &[String]) -> Result<u64, String> {
+    let [value] = values else {
+        return Err(format!("SET {} takes only one argument", name));
+    };
+    let invalid = || format!("Invalid value for parameter \"{}\": \"{}\"", name, value);
+    let digits = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let multiplier = match unit.trim() {
+        "" => 1,
+        unit => TIME_UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, multiplier)| *multiplier)
+            .ok_or_else(invalid)?,
+    };
+    number.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+// time is shown in the largest unit which divides it
+fn format_milliseconds(milliseconds: u64) -> String {
+    match TIME_UNITS
+        .iter()
+        .find(|(_, multiplier)| milliseconds.is_multiple_of(*multiplier))
+    {
+        _ if milliseconds == 0 => "0".to_string(),
+        Some((unit, multiplier)) => format!("{}{}", milliseconds / multiplier, unit),
+        None => milliseconds.to_string(),
+    }
+}
+
+fn unknown_setting(name: &str) -> String {
+    format!("Unrecognized configuration parameter \"{}\"", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        error::SqlState,
+        executor::QueryResult,
+        test_support::{open_cluster, Connection},
+    };
+
+    use super::*;
+
+    fn shown(value: &str) -> Result<QueryResult, String> {
+        Ok(QueryResult::Rows(Relation {
+            columns: vec!["lock_timeout".to_string()],
+            rows: vec![vec![SData::STRING(value.to_string())]],
+        }))
+    }
+
+    #[test]
+    fn when_timeout_is_set_with_unit_show_should_return_it_in_largest_unit() {
+        let (_directory, cluster) = open_cluster();
+        let mut connection = Connection::new(&cluster);
+
+        assert_eq!(connection.run(&cluster, "SHOW lock_timeout;"), shown("0"));
+        connection
+            .run(&cluster, "SET lock_timeout = \"120s\";")
+            .unwrap();
+        assert_eq!(
+            connection.run(&cluster, "SHOW lock_timeout;"),
+            shown("2min")
+        );
+        connection
+            .run(&cluster, "SET lock_timeout TO 1500;")
+            .unwrap();
+        assert_eq!(
+            connection.run(&cluster, "SHOW lock_timeout;"),
+            shown("1500ms")
+        );
+        assert_eq!(
+            connection.run(&cluster, "SET statement_timeout = \"5 weeks\";"),
+            Err("Invalid value for parameter \"statement_timeout\": \"5 weeks\"".to_string())
+        );
+    }
+
+    #[test]
+    fn when_row_lock_is_not_granted_before_lock_timeout_statement_should_be_canceled() {
+        let (_directory, cluster) = open_cluster();
+        let (mut first, mut second) = (Connection::new(&cluster), Connection::new(&cluster));
+        for sql in [
+            "CREATE TABLE items (id INT PRIMARY KEY, amount INT);",
+            "INSERT INTO items (id, amount) VALUES (1, 10);",
+            "BEGIN;",
+            "UPDATE items SET amount = 20 WHERE id = 1;",
+        ] {
+            first.run(&cluster, sql).unwrap();
+        }
+        second.run(&cluster, "SET lock_timeout = 20;").unwrap();
+
+        assert_eq!(
+            second.run(&cluster, "UPDATE items SET amount = 30 WHERE id = 1;"),
+            Err(SqlState::LockNotAvailable.error("Canceling statement due to lock timeout"))
+        );
+        assert!(second.session.transaction.is_none());
+        first.run(&cluster, "COMMIT;").unwrap();
+        assert_eq!(
+            second.run(&cluster, "UPDATE items SET amount = 30 WHERE id = 1;"),
+            Ok(QueryResult::Command("UPDATE 1".to_string()))
+        );
+    }
+
+    #[test]
+    fn when_query_runs_longer_than_statement_timeout_it_should_be_canceled() {
+        let (_directory, cluster) = open_cluster();
+        let mut connection = Connection::new(&cluster);
+        for sql in [
+            "CREATE TABLE one (id INT PRIMARY KEY);",
+            "INSERT INTO one (id) VALUES (1);",
+            "SET statement_timeout = 1;",
+        ] {
+            connection.run(&cluster, sql).unwrap();
+        }
+        // the statement keeps its start time, as when it's retried after waiting, so it's already too long
+        connection.session.statement_started = Some(Instant::now() - Duration::from_millis(10));
+
+        assert_eq!(
+            connection.run(&cluster, "SELECT id FROM one;"),
+            Err(SqlState::QueryCanceled.error("Canceling statement due to statement timeout"))
+        );
+        assert_eq!(
+            connection.run(&cluster, "SELECT id FROM one;"),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["id".to_string()],
+                rows: vec![vec![SData::INT(1)]]
+            }))
+        );
+    }
+}
@@ -0,0 +1,649 @@
+use crate::{
+    parser::ast::{IsolationLevel, SqlStatement},
+    storage::{
+        transaction::{Savepoint, Transaction},
+        Database, Session,
+    },
+};
+
+use super::QueryResult;
+
+pub fn begin(database: &mut Database) -> Result<QueryResult, String> {
+    if database.session.transaction.is_some() {
+        return Err("There is already a transaction in progress".to_string());
+    }
+    database.begin(true)?;
+    Ok(QueryResult::Command("BEGIN".to_string()))
+}
+
+pub fn commit(database: &mut Database) -> Result<QueryResult, String> {
+    if database.session.transaction.is_none() {
+        return Err("There is no transaction in progress".to_string());
+    }
+    database.commit()?;
+    Ok(QueryResult::Command("COMMIT".to_string()))
+}
+
+pub fn rollback(database: &mut Database) -> Result<QueryResult, String> {
+    if database.session.transaction.is_none() {
+        return Err("There is no transaction in progress".to_string());
+    }
+    database.rollback()?;
+    Ok(QueryResult::Command("ROLLBACK".to_string()))
+}
+
+// changes of the transaction stay in tables, but they can still be rolled back by ROLLBACK PREPARED
+pub fn prepare(gid: &str, database: &mut Database) -> Result<QueryResult, String> {
+    transaction("PREPARE TRANSACTION", database)?;
+    database.prepare(gid)?;
+    Ok(QueryResult::Command("PREPARE TRANSACTION".to_string()))
+}
+
+// prepared transaction can be finished by any session, but not inside of its own transaction block
+pub fn finish_prepared(
+    gid: &str,
+    commit: bool,
+    database: &mut Database,
+) -> Result<QueryResult, String> {
+    let command = if commit {
+        "COMMIT PREPARED"
+    } else {
+        "ROLLBACK PREPARED"
+    };
+    if database.session.transaction.is_some() {
+        return Err(format!("{} cannot run inside a transaction block", command));
+    }
+    database.finish_prepared(gid, commit)?;
+    Ok(QueryResult::Command(command.to_string()))
+}
+
+pub fn savepoint(name: &str, database: &mut Database) -> Result<QueryResult, String> {
+    let position = database.savepoint();
+    transaction("SAVEPOINT", database)?
+        .savepoints
+        .push(Savepoint {
+            name: name.to_string(),
+            position,
+        });
+    Ok(QueryResult::Command("SAVEPOINT".to_string()))
+}
+
+/*
+    changes after the savepoint are undone and later savepoints are forgotten,
+    the savepoint itself stays, like in postgres, locks are kept until the transaction ends
+*/
+pub fn rollback_to_savepoint(name: &str, database: &mut Database) -> Result<QueryResult, String> {
+    let savepoints = &mut transaction("ROLLBACK TO SAVEPOINT", database)?.savepoints;
+    let index = find_savepoint(name, savepoints)?;
+    let position = savepoints[index].position;
+    savepoints.truncate(index + 1);
+    database.rollback_to(position)?;
+    Ok(QueryResult::Command("ROLLBACK".to_string()))
+}
+
+// isolation level can be changed only before the first query of the transaction takes its snapshot
+pub fn set_transaction(
+    isolation: IsolationLevel,
+    database: &mut Database,
+) -> Result<QueryResult, String> {
+    let transaction = transaction("SET TRANSACTION", database)?;
+    if transaction.snapshot.is_some() {
+        return Err("SET TRANSACTION ISOLATION LEVEL must be called before any query".to_string());
+    }
+    transaction.isolation = isolation;
+    Ok(QueryResult::Command("SET".to_string()))
+}
+
+// changes after released savepoints are kept, they are committed or rolled back with the transaction
+pub fn release_savepoint(name: &str, database: &mut Database) -> Result<QueryResult, String> {
+    let savepoints = &mut transaction("RELEASE SAVEPOINT", database)?.savepoints;
+    let index = find_savepoint(name, savepoints)?;
+    savepoints.truncate(index);
+    Ok(QueryResult::Command("RELEASE".to_string()))
+}
+
+fn transaction<'a>(
+    command: &str,
+    database: &'a mut Database,
+) -> Result<&'a mut Transaction, String> {
+    database
+        .session
+        .transaction
+        .as_mut()
+        .ok_or_else(|| format!("{} can only be used in transaction blocks", command))
+}
+
+// the latest savepoint with the name is used, when there are more of them
+fn find_savepoint(name: &str, savepoints: &[Savepoint]) -> Result<usize, String> {
+    savepoints
+        .iter()
+        .rposition(|savepoint| savepoint.name == name)
+        .ok_or_else(|| format!("Savepoint \"{}\" does not exist", name))
+}
+
+/*
+    only row changes are undone by rollback, so statements which change the catalog
+    or storage of relations as a whole can't be part of a transaction block
+*/
+pub fn check_allowed_in_transaction(
+    statement: &SqlStatement,
+    session: &Session,
+) -> Result<(), String> {
+    match &session.transaction {
+        Some(transaction) if transaction.explicit => (),
+        _ => return Ok(()),
+    }
+    let command = match statement {
+        SqlStatement::CreateTable { .. } => "CREATE TABLE",
+        SqlStatement::CreateSequence { .. } => "CREATE SEQUENCE",
+        SqlStatement::CreateView { .. } => "CREATE VIEW",
+        SqlStatement::RefreshMaterializedView { .. } => "REFRESH MATERIALIZED VIEW",
+        SqlStatement::CreateIndex { .. } => "CREATE INDEX",
+        SqlStatement::CreateSchema { .. } => "CREATE SCHEMA",
+        SqlStatement::DropSchema { .. } => "DROP SCHEMA",
+        SqlStatement::CreateDatabase { .. } => "CREATE DATABASE",
+        SqlStatement::DropDatabase { .. } => "DROP DATABASE",
+        SqlStatement::Use { .. } => "USE",
+        SqlStatement::Vacuum { .. } => "VACUUM",
+        _ => return Ok(()),
+    };
+    Err(format!("{} cannot run inside a transaction block", command))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        error::SqlState,
+        executor::{abort_transaction, Relation},
+        information_schema::SData,
+        test_support::{open_database, run_in, select_in, sorted},
+    };
+
+    use super::*;
+
+    fn ids(database: &mut Database, session: &mut Session) -> Vec<Vec<SData>> {
+        sorted(select_in(database, session, "SELECT id FROM items;"))
+    }
+
+    fn command(tag: &str) -> Result<QueryResult, String> {
+        Ok(QueryResult::Command(tag.to_string()))
+    }
+
+    #[test]
+    fn when_transaction_is_rolled_back_should_undo_all_its_statements() {
+        let (_directory, mut database) = open_database();
+        let session = &mut Session::default();
+        run_in(
+            &mut database,
+            session,
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (1);").unwrap();
+
+        assert_eq!(run_in(&mut database, session, "BEGIN;"), command("BEGIN"));
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (2);").unwrap();
+        run_in(
+            &mut database,
+            session,
+            "UPDATE items SET id = 3 WHERE id = 1;",
+        )
+        .unwrap();
+        assert_eq!(
+            ids(&mut database, session),
+            vec![vec![SData::INT(2)], vec![SData::INT(3)]]
+        );
+        assert_eq!(
+            run_in(&mut database, session, "ROLLBACK;"),
+            command("ROLLBACK")
+        );
+
+        assert_eq!(ids(&mut database, session), vec![vec![SData::INT(1)]]);
+        assert_eq!(
+            run_in(&mut database, session, "INSERT INTO items (id) VALUES (2);"),
+            command("INSERT 0 1")
+        );
+        assert_eq!(
+            run_in(&mut database, session, "COMMIT;"),
+            Err("There is no transaction in progress".to_string())
+        );
+    }
+
+    #[test]
+    fn when_statement_of_transaction_fails_should_roll_back_only_itself() {
+        let (_directory, mut database) = open_database();
+        let session = &mut Session::default();
+        run_in(
+            &mut database,
+            session,
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+
+        run_in(&mut database, session, "START TRANSACTION;").unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (1);").unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (2);").unwrap();
+        assert!(run_in(&mut database, session, "UPDATE items SET id = 5;").is_err());
+        assert_eq!(run_in(&mut database, session, "COMMIT;"), command("COMMIT"));
+        assert_eq!(
+            ids(&mut database, session),
+            vec![vec![SData::INT(1)], vec![SData::INT(2)]]
+        );
+
+        run_in(&mut database, session, "BEGIN;").unwrap();
+        assert_eq!(
+            run_in(
+                &mut database,
+                session,
+                "CREATE TABLE other (id INT PRIMARY KEY);"
+            ),
+            Err("CREATE TABLE cannot run inside a transaction block".to_string())
+        );
+    }
+
+    #[test]
+    fn when_transaction_rolls_back_to_savepoint_should_keep_changes_before_it() {
+        let (_directory, mut database) = open_database();
+        let session = &mut Session::default();
+        run_in(
+            &mut database,
+            session,
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+        assert_eq!(
+            run_in(&mut database, session, "SAVEPOINT first;"),
+            Err("SAVEPOINT can only be used in transaction blocks".to_string())
+        );
+
+        run_in(&mut database, session, "BEGIN;").unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (1);").unwrap();
+        assert_eq!(
+            run_in(&mut database, session, "SAVEPOINT first;"),
+            command("SAVEPOINT")
+        );
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (2);").unwrap();
+        run_in(&mut database, session, "SAVEPOINT second;").unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (3);").unwrap();
+        assert_eq!(
+            run_in(&mut database, session, "ROLLBACK TO SAVEPOINT first;"),
+            command("ROLLBACK")
+        );
+        assert_eq!(ids(&mut database, session), vec![vec![SData::INT(1)]]);
+
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (2);").unwrap();
+        assert_eq!(
+            run_in(&mut database, session, "RELEASE first;"),
+            command("RELEASE")
+        );
+        assert_eq!(
+            run_in(&mut database, session, "ROLLBACK TO second;"),
+            Err("Savepoint \"second\" does not exist".to_string())
+        );
+        assert!(session.transaction.as_ref().unwrap().savepoints.is_empty());
+        run_in(&mut database, session, "COMMIT;").unwrap();
+        assert_eq!(
+            ids(&mut database, session),
+            vec![vec![SData::INT(1)], vec![SData::INT(2)]]
+        );
+    }
+
+    #[test]
+    fn when_prepared_transactions_survive_restart_should_be_committed_or_rolled_back() {
+        let (directory, mut database) = open_database();
+        let session = &mut Session::default();
+        run_in(
+            &mut database,
+            session,
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (1);").unwrap();
+        for (gid, sql) in [
+            ("load", "INSERT INTO items (id) VALUES (2);"),
+            ("cleanup", "DELETE FROM items WHERE id = 1;"),
+        ] {
+            run_in(&mut database, session, "BEGIN;").unwrap();
+            run_in(&mut database, session, sql).unwrap();
+            assert_eq!(
+                run_in(
+                    &mut database,
+                    session,
+                    &format!("PREPARE TRANSACTION \"{}\";", gid)
+                ),
+                command("PREPARE TRANSACTION")
+            );
+        }
+        assert_eq!(
+            run_in(&mut database, session, "PREPARE TRANSACTION \"load\";"),
+            Err("PREPARE TRANSACTION can only be used in transaction blocks".to_string())
+        );
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        match run_in(&mut database, session, "SELECT gid FROM pg_prepared_xacts;") {
+            Ok(QueryResult::Rows(Relation { mut rows, .. })) => {
+                rows.sort();
+                assert_eq!(
+                    rows,
+                    vec![
+                        vec![SData::STRING("cleanup".to_string())],
+                        vec![SData::STRING("load".to_string())]
+                    ]
+                );
+            }
+            other => panic!("Expected rows, got {:?}", other),
+        }
+        assert_eq!(
+            run_in(&mut database, session, "ROLLBACK PREPARED \"cleanup\";"),
+            command("ROLLBACK PREPARED")
+        );
+        assert_eq!(
+            run_in(&mut database, session, "COMMIT PREPARED \"load\";"),
+            command("COMMIT PREPARED")
+        );
+        assert_eq!(
+            run_in(&mut database, session, "COMMIT PREPARED \"load\";"),
+            Err("Prepared transaction with identifier \"load\" does not exist".to_string())
+        );
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        assert_eq!(
+            ids(&mut database, session),
+            vec![vec![SData::INT(1)], vec![SData::INT(2)]]
+        );
+        assert_eq!(
+            run_in(&mut database, session, "SELECT gid FROM pg_prepared_xacts;"),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["gid".to_string()],
+                rows: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn when_sessions_share_table_should_keep_only_committed_rows_after_reopen() {
+        let (directory, database) = open_database();
+        let database = Mutex::new(database);
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        {
+            let database = &mut database.lock().unwrap();
+            run_in(database, first, "CREATE TABLE items (id INT PRIMARY KEY);").unwrap();
+            run_in(database, first, "BEGIN;").unwrap();
+            run_in(database, first, "INSERT INTO items (id) VALUES (1);").unwrap();
+            run_in(database, second, "BEGIN;").unwrap();
+            run_in(database, second, "INSERT INTO items (id) VALUES (2);").unwrap();
+            run_in(database, second, "END;").unwrap();
+        }
+        abort_transaction(&database, first).unwrap();
+        assert!(first.transaction.is_none());
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        assert_eq!(ids(&mut database, first), vec![vec![SData::INT(2)]]);
+    }
+
+    #[test]
+    fn when_rows_are_changed_by_open_transaction_other_sessions_should_read_committed_versions() {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        for sql in [
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+            "INSERT INTO items (id) VALUES (1);",
+            "INSERT INTO items (id) VALUES (2);",
+            "BEGIN;",
+            "DELETE FROM items WHERE id = 1;",
+            "INSERT INTO items (id) VALUES (3);",
+            "UPDATE items SET id = 4 WHERE id = 2;",
+        ] {
+            run_in(&mut database, first, sql).unwrap();
+        }
+        let (old, new) = (
+            vec![vec![SData::INT(1)], vec![SData::INT(2)]],
+            vec![vec![SData::INT(3)], vec![SData::INT(4)]],
+        );
+
+        // reader isn't blocked by locks of the writer
+        assert_eq!(ids(&mut database, second), old);
+        assert_eq!(ids(&mut database, first), new);
+        run_in(&mut database, second, "BEGIN;").unwrap();
+        assert_eq!(ids(&mut database, second), old);
+        run_in(&mut database, first, "COMMIT;").unwrap();
+        assert_eq!(ids(&mut database, second), new);
+        assert_eq!(
+            run_in(&mut database, second, "INSERT INTO items (id) VALUES (4);"),
+            Err("duplicate key value violates unique constraint \"items_pkey\"".to_string())
+        );
+        run_in(&mut database, second, "INSERT INTO items (id) VALUES (1);").unwrap();
+        run_in(&mut database, second, "COMMIT;").unwrap();
+        assert_eq!(ids(&mut database, first).len(), 3);
+    }
+
+    #[test]
+    fn when_database_stops_with_open_transaction_its_rows_should_stay_invisible() {
+        let (directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        run_in(
+            &mut database,
+            first,
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+        run_in(&mut database, first, "BEGIN;").unwrap();
+        run_in(&mut database, first, "INSERT INTO items (id) VALUES (1);").unwrap();
+        // heap with the uncommitted row is saved by commit of the other session
+        run_in(&mut database, second, "INSERT INTO items (id) VALUES (2);").unwrap();
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        let session = &mut Session::default();
+        // new transactions don't get the id of the stopped one, its row would become visible
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (3);").unwrap();
+        run_in(&mut database, session, "INSERT INTO items (id) VALUES (1);").unwrap();
+        assert_eq!(
+            ids(&mut database, session),
+            vec![
+                vec![SData::INT(1)],
+                vec![SData::INT(2)],
+                vec![SData::INT(3)]
+            ]
+        );
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        assert_eq!(ids(&mut database, session).len(), 3);
+    }
+
+    #[test]
+    fn when_database_stops_with_open_transaction_its_deletes_should_not_block_changes_of_rows() {
+        let (directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        for sql in [
+            "CREATE TABLE items (id INT PRIMARY KEY, n INT);",
+            "INSERT INTO items (id, n) VALUES (1, 1);",
+            "INSERT INTO items (id, n) VALUES (2, 2);",
+            "INSERT INTO items (id, n) VALUES (3, 3);",
+            "BEGIN;",
+            "DELETE FROM items WHERE id = 1;",
+            "UPDATE items SET n = 20 WHERE id = 2;",
+            "UPDATE items SET n = 30 WHERE id = 3;",
+        ] {
+            run_in(&mut database, first, sql).unwrap();
+        }
+        run_in(
+            &mut database,
+            second,
+            "INSERT INTO items (id, n) VALUES (4, 4);",
+        )
+        .unwrap();
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        let session = &mut Session::default();
+        // versions keep xmax of the stopped transaction, which is aborted now
+        for sql in [
+            "DELETE FROM items WHERE id = 1;",
+            "UPDATE items SET n = 200 WHERE id = 2;",
+            "INSERT INTO items (id, n) VALUES (3, 0) ON CONFLICT (id) DO UPDATE SET n = 300;",
+        ] {
+            run_in(&mut database, session, sql).unwrap();
+        }
+        match run_in(&mut database, session, "SELECT id, n FROM items;") {
+            Ok(QueryResult::Rows(Relation { mut rows, .. })) => {
+                rows.sort();
+                assert_eq!(
+                    rows,
+                    vec![
+                        vec![SData::INT(2), SData::INT(200)],
+                        vec![SData::INT(3), SData::INT(300)],
+                        vec![SData::INT(4), SData::INT(4)]
+                    ]
+                );
+            }
+            other => panic!("Expected rows, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn when_repeatable_read_row_was_changed_concurrently_should_fail_to_change_it() {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        for sql in [
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+            "INSERT INTO items (id) VALUES (1);",
+            "BEGIN;",
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;",
+        ] {
+            run_in(&mut database, first, sql).unwrap();
+        }
+        assert_eq!(
+            run_in(&mut database, first, "SHOW transaction_isolation;"),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["transaction_isolation".to_string()],
+                rows: vec![vec![SData::STRING("repeatable read".to_string())]],
+            }))
+        );
+        assert_eq!(ids(&mut database, first), vec![vec![SData::INT(1)]]);
+        assert_eq!(
+            run_in(
+                &mut database,
+                first,
+                "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE;"
+            ),
+            Err("SET TRANSACTION ISOLATION LEVEL must be called before any query".to_string())
+        );
+        run_in(&mut database, second, "INSERT INTO items (id) VALUES (2);").unwrap();
+        run_in(
+            &mut database,
+            second,
+            "UPDATE items SET id = 3 WHERE id = 1;",
+        )
+        .unwrap();
+
+        assert_eq!(ids(&mut database, first), vec![vec![SData::INT(1)]]);
+        assert_eq!(
+            run_in(&mut database, first, "DELETE FROM items WHERE id = 1;"),
+            Err(SqlState::SerializationFailure
+                .error("Could not serialize access due to concurrent update"))
+        );
+        assert!(first.transaction.is_none());
+        assert_eq!(
+            ids(&mut database, first),
+            vec![vec![SData::INT(2)], vec![SData::INT(3)]]
+        );
+        assert_eq!(
+            run_in(
+                &mut database,
+                first,
+                "SET TRANSACTION ISOLATION LEVEL READ COMMITTED;"
+            ),
+            Err("SET TRANSACTION can only be used in transaction blocks".to_string())
+        );
+    }
+
+    #[test]
+    fn when_repeatable_read_updates_row_updated_concurrently_should_fail_with_serialization_failure(
+    ) {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        for sql in [
+            "CREATE TABLE acc (id INT PRIMARY KEY, bal INT);",
+            "INSERT INTO acc (id, bal) VALUES (1, 0);",
+            "BEGIN;",
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;",
+            "SELECT bal FROM acc WHERE id = 1;",
+        ] {
+            run_in(&mut database, first, sql).unwrap();
+        }
+        run_in(
+            &mut database,
+            second,
+            "UPDATE acc SET bal = 1 WHERE id = 1;",
+        )
+        .unwrap();
+
+        // the committed new version has the same primary key, but it's not a duplicate
+        let result = run_in(&mut database, first, "UPDATE acc SET bal = 2 WHERE id = 1;");
+
+        assert_eq!(
+            result,
+            Err(SqlState::SerializationFailure
+                .error("Could not serialize access due to concurrent update"))
+        );
+        assert!(first.transaction.is_none());
+        assert_eq!(
+            run_in(&mut database, first, "SELECT id, bal FROM acc;"),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["id".to_string(), "bal".to_string()],
+                rows: vec![vec![SData::INT(1), SData::INT(1)]],
+            }))
+        );
+    }
+
+    #[test]
+    fn when_serializable_transactions_make_write_skew_first_to_commit_should_fail() {
+        let (_directory, mut database) = open_database();
+        let (first, second) = (&mut Session::default(), &mut Session::default());
+        for sql in [
+            "CREATE TABLE doctors (id INT PRIMARY KEY, duty INT);",
+            "INSERT INTO doctors (id, duty) VALUES (1, 1);",
+            "INSERT INTO doctors (id, duty) VALUES (2, 1);",
+        ] {
+            run_in(&mut database, first, sql).unwrap();
+        }
+        // both doctors see that the other one is on duty, so they both go off duty
+        for (session, id) in [(&mut *first, 1), (&mut *second, 2)] {
+            for sql in [
+                "BEGIN;".to_string(),
+                "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE;".to_string(),
+                "SELECT id FROM doctors WHERE duty = 1;".to_string(),
+                format!("UPDATE doctors SET duty = 0 WHERE id = {};", id),
+            ] {
+                run_in(&mut database, session, &sql).unwrap();
+            }
+        }
+
+        assert_eq!(
+            run_in(&mut database, first, "COMMIT;"),
+            Err(SqlState::SerializationFailure.error(
+                "Could not serialize access due to read/write dependencies among transactions"
+            ))
+        );
+        assert!(first.transaction.is_none());
+        assert_eq!(run_in(&mut database, second, "COMMIT;"), command("COMMIT"));
+        assert_eq!(
+            run_in(
+                &mut database,
+                first,
+                "SELECT id FROM doctors WHERE duty = 1;"
+            ),
+            Ok(QueryResult::Rows(Relation {
+                columns: vec!["id".to_string()],
+                rows: vec![vec![SData::INT(1)]],
+            }))
+        );
+    }
+}
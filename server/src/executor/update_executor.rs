@@ -0,0 +1,93 @@
+use crate::{
+    catalog::tables::{Identity, PgClass},
+    information_schema::SData,
+    parser::ast::{Assignment, Expression},
+    storage::Database,
+};
+
+use super::{
+    constraints::TableConstraints,
+    expression::{evaluate_predicate, resolve_column},
+    modify::update_rows,
+    sequences::evaluate_with_sequences,
+};
+
+/*
+    assignments are evaluated against the old version of the row,
+    all new versions are checked before the table is changed, so unique values can be swapped between rows,
+    returns new versions of updated rows
+*/
+pub fn update(
+    table_name: &str,
+    assignments: &[Assignment],
+    selection: &Option<Expression>,
+    database: &mut Database,
+) -> Result<Vec<Vec<SData>>, String> {
+    let table = database
+        .catalog
+        .table(table_name, &database.session.search_path)?
+        .clone();
+    let columns = TableConstraints::load(database, &table)?.columns;
+    let targets = assignment_targets(&table, table_name, &columns, assignments, database)?;
+    let mut updates = vec![];
+    for (pointer, row) in database.rows(table.oid)? {
+        if let Some(selection) = selection {
+            if !evaluate_predicate(selection, &columns, &row)? {
+                continue;
+            }
+        }
+        let new_row = assign(&targets, &row, &columns, &row, database)?;
+        updates.push((pointer, new_row));
+    }
+    let updated = updates.iter().map(|(_, row)| row.clone()).collect();
+    update_rows(database, &table, updates)?;
+    Ok(updated)
+}
+
+// positions of assigned columns in the table with their values, shared with ON CONFLICT and MERGE
+pub fn assignment_targets<'a>(
+    table: &PgClass,
+    table_name: &str,
+    columns: &[String],
+    assignments: &'a [Assignment],
+    database: &Database,
+) -> Result<Vec<(usize, &'a Expression)>, String> {
+    let mut targets = Vec::with_capacity(assignments.len());
+    for assignment in assignments {
+        let index = resolve_column(columns, &assignment.column).map_err(|_| {
+            format!(
+                "Column \"{}\" of relation \"{}\" does not exist",
+                assignment.column, table_name
+            )
+        })?;
+        if database.catalog.attributes_of(table.oid)[index].identity == Identity::Always {
+            return Err(format!(
+                "column \"{}\" can only be updated to DEFAULT",
+                assignment.column
+            ));
+        }
+        if targets.iter().any(|(target, _)| *target == index) {
+            return Err(format!(
+                "Multiple assignments to same column \"{}\"",
+                assignment.column
+            ));
+        }
+        targets.push((index, &assignment.value));
+    }
+    Ok(targets)
+}
+
+// new version of the row, values can reference other relations than the updated table through context
+pub fn assign(
+    targets: &[(usize, &Expression)],
+    row: &[SData],
+    context_columns: &[String],
+    context_row: &[SData],
+    database: &mut Database,
+) -> Result<Vec<SData>, String> {
+    let mut new_row = row.to_vec();
+    for (index, value) in targets {
+        new_row[*index] = evaluate_with_sequences(value, context_columns, context_row, database)?;
+    }
+    Ok(new_row)
+}
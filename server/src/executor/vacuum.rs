@@ -0,0 +1,145 @@
+use crate::{catalog::Oid, storage::Database};
+
+use super::QueryResult;
+
+/*
+    VACUUM runs outside of any transaction, so it doesn't keep dead versions for itself,
+    without table name all tables and materialized views of the database are vacuumed
+*/
+pub fn vacuum(table_name: Option<&str>, database: &mut Database) -> Result<QueryResult, String> {
+    let tables: Vec<Oid> = match table_name {
+        Some(name) => vec![
+            database
+                .catalog
+                .relation_with_heap(name, &database.session.search_path)?
+                .oid,
+        ],
+        None => database
+            .catalog
+            .classes
+            .iter()
+            .filter(|class| class.kind.has_heap())
+            .map(|class| class.oid)
+            .collect(),
+    };
+    for table in tables {
+        database.vacuum(table)?;
+    }
+    Ok(QueryResult::Command("VACUUM".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constants::AUTOVACUUM_VACUUM_THRESHOLD,
+        information_schema::SData,
+        storage::Session,
+        test_support::{open_database, run_in, select_in, sorted},
+    };
+
+    use super::*;
+
+    fn ids(database: &mut Database, session: &mut Session) -> Vec<Vec<SData>> {
+        sorted(select_in(database, session, "SELECT id FROM items;"))
+    }
+
+    #[test]
+    fn when_old_versions_are_seen_by_running_transaction_vacuum_should_keep_them() {
+        let (_directory, mut database) = open_database();
+        let (writer, reader) = (&mut Session::default(), &mut Session::default());
+        for sql in [
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+            "INSERT INTO items (id) VALUES (1);",
+            "INSERT INTO items (id) VALUES (2);",
+            "INSERT INTO items (id) VALUES (3);",
+        ] {
+            run_in(&mut database, writer, sql).unwrap();
+        }
+        run_in(&mut database, reader, "BEGIN;").unwrap();
+        run_in(
+            &mut database,
+            reader,
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;",
+        )
+        .unwrap();
+        let old = ids(&mut database, reader);
+
+        run_in(
+            &mut database,
+            writer,
+            "UPDATE items SET id = 4 WHERE id = 1;",
+        )
+        .unwrap();
+        run_in(&mut database, writer, "DELETE FROM items WHERE id = 2;").unwrap();
+        assert_eq!(
+            run_in(&mut database, writer, "VACUUM items;"),
+            Ok(QueryResult::Command("VACUUM".to_string()))
+        );
+        assert_eq!(ids(&mut database, reader), old);
+        run_in(&mut database, reader, "COMMIT;").unwrap();
+
+        let table = database
+            .catalog
+            .relation_with_heap("items", &writer.search_path)
+            .unwrap()
+            .oid;
+        assert_eq!(database.vacuum(table), Ok(2));
+        assert_eq!(database.vacuum(table), Ok(0));
+        run_in(&mut database, writer, "INSERT INTO items (id) VALUES (1);").unwrap();
+        assert_eq!(
+            ids(&mut database, writer),
+            vec![
+                vec![SData::INT(1)],
+                vec![SData::INT(3)],
+                vec![SData::INT(4)]
+            ]
+        );
+        assert_eq!(
+            run_in(&mut database, writer, "INSERT INTO items (id) VALUES (4);"),
+            Err("duplicate key value violates unique constraint \"items_pkey\"".to_string())
+        );
+    }
+
+    #[test]
+    fn when_enough_versions_are_deleted_autovacuum_should_vacuum_the_table() {
+        let (_directory, mut database) = open_database();
+        let session = &mut Session::default();
+        run_in(
+            &mut database,
+            session,
+            "CREATE TABLE items (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+        for id in 0..AUTOVACUUM_VACUUM_THRESHOLD {
+            let sql = format!("INSERT INTO items (id) VALUES ({});", id);
+            run_in(&mut database, session, &sql).unwrap();
+        }
+
+        run_in(&mut database, session, "DELETE FROM items WHERE id > 0;").unwrap();
+        assert_eq!(database.autovacuum(), Ok(0));
+        run_in(&mut database, session, "DELETE FROM items;").unwrap();
+
+        assert_eq!(database.autovacuum(), Ok(AUTOVACUUM_VACUUM_THRESHOLD));
+        assert_eq!(database.autovacuum(), Ok(0));
+    }
+
+    #[test]
+    fn when_vacuum_is_in_transaction_block_should_fail() {
+        let (_directory, mut database) = open_database();
+        let session = &mut Session::default();
+        run_in(&mut database, session, "BEGIN;").unwrap();
+
+        assert_eq!(
+            run_in(&mut database, session, "VACUUM;"),
+            Err("VACUUM cannot run inside a transaction block".to_string())
+        );
+        assert_eq!(
+            run_in(&mut database, session, "COMMIT;"),
+            Ok(QueryResult::Command("COMMIT".to_string()))
+        );
+        assert_eq!(
+            run_in(&mut database, session, "VACUUM missing;"),
+            Err("Relation \"missing\" does not exist".to_string())
+        );
+    }
+}
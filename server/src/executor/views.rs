@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+
+use crate::{
+    catalog::tables::{Identity, Kind, PgAttribute, PgClass, PgView},
+    information_schema::{SData, SType},
+    lock_manager::lock::{LockMode, LockTarget},
+    parser::{ast::Query, parse_query_text},
+    storage::Database,
+};
+
+use super::{
+    expression::unqualified_name,
+    select_executor::{execute_query, rename_columns},
+    Relation,
+};
+
+/*
+    view keeps only text of its query, the query is executed whenever the view is scanned,
+    materialized view also stores result of the query in its own heap, which is replaced by REFRESH,
+    query is executed at creation, so view can't be created over missing tables or columns
+*/
+pub fn create_view(
+    name: &str,
+    columns: &[String],
+    query: &Query,
+    materialized: bool,
+    database: &mut Database,
+) -> Result<(), String> {
+    let (namespace, name) = database
+        .catalog
+        .creation_namespace(name, &database.session.search_path)?;
+    if database
+        .catalog
+        .find_in_namespace(namespace, name)
+        .is_some()
+    {
+        return Err(format!("Relation \"{}\" already exists", name));
+    }
+    let relation = rename_columns(execute_query(query, database)?, columns)?;
+    let mut names = HashSet::new();
+    for column in &relation.columns {
+        if !names.insert(unqualified_name(column)) {
+            return Err(format!(
+                "Column \"{}\" specified more than once",
+                unqualified_name(column)
+            ));
+        }
+    }
+
+    let mut catalog = database.catalog.clone();
+    let oid = catalog.allocate_oid();
+    catalog.classes.push(PgClass {
+        oid,
+        name: name.to_string(),
+        kind: if materialized {
+            Kind::MaterializedView
+        } else {
+            Kind::View
+        },
+        namespace,
+    });
+    catalog.views.push(PgView {
+        oid,
+        columns: columns.to_vec(),
+        definition: query.to_string(),
+    });
+    if materialized {
+        let types = column_types(&relation, &vec![SType::STRING; relation.columns.len()])?;
+        for (position, (column, data_type)) in relation.columns.iter().zip(types).enumerate() {
+            catalog.attributes.push(PgAttribute {
+                owner_id: oid,
+                name: unqualified_name(column).to_string(),
+                num: position as i32 + 1,
+                data_type,
+                default: None,
+                identity: Identity::None,
+            });
+        }
+    }
+    database.catalog = catalog;
+    if materialized {
+        database.create_heap(oid);
+        for row in relation.rows {
+            database.insert_row(oid, row)?;
+        }
+        database.save_table(oid)?;
+    }
+    database.save_catalog()
+}
+
+// rows of materialized view are replaced with current result of its query
+pub fn refresh_materialized_view(name: &str, database: &mut Database) -> Result<(), String> {
+    let view = match database
+        .catalog
+        .find_relation(name, &database.session.search_path)
+    {
+        Some(class) if class.kind == Kind::MaterializedView => class.oid,
+        _ => return Err(format!("\"{}\" is not a materialized view", name)),
+    };
+    database.lock(LockTarget::Table(view), LockMode::Exclusive)?;
+    let definition = database.catalog.view(view)?.clone();
+    let query = parse_query_text(definition.definition)?;
+    let relation = rename_columns(execute_query(&query, database)?, &definition.columns)?;
+    let previous = database.column_types(view);
+    if relation.columns.len() != previous.len() {
+        return Err(format!(
+            "Query of materialized view \"{}\" returns {} columns instead of {}",
+            name,
+            relation.columns.len(),
+            previous.len()
+        ));
+    }
+    let types = column_types(&relation, &previous)?;
+
+    // old heap is dropped as a whole, so the rows don't have to be readable with new column types
+    for attribute in database.catalog.attributes.iter_mut() {
+        if attribute.owner_id == view {
+            attribute.data_type = types[attribute.num as usize - 1];
+        }
+    }
+    database.create_heap(view);
+    for row in relation.rows {
+        database.insert_row(view, row)?;
+    }
+    database.save_table(view)?;
+    database.save_catalog()
+}
+
+// type of column is given by its values, column with only NULLs keeps previous type
+fn column_types(relation: &Relation, previous: &[SType]) -> Result<Vec<SType>, String> {
+    let mut types: Vec<Option<SType>> = vec![None; relation.columns.len()];
+    for row in &relation.rows {
+        for ((value, data_type), column) in row.iter().zip(types.iter_mut()).zip(&relation.columns)
+        {
+            let value_type = match value {
+                SData::INT(_) => SType::INT,
+                SData::STRING(_) => SType::STRING,
+                SData::NULL => continue,
+                SData::BOOL(_) => {
+                    return Err(format!(
+                        "Column \"{}\" of materialized view can't store boolean values",
+                        unqualified_name(column)
+                    ))
+                }
+            };
+            match data_type {
+                Some(data_type) if *data_type != value_type => {
+                    return Err(format!(
+                        "Column \"{}\" of materialized view has values of different types",
+                        unqualified_name(column)
+                    ))
+                }
+                _ => *data_type = Some(value_type),
+            }
+        }
+    }
+    Ok(types
+        .into_iter()
+        .zip(previous)
+        .map(|(data_type, previous)| data_type.unwrap_or(*previous))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::QueryResult,
+        test_support::{open_database, run, select, sorted},
+    };
+
+    use super::*;
+
+    fn setup(database: &mut Database) {
+        for sql in [
+            "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR);",
+            "CREATE TABLE orders (id INT PRIMARY KEY, user_id INT, amount INT);",
+            "INSERT INTO users (id, name) VALUES (1, \"ann\");",
+            "INSERT INTO users (id, name) VALUES (2, \"bob\");",
+            "INSERT INTO orders (id, user_id, amount) VALUES (1, 1, 30);",
+            "INSERT INTO orders (id, user_id, amount) VALUES (2, 2, 5);",
+        ] {
+            run(database, sql).unwrap();
+        }
+    }
+
+    fn row(name: &str, amount: i32) -> Vec<SData> {
+        vec![SData::STRING(name.to_string()), SData::INT(amount)]
+    }
+
+    #[test]
+    fn when_view_over_join_is_selected_should_return_current_rows_of_tables() {
+        let (_directory, mut database) = open_database();
+        setup(&mut database);
+
+        assert_eq!(
+            run(
+                &mut database,
+                "CREATE VIEW big_orders (customer, amount) AS SELECT u.name, o.amount FROM users AS u JOIN orders AS o ON u.id = o.user_id WHERE o.amount > 10;"
+            ),
+            Ok(QueryResult::Command("CREATE VIEW".to_string()))
+        );
+        run(
+            &mut database,
+            "INSERT INTO orders (id, user_id, amount) VALUES (3, 2, 50);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            sorted(select(
+                &mut database,
+                "SELECT b.customer, b.amount FROM big_orders AS b WHERE b.amount < 40;"
+            )),
+            vec![row("ann", 30)]
+        );
+        assert_eq!(
+            sorted(select(
+                &mut database,
+                "SELECT customer, amount FROM big_orders WHERE customer LIKE \"b%\";"
+            )),
+            vec![row("bob", 50)]
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "INSERT INTO big_orders (customer, amount) VALUES (\"eve\", 1);"
+            ),
+            Err("\"big_orders\" is not a table".to_string())
+        );
+        assert_eq!(
+            run(
+                &mut database,
+                "CREATE VIEW pairs AS SELECT * FROM users JOIN orders ON users.id = orders.user_id;"
+            ),
+            Err("Column \"id\" specified more than once".to_string())
+        );
+    }
+
+    #[test]
+    fn when_materialized_view_is_refreshed_should_store_new_rows_which_survive_reopen() {
+        let (directory, mut database) = open_database();
+        setup(&mut database);
+        run(
+            &mut database,
+            "CREATE MATERIALIZED VIEW spent AS SELECT u.name, o.amount FROM users AS u JOIN orders AS o ON u.id = o.user_id;",
+        )
+        .unwrap();
+        run(&mut database, "UPDATE orders SET amount = 7 WHERE id = 1;").unwrap();
+
+        assert_eq!(
+            sorted(select(&mut database, "SELECT name, amount FROM spent;")),
+            vec![row("ann", 30), row("bob", 5)]
+        );
+        assert_eq!(
+            run(&mut database, "REFRESH MATERIALIZED VIEW spent;"),
+            Ok(QueryResult::Command(
+                "REFRESH MATERIALIZED VIEW".to_string()
+            ))
+        );
+        assert_eq!(
+            run(&mut database, "REFRESH MATERIALIZED VIEW users;"),
+            Err("\"users\" is not a materialized view".to_string())
+        );
+        drop(database);
+
+        let mut database = Database::open(directory.path()).unwrap();
+        assert_eq!(
+            sorted(select(&mut database, "SELECT name, amount FROM spent;")),
+            vec![row("ann", 7), row("bob", 5)]
+        );
+    }
+}
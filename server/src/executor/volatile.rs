@@ -0,0 +1,98 @@
+use crate::{
+    information_schema::SData,
+    parser::ast::{Columns, Expression, Query, QueryBody},
+    storage::Database,
+};
+
+use super::{
+    advisory::{call_advisory_functions, is_advisory_function},
+    aggregate::contains_aggregate,
+    expression::children,
+    select_executor::{output_name, select_rows},
+    sequences::{evaluate_with_sequences, is_sequence_function},
+    set_operations::distinct,
+    Relation,
+};
+
+/*
+    select list which calls functions changing the database is computed here, where the database can be changed:
+        advisory lock functions are called once for the statement before its rows are read,
+        their results are appended to every row as hidden columns
+        sequence functions are called for every row, only when evaluation of the select list needs their value
+    rows are read as by any other select, query can't have WITH, set operations or aggregate functions
+*/
+pub fn calls_volatile_functions(query: &Query) -> bool {
+    match (&query.body, &query.with) {
+        (QueryBody::Select(select), None) => match &select.columns {
+            Columns::Specific(items) => items
+                .iter()
+                .any(|item| contains_volatile_function(&item.expression)),
+            Columns::All => false,
+        },
+        _ => false,
+    }
+}
+
+fn contains_volatile_function(expression: &Expression) -> bool {
+    match expression {
+        Expression::Function { name, .. }
+            if is_advisory_function(name) || is_sequence_function(name) =>
+        {
+            true
+        }
+        other => children(other).into_iter().any(contains_volatile_function),
+    }
+}
+
+pub fn execute_volatile_query(query: &Query, database: &mut Database) -> Result<Relation, String> {
+    let (select, items) = match &query.body {
+        QueryBody::Select(select) => match &select.columns {
+            Columns::Specific(items) => (select, items),
+            Columns::All => unreachable!("select list without volatile functions"),
+        },
+        QueryBody::SetOperation { .. } => unreachable!("set operation without volatile functions"),
+    };
+    if items
+        .iter()
+        .any(|item| contains_aggregate(&item.expression))
+    {
+        return Err(
+            "Advisory lock and sequence functions are not allowed with aggregate functions"
+                .to_string(),
+        );
+    }
+    let (mut hidden_columns, mut results) = (vec![], vec![]);
+    let expressions = items
+        .iter()
+        .map(|item| {
+            call_advisory_functions(
+                &item.expression,
+                &mut hidden_columns,
+                &mut results,
+                database,
+            )
+        })
+        .collect::<Result<Vec<Expression>, String>>()?;
+    let relation = select_rows(select, database)?;
+    let mut columns = relation.columns;
+    columns.extend(hidden_columns);
+    let mut rows = Vec::with_capacity(relation.rows.len());
+    for mut row in relation.rows {
+        row.extend(results.iter().cloned());
+        rows.push(
+            expressions
+                .iter()
+                .map(|expression| evaluate_with_sequences(expression, &columns, &row, database))
+                .collect::<Result<Vec<SData>, String>>()?,
+        );
+    }
+    let relation = Relation {
+        columns: items.iter().map(output_name).collect(),
+        rows,
+    };
+    if select.distinct {
+        Ok(distinct(relation))
+    } else {
+        Ok(relation)
+    }
+}
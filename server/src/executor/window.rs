@@ -0,0 +1,274 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    information_schema::SData,
+    parser::ast::{Expression, FrameBound, OrderByExpression, WindowSpecification},
+};
+
+use super::{
+    aggregate::aggregate,
+    expression::{evaluate, map_children},
+    Relation,
+};
+
+/*
+    window functions are evaluated before projection:
+        1. every function call with OVER is replaced in the expression with hidden column reference
+        2. for each call rows are split into partitions and sorted by ORDER BY of the window
+        3. value for each row is computed over its partition and appended to the row as hidden column
+    order of rows in relation is preserved
+*/
+pub fn apply_window_functions(
+    mut relation: Relation,
+    expressions: &[Expression],
+) -> Result<(Relation, Vec<Expression>), String> {
+    let mut calls = vec![];
+    let expressions: Vec<Expression> = expressions
+        .iter()
+        .map(|expression| extract_window_calls(expression, &mut calls))
+        .collect::<Result<Vec<Expression>, String>>()?;
+    let mut computed = Vec::with_capacity(calls.len());
+    for call in &calls {
+        computed.push(evaluate_window_call(&relation, call)?);
+    }
+    for (index, values) in computed.into_iter().enumerate() {
+        relation.columns.push(window_column_name(index));
+        for (row, value) in relation.rows.iter_mut().zip(values) {
+            row.push(value);
+        }
+    }
+    Ok((relation, expressions))
+}
+
+fn window_column_name(index: usize) -> String {
+    format!("#window{}", index)
+}
+
+fn extract_window_calls(
+    expression: &Expression,
+    calls: &mut Vec<Expression>,
+) -> Result<Expression, String> {
+    match expression {
+        Expression::Function { over: Some(_), .. } => {
+            calls.push(expression.clone());
+            Ok(Expression::Column(window_column_name(calls.len() - 1)))
+        }
+        other => map_children(other, &mut |child| extract_window_calls(child, calls)),
+    }
+}
+
+// rows of one partition sorted by ORDER BY of the window
+struct Partition<'a> {
+    rows: Vec<&'a [SData]>,
+    // index of the first and the last row with the same ORDER BY values as row on given position
+    peer_start: Vec<usize>,
+    peer_end: Vec<usize>,
+    // number of distinct ORDER BY values up to row on given position
+    peer_group: Vec<usize>,
+}
+
+fn evaluate_window_call(relation: &Relation, call: &Expression) -> Result<Vec<SData>, String> {
+    let (name, arguments, window) = match call {
+        Expression::Function {
+            name,
+            distinct: false,
+            arguments,
+            over: Some(window),
+        } => (name, arguments, window),
+        Expression::Function {
+            name,
+            distinct: true,
+            ..
+        } => {
+            return Err(format!(
+                "DISTINCT is not implemented for window function {}",
+                name
+            ))
+        }
+        _ => return Err("Expected window function call".to_string()),
+    };
+    let mut partitions: Vec<Vec<usize>> = vec![];
+    let mut partition_by_key: HashMap<Vec<SData>, usize> = HashMap::new();
+    for (index, row) in relation.rows.iter().enumerate() {
+        let key = window
+            .partition_by
+            .iter()
+            .map(|expression| evaluate(expression, &relation.columns, row))
+            .collect::<Result<Vec<SData>, String>>()?;
+        let partition = *partition_by_key.entry(key).or_insert_with(|| {
+            partitions.push(vec![]);
+            partitions.len() - 1
+        });
+        partitions[partition].push(index);
+    }
+    let mut results = vec![SData::NULL; relation.rows.len()];
+    for partition_rows in partitions {
+        let mut keyed = Vec::with_capacity(partition_rows.len());
+        for index in partition_rows {
+            let key = window
+                .order_by
+                .iter()
+                .map(|order| evaluate(&order.expression, &relation.columns, &relation.rows[index]))
+                .collect::<Result<Vec<SData>, String>>()?;
+            keyed.push((index, key));
+        }
+        keyed.sort_by(|(_, left), (_, right)| compare_order_keys(left, right, &window.order_by));
+        let partition = build_partition(relation, &keyed);
+        for (position, (index, _)) in keyed.iter().enumerate() {
+            results[*index] =
+                evaluate_at_position(name, arguments, window, &partition, position, relation)?;
+        }
+    }
+    Ok(results)
+}
+
+fn compare_order_keys(left: &[SData], right: &[SData], order_by: &[OrderByExpression]) -> Ordering {
+    for ((left, right), order) in left.iter().zip(right.iter()).zip(order_by.iter()) {
+        // NULL is greater than any other value
+        let ordering = left.cmp(right);
+        let ordering = if order.ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn build_partition<'a>(relation: &'a Relation, keyed: &[(usize, Vec<SData>)]) -> Partition<'a> {
+    let size = keyed.len();
+    let mut peer_start = vec![0; size];
+    let mut peer_group = vec![0; size];
+    for position in 0..size {
+        if position > 0 && keyed[position].1 == keyed[position - 1].1 {
+            peer_start[position] = peer_start[position - 1];
+            peer_group[position] = peer_group[position - 1];
+        } else {
+            peer_start[position] = position;
+            peer_group[position] = if position == 0 {
+                1
+            } else {
+                peer_group[position - 1] + 1
+            };
+        }
+    }
+    let mut peer_end = vec![0; size];
+    for position in (0..size).rev() {
+        peer_end[position] = if position + 1 < size && keyed[position].1 == keyed[position + 1].1 {
+            peer_end[position + 1]
+        } else {
+            position
+        };
+    }
+    Partition {
+        rows: keyed
+            .iter()
+            .map(|(index, _)| relation.rows[*index].as_slice())
+            .collect(),
+        peer_start,
+        peer_end,
+        peer_group,
+    }
+}
+
+fn evaluate_at_position(
+    name: &str,
+    arguments: &[Expression],
+    window: &WindowSpecification,
+    partition: &Partition,
+    position: usize,
+    relation: &Relation,
+) -> Result<SData, String> {
+    let columns = &relation.columns;
+    match name {
+        "row_number" => Ok(SData::INT(position as i32 + 1)),
+        "rank" => Ok(SData::INT(partition.peer_start[position] as i32 + 1)),
+        "dense_rank" => Ok(SData::INT(partition.peer_group[position] as i32)),
+        "lag" | "lead" => {
+            let value = arguments
+                .first()
+                .ok_or_else(|| format!("{} expects at least one argument", name))?;
+            let current = partition.rows[position];
+            let offset = match arguments.get(1) {
+                Some(offset) => match evaluate(offset, columns, current)? {
+                    SData::INT(offset) if offset >= 0 => offset as usize,
+                    other => return Err(format!("Invalid offset {:?} given to {}", other, name)),
+                },
+                None => 1,
+            };
+            let target = if name == "lag" {
+                position.checked_sub(offset)
+            } else {
+                Some(position + offset).filter(|target| *target < partition.rows.len())
+            };
+            match target {
+                Some(target) => evaluate(value, columns, partition.rows[target]),
+                None => match arguments.get(2) {
+                    Some(default) => evaluate(default, columns, current),
+                    None => Ok(SData::NULL),
+                },
+            }
+        }
+        "first_value" | "last_value" => {
+            let value = arguments
+                .first()
+                .ok_or_else(|| format!("{} expects one argument", name))?;
+            let (start, end) = frame_range(window, partition, position);
+            if start >= end {
+                return Ok(SData::NULL);
+            }
+            let target = if name == "first_value" {
+                start
+            } else {
+                end - 1
+            };
+            evaluate(value, columns, partition.rows[target])
+        }
+        "count" | "sum" | "avg" | "min" | "max" => {
+            let (start, end) = frame_range(window, partition, position);
+            let frame = if start < end {
+                &partition.rows[start..end]
+            } else {
+                &partition.rows[0..0]
+            };
+            aggregate(name, false, arguments, frame, columns)
+        }
+        _ => Err(format!("Unknown window function {}", name)),
+    }
+}
+
+/*
+    returns [start, end) positions of the frame inside of the partition,
+    without frame clause frame is whole partition, or with ORDER BY it ends with the last peer of current row
+*/
+fn frame_range(
+    window: &WindowSpecification,
+    partition: &Partition,
+    position: usize,
+) -> (usize, usize) {
+    let size = partition.rows.len();
+    match &window.frame {
+        None if window.order_by.is_empty() => (0, size),
+        None => (0, partition.peer_end[position] + 1),
+        Some(frame) => {
+            let start = match frame.start {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(offset) => position.saturating_sub(offset as usize),
+                FrameBound::CurrentRow => position,
+                FrameBound::Following(offset) => position + offset as usize,
+                FrameBound::UnboundedFollowing => size,
+            };
+            let end = match frame.end {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(offset) => (position + 1).saturating_sub(offset as usize),
+                FrameBound::CurrentRow => position + 1,
+                FrameBound::Following(offset) => position + 1 + offset as usize,
+                FrameBound::UnboundedFollowing => size,
+            };
+            (start.min(size), end.min(size))
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use std::{cmp::Ordering, fs::{self, File}, io::Read};
-use byteorder::{ByteOrder, LittleEndian};
+use std::cmp::Ordering;
 
+// format of the file format file, it's read only by the code commented out below
+#[allow(dead_code)]
 #[derive(Debug)] 
 struct FileFormat {
     table_name: String,
@@ -8,6 +9,7 @@ struct FileFormat {
     columns: Vec<ColumnDefinition>
 }
 
+#[allow(dead_code)]
 #[derive(Debug)] 
 struct ColumnDefinition {
     name: String,
@@ -15,38 +17,75 @@ struct ColumnDefinition {
     data_type: ColumnType,
 }
 
+#[allow(dead_code, clippy::upper_case_acronyms)]
 #[derive(Debug)] 
 enum ColumnType {
     INT,
     STRING,
 }
 
-#[derive(Debug,PartialEq, PartialOrd, Eq)] 
-
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq)]
 pub enum SType {
     INT,
     STRING,
 }
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SData {
     INT(i32),
     STRING(String),
+    BOOL(bool),
+    NULL,
 }
 
+/*
+    total order used by sorts, DISTINCT and set operations:
+        values of the same type are compared by value, otherwise by type, NULL is greater than any other value
+*/
 impl Ord for SData {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
             (SData::INT(a), SData::INT(b)) => a.cmp(b),
             (SData::STRING(a), SData::STRING(b)) => a.cmp(b),
-            _ => panic!("Tried to compere 2 different types")
+            (SData::BOOL(a), SData::BOOL(b)) => a.cmp(b),
+            (a, b) => a.type_rank().cmp(&b.type_rank()),
         }
     }
 }
 
+impl PartialOrd for SData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl SData {
+    fn type_rank(&self) -> u8 {
+        match self {
+            SData::BOOL(_) => 0,
+            SData::INT(_) => 1,
+            SData::STRING(_) => 2,
+            SData::NULL => 3,
+        }
+    }
+
+    // comparison of SQL values, values of different types or NULL can't be compared
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (SData::INT(_), SData::INT(_))
+            | (SData::STRING(_), SData::STRING(_))
+            | (SData::BOOL(_), SData::BOOL(_)) => Some(self.cmp(other)),
+            _ => None,
+        }
+    }
+
     pub fn serialized_size(&self) -> u32 {
         match self {
-            SData::INT(_) => 2,
-            SData::STRING(s) => 2 + s.len() as u32,
+            SData::INT(_) => 4,
+            SData::STRING(s) => 4 + s.len() as u32,
+            SData::BOOL(_) => 1,
+            SData::NULL => 0,
         }
     }
 
@@ -60,10 +99,48 @@ impl SData {
                 buffer.extend(&len.to_le_bytes());
                 buffer.extend(value.as_bytes());
             },
+            SData::BOOL(value) => {
+                buffer.push(*value as u8);
+            },
+            SData::NULL => {},
+        }
+    }
+
+    // reads value written by serialize and advances buffer past it, NULL isn't serialized so it can't be read
+    pub fn deserialize(buffer: &mut &[u8], data_type: &SType) -> Result<SData, String> {
+        match data_type {
+            SType::INT => {
+                let value = i32::from_le_bytes(
+                    buffer
+                        .get(0..4)
+                        .ok_or_else(|| "Slice with incorrect length".to_string())?
+                        .try_into()
+                        .map_err(|_| "Slice with incorrect length".to_string())?,
+                );
+                *buffer = &buffer[4..];
+                Ok(SData::INT(value))
+            }
+            SType::STRING => {
+                let len = u32::from_le_bytes(
+                    buffer
+                        .get(0..4)
+                        .ok_or_else(|| "Slice with incorrect length".to_string())?
+                        .try_into()
+                        .map_err(|_| "Slice with incorrect length".to_string())?,
+                ) as usize;
+                let value = buffer
+                    .get(4..4 + len)
+                    .ok_or_else(|| "Slice with incorrect length".to_string())?;
+                let value = String::from_utf8(value.to_vec())
+                    .map_err(|_| "String value isn't valid UTF-8".to_string())?;
+                *buffer = &buffer[4 + len..];
+                Ok(SData::STRING(value))
+            }
         }
     }
 }
 
+#[allow(dead_code)]
 const FILE_FORMAT_FILE_NAME: &str  = "ff.sl";
 
 /* impl InformationSchema {
@@ -1,4 +1,80 @@
-struct Lock {
+use std::fmt;
 
-    
-}
\ No newline at end of file
+use crate::{bptree::page::TuplePointer, catalog::Oid};
+
+/*
+    rows are locked only in S or X mode, after their table is locked in the matching intention mode,
+    advisory lock is a key of the application, the database doesn't give it any meaning
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockTarget {
+    Table(Oid),
+    Row(Oid, TuplePointer),
+    Advisory(AdvisoryKey),
+}
+
+// key given by one int8 and key given by two int4 are separate keyspaces, (0, 42) isn't the same lock as 42
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdvisoryKey {
+    Single(i64),
+    Pair(i32, i32),
+}
+
+impl fmt::Display for AdvisoryKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdvisoryKey::Single(key) => write!(f, "{}", key),
+            AdvisoryKey::Pair(high, low) => write!(f, "({}, {})", high, low),
+        }
+    }
+}
+
+/*
+    modes of hierarchical locking:
+        IS and IX announce S or X locks on rows of the table
+        S and X lock the whole table or one row
+        SIX is S on the whole table together with IX, it's the result of upgrading between them
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    IntentionShared,
+    IntentionExclusive,
+    Shared,
+    SharedIntentionExclusive,
+    Exclusive,
+}
+
+impl LockMode {
+    // locks of different transactions can be held on one target at once
+    pub fn is_compatible(self, other: LockMode) -> bool {
+        use LockMode::*;
+        match (self, other) {
+            (Exclusive, _) | (_, Exclusive) => false,
+            (IntentionShared, _) | (_, IntentionShared) => true,
+            (IntentionExclusive, IntentionExclusive) | (Shared, Shared) => true,
+            _ => false,
+        }
+    }
+
+    // holder of this mode has all rights of the other mode
+    pub fn covers(self, other: LockMode) -> bool {
+        use LockMode::*;
+        match (self, other) {
+            (Exclusive, _) | (_, IntentionShared) => true,
+            (SharedIntentionExclusive, Exclusive) => false,
+            (SharedIntentionExclusive, _) => true,
+            (mode, other) => mode == other,
+        }
+    }
+
+    // the weakest mode which covers both modes, it's given when a transaction upgrades its lock
+    pub fn combine(self, other: LockMode) -> LockMode {
+        if self.covers(other) {
+            self
+        } else if other.covers(self) {
+            other
+        } else {
+            LockMode::SharedIntentionExclusive
+        }
+    }
+}
@@ -1,5 +1,447 @@
-// for now lock method is implied to be table-level
+pub mod lock;
+pub mod predicate;
 
-struct LockManger {
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
 
-}
\ No newline at end of file
+use crate::{constants::DEADLOCK_TIMEOUT_MS, error::SqlState, storage::transaction::TransactionId};
+use lock::{LockMode, LockTarget};
+
+// granted locks of one target and requests waiting for it in order of arrival
+#[derive(Default)]
+struct LockQueue {
+    granted: Vec<(TransactionId, LockMode)>,
+    waiting: VecDeque<(TransactionId, LockMode)>,
+}
+
+impl LockQueue {
+    fn held(&self, owner: TransactionId) -> Option<LockMode> {
+        self.granted
+            .iter()
+            .find(|(holder, _)| *holder == owner)
+            .map(|(_, mode)| *mode)
+    }
+
+    // locks of the owner itself don't conflict with its request, so it can upgrade them
+    fn is_grantable(&self, owner: TransactionId, mode: LockMode) -> bool {
+        self.granted
+            .iter()
+            .all(|(holder, held)| *holder == owner || held.is_compatible(mode))
+    }
+
+    fn grant(&mut self, owner: TransactionId, mode: LockMode) {
+        match self.granted.iter_mut().find(|(holder, _)| *holder == owner) {
+            Some((_, held)) => *held = held.combine(mode),
+            None => self.granted.push((owner, mode)),
+        }
+    }
+
+    // requests are granted in order of arrival until the first one which conflicts
+    fn grant_waiting(&mut self, waiting: &mut HashMap<TransactionId, LockTarget>) {
+        while let Some(&(next, mode)) = self.waiting.front() {
+            if !self.is_grantable(next, mode) {
+                break;
+            }
+            self.waiting.pop_front();
+            self.grant(next, mode);
+            waiting.remove(&next);
+        }
+    }
+}
+
+#[derive(Default)]
+struct LockTable {
+    queues: HashMap<LockTarget, LockQueue>,
+    // transaction waits for at most one lock, because its statement stops at the first one
+    waiting: HashMap<TransactionId, LockTarget>,
+    // victims of deadlocks with the error they get when they wake up, it has the code of deadlock
+    aborted: HashMap<TransactionId, String>,
+}
+
+impl LockTable {
+    /*
+        edges of the waits-for graph, waiting transaction waits for holders of conflicting locks
+        and for all requests queued before it, because requests are granted strictly in order
+    */
+    fn blockers(&self, owner: TransactionId) -> Vec<TransactionId> {
+        let Some(queue) = self.waiting.get(&owner).map(|target| &self.queues[target]) else {
+            return vec![];
+        };
+        let Some(position) = queue
+            .waiting
+            .iter()
+            .position(|(waiter, _)| *waiter == owner)
+        else {
+            return vec![];
+        };
+        let mode = queue.waiting[position].1;
+        queue
+            .granted
+            .iter()
+            .filter(|(holder, held)| *holder != owner && !held.is_compatible(mode))
+            .map(|(holder, _)| *holder)
+            .chain(
+                queue
+                    .waiting
+                    .iter()
+                    .take(position)
+                    .map(|(waiter, _)| *waiter),
+            )
+            .collect()
+    }
+
+    // depth first search for a path leading back to the first transaction of the path
+    fn find_cycle(
+        &self,
+        path: &mut Vec<TransactionId>,
+        visited: &mut HashSet<TransactionId>,
+    ) -> bool {
+        let last = path[path.len() - 1];
+        for next in self.blockers(last) {
+            if next == path[0] {
+                return true;
+            }
+            if visited.insert(next) {
+                path.push(next);
+                if self.find_cycle(path, visited) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+
+    /*
+        the youngest transaction of the cycle is aborted, it has probably done the least work,
+        its request is removed from the queue, so requests behind it can be granted
+    */
+    fn break_deadlock(&mut self, owner: TransactionId) {
+        let mut cycle = vec![owner];
+        if !self.find_cycle(&mut cycle, &mut HashSet::from([owner])) {
+            return;
+        }
+        let edges: Vec<String> = cycle
+            .iter()
+            .zip(cycle.iter().cycle().skip(1))
+            .map(|(waiter, holder)| {
+                format!(
+                    "transaction {} waits for transaction {}",
+                    waiter.0, holder.0
+                )
+            })
+            .collect();
+        let message = format!("Deadlock detected, {}", edges.join(", "));
+        let victim = cycle.iter().max_by_key(|id| id.0).copied().unwrap_or(owner);
+        self.withdraw(victim);
+        self.aborted
+            .insert(victim, SqlState::DeadlockDetected.error(&message));
+    }
+
+    // request stops waiting without being granted, requests queued behind it may be granted now
+    fn withdraw(&mut self, owner: TransactionId) {
+        if let Some(target) = self.waiting.remove(&owner) {
+            if let Some(queue) = self.queues.get_mut(&target) {
+                queue.waiting.retain(|(waiter, _)| *waiter != owner);
+                queue.grant_waiting(&mut self.waiting);
+            }
+        }
+    }
+}
+
+/*
+    locks of tables and rows held by top-level transactions until they commit or roll back,
+    request which can't be granted is queued and the statement which made it is retried once it's granted,
+    waiting is done without holding the database, so holders of locks can finish their transactions,
+    queue is FIFO, new request doesn't overtake waiting ones, only upgrade of a held lock can,
+    transaction waiting longer than the deadlock timeout searches the waits-for graph for a cycle
+*/
+#[derive(Default)]
+pub struct LockManager {
+    table: Mutex<LockTable>,
+    released: Condvar,
+}
+
+impl LockManager {
+    // returns false when the request has to wait
+    pub fn acquire(
+        &self,
+        owner: TransactionId,
+        target: LockTarget,
+        mode: LockMode,
+    ) -> Result<bool, String> {
+        self.request(owner, target, mode, true)
+    }
+
+    // returns false when the lock isn't available, the request isn't queued
+    pub fn try_acquire(
+        &self,
+        owner: TransactionId,
+        target: LockTarget,
+        mode: LockMode,
+    ) -> Result<bool, String> {
+        self.request(owner, target, mode, false)
+    }
+
+    fn request(
+        &self,
+        owner: TransactionId,
+        target: LockTarget,
+        mode: LockMode,
+        wait: bool,
+    ) -> Result<bool, String> {
+        let mut table = self.lock()?;
+        let LockTable {
+            queues, waiting, ..
+        } = &mut *table;
+        let queue = queues.entry(target).or_default();
+        let held = queue.held(owner);
+        if held.is_some_and(|held| held.covers(mode)) {
+            return Ok(true);
+        }
+        if queue.is_grantable(owner, mode) && (held.is_some() || queue.waiting.is_empty()) {
+            queue.grant(owner, mode);
+            return Ok(true);
+        }
+        if !wait {
+            return Ok(false);
+        }
+        if waiting.insert(owner, target).is_none() {
+            queue.waiting.push_back((owner, mode));
+        }
+        Ok(false)
+    }
+
+    pub fn is_waiting(&self, owner: TransactionId) -> Result<bool, String> {
+        Ok(self.lock()?.waiting.contains_key(&owner))
+    }
+
+    /*
+        blocks until the queued request of the owner is granted, returns false when the deadline passes first,
+        then the request is withdrawn, fails when the owner is a victim of a deadlock
+    */
+    pub fn wait(&self, owner: TransactionId, deadline: Option<Instant>) -> Result<bool, String> {
+        let deadlock_timeout = Duration::from_millis(DEADLOCK_TIMEOUT_MS);
+        let mut deadlock_check = Instant::now() + deadlock_timeout;
+        let mut table = self.lock()?;
+        loop {
+            if let Some(message) = table.aborted.remove(&owner) {
+                return Err(message);
+            }
+            if !table.waiting.contains_key(&owner) {
+                return Ok(true);
+            }
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| deadline <= now) {
+                table.withdraw(owner);
+                self.released.notify_all();
+                return Ok(false);
+            }
+            if deadlock_check <= now {
+                table.break_deadlock(owner);
+                self.released.notify_all();
+                deadlock_check = now + deadlock_timeout;
+                continue;
+            }
+            let wake_up = deadline.map_or(deadlock_check, |deadline| deadline.min(deadlock_check));
+            table = self
+                .released
+                .wait_timeout(table, wake_up - now)
+                .map_err(|e| e.to_string())?
+                .0;
+        }
+    }
+
+    // all locks and the waiting request of the ended transaction are removed, waiting requests are granted in order
+    pub fn release_all(&self, owner: TransactionId) -> Result<(), String> {
+        self.release_except(owner, &[])
+    }
+
+    // lock of the owner is removed before it ends, it's done only with locks which don't protect data
+    pub fn release(&self, owner: TransactionId, target: LockTarget) -> Result<(), String> {
+        let mut table = self.lock()?;
+        let LockTable {
+            queues, waiting, ..
+        } = &mut *table;
+        if let Some(queue) = queues.get_mut(&target) {
+            queue.granted.retain(|(holder, _)| *holder != owner);
+            queue.grant_waiting(waiting);
+            if queue.granted.is_empty() && queue.waiting.is_empty() {
+                queues.remove(&target);
+            }
+        }
+        self.released.notify_all();
+        Ok(())
+    }
+
+    // like release_all, but the kept locks stay granted to the ended owner, until they're handed over
+    pub fn release_except(&self, owner: TransactionId, kept: &[LockTarget]) -> Result<(), String> {
+        let mut table = self.lock()?;
+        let LockTable {
+            queues,
+            waiting,
+            aborted,
+        } = &mut *table;
+        waiting.remove(&owner);
+        aborted.remove(&owner);
+        for (target, queue) in queues.iter_mut() {
+            if !kept.contains(target) {
+                queue.granted.retain(|(holder, _)| *holder != owner);
+            }
+            queue.waiting.retain(|(holder, _)| *holder != owner);
+            queue.grant_waiting(waiting);
+        }
+        queues.retain(|_, queue| !queue.granted.is_empty() || !queue.waiting.is_empty());
+        self.released.notify_all();
+        Ok(())
+    }
+
+    // locks granted to one owner are given to another one, which gets the stronger mode of the two
+    pub fn transfer(&self, from: TransactionId, to: TransactionId) -> Result<(), String> {
+        let mut table = self.lock()?;
+        for queue in table.queues.values_mut() {
+            if let Some(mode) = queue.held(from) {
+                queue.granted.retain(|(holder, _)| *holder != from);
+                queue.grant(to, mode);
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, LockTable>, String> {
+        self.table.lock().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::Oid;
+
+    use super::{lock::AdvisoryKey, *};
+
+    const TABLE: LockTarget = LockTarget::Table(Oid(1));
+
+    #[test]
+    fn when_lock_is_released_should_grant_waiting_requests_in_order() {
+        let locks = LockManager::default();
+        let (first, second, third) = (TransactionId(1), TransactionId(2), TransactionId(3));
+
+        assert_eq!(locks.acquire(first, TABLE, LockMode::Shared), Ok(true));
+        assert_eq!(locks.acquire(second, TABLE, LockMode::Exclusive), Ok(false));
+        // compatible with the granted lock, but it can't overtake the waiting request
+        assert_eq!(
+            locks.acquire(third, TABLE, LockMode::IntentionShared),
+            Ok(false)
+        );
+
+        locks.release_all(first).unwrap();
+        assert_eq!(locks.is_waiting(second), Ok(false));
+        assert_eq!(locks.is_waiting(third), Ok(true));
+        locks.release_all(second).unwrap();
+        assert_eq!(locks.is_waiting(third), Ok(false));
+        assert_eq!(
+            locks.acquire(third, TABLE, LockMode::IntentionShared),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn when_transactions_wait_for_each_other_should_abort_the_youngest() {
+        let locks = LockManager::default();
+        let (first, second) = (TransactionId(1), TransactionId(2));
+        let other = LockTarget::Table(Oid(2));
+
+        assert_eq!(locks.acquire(first, TABLE, LockMode::Exclusive), Ok(true));
+        assert_eq!(locks.acquire(second, other, LockMode::Exclusive), Ok(true));
+        assert_eq!(locks.acquire(first, other, LockMode::Shared), Ok(false));
+        assert_eq!(locks.acquire(second, TABLE, LockMode::Shared), Ok(false));
+
+        assert_eq!(
+            locks.wait(second, None),
+            Err(SqlState::DeadlockDetected.error(
+                "Deadlock detected, transaction 2 waits for transaction 1, \
+                transaction 1 waits for transaction 2"
+            ))
+        );
+        // the victim isn't waiting anymore, but it holds its locks until it rolls back
+        assert_eq!(locks.is_waiting(second), Ok(false));
+        assert_eq!(locks.is_waiting(first), Ok(true));
+        locks.release_all(second).unwrap();
+        assert_eq!(locks.wait(first, None), Ok(true));
+    }
+
+    #[test]
+    fn when_deadline_passes_request_should_be_withdrawn_and_requests_behind_granted() {
+        let locks = LockManager::default();
+        let (first, second, third) = (TransactionId(1), TransactionId(2), TransactionId(3));
+        assert_eq!(locks.acquire(first, TABLE, LockMode::Shared), Ok(true));
+        assert_eq!(locks.acquire(second, TABLE, LockMode::Exclusive), Ok(false));
+        assert_eq!(locks.acquire(third, TABLE, LockMode::Shared), Ok(false));
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert_eq!(locks.wait(second, Some(deadline)), Ok(false));
+
+        assert!(Instant::now() >= deadline);
+        assert_eq!(locks.is_waiting(second), Ok(false));
+        assert_eq!(locks.is_waiting(third), Ok(false));
+        assert_eq!(
+            locks.try_acquire(second, TABLE, LockMode::Exclusive),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn when_transaction_upgrades_lock_should_hold_combined_mode() {
+        let locks = LockManager::default();
+        let (first, second) = (TransactionId(1), TransactionId(2));
+
+        assert_eq!(locks.acquire(first, TABLE, LockMode::Shared), Ok(true));
+        assert_eq!(
+            locks.acquire(second, TABLE, LockMode::IntentionShared),
+            Ok(true)
+        );
+        assert_eq!(
+            locks.acquire(first, TABLE, LockMode::IntentionExclusive),
+            Ok(true)
+        );
+        // SIX conflicts with IX, but not with IS
+        assert_eq!(
+            locks.acquire(second, TABLE, LockMode::IntentionExclusive),
+            Ok(false)
+        );
+        assert_eq!(
+            LockMode::Shared.combine(LockMode::IntentionExclusive),
+            LockMode::SharedIntentionExclusive
+        );
+        assert!(LockMode::SharedIntentionExclusive.covers(LockMode::Shared));
+        assert!(!LockMode::IntentionExclusive.is_compatible(LockMode::Shared));
+    }
+
+    #[test]
+    fn when_kept_locks_are_transferred_new_owner_should_hold_them() {
+        let locks = LockManager::default();
+        let (ended, next, other) = (TransactionId(1), TransactionId(2), TransactionId(3));
+        let advisory = LockTarget::Advisory(AdvisoryKey::Single(7));
+        assert_eq!(locks.acquire(ended, TABLE, LockMode::Exclusive), Ok(true));
+        assert_eq!(
+            locks.acquire(ended, advisory, LockMode::Exclusive),
+            Ok(true)
+        );
+
+        locks.release_except(ended, &[advisory]).unwrap();
+        locks.transfer(ended, next).unwrap();
+
+        assert_eq!(locks.try_acquire(other, TABLE, LockMode::Shared), Ok(true));
+        assert_eq!(
+            locks.acquire(other, advisory, LockMode::Exclusive),
+            Ok(false)
+        );
+        locks.release_all(ended).unwrap();
+        assert_eq!(locks.is_waiting(other), Ok(true));
+        locks.release(next, advisory).unwrap();
+        assert_eq!(locks.is_waiting(other), Ok(false));
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{catalog::Oid, error::SqlState, storage::transaction::TransactionId};
+
+// whole relation is read by scans, so rows inserted later into any page change their result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PredicateTarget {
+    Relation(Oid),
+    Page(Oid, u32),
+}
+
+// serializable transaction with what it read and its read-write dependencies on concurrent transactions
+#[derive(Default)]
+struct Serializable {
+    // next transaction id when it committed, transactions with smaller ids ran concurrently with it
+    committed: Option<TransactionId>,
+    reads: HashSet<PredicateTarget>,
+    // transactions which read what this one wrote, without seeing it
+    conflicts_in: HashSet<TransactionId>,
+    // transactions which wrote what this one read, without it seeing their change
+    conflicts_out: HashSet<TransactionId>,
+}
+
+/*
+    SIREAD locks of serializable snapshot isolation, they don't block anybody, they only record reads,
+    reader and concurrent writer of the same data make read-write dependency reader -> writer,
+    cycle of transactions can't be serialized, every cycle has a transaction with dependency in and out,
+    so such transaction fails when it commits, committed transaction is kept until no transaction
+    concurrent with it runs, because its reads can still make dependencies
+*/
+#[derive(Default)]
+pub struct PredicateLocks {
+    transactions: Mutex<HashMap<TransactionId, Serializable>>,
+}
+
+impl PredicateLocks {
+    pub fn register(&self, id: TransactionId) -> Result<(), String> {
+        self.lock()?.entry(id).or_default();
+        Ok(())
+    }
+
+    pub fn read(&self, reader: TransactionId, target: PredicateTarget) -> Result<(), String> {
+        if let Some(transaction) = self.lock()?.get_mut(&reader) {
+            transaction.reads.insert(target);
+        }
+        Ok(())
+    }
+
+    // reader didn't see change of the writer, dependencies are tracked only among serializable transactions
+    pub fn conflict(&self, reader: TransactionId, writer: TransactionId) -> Result<(), String> {
+        let mut transactions = self.lock()?;
+        if reader == writer
+            || !transactions.contains_key(&reader)
+            || !transactions.contains_key(&writer)
+        {
+            return Ok(());
+        }
+        if let Some(transaction) = transactions.get_mut(&reader) {
+            transaction.conflicts_out.insert(writer);
+        }
+        if let Some(transaction) = transactions.get_mut(&writer) {
+            transaction.conflicts_in.insert(reader);
+        }
+        Ok(())
+    }
+
+    // readers of the written page which ran concurrently with the writer didn't see the change
+    pub fn write(&self, writer: TransactionId, table: Oid, page: u32) -> Result<(), String> {
+        let readers: Vec<TransactionId> = self
+            .lock()?
+            .iter()
+            .filter(|(_, reader)| reader.committed.is_none_or(|committed| writer < committed))
+            .filter(|(_, reader)| {
+                reader.reads.contains(&PredicateTarget::Relation(table))
+                    || reader.reads.contains(&PredicateTarget::Page(table, page))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for reader in readers {
+            self.conflict(reader, writer)?;
+        }
+        Ok(())
+    }
+
+    // transaction with dependencies in and out may be a part of a cycle, it fails instead of committing
+    pub fn check_commit(&self, id: TransactionId) -> Result<(), String> {
+        match self.lock()?.get(&id) {
+            Some(transaction)
+                if !transaction.conflicts_in.is_empty()
+                    && !transaction.conflicts_out.is_empty() =>
+            {
+                Err(SqlState::SerializationFailure.error(
+                    "Could not serialize access due to read/write dependencies among transactions",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /*
+        rolled back transaction is forgotten together with its dependencies,
+        committed transactions are forgotten when the oldest running transaction began after they committed
+    */
+    pub fn end(
+        &self,
+        id: TransactionId,
+        committed: bool,
+        next_id: TransactionId,
+        oldest_active: TransactionId,
+    ) -> Result<(), String> {
+        let mut transactions = self.lock()?;
+        if committed {
+            if let Some(transaction) = transactions.get_mut(&id) {
+                transaction.committed = Some(next_id);
+            }
+        } else if transactions.remove(&id).is_some() {
+            for transaction in transactions.values_mut() {
+                transaction.conflicts_in.remove(&id);
+                transaction.conflicts_out.remove(&id);
+            }
+        }
+        transactions.retain(|_, transaction| {
+            transaction
+                .committed
+                .is_none_or(|committed| oldest_active < committed)
+        });
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<TransactionId, Serializable>>, String> {
+        self.transactions.lock().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_transactions_read_what_the_other_writes_first_to_commit_should_fail() {
+        let locks = PredicateLocks::default();
+        let (first, second) = (TransactionId(1), TransactionId(2));
+        let (table, next) = (Oid(1), TransactionId(3));
+        locks.register(first).unwrap();
+        locks.register(second).unwrap();
+
+        locks.read(first, PredicateTarget::Relation(table)).unwrap();
+        locks.read(second, PredicateTarget::Page(table, 0)).unwrap();
+        locks.write(first, table, 0).unwrap();
+        assert_eq!(locks.check_commit(first), Ok(()));
+        locks.write(second, table, 1).unwrap();
+
+        assert_eq!(
+            locks.check_commit(first).map_err(|e| SqlState::of(&e)),
+            Err(Some(SqlState::SerializationFailure))
+        );
+        locks.end(first, false, next, second).unwrap();
+        assert_eq!(locks.check_commit(second), Ok(()));
+    }
+}
@@ -3,24 +3,46 @@ mod config;
 mod parser;
 mod string_helpers;
 mod constants;
+mod error;
+// pages and the buffer pool aren't used yet, tables are kept in heap files
+#[allow(dead_code)]
 mod page;
 mod information_schema;
 mod bptree;
 mod catalog;
 mod executor;
+#[allow(dead_code)]
 mod buff;
 pub mod new_page;
+mod storage;
+mod lock_manager;
+#[cfg(test)]
+mod test_support;
 use crate::config::Config;
-use parser::ast::{ColumnDefinition, SqlStatement};
+use executor::{end_session, execute_in_cluster, QueryResult};
+use information_schema::SData;
+use parser::ast::ColumnDefinition;
 use std::io::prelude::*;
+use std::io::ErrorKind;
 use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
+use constants::{AUTOVACUUM_NAPTIME_MS, DEFAULT_DATABASE};
+use storage::{cluster::Cluster, Database, Session};
 
 fn main() -> Result<(), String> {
     let config = Config::build().unwrap_or_else(|e| {
         eprintln!("{e}");
         std::process::exit(1);
     });
+    let cluster = Cluster::open(&config.data_directory).unwrap_or_else(|e| {
+        eprintln!("Couldnt open databases, {e}");
+        std::process::exit(1);
+    });
+    let cluster = Arc::new(cluster);
+    let autovacuum_cluster = Arc::clone(&cluster);
+    thread::spawn(move || autovacuum(autovacuum_cluster));
     let listeniing_address = format!("127.0.0.1:{}", config.port);
     let listener = TcpListener::bind(listeniing_address).unwrap_or_else(|e| {
         eprintln!("Couldt start listening for tcp connection, {e}");
@@ -29,7 +51,8 @@ fn main() -> Result<(), String> {
     for stream in listener.incoming() {
         match stream {
             Ok(stream) => {
-                thread::spawn(move || handle_client(stream));
+                let cluster = Arc::clone(&cluster);
+                thread::spawn(move || handle_client(stream, cluster));
             }
             Err(e) => eprintln!("Failed to accept connection: {}", e),
         }
@@ -37,10 +60,50 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn handle_client(mut stream: TcpStream) {
+// background worker, which removes dead versions from tables changed enough since they were vacuumed
+fn autovacuum(cluster: Arc<Cluster>) {
+    loop {
+        thread::sleep(Duration::from_millis(AUTOVACUUM_NAPTIME_MS));
+        if let Err(e) = cluster.autovacuum() {
+            eprintln!("Autovacuum failed: {}", e);
+        }
+    }
+}
+
+// connection starts in the default database, its unfinished transaction is rolled back and its advisory locks released when it's closed
+fn handle_client(mut stream: TcpStream, cluster: Arc<Cluster>) {
+    let mut session = Session::default();
+    let mut database: Arc<Mutex<Database>> = match cluster.database(DEFAULT_DATABASE) {
+        Ok(database) => database,
+        Err(e) => {
+            eprintln!("Couldnt open default database, {e}");
+            return;
+        }
+    };
+    serve_statements(&mut stream, &cluster, &mut database, &mut session);
+    if let Err(e) = end_session(&database, &mut session) {
+        eprintln!("Failed to end session of closed connection: {}", e);
+    }
+}
+
+/*
+    statements are executed one at a time, every statement gets one response,
+    connection idle inside of transaction block longer than idle_in_transaction_session_timeout is closed
+*/
+fn serve_statements(
+    stream: &mut TcpStream,
+    cluster: &Cluster,
+    database: &mut Arc<Mutex<Database>>,
+    session: &mut Session,
+) {
     let mut buffer = [0; 512];
     loop {
+        if let Err(e) = stream.set_read_timeout(session.idle_timeout()) {
+            eprintln!("Failed to set timeout of stream: {}", e);
+            return;
+        }
         match stream.read(&mut buffer) {
+            Ok(0) => return,
             Ok(bytes_read) => {
                 let received_data = &buffer[..bytes_read];
                 let command = match String::from_utf8(received_data.to_vec()) {
@@ -49,53 +112,60 @@ fn handle_client(mut stream: TcpStream) {
                         continue;
                     }
                 };
-                let sql_statemant = match parser::parse(command) {
-                    Ok(statemant) => statemant,
-                    Err(_) => {
-                        continue;
-                    }
+                let result = parser::parse(command).and_then(|statement| {
+                    execute_in_cluster(&statement, cluster, database, session)
+                });
+                let response = match result {
+                    Ok(result) => format_result(&result),
+                    Err(e) => format!("ERROR: {}\n", e),
                 };
-                match sql_statemant {
-                    SqlStatement::CreateTable {
-                        table_name,
-                        primary_key,
-                        columns,
-                    } => {}
-                    SqlStatement::Select { 
-                        columns, 
-                        table 
-                    } => {
-                        // pogledaj koji od columnsa ima index
-                        // nadi OID 
-                    }
-                    _other => {
-                        continue;
-                    }
+                if let Err(e) = stream.write_all(response.as_bytes()) {
+                    eprintln!("Failed to write to stream: {}", e);
+                    return;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                let response = "FATAL: Terminating connection due to idle-in-transaction timeout\n";
+                if let Err(e) = stream.write_all(response.as_bytes()) {
+                    eprintln!("Failed to write to stream: {}", e);
                 }
+                return;
+            }
+            Err(e) => {
+                eprintln!("Failed to read from stream: {}", e);
+                return;
             }
-            Err(e) => eprintln!("Failed to read from stream: {}", e),
         }
     }
 }
 
-fn create_table(
-    table_name: String,
-    primary_key: String,
-    columns: Vec<ColumnDefinition>,
-) -> Result<(), String> {
-    if table_name.is_empty() {
-        return Err("Table name cannot be empty".to_string());
-    }
-    if primary_key.is_empty() {
-        return Err("Primary key cannot be empty".into());
-    }
-    if columns.is_empty() {
-        return Err("At least one column must be provided".into());
+fn format_result(result: &QueryResult) -> String {
+    match result {
+        QueryResult::Command(tag) => format!("{}\n", tag),
+        QueryResult::Rows(relation) => {
+            let mut response = relation.columns.join(" | ");
+            response.push('\n');
+            for row in &relation.rows {
+                let values: Vec<String> = row.iter().map(format_value).collect();
+                response.push_str(&values.join(" | "));
+                response.push('\n');
+            }
+            response.push_str(&format!("({} rows)\n", relation.rows.len()));
+            response
+        }
     }
+}
 
-    Err("STUB".to_string())
+fn format_value(value: &SData) -> String {
+    match value {
+        SData::INT(integer) => integer.to_string(),
+        SData::STRING(s) => s.clone(),
+        SData::BOOL(value) => if *value { "t" } else { "f" }.to_string(),
+        SData::NULL => "NULL".to_string(),
+    }
 }
 
+#[allow(dead_code)]
 struct FileMetadata {
     table_name: String,
     primary_key: String,
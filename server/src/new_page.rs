@@ -32,7 +32,7 @@ impl NewPage {
     }
 
     pub fn get_header_size_in_bytes(&self) -> u8 {
-        return 1;
+        1
     }
 
     pub fn get_first_tuple(&self) -> Result<&[u8], String> {
@@ -91,20 +91,20 @@ impl NewPage {
     }
 
     fn get_number_of_items(&self) -> u16 {
-        return u16::from_le_bytes(self.data[0..1].try_into().unwrap());
+        u16::from_le_bytes(self.data[0..1].try_into().unwrap())
     }
 
     fn get_page_heap_top(&self) -> u16 {
-        return u16::from_le_bytes(self.data[4..5].try_into().unwrap());
+        u16::from_le_bytes(self.data[4..5].try_into().unwrap())
     }
 
     fn get_line_pointer(&self, index: u16) -> u16 {
         let line_pointer_offset = PAGE_SIZE_IN_BYTES - 2 - 2 * (index as u32);
-        return u16::from_le_bytes(
+        u16::from_le_bytes(
             self.data[line_pointer_offset as usize..(line_pointer_offset + 2) as usize]
                 .try_into()
                 .unwrap(),
-        );
+        )
     }
 
     pub fn get_new_insert_pos(&self, bytes_required: u16) -> Option<u16> {
@@ -112,9 +112,9 @@ impl NewPage {
         let page_heap_top = u16::from_le_bytes(self.data[6..7].try_into().unwrap());
         let free_bytes = page_heap_top - data_start_offset - 2;
         if bytes_required > free_bytes {
-            return None;
+            None
         } else {
-            return Some(data_start_offset);
+            Some(data_start_offset)
         }
     } 
 }
@@ -9,9 +9,9 @@
         offsets_of_specific_item: vec<u16> [data_end_offset..data_end_offset + number_of_items * size(u16)]
 */
 
-use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
+use byteorder::{ByteOrder, LittleEndian};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 
 const PAGE_SIZE: u16 = 8 * 1024;
 
@@ -45,12 +45,12 @@ impl Page {
         Ok(first_entry)
     }
 
-    fn insert_tuple(&mut self, serialized_data: &Vec<u8>, offset: u16) {
+    fn insert_tuple(&mut self, serialized_data: &[u8], offset: u16) {
         let end = offset as usize + serialized_data.len();
         if end > self.data.len() {
             panic!("Data does not fit into the page at the given offset!");
         }
-        self.data[offset as usize..end].copy_from_slice(&serialized_data[..]);
+        self.data[offset as usize..end].copy_from_slice(serialized_data);
         self.set_number_of_items(self.get_number_of_items() + 1);
         self.set_page_heap_top(end.try_into().unwrap());
         self.insert_new_line_pointer(offset);
@@ -62,14 +62,14 @@ impl Page {
         let page_heap_top = u16::from_le_bytes(self.data[6..7].try_into().unwrap());
         let free_bytes = page_heap_top - data_start_offset - 2;
         if bytes_required > free_bytes {
-            return None;
+            None
         } else {
-            return Some(data_start_offset);
+            Some(data_start_offset)
         }
     }
 
     fn get_page_heap_top(&self) -> u16 {
-        return u16::from_le_bytes(self.data[4..5].try_into().unwrap());
+        u16::from_le_bytes(self.data[4..5].try_into().unwrap())
     }
 
     fn set_page_heap_top(&mut self, new_heap_top: u16) {
@@ -78,7 +78,7 @@ impl Page {
     }
 
     fn get_page_free(&self) -> u16 {
-        return u16::from_le_bytes(self.data[6..7].try_into().unwrap());
+        u16::from_le_bytes(self.data[6..7].try_into().unwrap())
     }
 
     fn set_page_free(&mut self, new_page_free: u16) {
@@ -95,7 +95,7 @@ impl Page {
 
 
     fn get_number_of_items(&self) -> u16 {
-        return u16::from_le_bytes(self.data[0..1].try_into().unwrap());
+        u16::from_le_bytes(self.data[0..1].try_into().unwrap())
     }
 
     fn set_number_of_items(&mut self, number_od_items: u16) {
@@ -133,7 +133,7 @@ impl Page {
 
     fn get_line_pointer(&self, index: u16) -> u16 {
         let line_pointer_offset =  PAGE_SIZE - 2 - 2 * index;
-        return u16::from_le_bytes(self.data[line_pointer_offset as usize..(line_pointer_offset + 2) as usize].try_into().unwrap());
+        u16::from_le_bytes(self.data[line_pointer_offset as usize..(line_pointer_offset + 2) as usize].try_into().unwrap())
     }
 }
 
@@ -142,11 +142,12 @@ fn load_page(file_name: String, page_number: u32) -> Result<Page, String> {
         .read(true)
         .write(true)
         .create(true)
+        .truncate(false)
         .open(&file_name)
         .map_err(|e| e.to_string())?;
     file.seek(SeekFrom::Start((PAGE_SIZE as u32 * page_number).into()))
         .unwrap();
-    let mut page_content = vec![0; PAGE_SIZE.try_into().unwrap()];
+    let mut page_content = vec![0; PAGE_SIZE.into()];
     file.read(&mut page_content).map_err(|e| e.to_string())?;
     let number_of_items_as_bytes = &page_content[0..2];
     let page_heap_top_offset_as_bytes = &page_content[4..6];
@@ -154,7 +155,7 @@ fn load_page(file_name: String, page_number: u32) -> Result<Page, String> {
     let page_free_as_bytes = &page_content[6..8];
     let page_free = LittleEndian::read_u16(page_free_as_bytes) as usize;
     let items_offsets_as_bytes = &page_content[page_free..];
-    let number_of_items = LittleEndian::read_u16(&number_of_items_as_bytes) as usize;
+    let number_of_items = LittleEndian::read_u16(number_of_items_as_bytes) as usize;
     let reversed_items_offsets = {
         let mut offsets = Vec::with_capacity(number_of_items);
         for item_offset in items_offsets_as_bytes.chunks(2) {
@@ -172,7 +173,7 @@ fn load_page(file_name: String, page_number: u32) -> Result<Page, String> {
                     ..reversed_items_offsets[reversed_items_offsets.len() - 2 - i]]
                     .to_vec()
             } else {
-                page_content[reversed_items_offsets.first().unwrap().clone()..page_heap_top]
+                page_content[*reversed_items_offsets.first().unwrap()..page_heap_top]
                     .to_vec()
             };
             rows.push(row_data);
@@ -275,7 +276,7 @@ fn load_page(file_name: String, page_number: u32) -> Result<Page, String> {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::OpenOptions, os::unix::fs::FileExt};
+    use std::{io::Write, os::unix::fs::FileExt};
 
     use super::*;
     use byteorder::{LittleEndian, WriteBytesExt};
@@ -294,7 +295,7 @@ mod tests {
         let item1: Vec<u8> = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
         let item2: Vec<u8> = vec![10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
         file.seek(SeekFrom::Start(
-            (PAGE_SIZE * page_number).try_into().unwrap(),
+            (PAGE_SIZE * page_number).into(),
         ))
         .unwrap();
         file.write_all(&number_of_items.to_le_bytes()).unwrap();
@@ -317,7 +318,7 @@ mod tests {
         )
         .unwrap();
 
-        let result =
+        let _result =
             load_page(file_path.to_string_lossy().to_string(), page_number.into()).unwrap();
 
         /*         assert_eq!(result.rows_data.len(), 2);
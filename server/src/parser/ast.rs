@@ -1,41 +1,693 @@
+use std::fmt;
+
 #[derive(Debug, PartialEq)]
 pub enum SqlStatement {
     CreateTable {
         table_name: String,
-        primary_key: String,
         columns: Vec<ColumnDefinition>,
+        constraints: Vec<TableConstraint>,
+    },
+    CreateSequence {
+        name: String,
+        options: SequenceOptions,
+    },
+    // view stores its query, materialized view also stores result of the query
+    CreateView {
+        name: String,
+        columns: Vec<String>,
+        query: Query,
+        materialized: bool,
+    },
+    RefreshMaterializedView {
+        name: String,
+    },
+    // without table name all tables of the database are vacuumed
+    Vacuum {
+        table_name: Option<String>,
+    },
+    CreateIndex {
+        name: String,
+        table_name: String,
+        columns: Vec<String>,
+        unique: bool,
     },
     InsertInto {
         table_name: String,
         column_names: Vec<String>,
-        values: Vec<Value>,
+        values: Vec<Vec<Expression>>,
+        on_conflict: Option<OnConflict>,
+        returning: Option<Columns>,
+    },
+    Update {
+        table_name: String,
+        assignments: Vec<Assignment>,
+        selection: Option<Expression>,
+        returning: Option<Columns>,
+    },
+    Delete {
+        table_name: String,
+        selection: Option<Expression>,
+        returning: Option<Columns>,
+    },
+    Query(Query),
+    // rows of the source are joined with the target, first WHEN clause which applies decides the change
+    Merge {
+        table_name: String,
+        alias: Option<String>,
+        source: String,
+        source_alias: Option<String>,
+        condition: Expression,
+        clauses: Vec<MergeClause>,
+    },
+    CreateSchema {
+        name: String,
+    },
+    // RESTRICT refuses to drop schema which isn't empty, CASCADE drops its relations too
+    DropSchema {
+        name: String,
+        if_exists: bool,
+        cascade: bool,
+    },
+    // setting of the session, values are kept as text
+    Set {
+        name: String,
+        values: Vec<String>,
+    },
+    Show {
+        name: String,
+    },
+    // statements about databases are executed by the cluster, not by one database
+    CreateDatabase {
+        name: String,
+    },
+    DropDatabase {
+        name: String,
+        if_exists: bool,
+    },
+    Use {
+        name: String,
+    },
+    // transaction block, statements outside of it run in their own transaction
+    Begin,
+    Commit,
+    Rollback,
+    Savepoint {
+        name: String,
+    },
+    RollbackToSavepoint {
+        name: String,
+    },
+    ReleaseSavepoint {
+        name: String,
+    },
+    // two-phase commit, gid identifies the prepared transaction
+    PrepareTransaction {
+        gid: String,
+    },
+    CommitPrepared {
+        gid: String,
+    },
+    RollbackPrepared {
+        gid: String,
+    },
+    // has to be the first statement of the transaction block
+    SetTransaction {
+        isolation: IsolationLevel,
+    },
+}
+
+// READ COMMITTED takes snapshot for every statement, others once for the whole transaction
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IsolationLevel {
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+// columns of arbiter unique index, they are empty when any unique index can be the arbiter
+#[derive(Debug, PartialEq)]
+pub struct OnConflict {
+    pub columns: Vec<String>,
+    pub action: ConflictAction,
+}
+
+// DO UPDATE can reference the row which wasn't inserted as excluded
+#[derive(Debug, PartialEq)]
+pub enum ConflictAction {
+    Nothing,
+    Update {
+        assignments: Vec<Assignment>,
+        selection: Option<Expression>,
     },
-    Select {
-        columns: Columns,
-        table: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MergeClause {
+    pub matched: bool,
+    pub condition: Option<Expression>,
+    pub action: MergeAction,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MergeAction {
+    Update(Vec<Assignment>),
+    Delete,
+    Insert {
+        columns: Vec<String>,
+        values: Vec<Expression>,
+    },
+    Nothing,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    pub with: Option<With>,
+    pub body: QueryBody,
+    // only SELECT statement can lock its rows, queries nested in it can't
+    pub locking: Option<Locking>,
+}
+
+/* FOR UPDATE | FOR SHARE [NOWAIT | SKIP LOCKED], rows returned by the query are locked until the transaction ends */
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Locking {
+    pub strength: LockStrength,
+    pub wait: LockWait,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LockStrength {
+    Update,
+    Share,
+}
+
+// NOWAIT fails on a row locked by another transaction, SKIP LOCKED leaves such row out
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LockWait {
+    Wait,
+    NoWait,
+    SkipLocked,
+}
+
+/* select, or set operation combining results of two query bodies */
+#[derive(Debug, PartialEq)]
+pub enum QueryBody {
+    Select(Box<Select>),
+    SetOperation {
+        operator: SetOperator,
+        all: bool,
+        left: Box<QueryBody>,
+        right: Box<QueryBody>,
     },
 }
 
+#[derive(Debug, PartialEq)]
+pub enum SetOperator {
+    Union,
+    Intersect,
+    Except,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Select {
+    pub distinct: bool,
+    pub columns: Columns,
+    // None for SELECT without FROM
+    pub table: Option<String>,
+    pub alias: Option<String>,
+    pub joins: Vec<Join>,
+    pub selection: Option<Expression>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ColumnDefinition {
     pub name: String,
     pub data_type: DataType,
+    pub default: Option<Expression>,
+    pub identity: Option<ColumnIdentity>,
+}
+
+/* SERIAL is INT with DEFAULT nextval of its own sequence, GENERATED ... AS IDENTITY can have sequence options */
+#[derive(Debug, PartialEq)]
+pub enum ColumnIdentity {
+    Serial,
+    Always(SequenceOptions),
+    ByDefault(SequenceOptions),
+}
+
+/* options which aren't given get their default value from direction of the increment */
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct SequenceOptions {
+    pub increment: Option<i32>,
+    pub min_value: Option<i32>,
+    pub max_value: Option<i32>,
+    pub start: Option<i32>,
+    pub cycle: Option<bool>,
+}
+
+/* column constraints are turned into table constraints on that column */
+#[derive(Debug, PartialEq)]
+pub struct TableConstraint {
+    pub name: Option<String>,
+    pub kind: TableConstraintKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TableConstraintKind {
+    PrimaryKey(Vec<String>),
+    Unique(Vec<String>),
+    Check(Expression),
+    ForeignKey(ForeignKey),
 }
 
+/* without referenced columns primary key of referenced table is referenced */
 #[derive(Debug, PartialEq)]
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+// NO ACTION and RESTRICT are the same, since constraints can't be deferred
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReferentialAction {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+/* SET column = value */
+#[derive(Debug, PartialEq)]
+pub struct Assignment {
+    pub column: String,
+    pub value: Expression,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum DataType {
     VarChar256,
     Int32,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Value {
     VarChar256(String),
     Int32(i32),
+    Null,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Columns {
     All,
-    Specific(Vec<String>),
+    Specific(Vec<SelectItem>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SelectItem {
+    pub expression: Expression,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct With {
+    pub recursive: bool,
+    pub ctes: Vec<Cte>,
+}
+
+/* WITH name (columns) AS (query), recursive CTE query is anchor UNION [ALL] recursive_term */
+#[derive(Debug, PartialEq)]
+pub struct Cte {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: Box<Query>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Join {
+    pub table: String,
+    pub alias: Option<String>,
+    pub on: Expression,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    Column(String),
+    Value(Value),
+    BinaryOperation {
+        left: Box<Expression>,
+        operator: BinaryOperator,
+        right: Box<Expression>,
+    },
+    // name is lowercase, count(*) has no arguments
+    Function {
+        name: String,
+        distinct: bool,
+        arguments: Vec<Expression>,
+        over: Option<WindowSpecification>,
+    },
+    // CASE [operand] WHEN condition THEN result ... [ELSE result] END, without operand conditions are predicates
+    Case {
+        operand: Option<Box<Expression>>,
+        conditions: Vec<(Expression, Expression)>,
+        else_result: Option<Box<Expression>>,
+    },
+    Cast {
+        expression: Box<Expression>,
+        data_type: DataType,
+    },
+    // expression [I]LIKE pattern [ESCAPE character], ILIKE ignores case
+    Like {
+        expression: Box<Expression>,
+        pattern: Box<Expression>,
+        escape: Option<Box<Expression>>,
+        case_insensitive: bool,
+    },
+}
+
+/* OVER (PARTITION BY expressions ORDER BY expressions ROWS BETWEEN start AND end) */
+#[derive(Debug, PartialEq, Clone)]
+pub struct WindowSpecification {
+    pub partition_by: Vec<Expression>,
+    pub order_by: Vec<OrderByExpression>,
+    pub frame: Option<WindowFrame>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct OrderByExpression {
+    pub expression: Expression,
+    pub ascending: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct WindowFrame {
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(u32),
+    CurrentRow,
+    Following(u32),
+    UnboundedFollowing,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BinaryOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    RegexMatch,
+}
+
+/* writes expression as text which parses back to the same expression, used to persist CHECK and DEFAULT */
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Column(name) => write!(f, "{}", name),
+            Expression::Value(value) => write!(f, "{}", value),
+            Expression::BinaryOperation {
+                left,
+                operator,
+                right,
+            } => write!(f, "({} {} {})", left, operator, right),
+            Expression::Function {
+                name,
+                distinct,
+                arguments,
+                over,
+            } => {
+                write!(f, "{}(", name)?;
+                if *distinct {
+                    write!(f, "DISTINCT ")?;
+                }
+                if arguments.is_empty() && name == "count" {
+                    write!(f, "*")?;
+                }
+                write_list(f, arguments)?;
+                write!(f, ")")?;
+                match over {
+                    Some(window) => write!(f, " OVER ({})", window),
+                    None => Ok(()),
+                }
+            }
+            Expression::Case {
+                operand,
+                conditions,
+                else_result,
+            } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (condition, result) in conditions {
+                    write!(f, " WHEN {} THEN {}", condition, result)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " ELSE {}", else_result)?;
+                }
+                write!(f, " END")
+            }
+            Expression::Cast {
+                expression,
+                data_type,
+            } => write!(f, "CAST({} AS {})", expression, data_type),
+            Expression::Like {
+                expression,
+                pattern,
+                escape,
+                case_insensitive,
+            } => {
+                let operator = if *case_insensitive { "ILIKE" } else { "LIKE" };
+                write!(f, "({} {} {}", expression, operator, pattern)?;
+                if let Some(escape) = escape {
+                    write!(f, " ESCAPE {}", escape)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter, items: &[T]) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::VarChar256(value) => write!(f, "\"{}\"", value),
+            Value::Int32(value) => write!(f, "{}", value),
+            Value::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataType::VarChar256 => write!(f, "VARCHAR"),
+            DataType::Int32 => write!(f, "INT"),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = match self {
+            BinaryOperator::Equal => "=",
+            BinaryOperator::NotEqual => "<>",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessOrEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterOrEqual => ">=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Plus => "+",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::RegexMatch => "~",
+        };
+        write!(f, "{}", operator)
+    }
+}
+
+impl fmt::Display for WindowSpecification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut clauses = vec![];
+        if !self.partition_by.is_empty() {
+            clauses.push(format!("PARTITION BY {}", DisplayList(&self.partition_by)));
+        }
+        if !self.order_by.is_empty() {
+            clauses.push(format!("ORDER BY {}", DisplayList(&self.order_by)));
+        }
+        if let Some(frame) = &self.frame {
+            clauses.push(format!("ROWS BETWEEN {} AND {}", frame.start, frame.end));
+        }
+        write!(f, "{}", clauses.join(" "))
+    }
+}
+
+impl fmt::Display for OrderByExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = if self.ascending { "ASC" } else { "DESC" };
+        write!(f, "{} {}", self.expression, direction)
+    }
+}
+
+impl fmt::Display for FrameBound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameBound::UnboundedPreceding => write!(f, "UNBOUNDED PRECEDING"),
+            FrameBound::Preceding(offset) => write!(f, "{} PRECEDING", offset),
+            FrameBound::CurrentRow => write!(f, "CURRENT ROW"),
+            FrameBound::Following(offset) => write!(f, "{} FOLLOWING", offset),
+            FrameBound::UnboundedFollowing => write!(f, "UNBOUNDED FOLLOWING"),
+        }
+    }
+}
+
+/* writes query as text which parses back to the same query, used to persist view definitions */
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(with) = &self.with {
+            write!(f, "{} ", with)?;
+        }
+        write!(f, "{}", self.body)?;
+        if let Some(locking) = &self.locking {
+            write!(f, " {}", locking)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Locking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.strength)?;
+        match self.wait {
+            LockWait::Wait => Ok(()),
+            LockWait::NoWait => write!(f, " NOWAIT"),
+            LockWait::SkipLocked => write!(f, " SKIP LOCKED"),
+        }
+    }
+}
+
+impl fmt::Display for LockStrength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockStrength::Update => write!(f, "FOR UPDATE"),
+            LockStrength::Share => write!(f, "FOR SHARE"),
+        }
+    }
+}
+
+impl fmt::Display for With {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WITH ")?;
+        if self.recursive {
+            write!(f, "RECURSIVE ")?;
+        }
+        write_list(f, &self.ctes)
+    }
+}
+
+impl fmt::Display for Cte {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.columns.is_empty() {
+            write!(f, " ({})", self.columns.join(", "))?;
+        }
+        write!(f, " AS ({})", self.query)
+    }
+}
+
+// operands of set operation are parenthesized, so precedence of the operators doesn't matter
+impl fmt::Display for QueryBody {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryBody::Select(select) => write!(f, "{}", select),
+            QueryBody::SetOperation {
+                operator,
+                all,
+                left,
+                right,
+            } => {
+                let operator = match operator {
+                    SetOperator::Union => "UNION",
+                    SetOperator::Intersect => "INTERSECT",
+                    SetOperator::Except => "EXCEPT",
+                };
+                let quantifier = if *all { " ALL" } else { "" };
+                write!(f, "({}) {}{} ({})", left, operator, quantifier, right)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Select {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SELECT ")?;
+        if self.distinct {
+            write!(f, "DISTINCT ")?;
+        }
+        match &self.columns {
+            Columns::All => write!(f, "*")?,
+            Columns::Specific(items) => write_list(f, items)?,
+        }
+        if let Some(table) = &self.table {
+            write!(f, " FROM {}", table)?;
+        }
+        if let Some(alias) = &self.alias {
+            write!(f, " AS {}", alias)?;
+        }
+        for join in &self.joins {
+            write!(f, " JOIN {}", join.table)?;
+            if let Some(alias) = &join.alias {
+                write!(f, " AS {}", alias)?;
+            }
+            write!(f, " ON {}", join.on)?;
+        }
+        match &self.selection {
+            Some(selection) => write!(f, " WHERE {}", selection),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for SelectItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expression)?;
+        match &self.alias {
+            Some(alias) => write!(f, " AS {}", alias),
+            None => Ok(()),
+        }
+    }
+}
+
+struct DisplayList<'a, T>(&'a [T]);
+
+impl<T: fmt::Display> fmt::Display for DisplayList<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, self.0)
+    }
 }
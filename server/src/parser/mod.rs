@@ -2,8 +2,14 @@ pub mod ast;
 pub mod token;
 mod tokenizer;
 
-use ast::{ColumnDefinition, Columns, SqlStatement};
-use token::{DataType, Keyword, Punctuation, Token, Value};
+use ast::{
+    Assignment, BinaryOperator, ColumnDefinition, ColumnIdentity, Columns, ConflictAction, Cte,
+    Expression, ForeignKey, FrameBound, IsolationLevel, Join, LockStrength, LockWait, Locking,
+    MergeAction, MergeClause, OnConflict, OrderByExpression, Query, QueryBody, ReferentialAction,
+    Select, SelectItem, SequenceOptions, SetOperator, SqlStatement, TableConstraint,
+    TableConstraintKind, WindowFrame, WindowSpecification, With,
+};
+use token::{DataType, Keyword, Operator, Punctuation, Token, Value};
 use tokenizer::Tokenizer;
 
 pub fn parse(input: String) -> Result<SqlStatement, String> {
@@ -11,89 +17,788 @@ pub fn parse(input: String) -> Result<SqlStatement, String> {
     match tokenizer.peek()? {
         Some(Token::Keyword(Keyword::Create)) => parse_create_command(tokenizer),
         Some(Token::Keyword(Keyword::Insert)) => parse_insert_command(tokenizer),
+        Some(Token::Keyword(Keyword::Update)) => parse_update_command(tokenizer),
+        Some(Token::Keyword(Keyword::Delete)) => parse_delete_command(tokenizer),
         Some(Token::Keyword(Keyword::Select)) => parse_select_command(tokenizer),
+        Some(Token::Keyword(Keyword::With)) => parse_select_command(tokenizer),
+        Some(Token::Keyword(Keyword::Refresh)) => parse_refresh_command(tokenizer),
+        Some(Token::Keyword(Keyword::Vacuum)) => parse_vacuum_command(tokenizer),
+        Some(Token::Keyword(Keyword::Drop)) => parse_drop_command(tokenizer),
+        Some(Token::Keyword(Keyword::Set)) => parse_set_command(tokenizer),
+        Some(Token::Keyword(Keyword::Show)) => parse_show_command(tokenizer),
+        Some(Token::Keyword(Keyword::Use)) => parse_use_command(tokenizer),
+        Some(Token::Keyword(Keyword::Merge)) => parse_merge_command(tokenizer),
+        Some(Token::Keyword(
+            Keyword::Begin
+            | Keyword::Start
+            | Keyword::Commit
+            | Keyword::End
+            | Keyword::Rollback
+            | Keyword::Abort
+            | Keyword::Savepoint
+            | Keyword::Release
+            | Keyword::Prepare,
+        )) => parse_transaction_command(tokenizer),
         Some(_) => Err("First token error, no such command".to_string()),
         None => Err("Error reading first token".to_string()),
     }
 }
 
+// parses expression stored as text in the catalog, like CHECK or DEFAULT
+pub fn parse_expression_text(input: String) -> Result<Expression, String> {
+    let mut tokenizer = Tokenizer::new(input);
+    let expression = parse_expression(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        None => Ok(expression),
+        Some(token) => Err(format!("Unexpected token {:?} after expression", token)),
+    }
+}
+
+// parses query stored as text in the catalog, like definition of view
+pub fn parse_query_text(input: String) -> Result<Query, String> {
+    let mut tokenizer = Tokenizer::new(input);
+    let query = parse_query(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        None => Ok(query),
+        Some(token) => Err(format!("Unexpected token {:?} after query", token)),
+    }
+}
+
+/*
+    CREATE TABLE name (element ("," element)*);
+        element:           column | [CONSTRAINT name] table_constraint
+        column:            name (type | SERIAL) [DEFAULT additive] [identity] ([CONSTRAINT name] column_constraint)*
+        identity:          GENERATED (ALWAYS | BY DEFAULT) AS IDENTITY ["(" sequence_options ")"]
+        column_constraint: PRIMARY KEY | UNIQUE | CHECK "(" or ")" | REFERENCES references
+        table_constraint:  PRIMARY KEY "(" names ")" | UNIQUE "(" names ")" | CHECK "(" or ")"
+                           | FOREIGN KEY "(" names ")" REFERENCES references
+*/
 fn parse_create_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
     if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Create) {
         return Err("No keyword \"CREATE\"".to_string());
     }
-    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Table) {
-        return Err("No keyword \"TABLE\" after CREATE".to_string());
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Table)) => (),
+        Some(Token::Keyword(Keyword::Index)) => return parse_create_index(tokenizer, false),
+        Some(Token::Keyword(Keyword::Sequence)) => return parse_create_sequence(tokenizer),
+        Some(Token::Keyword(Keyword::Schema)) => return parse_create_schema(tokenizer),
+        Some(Token::Keyword(Keyword::Database)) => return parse_create_database(tokenizer),
+        Some(Token::Keyword(Keyword::View)) => return parse_create_view(tokenizer, false),
+        Some(Token::Keyword(Keyword::Materialized)) => match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::View)) => return parse_create_view(tokenizer, true),
+            _ => return Err("No keyword \"VIEW\" after CREATE MATERIALIZED".to_string()),
+        },
+        Some(Token::Keyword(Keyword::Unique)) => match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Index)) => return parse_create_index(tokenizer, true),
+            _ => return Err("No keyword \"INDEX\" after CREATE UNIQUE".to_string()),
+        },
+        _ => return Err("No keyword \"TABLE\" after CREATE".to_string()),
     }
     let table_name = match tokenizer.next_token() {
-        Ok(Some(Token::Indentifer(name))) => name,
+        Ok(Some(Token::Indentifer(name))) => relation_name(name)?,
         _ => return Err("Expected table name".to_string()),
     };
     if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
         return Err("Expected left parenthesis after table name".to_string());
     }
     let mut columns = vec![];
+    let mut constraints = vec![];
     loop {
-        let name = match tokenizer.next_token() {
-            Ok(Some(Token::Indentifer(name))) => name,
-            Ok(None) => return Err("Unexpected end of input while parsing column name".to_string()),
-            _ => return Err("Expected column name".to_string()),
-        };
-        let data_type = match tokenizer.next_token() {
-            Ok(Some(Token::DataType(type_token))) => match type_token {
-                DataType::Integer32 => ast::DataType::Int32,
-                DataType::Varchar256 => ast::DataType::VarChar256,
-            },
-            Ok(None) => return Err("Unexpected end of input while parsing data type".to_string()),
-            _ => return Err("Expected data type".to_string()),
+        match tokenizer.peek()? {
+            Some(Token::Indentifer(_)) => {
+                columns.push(parse_column_definition(&mut tokenizer, &mut constraints)?)
+            }
+            Some(Token::Keyword(
+                Keyword::Constraint
+                | Keyword::Primary
+                | Keyword::Unique
+                | Keyword::Check
+                | Keyword::Foreign,
+            )) => {
+                let name = parse_constraint_name(&mut tokenizer)?;
+                constraints.push(parse_table_constraint(&mut tokenizer, name)?);
+            }
+            Some(_) => return Err("Expected column or constraint definition".to_string()),
+            None => return Err("Unexpected end of input while parsing column name".to_string()),
+        }
+        match tokenizer.next_token()? {
+            Some(Token::Punctuation(Punctuation::Comma)) => (),
+            Some(Token::Punctuation(Punctuation::RightParen)) => break,
+            _ => {
+                return Err(
+                    "Expected comma or closing parenthesis after column definition".to_string(),
+                )
+            }
+        }
+    }
+    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
+        return Err("Expected semicolon at the end of the command".to_string());
+    }
+    Ok(SqlStatement::CreateTable {
+        table_name,
+        columns,
+        constraints,
+    })
+}
+
+// CREATE [UNIQUE] INDEX name ON table "(" names ")";
+fn parse_create_index(
+    mut tokenizer: tokenizer::Tokenizer,
+    unique: bool,
+) -> Result<SqlStatement, String> {
+    let name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => name,
+        _ => return Err("Expected index name".to_string()),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::On)) => (),
+        _ => return Err("No keyword \"ON\" after index name".to_string()),
+    }
+    let table_name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected table name after \"ON\"".to_string()),
+    };
+    let columns = parse_constraint_columns(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::CreateIndex {
+            name,
+            table_name,
+            columns,
+            unique,
+        }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// CREATE [MATERIALIZED] VIEW name ["(" names ")"] AS query;
+fn parse_create_view(
+    mut tokenizer: tokenizer::Tokenizer,
+    materialized: bool,
+) -> Result<SqlStatement, String> {
+    let name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected view name".to_string()),
+    };
+    let columns = match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::LeftParen)) => {
+            let columns = parse_identifier_list(&mut tokenizer)?;
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::As)) => columns,
+                _ => return Err("Expected keyword \"AS\" after view columns".to_string()),
+            }
+        }
+        Some(Token::Keyword(Keyword::As)) => vec![],
+        _ => return Err("Expected keyword \"AS\" after view name".to_string()),
+    };
+    let query = parse_query(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::CreateView {
+            name,
+            columns,
+            query,
+            materialized,
+        }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// REFRESH MATERIALIZED VIEW name;
+fn parse_refresh_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    match (tokenizer.next_token()?, tokenizer.next_token()?) {
+        (Some(Token::Keyword(Keyword::Materialized)), Some(Token::Keyword(Keyword::View))) => (),
+        _ => return Err("Expected MATERIALIZED VIEW after REFRESH".to_string()),
+    }
+    let name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected materialized view name".to_string()),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => {
+            Ok(SqlStatement::RefreshMaterializedView { name })
+        }
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// VACUUM [table];
+fn parse_vacuum_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    let table_name = match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => {
+            return Ok(SqlStatement::Vacuum { table_name: None })
+        }
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected table name after VACUUM".to_string()),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::Vacuum {
+            table_name: Some(table_name),
+        }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// CREATE SCHEMA name;
+fn parse_create_schema(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    let name = parse_unqualified_name(&mut tokenizer, "schema")?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::CreateSchema { name }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// CREATE DATABASE name;
+fn parse_create_database(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    let name = parse_unqualified_name(&mut tokenizer, "database")?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => {
+            Ok(SqlStatement::CreateDatabase { name })
+        }
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+/*
+    DROP SCHEMA [IF EXISTS] name [CASCADE | RESTRICT];
+    DROP DATABASE [IF EXISTS] name;
+*/
+fn parse_drop_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    let database = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Schema)) => false,
+        Some(Token::Keyword(Keyword::Database)) => true,
+        _ => return Err("No keyword \"SCHEMA\" or \"DATABASE\" after DROP".to_string()),
+    };
+    let if_exists = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::If)) => {
+            tokenizer.next_token()?;
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Exists)) => true,
+                _ => return Err("No keyword \"EXISTS\" after IF".to_string()),
+            }
+        }
+        _ => false,
+    };
+    if database {
+        let name = parse_unqualified_name(&mut tokenizer, "database")?;
+        return match tokenizer.next_token()? {
+            Some(Token::Punctuation(Punctuation::SemiColon)) => {
+                Ok(SqlStatement::DropDatabase { name, if_exists })
+            }
+            _ => Err("Expected semicolon at the end of the command".to_string()),
         };
-        columns.push(ColumnDefinition { name, data_type });
-        if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::Comma) {
-            return Err("Expected left parenthesis after collumn definition".to_string());
+    }
+    let name = parse_unqualified_name(&mut tokenizer, "schema")?;
+    let cascade = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::Cascade)) => {
+            tokenizer.next_token()?;
+            true
         }
-        if let Ok(Some(token)) = tokenizer.peek() {
-            match token {
-                Token::Indentifer(_) => (),
-                Token::Keyword(Keyword::Primary) => break,
-                _ => {
-                    return Err(
-                        "Expected comma or closing parenthesis after column definition".to_string(),
-                    )
+        Some(Token::Keyword(Keyword::Restrict)) => {
+            tokenizer.next_token()?;
+            false
+        }
+        _ => false,
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::DropSchema {
+            name,
+            if_exists,
+            cascade,
+        }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// USE name;
+fn parse_use_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    let name = parse_unqualified_name(&mut tokenizer, "database")?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::Use { name }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+/*
+    BEGIN [TRANSACTION | WORK];     START TRANSACTION;
+    COMMIT [TRANSACTION | WORK];    END [TRANSACTION | WORK];
+    ROLLBACK [TRANSACTION | WORK];  ABORT [TRANSACTION | WORK];
+    ROLLBACK [TRANSACTION | WORK] TO [SAVEPOINT] name;
+    SAVEPOINT name;                 RELEASE [SAVEPOINT] name;
+    PREPARE TRANSACTION gid;        COMMIT PREPARED gid;        ROLLBACK PREPARED gid;
+*/
+fn parse_transaction_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    let statement = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Start)) => match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Transaction)) => SqlStatement::Begin,
+            _ => return Err("Expected keyword \"TRANSACTION\" after START".to_string()),
+        },
+        Some(Token::Keyword(Keyword::Begin)) => SqlStatement::Begin,
+        Some(Token::Keyword(Keyword::Prepare)) => match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Transaction)) => SqlStatement::PrepareTransaction {
+                gid: parse_gid(&mut tokenizer)?,
+            },
+            _ => return Err("Expected keyword \"TRANSACTION\" after PREPARE".to_string()),
+        },
+        Some(Token::Keyword(Keyword::Commit)) => match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Prepared)) => {
+                tokenizer.next_token()?;
+                SqlStatement::CommitPrepared {
+                    gid: parse_gid(&mut tokenizer)?,
                 }
             }
-        } else {
-            return Err("Failed while parsing command".to_string());
+            _ => SqlStatement::Commit,
+        },
+        Some(Token::Keyword(Keyword::Rollback)) => match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Prepared)) => {
+                tokenizer.next_token()?;
+                SqlStatement::RollbackPrepared {
+                    gid: parse_gid(&mut tokenizer)?,
+                }
+            }
+            _ => SqlStatement::Rollback,
+        },
+        Some(Token::Keyword(Keyword::End)) => SqlStatement::Commit,
+        Some(Token::Keyword(Keyword::Savepoint)) => SqlStatement::Savepoint {
+            name: parse_unqualified_name(&mut tokenizer, "savepoint")?,
+        },
+        Some(Token::Keyword(Keyword::Release)) => {
+            if let Some(Token::Keyword(Keyword::Savepoint)) = tokenizer.peek()? {
+                tokenizer.next_token()?;
+            }
+            SqlStatement::ReleaseSavepoint {
+                name: parse_unqualified_name(&mut tokenizer, "savepoint")?,
+            }
         }
+        _ => SqlStatement::Rollback,
+    };
+    if let Some(Token::Keyword(Keyword::Transaction | Keyword::Work)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+    }
+    let statement = match (statement, tokenizer.peek()?) {
+        (SqlStatement::Rollback, Some(Token::Keyword(Keyword::To))) => {
+            tokenizer.next_token()?;
+            if let Some(Token::Keyword(Keyword::Savepoint)) = tokenizer.peek()? {
+                tokenizer.next_token()?;
+            }
+            SqlStatement::RollbackToSavepoint {
+                name: parse_unqualified_name(&mut tokenizer, "savepoint")?,
+            }
+        }
+        (statement, _) => statement,
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(statement),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+// global identifier of prepared transaction is a string
+fn parse_gid(tokenizer: &mut tokenizer::Tokenizer) -> Result<String, String> {
+    match tokenizer.next_token()? {
+        Some(Token::Value(Value::String(gid))) => Ok(gid),
+        _ => Err("Expected transaction identifier string".to_string()),
     }
-    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Primary) {
-        return Err("No keyword \"PRIMARY\" after collumn definitions".to_string());
+}
+
+// name of schema or database, it can't be qualified
+fn parse_unqualified_name(
+    tokenizer: &mut tokenizer::Tokenizer,
+    object: &str,
+) -> Result<String, String> {
+    match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) if !name.contains('.') => Ok(name),
+        _ => Err(format!("Expected {} name", object)),
     }
-    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Key) {
-        return Err("No keyword \"KEY\" after PRIMARY".to_string());
+}
+
+// SET name (TO | =) value ("," value)*;   value is name, string or integer
+fn parse_set_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    if let Some(Token::Keyword(Keyword::Transaction)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        return parse_set_transaction(tokenizer);
     }
-    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
-        return Err("Expected left parenthesis after table name".to_string());
+    let name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => name,
+        _ => return Err("Expected name of setting after SET".to_string()),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::To)) | Some(Token::Operator(Operator::Equal)) => (),
+        _ => return Err(format!("Expected TO or = after \"{}\"", name)),
+    }
+    let mut values = vec![];
+    loop {
+        values.push(match tokenizer.next_token()? {
+            Some(Token::Indentifer(value)) | Some(Token::Value(Value::String(value))) => value,
+            Some(Token::Value(Value::Integer(value))) => value.to_string(),
+            _ => return Err(format!("Expected value of \"{}\"", name)),
+        });
+        match tokenizer.next_token()? {
+            Some(Token::Punctuation(Punctuation::Comma)) => (),
+            Some(Token::Punctuation(Punctuation::SemiColon)) => break,
+            _ => return Err("Expected semicolon at the end of the command".to_string()),
+        }
+    }
+    Ok(SqlStatement::Set { name, values })
+}
+
+// SET TRANSACTION ISOLATION LEVEL (READ COMMITTED | REPEATABLE READ | SERIALIZABLE);
+fn parse_set_transaction(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    for (keyword, name) in [(Keyword::Isolation, "ISOLATION"), (Keyword::Level, "LEVEL")] {
+        if tokenizer.next_token()? != Some(Token::Keyword(keyword)) {
+            return Err(format!("Expected keyword \"{}\"", name));
+        }
     }
-    let primary_key_collumn = match tokenizer.next_token() {
-        Ok(Some(token::Token::Indentifer(name))) => name,
-        Ok(None) => {
-            return Err("Unexpected end of input when tried to parse primary key".to_string())
+    let isolation = match (tokenizer.next_token()?, tokenizer.peek()?) {
+        (Some(Token::Keyword(Keyword::Read)), Some(Token::Keyword(Keyword::Committed))) => {
+            tokenizer.next_token()?;
+            IsolationLevel::ReadCommitted
+        }
+        (Some(Token::Keyword(Keyword::Repeatable)), Some(Token::Keyword(Keyword::Read))) => {
+            tokenizer.next_token()?;
+            IsolationLevel::RepeatableRead
         }
-        _ => return Err("Expected name of primary key collumn".to_string()),
+        (Some(Token::Keyword(Keyword::Serializable)), _) => IsolationLevel::Serializable,
+        _ => return Err("Expected isolation level".to_string()),
     };
-    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
-        return Err("Expected right parenthesis after primary collumn key name".to_string());
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => {
+            Ok(SqlStatement::SetTransaction { isolation })
+        }
+        _ => Err("Expected semicolon at the end of the command".to_string()),
     }
-    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
-        return Err("Expected right parenthesis when closing collumn definitions".to_string());
+}
+
+// SHOW name;
+fn parse_show_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    let name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => name,
+        _ => return Err("Expected name of setting after SHOW".to_string()),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => Ok(SqlStatement::Show { name }),
+        _ => Err("Expected semicolon at the end of the command".to_string()),
     }
+}
 
-    Ok(SqlStatement::CreateTable {
-        table_name,
-        primary_key: primary_key_collumn,
-        columns,
+// CREATE SEQUENCE name sequence_options;
+fn parse_create_sequence(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    let name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected sequence name".to_string()),
+    };
+    let options = parse_sequence_options(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::SemiColon)) => {
+            Ok(SqlStatement::CreateSequence { name, options })
+        }
+        _ => Err("Expected semicolon at the end of the command".to_string()),
+    }
+}
+
+/*
+    sequence_options: (INCREMENT [BY] integer | MINVALUE integer | MAXVALUE integer
+                       | START [WITH] integer | CYCLE | NO CYCLE)*
+*/
+fn parse_sequence_options(tokenizer: &mut tokenizer::Tokenizer) -> Result<SequenceOptions, String> {
+    let mut options = SequenceOptions::default();
+    loop {
+        match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Increment)) => {
+                tokenizer.next_token()?;
+                if let Some(Token::Keyword(Keyword::By)) = tokenizer.peek()? {
+                    tokenizer.next_token()?;
+                }
+                options.increment = Some(parse_signed_integer(tokenizer)?);
+            }
+            Some(Token::Keyword(Keyword::Minvalue)) => {
+                tokenizer.next_token()?;
+                options.min_value = Some(parse_signed_integer(tokenizer)?);
+            }
+            Some(Token::Keyword(Keyword::Maxvalue)) => {
+                tokenizer.next_token()?;
+                options.max_value = Some(parse_signed_integer(tokenizer)?);
+            }
+            Some(Token::Keyword(Keyword::Start)) => {
+                tokenizer.next_token()?;
+                if let Some(Token::Keyword(Keyword::With)) = tokenizer.peek()? {
+                    tokenizer.next_token()?;
+                }
+                options.start = Some(parse_signed_integer(tokenizer)?);
+            }
+            Some(Token::Keyword(Keyword::Cycle)) => {
+                tokenizer.next_token()?;
+                options.cycle = Some(true);
+            }
+            Some(Token::Keyword(Keyword::No)) => {
+                tokenizer.next_token()?;
+                match tokenizer.next_token()? {
+                    Some(Token::Keyword(Keyword::Cycle)) => options.cycle = Some(false),
+                    _ => return Err("Expected keyword \"CYCLE\" after NO".to_string()),
+                }
+            }
+            _ => return Ok(options),
+        }
+    }
+}
+
+fn parse_signed_integer(tokenizer: &mut tokenizer::Tokenizer) -> Result<i32, String> {
+    let negative = match tokenizer.peek()? {
+        Some(Token::Operator(Operator::Minus)) => {
+            tokenizer.next_token()?;
+            true
+        }
+        _ => false,
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Value(Value::Integer(integer))) if negative => Ok(-integer),
+        Some(Token::Value(Value::Integer(integer))) => Ok(integer),
+        _ => Err("Expected integer".to_string()),
+    }
+}
+
+// GENERATED (ALWAYS | BY DEFAULT) AS IDENTITY ["(" sequence_options ")"], GENERATED is already consumed
+fn parse_identity(tokenizer: &mut tokenizer::Tokenizer) -> Result<ColumnIdentity, String> {
+    let always = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Always)) => true,
+        Some(Token::Keyword(Keyword::By)) => match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Default)) => false,
+            _ => return Err("Expected keyword \"DEFAULT\" after GENERATED BY".to_string()),
+        },
+        _ => return Err("Expected ALWAYS or BY DEFAULT after GENERATED".to_string()),
+    };
+    match (tokenizer.next_token()?, tokenizer.next_token()?) {
+        (Some(Token::Keyword(Keyword::As)), Some(Token::Keyword(Keyword::Identity))) => (),
+        _ => return Err("Expected AS IDENTITY in identity column definition".to_string()),
+    }
+    let options = match tokenizer.peek()? {
+        Some(Token::Punctuation(Punctuation::LeftParen)) => {
+            tokenizer.next_token()?;
+            let options = parse_sequence_options(tokenizer)?;
+            match tokenizer.next_token()? {
+                Some(Token::Punctuation(Punctuation::RightParen)) => options,
+                _ => return Err("Expected right parenthesis after sequence options".to_string()),
+            }
+        }
+        _ => SequenceOptions::default(),
+    };
+    Ok(if always {
+        ColumnIdentity::Always(options)
+    } else {
+        ColumnIdentity::ByDefault(options)
+    })
+}
+
+fn parse_column_definition(
+    tokenizer: &mut tokenizer::Tokenizer,
+    constraints: &mut Vec<TableConstraint>,
+) -> Result<ColumnDefinition, String> {
+    let name = match tokenizer.next_token() {
+        Ok(Some(Token::Indentifer(name))) => name,
+        _ => return Err("Expected column name".to_string()),
+    };
+    let mut identity = None;
+    let data_type = match tokenizer.next_token() {
+        Ok(Some(Token::DataType(type_token))) => match type_token {
+            DataType::Integer32 => ast::DataType::Int32,
+            DataType::Varchar256 => ast::DataType::VarChar256,
+        },
+        Ok(Some(Token::Keyword(Keyword::Serial))) => {
+            identity = Some(ColumnIdentity::Serial);
+            ast::DataType::Int32
+        }
+        Ok(None) => return Err("Unexpected end of input while parsing data type".to_string()),
+        _ => return Err("Expected data type".to_string()),
+    };
+    let mut default = None;
+    loop {
+        match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Default)) => {
+                tokenizer.next_token()?;
+                default = Some(parse_additive(tokenizer)?);
+            }
+            Some(Token::Keyword(Keyword::Generated)) => {
+                tokenizer.next_token()?;
+                if identity.is_some() {
+                    return Err(format!(
+                        "Multiple identity specifications for column \"{}\"",
+                        name
+                    ));
+                }
+                identity = Some(parse_identity(tokenizer)?);
+            }
+            Some(Token::Keyword(
+                Keyword::Constraint
+                | Keyword::Primary
+                | Keyword::Unique
+                | Keyword::Check
+                | Keyword::References,
+            )) => {
+                let constraint_name = parse_constraint_name(tokenizer)?;
+                let kind = match tokenizer.next_token()? {
+                    Some(Token::Keyword(Keyword::Primary)) => {
+                        match tokenizer.next_token()? {
+                            Some(Token::Keyword(Keyword::Key)) => (),
+                            _ => return Err("No keyword \"KEY\" after PRIMARY".to_string()),
+                        }
+                        TableConstraintKind::PrimaryKey(vec![name.clone()])
+                    }
+                    Some(Token::Keyword(Keyword::Unique)) => {
+                        TableConstraintKind::Unique(vec![name.clone()])
+                    }
+                    Some(Token::Keyword(Keyword::Check)) => {
+                        TableConstraintKind::Check(parse_check_expression(tokenizer)?)
+                    }
+                    Some(Token::Keyword(Keyword::References)) => TableConstraintKind::ForeignKey(
+                        parse_references(tokenizer, vec![name.clone()])?,
+                    ),
+                    _ => {
+                        return Err("Expected PRIMARY KEY, UNIQUE, CHECK or REFERENCES".to_string())
+                    }
+                };
+                constraints.push(TableConstraint {
+                    name: constraint_name,
+                    kind,
+                });
+            }
+            _ => break,
+        }
+    }
+    Ok(ColumnDefinition {
+        name,
+        data_type,
+        default,
+        identity,
     })
 }
 
+// CONSTRAINT name, if present
+fn parse_constraint_name(tokenizer: &mut tokenizer::Tokenizer) -> Result<Option<String>, String> {
+    if let Some(Token::Keyword(Keyword::Constraint)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        match tokenizer.next_token()? {
+            Some(Token::Indentifer(name)) => Ok(Some(name)),
+            _ => Err("Expected constraint name after \"CONSTRAINT\"".to_string()),
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_table_constraint(
+    tokenizer: &mut tokenizer::Tokenizer,
+    name: Option<String>,
+) -> Result<TableConstraint, String> {
+    let kind = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Primary)) => {
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Key)) => (),
+                _ => return Err("No keyword \"KEY\" after PRIMARY".to_string()),
+            }
+            TableConstraintKind::PrimaryKey(parse_constraint_columns(tokenizer)?)
+        }
+        Some(Token::Keyword(Keyword::Unique)) => {
+            TableConstraintKind::Unique(parse_constraint_columns(tokenizer)?)
+        }
+        Some(Token::Keyword(Keyword::Check)) => {
+            TableConstraintKind::Check(parse_check_expression(tokenizer)?)
+        }
+        Some(Token::Keyword(Keyword::Foreign)) => {
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Key)) => (),
+                _ => return Err("No keyword \"KEY\" after FOREIGN".to_string()),
+            }
+            let columns = parse_constraint_columns(tokenizer)?;
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::References)) => (),
+                _ => return Err("Expected keyword \"REFERENCES\" after FOREIGN KEY".to_string()),
+            }
+            TableConstraintKind::ForeignKey(parse_references(tokenizer, columns)?)
+        }
+        _ => return Err("Expected PRIMARY KEY, UNIQUE, CHECK or FOREIGN KEY".to_string()),
+    };
+    Ok(TableConstraint { name, kind })
+}
+
+/*
+    called after REFERENCES was consumed:
+        references: table ["(" names ")"] (ON (DELETE | UPDATE) action)*
+        action:     NO ACTION | RESTRICT | CASCADE | SET NULL | SET DEFAULT
+*/
+fn parse_references(
+    tokenizer: &mut tokenizer::Tokenizer,
+    columns: Vec<String>,
+) -> Result<ForeignKey, String> {
+    let referenced_table = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected table name after \"REFERENCES\"".to_string()),
+    };
+    let referenced_columns =
+        if let Some(Token::Punctuation(Punctuation::LeftParen)) = tokenizer.peek()? {
+            parse_constraint_columns(tokenizer)?
+        } else {
+            vec![]
+        };
+    let mut foreign_key = ForeignKey {
+        columns,
+        referenced_table,
+        referenced_columns,
+        on_delete: ReferentialAction::NoAction,
+        on_update: ReferentialAction::NoAction,
+    };
+    while let Some(Token::Keyword(Keyword::On)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let on_delete = match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Delete)) => true,
+            Some(Token::Keyword(Keyword::Update)) => false,
+            _ => return Err("Expected DELETE or UPDATE after \"ON\"".to_string()),
+        };
+        let action = match (tokenizer.next_token()?, on_delete) {
+            (Some(Token::Keyword(Keyword::No)), _) => match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Action)) => ReferentialAction::NoAction,
+                _ => return Err("Expected keyword \"ACTION\" after NO".to_string()),
+            },
+            (Some(Token::Keyword(Keyword::Restrict)), _) => ReferentialAction::Restrict,
+            (Some(Token::Keyword(Keyword::Cascade)), _) => ReferentialAction::Cascade,
+            (Some(Token::Keyword(Keyword::Set)), _) => match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Null)) => ReferentialAction::SetNull,
+                Some(Token::Keyword(Keyword::Default)) => ReferentialAction::SetDefault,
+                _ => return Err("Expected NULL or DEFAULT after \"SET\"".to_string()),
+            },
+            _ => return Err("Expected referential action".to_string()),
+        };
+        if on_delete {
+            foreign_key.on_delete = action;
+        } else {
+            foreign_key.on_update = action;
+        }
+    }
+    Ok(foreign_key)
+}
+
+fn parse_constraint_columns(tokenizer: &mut tokenizer::Tokenizer) -> Result<Vec<String>, String> {
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::LeftParen)) => parse_identifier_list(tokenizer),
+        _ => Err("Expected left parenthesis before constraint columns".to_string()),
+    }
+}
+
+fn parse_check_expression(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::LeftParen)) => (),
+        _ => return Err("Expected left parenthesis after \"CHECK\"".to_string()),
+    }
+    let expression = parse_expression(tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Punctuation(Punctuation::RightParen)) => Ok(expression),
+        _ => Err("Expected right parenthesis after CHECK expression".to_string()),
+    }
+}
+
 fn parse_insert_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
     if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Insert) {
         return Err("No keyword \"INSERT\"".to_string());
@@ -102,7 +807,7 @@ fn parse_insert_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatem
         return Err("No keyword \"INTO\" after INSERT".to_string());
     }
     let table_name = match tokenizer.next_token() {
-        Ok(Some(Token::Indentifer(name))) => name,
+        Ok(Some(Token::Indentifer(name))) => relation_name(name)?,
         _ => return Err("Expected table name after \"INTO\" keyword".to_string()),
     };
     if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
@@ -133,44 +838,18 @@ fn parse_insert_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatem
         }
     }
     if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::RightParen) {
-        return Err("Expected left parenthesis after table name".to_string());
+        return Err("Expected right parenthesis after column names".to_string());
     }
     if let Err(_err) = expect_keyword(&mut tokenizer, token::Keyword::Values) {
         return Err("Expected keyword \"Values\"".to_string());
     }
-    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
-        return Err("Expected left parenthesis after \"Values keyword\"".to_string());
-    }
-    let mut values = vec![];
-    loop {
-        let value = match tokenizer.next_token() {
-            Ok(Some(Token::Value(Value::String(s)))) => ast::Value::VarChar256(s),
-            Ok(Some(Token::Value(Value::Integer(integer)))) => ast::Value::Int32(integer),
-            Ok(None) => {
-                return Err("Unexpected end of input when tried to parse primary key".to_string())
-            }
-            _ => return Err("Expected value".to_string()),
-        };
-        values.push(value);
-        if let Ok(Some(token)) = tokenizer.peek() {
-            match token {
-                token::Token::Punctuation(Punctuation::Comma) => {
-                    tokenizer.next_token()?;
-                }
-                token::Token::Punctuation(Punctuation::RightParen) => {
-                    break;
-                }
-                _ => {
-                    return Err(
-                        "Expected comma or closing parenthesis after column value".to_string()
-                    );
-                }
-            }
-        }
-    }
-    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::LeftParen) {
-        return Err("Expected left parenthesis after values".to_string());
+    let mut values = vec![parse_values_row(&mut tokenizer)?];
+    while let Ok(Some(Token::Punctuation(Punctuation::Comma))) = tokenizer.peek() {
+        tokenizer.next_token()?;
+        values.push(parse_values_row(&mut tokenizer)?);
     }
+    let on_conflict = parse_on_conflict(&mut tokenizer)?;
+    let returning = parse_returning(&mut tokenizer)?;
     if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
         return Err("Expected semicolon at the end of the command".to_string());
     }
@@ -178,58 +857,1006 @@ fn parse_insert_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatem
         table_name,
         column_names,
         values,
+        on_conflict,
+        returning,
     })
 }
 
-fn parse_select_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
-    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Select) {
-        return Err("No keyword \"SELECT\"".to_string());
+// "(" expression ("," expression)* ")"
+fn parse_values_row(tokenizer: &mut tokenizer::Tokenizer) -> Result<Vec<Expression>, String> {
+    if let Err(_err) = expect_punctuation(tokenizer, Punctuation::LeftParen) {
+        return Err("Expected left parenthesis after \"Values keyword\"".to_string());
     }
-    let columns: Columns = match tokenizer.peek() {
-        Ok(Some(Token::Wildcard)) => {
-            tokenizer.next_token()?;
-            Columns::All
+    let mut values = vec![];
+    loop {
+        if let Ok(None) = tokenizer.peek() {
+            return Err("Unexpected end of input when tried to parse values".to_string());
         }
-        Ok(Some(Token::Indentifer(_))) => {
-            let mut collumns = vec![];
-            loop {
-                let name = match tokenizer.next_token() {
-                    Ok(Some(Token::Indentifer(name))) => name,
-                    Ok(None) => {
-                        return Err("Unexpected end of input while parsing column name".to_string())
-                    }
-                    _ => return Err("Expected column name".to_string()),
-                };
-                collumns.push(name);
-                if let Ok(Some(token)) = tokenizer.peek() {
-                    match token {
-                        token::Token::Punctuation(Punctuation::Comma) => {
-                            tokenizer.next_token()?;
-                        }
-                        token::Token::Keyword(Keyword::From) => break,
-                        _ => {
-                            return Err("Expected comma or closing parenthesis after column value"
-                                .to_string());
-                        }
+        values.push(parse_expression(tokenizer)?);
+        match tokenizer.next_token()? {
+            Some(Token::Punctuation(Punctuation::Comma)) => (),
+            Some(Token::Punctuation(Punctuation::RightParen)) => return Ok(values),
+            _ => return Err("Expected comma or closing parenthesis after column value".to_string()),
+        }
+    }
+}
+
+// [ON CONFLICT ["(" names ")"] DO (NOTHING | UPDATE SET assignments [WHERE expression])]
+fn parse_on_conflict(tokenizer: &mut tokenizer::Tokenizer) -> Result<Option<OnConflict>, String> {
+    match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::On)) => tokenizer.next_token()?,
+        _ => return Ok(None),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Conflict)) => (),
+        _ => return Err("Expected keyword \"CONFLICT\" after ON".to_string()),
+    }
+    let columns = match tokenizer.peek()? {
+        Some(Token::Punctuation(Punctuation::LeftParen)) => {
+            tokenizer.next_token()?;
+            parse_identifier_list(tokenizer)?
+        }
+        _ => vec![],
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Do)) => (),
+        _ => return Err("Expected keyword \"DO\" after ON CONFLICT".to_string()),
+    }
+    let action = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Nothing)) => ConflictAction::Nothing,
+        Some(Token::Keyword(Keyword::Update)) => {
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Set)) => (),
+                _ => return Err("Expected keyword \"SET\" after DO UPDATE".to_string()),
+            }
+            let assignments = parse_assignments(tokenizer)?;
+            let selection = if let Some(Token::Keyword(Keyword::Where)) = tokenizer.peek()? {
+                tokenizer.next_token()?;
+                Some(parse_expression(tokenizer)?)
+            } else {
+                None
+            };
+            ConflictAction::Update {
+                assignments,
+                selection,
+            }
+        }
+        _ => return Err("Expected NOTHING or UPDATE after ON CONFLICT DO".to_string()),
+    };
+    Ok(Some(OnConflict { columns, action }))
+}
+
+/*
+    MERGE INTO table [[AS] alias] USING table [[AS] alias] ON expression when_clause+;
+        when_clause:     WHEN MATCHED [AND expression] THEN (UPDATE SET assignments | DELETE | DO NOTHING)
+                         | WHEN NOT MATCHED [AND expression] THEN (INSERT ["(" names ")"] VALUES "(" values ")" | DO NOTHING)
+*/
+fn parse_merge_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    tokenizer.next_token()?;
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Into)) => (),
+        _ => return Err("Expected keyword \"INTO\" after MERGE".to_string()),
+    }
+    let table_name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected table name after \"INTO\"".to_string()),
+    };
+    let alias = parse_alias(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Using)) => (),
+        _ => return Err("Expected keyword \"USING\" after target table".to_string()),
+    }
+    let source = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected source table name after \"USING\"".to_string()),
+    };
+    let source_alias = parse_alias(&mut tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::On)) => (),
+        _ => return Err("Expected keyword \"ON\" after source table".to_string()),
+    }
+    let condition = parse_expression(&mut tokenizer)?;
+    let mut clauses = vec![];
+    while let Some(Token::Keyword(Keyword::When)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        clauses.push(parse_merge_clause(&mut tokenizer)?);
+    }
+    if clauses.is_empty() {
+        return Err("Expected WHEN clause in MERGE".to_string());
+    }
+    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
+        return Err("Expected semicolon at the end of the command".to_string());
+    }
+    Ok(SqlStatement::Merge {
+        table_name,
+        alias,
+        source,
+        source_alias,
+        condition,
+        clauses,
+    })
+}
+
+// WHEN is already consumed
+fn parse_merge_clause(tokenizer: &mut tokenizer::Tokenizer) -> Result<MergeClause, String> {
+    let matched = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Matched)) => true,
+        Some(Token::Keyword(Keyword::Not)) => match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Matched)) => false,
+            _ => return Err("Expected keyword \"MATCHED\" after WHEN NOT".to_string()),
+        },
+        _ => return Err("Expected MATCHED or NOT MATCHED after WHEN".to_string()),
+    };
+    let condition = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::And)) => {
+            tokenizer.next_token()?;
+            Some(parse_expression(tokenizer)?)
+        }
+        _ => None,
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Then)) => (),
+        _ => return Err("Expected keyword \"THEN\" in WHEN clause".to_string()),
+    }
+    let action = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Do)) => match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Nothing)) => MergeAction::Nothing,
+            _ => return Err("Expected keyword \"NOTHING\" after DO".to_string()),
+        },
+        Some(Token::Keyword(Keyword::Update)) if matched => {
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Set)) => (),
+                _ => return Err("Expected keyword \"SET\" after UPDATE".to_string()),
+            }
+            MergeAction::Update(parse_assignments(tokenizer)?)
+        }
+        Some(Token::Keyword(Keyword::Delete)) if matched => MergeAction::Delete,
+        Some(Token::Keyword(Keyword::Insert)) if !matched => {
+            let columns = match tokenizer.peek()? {
+                Some(Token::Punctuation(Punctuation::LeftParen)) => {
+                    tokenizer.next_token()?;
+                    parse_identifier_list(tokenizer)?
+                }
+                _ => vec![],
+            };
+            match (tokenizer.next_token()?, tokenizer.next_token()?) {
+                (
+                    Some(Token::Keyword(Keyword::Values)),
+                    Some(Token::Punctuation(Punctuation::LeftParen)),
+                ) => (),
+                _ => return Err("Expected VALUES list in INSERT of MERGE".to_string()),
+            }
+            let mut values = vec![];
+            loop {
+                values.push(parse_expression(tokenizer)?);
+                match tokenizer.next_token()? {
+                    Some(Token::Punctuation(Punctuation::Comma)) => (),
+                    Some(Token::Punctuation(Punctuation::RightParen)) => break,
+                    _ => {
+                        return Err("Expected comma or closing parenthesis after value".to_string())
                     }
                 }
             }
-            Columns::Specific(collumns)
+            MergeAction::Insert { columns, values }
+        }
+        _ if matched => {
+            return Err("Expected UPDATE, DELETE or DO NOTHING in WHEN MATCHED".to_string())
+        }
+        _ => return Err("Expected INSERT or DO NOTHING in WHEN NOT MATCHED".to_string()),
+    };
+    Ok(MergeClause {
+        matched,
+        condition,
+        action,
+    })
+}
+
+// UPDATE table SET column = expression ("," column = expression)* [WHERE expression] [returning];
+fn parse_update_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Update) {
+        return Err("No keyword \"UPDATE\"".to_string());
+    }
+    let table_name = match tokenizer.next_token() {
+        Ok(Some(Token::Indentifer(name))) => relation_name(name)?,
+        _ => return Err("Expected table name after \"UPDATE\" keyword".to_string()),
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Set)) => (),
+        _ => return Err("Expected keyword \"SET\" after table name".to_string()),
+    }
+    let assignments = parse_assignments(&mut tokenizer)?;
+    let selection = if let Some(Token::Keyword(Keyword::Where)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Some(parse_expression(&mut tokenizer)?)
+    } else {
+        None
+    };
+    let returning = parse_returning(&mut tokenizer)?;
+    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
+        return Err("Expected semicolon at the end of the command".to_string());
+    }
+    Ok(SqlStatement::Update {
+        table_name,
+        assignments,
+        selection,
+        returning,
+    })
+}
+
+// column = expression ("," column = expression)*
+fn parse_assignments(tokenizer: &mut tokenizer::Tokenizer) -> Result<Vec<Assignment>, String> {
+    let mut assignments = vec![];
+    loop {
+        let column = match tokenizer.next_token()? {
+            Some(Token::Indentifer(name)) => name,
+            _ => return Err("Expected column name in SET".to_string()),
+        };
+        match tokenizer.next_token()? {
+            Some(Token::Operator(Operator::Equal)) => (),
+            _ => return Err(format!("Expected \"=\" after column {} in SET", column)),
+        }
+        let value = parse_expression(tokenizer)?;
+        assignments.push(Assignment { column, value });
+        match tokenizer.peek()? {
+            Some(Token::Punctuation(Punctuation::Comma)) => {
+                tokenizer.next_token()?;
+            }
+            _ => return Ok(assignments),
+        }
+    }
+}
+
+// DELETE FROM table [WHERE expression] [returning];
+fn parse_delete_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::Delete) {
+        return Err("No keyword \"DELETE\"".to_string());
+    }
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::From)) => (),
+        _ => return Err("Expected keyword \"FROM\" after DELETE".to_string()),
+    }
+    let table_name = match tokenizer.next_token()? {
+        Some(Token::Indentifer(name)) => relation_name(name)?,
+        _ => return Err("Expected table name after \"FROM\" keyword".to_string()),
+    };
+    let selection = if let Some(Token::Keyword(Keyword::Where)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Some(parse_expression(&mut tokenizer)?)
+    } else {
+        None
+    };
+    let returning = parse_returning(&mut tokenizer)?;
+    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
+        return Err("Expected semicolon at the end of the command".to_string());
+    }
+    Ok(SqlStatement::Delete {
+        table_name,
+        selection,
+        returning,
+    })
+}
+
+// [RETURNING ("*" | expression [[AS] alias] ("," expression [[AS] alias])*)]
+fn parse_returning(tokenizer: &mut tokenizer::Tokenizer) -> Result<Option<Columns>, String> {
+    match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::Returning)) => tokenizer.next_token()?,
+        _ => return Ok(None),
+    };
+    if let Some(Token::Wildcard) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        return Ok(Some(Columns::All));
+    }
+    let mut items = vec![];
+    loop {
+        let expression = parse_expression(tokenizer)?;
+        let alias = parse_alias(tokenizer)?;
+        items.push(SelectItem { expression, alias });
+        match tokenizer.peek()? {
+            Some(Token::Punctuation(Punctuation::Comma)) => {
+                tokenizer.next_token()?;
+            }
+            _ => return Ok(Some(Columns::Specific(items))),
+        }
+    }
+}
+
+fn parse_select_command(mut tokenizer: tokenizer::Tokenizer) -> Result<SqlStatement, String> {
+    let mut query = parse_query(&mut tokenizer)?;
+    if let Some(Token::Keyword(Keyword::For)) = tokenizer.peek()? {
+        query.locking = Some(parse_locking(&mut tokenizer)?);
+    }
+    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
+        return Err("Expected semicolon at the end of the command".to_string());
+    }
+    Ok(SqlStatement::Query(query))
+}
+
+fn parse_query(tokenizer: &mut tokenizer::Tokenizer) -> Result<Query, String> {
+    let with = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::With)) => Some(parse_with(tokenizer)?),
+        _ => None,
+    };
+    let body = parse_query_body(tokenizer)?;
+    Ok(Query {
+        with,
+        body,
+        locking: None,
+    })
+}
+
+// FOR (UPDATE | SHARE) [NOWAIT | SKIP LOCKED]
+fn parse_locking(tokenizer: &mut tokenizer::Tokenizer) -> Result<Locking, String> {
+    tokenizer.next_token()?;
+    let strength = match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::Update)) => LockStrength::Update,
+        Some(Token::Keyword(Keyword::Share)) => LockStrength::Share,
+        _ => return Err("Expected UPDATE or SHARE after FOR".to_string()),
+    };
+    let wait = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::Nowait)) => {
+            tokenizer.next_token()?;
+            LockWait::NoWait
+        }
+        Some(Token::Keyword(Keyword::Skip)) => {
+            tokenizer.next_token()?;
+            match tokenizer.next_token()? {
+                Some(Token::Keyword(Keyword::Locked)) => LockWait::SkipLocked,
+                _ => return Err("Expected LOCKED after SKIP".to_string()),
+            }
+        }
+        _ => LockWait::Wait,
+    };
+    Ok(Locking { strength, wait })
+}
+
+/*
+    INTERSECT binds tighter than UNION and EXCEPT, operators of the same precedence are left associative:
+        body:      term ((UNION | EXCEPT) [ALL | DISTINCT] term)*
+        term:      primary (INTERSECT [ALL | DISTINCT] primary)*
+        primary:   select | "(" body ")"
+*/
+fn parse_query_body(tokenizer: &mut tokenizer::Tokenizer) -> Result<QueryBody, String> {
+    let mut left = parse_intersect_term(tokenizer)?;
+    loop {
+        let operator = match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Union)) => SetOperator::Union,
+            Some(Token::Keyword(Keyword::Except)) => SetOperator::Except,
+            _ => return Ok(left),
+        };
+        tokenizer.next_token()?;
+        let all = parse_set_quantifier(tokenizer)?;
+        let right = parse_intersect_term(tokenizer)?;
+        left = QueryBody::SetOperation {
+            operator,
+            all,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+    }
+}
+
+fn parse_intersect_term(tokenizer: &mut tokenizer::Tokenizer) -> Result<QueryBody, String> {
+    let mut left = parse_query_primary(tokenizer)?;
+    while let Some(Token::Keyword(Keyword::Intersect)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let all = parse_set_quantifier(tokenizer)?;
+        let right = parse_query_primary(tokenizer)?;
+        left = QueryBody::SetOperation {
+            operator: SetOperator::Intersect,
+            all,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+    }
+    Ok(left)
+}
+
+fn parse_set_quantifier(tokenizer: &mut tokenizer::Tokenizer) -> Result<bool, String> {
+    match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::All)) => {
+            tokenizer.next_token()?;
+            Ok(true)
+        }
+        Some(Token::Keyword(Keyword::Distinct)) => {
+            tokenizer.next_token()?;
+            Ok(false)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn parse_query_primary(tokenizer: &mut tokenizer::Tokenizer) -> Result<QueryBody, String> {
+    match tokenizer.peek()? {
+        Some(Token::Punctuation(Punctuation::LeftParen)) => {
+            tokenizer.next_token()?;
+            let body = parse_query_body(tokenizer)?;
+            if let Err(_err) = expect_punctuation(tokenizer, Punctuation::RightParen) {
+                return Err("Expected right parenthesis after query".to_string());
+            }
+            Ok(body)
+        }
+        _ => Ok(QueryBody::Select(Box::new(parse_select(tokenizer)?))),
+    }
+}
+
+fn parse_with(tokenizer: &mut tokenizer::Tokenizer) -> Result<With, String> {
+    if let Err(_err) = expect_keyword(tokenizer, Keyword::With) {
+        return Err("No keyword \"WITH\"".to_string());
+    }
+    let recursive = if let Some(Token::Keyword(Keyword::Recursive)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        true
+    } else {
+        false
+    };
+    let mut ctes = vec![];
+    loop {
+        let name = match tokenizer.next_token() {
+            Ok(Some(Token::Indentifer(name))) => name,
+            Ok(None) => return Err("Unexpected end of input while parsing CTE name".to_string()),
+            _ => return Err("Expected CTE name after \"WITH\"".to_string()),
+        };
+        let columns = if let Some(Token::Punctuation(Punctuation::LeftParen)) = tokenizer.peek()? {
+            tokenizer.next_token()?;
+            parse_identifier_list(tokenizer)?
+        } else {
+            vec![]
+        };
+        if let Err(_err) = expect_keyword(tokenizer, Keyword::As) {
+            return Err(format!("Expected keyword \"AS\" after CTE name {}", name));
+        }
+        if let Err(_err) = expect_punctuation(tokenizer, Punctuation::LeftParen) {
+            return Err("Expected left parenthesis before CTE query".to_string());
+        }
+        let query = parse_query(tokenizer)?;
+        if let Err(_err) = expect_punctuation(tokenizer, Punctuation::RightParen) {
+            return Err("Expected right parenthesis after CTE query".to_string());
+        }
+        ctes.push(Cte {
+            name,
+            columns,
+            query: Box::new(query),
+        });
+        if let Some(Token::Punctuation(Punctuation::Comma)) = tokenizer.peek()? {
+            tokenizer.next_token()?;
+        } else {
+            break;
+        }
+    }
+    Ok(With { recursive, ctes })
+}
+
+// parses "a, b, c)" and consumes the closing parenthesis
+// relation can be qualified with name of its schema, like schema.table
+fn relation_name(name: String) -> Result<String, String> {
+    if name.split('.').count() > 2 {
+        return Err(format!(
+            "Improper qualified name (too many dotted names): {}",
+            name
+        ));
+    }
+    if name.split('.').any(str::is_empty) {
+        return Err(format!("Invalid relation name \"{}\"", name));
+    }
+    Ok(name)
+}
+
+fn parse_identifier_list(tokenizer: &mut tokenizer::Tokenizer) -> Result<Vec<String>, String> {
+    let mut names = vec![];
+    loop {
+        match tokenizer.next_token() {
+            Ok(Some(Token::Indentifer(name))) => names.push(name),
+            Ok(None) => return Err("Unexpected end of input while parsing name list".to_string()),
+            _ => return Err("Expected name in list".to_string()),
+        };
+        match tokenizer.next_token() {
+            Ok(Some(Token::Punctuation(Punctuation::Comma))) => (),
+            Ok(Some(Token::Punctuation(Punctuation::RightParen))) => break,
+            _ => return Err("Expected comma or closing parenthesis in name list".to_string()),
+        }
+    }
+    Ok(names)
+}
+
+fn parse_select(tokenizer: &mut tokenizer::Tokenizer) -> Result<Select, String> {
+    if let Err(_err) = expect_keyword(tokenizer, Keyword::Select) {
+        return Err("No keyword \"SELECT\"".to_string());
+    }
+    let distinct = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::Distinct)) => {
+            tokenizer.next_token()?;
+            true
+        }
+        Some(Token::Keyword(Keyword::All)) => {
+            tokenizer.next_token()?;
+            false
+        }
+        _ => false,
+    };
+    let columns: Columns = match tokenizer.peek() {
+        Ok(Some(Token::Wildcard)) => {
+            tokenizer.next_token()?;
+            Columns::All
+        }
+        Ok(Some(_)) => {
+            let mut items = vec![];
+            loop {
+                let expression = parse_expression(tokenizer)?;
+                let alias = parse_alias(tokenizer)?;
+                items.push(SelectItem { expression, alias });
+                match tokenizer.peek() {
+                    Ok(Some(Token::Punctuation(Punctuation::Comma))) => {
+                        tokenizer.next_token()?;
+                    }
+                    Ok(None) => {
+                        return Err("Unexpected end of input while parsing select list".to_string())
+                    }
+                    _ => break,
+                }
+            }
+            Columns::Specific(items)
         }
         _ => return Err("Error parsing collumns to show".to_string()),
     };
-    if let Err(_err) = expect_keyword(&mut tokenizer, Keyword::From) {
-        return Err("No keyword \"FROM\" after SELECT".to_string());
+    // SELECT without FROM computes one row of its select list
+    if tokenizer.peek()? != Some(Token::Keyword(Keyword::From)) {
+        if columns == Columns::All {
+            return Err("SELECT * with no tables specified is not valid".to_string());
+        }
+        let selection = parse_where(tokenizer)?;
+        return Ok(Select {
+            distinct,
+            columns,
+            table: None,
+            alias: None,
+            joins: vec![],
+            selection,
+        });
     }
+    tokenizer.next_token()?;
     let table = match tokenizer.next_token() {
-        Ok(Some(Token::Indentifer(name))) => name,
+        Ok(Some(Token::Indentifer(name))) => Some(relation_name(name)?),
         Ok(None) => return Err("Unexpected end of input while parsing table name".to_string()),
         _ => return Err("Expected table name".to_string()),
     };
-    if let Err(_err) = expect_punctuation(&mut tokenizer, Punctuation::SemiColon) {
-        return Err("Expected semicolon at the end of the command".to_string());
+    let alias = parse_alias(tokenizer)?;
+    let mut joins = vec![];
+    while let Some(Token::Keyword(Keyword::Join)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let table = match tokenizer.next_token() {
+            Ok(Some(Token::Indentifer(name))) => relation_name(name)?,
+            _ => return Err("Expected table name after \"JOIN\"".to_string()),
+        };
+        let alias = parse_alias(tokenizer)?;
+        if let Some(Token::Keyword(Keyword::On)) = tokenizer.peek()? {
+            tokenizer.next_token()?;
+        } else {
+            return Err(format!(
+                "Expected keyword \"ON\" after joined table {}",
+                table
+            ));
+        }
+        let on = parse_expression(tokenizer)?;
+        joins.push(Join { table, alias, on });
+    }
+    let selection = parse_where(tokenizer)?;
+    Ok(Select {
+        distinct,
+        columns,
+        table,
+        alias,
+        joins,
+        selection,
+    })
+}
+
+fn parse_where(tokenizer: &mut tokenizer::Tokenizer) -> Result<Option<Expression>, String> {
+    if let Some(Token::Keyword(Keyword::Where)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Ok(Some(parse_expression(tokenizer)?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_alias(tokenizer: &mut tokenizer::Tokenizer) -> Result<Option<String>, String> {
+    match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::As)) => {
+            tokenizer.next_token()?;
+            match tokenizer.next_token() {
+                Ok(Some(Token::Indentifer(alias))) => Ok(Some(alias)),
+                _ => Err("Expected alias after \"AS\"".to_string()),
+            }
+        }
+        Some(Token::Indentifer(alias)) => {
+            tokenizer.next_token()?;
+            Ok(Some(alias))
+        }
+        _ => Ok(None),
+    }
+}
+
+/*
+    expression grammar, from lowest to highest precedence:
+        or:             and (OR and)*
+        and:            comparison (AND comparison)*
+        comparison:     additive ((= | <> | < | <= | > | >= | ~) additive | (LIKE | ILIKE) additive [ESCAPE additive])?
+        additive:       multiplicative ((+ | -) multiplicative)*
+        multiplicative: primary ((* | /) primary)*
+        primary:        column | value | NULL | - primary | function | case | cast | "(" or ")"
+        function:       name "(" [* | [DISTINCT] or ("," or)*] ")" [OVER window]
+        case:           CASE [or] (WHEN or THEN or)+ [ELSE or] END
+        cast:           CAST "(" or AS type ")"
+*/
+fn parse_expression(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    let mut left = parse_and_expression(tokenizer)?;
+    while let Some(Token::Keyword(Keyword::Or)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let right = parse_and_expression(tokenizer)?;
+        left = binary_operation(left, BinaryOperator::Or, right);
+    }
+    Ok(left)
+}
+
+fn parse_and_expression(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    let mut left = parse_comparison(tokenizer)?;
+    while let Some(Token::Keyword(Keyword::And)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let right = parse_comparison(tokenizer)?;
+        left = binary_operation(left, BinaryOperator::And, right);
+    }
+    Ok(left)
+}
+
+fn parse_comparison(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    let left = parse_additive(tokenizer)?;
+    let operator = match tokenizer.peek()? {
+        Some(Token::Operator(Operator::Equal)) => BinaryOperator::Equal,
+        Some(Token::Operator(Operator::NotEqual)) => BinaryOperator::NotEqual,
+        Some(Token::Operator(Operator::Less)) => BinaryOperator::Less,
+        Some(Token::Operator(Operator::LessOrEqual)) => BinaryOperator::LessOrEqual,
+        Some(Token::Operator(Operator::Greater)) => BinaryOperator::Greater,
+        Some(Token::Operator(Operator::GreaterOrEqual)) => BinaryOperator::GreaterOrEqual,
+        Some(Token::Operator(Operator::RegexMatch)) => BinaryOperator::RegexMatch,
+        Some(Token::Keyword(Keyword::Like)) => return parse_like(tokenizer, left, false),
+        Some(Token::Keyword(Keyword::Ilike)) => return parse_like(tokenizer, left, true),
+        _ => return Ok(left),
+    };
+    tokenizer.next_token()?;
+    let right = parse_additive(tokenizer)?;
+    Ok(binary_operation(left, operator, right))
+}
+
+// called before LIKE or ILIKE was consumed
+fn parse_like(
+    tokenizer: &mut tokenizer::Tokenizer,
+    expression: Expression,
+    case_insensitive: bool,
+) -> Result<Expression, String> {
+    tokenizer.next_token()?;
+    let pattern = parse_additive(tokenizer)?;
+    let escape = if let Some(Token::Keyword(Keyword::Escape)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Some(Box::new(parse_additive(tokenizer)?))
+    } else {
+        None
+    };
+    Ok(Expression::Like {
+        expression: Box::new(expression),
+        pattern: Box::new(pattern),
+        escape,
+        case_insensitive,
+    })
+}
+
+fn parse_additive(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    let mut left = parse_multiplicative(tokenizer)?;
+    loop {
+        let operator = match tokenizer.peek()? {
+            Some(Token::Operator(Operator::Plus)) => BinaryOperator::Plus,
+            Some(Token::Operator(Operator::Minus)) => BinaryOperator::Minus,
+            _ => return Ok(left),
+        };
+        tokenizer.next_token()?;
+        let right = parse_multiplicative(tokenizer)?;
+        left = binary_operation(left, operator, right);
+    }
+}
+
+fn parse_multiplicative(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    let mut left = parse_primary(tokenizer)?;
+    loop {
+        let operator = match tokenizer.peek()? {
+            Some(Token::Wildcard) => BinaryOperator::Multiply,
+            Some(Token::Operator(Operator::Divide)) => BinaryOperator::Divide,
+            _ => return Ok(left),
+        };
+        tokenizer.next_token()?;
+        let right = parse_primary(tokenizer)?;
+        left = binary_operation(left, operator, right);
+    }
+}
+
+fn parse_primary(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    match tokenizer.next_token() {
+        Ok(Some(Token::Indentifer(name))) => {
+            if let Some(Token::Punctuation(Punctuation::LeftParen)) = tokenizer.peek()? {
+                tokenizer.next_token()?;
+                parse_function(tokenizer, name)
+            } else {
+                Ok(Expression::Column(name))
+            }
+        }
+        Ok(Some(Token::Value(Value::String(s)))) => {
+            Ok(Expression::Value(ast::Value::VarChar256(s)))
+        }
+        Ok(Some(Token::Value(Value::Integer(integer)))) => {
+            Ok(Expression::Value(ast::Value::Int32(integer)))
+        }
+        Ok(Some(Token::Keyword(Keyword::Null))) => Ok(Expression::Value(ast::Value::Null)),
+        Ok(Some(Token::Operator(Operator::Minus))) => match parse_primary(tokenizer)? {
+            Expression::Value(ast::Value::Int32(integer)) => {
+                Ok(Expression::Value(ast::Value::Int32(-integer)))
+            }
+            expression => Ok(binary_operation(
+                Expression::Value(ast::Value::Int32(0)),
+                BinaryOperator::Minus,
+                expression,
+            )),
+        },
+        Ok(Some(Token::Keyword(Keyword::Case))) => parse_case(tokenizer),
+        Ok(Some(Token::Keyword(Keyword::Cast))) => parse_cast(tokenizer),
+        Ok(Some(Token::Punctuation(Punctuation::LeftParen))) => {
+            let expression = parse_expression(tokenizer)?;
+            if let Err(_err) = expect_punctuation(tokenizer, Punctuation::RightParen) {
+                return Err("Expected right parenthesis after expression".to_string());
+            }
+            Ok(expression)
+        }
+        Ok(None) => Err("Unexpected end of input while parsing expression".to_string()),
+        Ok(Some(token)) => Err(format!("Unexpected token {:?} in expression", token)),
+        Err(e) => Err(e),
+    }
+}
+
+// called after CASE was consumed
+fn parse_case(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    let operand = match tokenizer.peek()? {
+        Some(Token::Keyword(Keyword::When)) => None,
+        _ => Some(Box::new(parse_expression(tokenizer)?)),
+    };
+    let mut conditions = vec![];
+    while let Some(Token::Keyword(Keyword::When)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let condition = parse_expression(tokenizer)?;
+        match tokenizer.next_token()? {
+            Some(Token::Keyword(Keyword::Then)) => (),
+            _ => return Err("Expected keyword \"THEN\" after WHEN condition".to_string()),
+        }
+        conditions.push((condition, parse_expression(tokenizer)?));
+    }
+    if conditions.is_empty() {
+        return Err("CASE expression needs at least one WHEN clause".to_string());
+    }
+    let else_result = if let Some(Token::Keyword(Keyword::Else)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Some(Box::new(parse_expression(tokenizer)?))
+    } else {
+        None
+    };
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::End)) => Ok(Expression::Case {
+            operand,
+            conditions,
+            else_result,
+        }),
+        _ => Err("Expected keyword \"END\" at the end of CASE expression".to_string()),
+    }
+}
+
+// called after CAST was consumed
+fn parse_cast(tokenizer: &mut tokenizer::Tokenizer) -> Result<Expression, String> {
+    if let Err(_err) = expect_punctuation(tokenizer, Punctuation::LeftParen) {
+        return Err("Expected left parenthesis after \"CAST\"".to_string());
+    }
+    let expression = parse_expression(tokenizer)?;
+    match tokenizer.next_token()? {
+        Some(Token::Keyword(Keyword::As)) => (),
+        _ => return Err("Expected keyword \"AS\" in CAST".to_string()),
+    }
+    let data_type = match tokenizer.next_token()? {
+        Some(Token::DataType(DataType::Integer32)) => ast::DataType::Int32,
+        Some(Token::DataType(DataType::Varchar256)) => ast::DataType::VarChar256,
+        _ => return Err("Expected data type in CAST".to_string()),
+    };
+    if let Err(_err) = expect_punctuation(tokenizer, Punctuation::RightParen) {
+        return Err("Expected right parenthesis after CAST".to_string());
+    }
+    Ok(Expression::Cast {
+        expression: Box::new(expression),
+        data_type,
+    })
+}
+
+// called after the opening parenthesis was consumed
+fn parse_function(
+    tokenizer: &mut tokenizer::Tokenizer,
+    name: String,
+) -> Result<Expression, String> {
+    let mut arguments = vec![];
+    let distinct = if let Some(Token::Keyword(Keyword::Distinct)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        true
+    } else {
+        false
+    };
+    match tokenizer.peek()? {
+        Some(Token::Wildcard) => {
+            tokenizer.next_token()?;
+            if let Err(_err) = expect_punctuation(tokenizer, Punctuation::RightParen) {
+                return Err(format!("Expected right parenthesis after {}(*", name));
+            }
+        }
+        Some(Token::Punctuation(Punctuation::RightParen)) => {
+            tokenizer.next_token()?;
+        }
+        _ => loop {
+            arguments.push(parse_expression(tokenizer)?);
+            match tokenizer.next_token() {
+                Ok(Some(Token::Punctuation(Punctuation::Comma))) => (),
+                Ok(Some(Token::Punctuation(Punctuation::RightParen))) => break,
+                _ => {
+                    return Err(format!(
+                        "Expected comma or closing parenthesis in arguments of {}",
+                        name
+                    ))
+                }
+            }
+        },
+    }
+    let over = if let Some(Token::Keyword(Keyword::Over)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Some(parse_window_specification(tokenizer)?)
+    } else {
+        None
+    };
+    Ok(Expression::Function {
+        name: name.to_lowercase(),
+        distinct,
+        arguments,
+        over,
+    })
+}
+
+fn parse_window_specification(
+    tokenizer: &mut tokenizer::Tokenizer,
+) -> Result<WindowSpecification, String> {
+    if let Err(_err) = expect_punctuation(tokenizer, Punctuation::LeftParen) {
+        return Err("Expected left parenthesis after \"OVER\"".to_string());
+    }
+    let mut partition_by = vec![];
+    if let Some(Token::Keyword(Keyword::Partition)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        if let Err(_err) = expect_keyword(tokenizer, Keyword::By) {
+            return Err("Expected keyword \"BY\" after PARTITION".to_string());
+        }
+        loop {
+            partition_by.push(parse_expression(tokenizer)?);
+            if let Some(Token::Punctuation(Punctuation::Comma)) = tokenizer.peek()? {
+                tokenizer.next_token()?;
+            } else {
+                break;
+            }
+        }
+    }
+    let order_by = if let Some(Token::Keyword(Keyword::Order)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        parse_order_by_list(tokenizer)?
+    } else {
+        vec![]
+    };
+    let frame = if let Some(Token::Keyword(Keyword::Rows)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        Some(parse_window_frame(tokenizer)?)
+    } else {
+        None
+    };
+    if let Err(_err) = expect_punctuation(tokenizer, Punctuation::RightParen) {
+        return Err("Expected right parenthesis after window specification".to_string());
+    }
+    Ok(WindowSpecification {
+        partition_by,
+        order_by,
+        frame,
+    })
+}
+
+// called after ORDER was consumed
+fn parse_order_by_list(
+    tokenizer: &mut tokenizer::Tokenizer,
+) -> Result<Vec<OrderByExpression>, String> {
+    if let Err(_err) = expect_keyword(tokenizer, Keyword::By) {
+        return Err("Expected keyword \"BY\" after ORDER".to_string());
+    }
+    let mut order_by = vec![];
+    loop {
+        let expression = parse_expression(tokenizer)?;
+        let ascending = match tokenizer.peek()? {
+            Some(Token::Keyword(Keyword::Asc)) => {
+                tokenizer.next_token()?;
+                true
+            }
+            Some(Token::Keyword(Keyword::Desc)) => {
+                tokenizer.next_token()?;
+                false
+            }
+            _ => true,
+        };
+        order_by.push(OrderByExpression {
+            expression,
+            ascending,
+        });
+        if let Some(Token::Punctuation(Punctuation::Comma)) = tokenizer.peek()? {
+            tokenizer.next_token()?;
+        } else {
+            break;
+        }
+    }
+    Ok(order_by)
+}
+
+// ROWS start | ROWS BETWEEN start AND end, called after ROWS was consumed
+fn parse_window_frame(tokenizer: &mut tokenizer::Tokenizer) -> Result<WindowFrame, String> {
+    if let Some(Token::Keyword(Keyword::Between)) = tokenizer.peek()? {
+        tokenizer.next_token()?;
+        let start = parse_frame_bound(tokenizer)?;
+        if let Some(Token::Keyword(Keyword::And)) = tokenizer.peek()? {
+            tokenizer.next_token()?;
+        } else {
+            return Err("Expected keyword \"AND\" between frame bounds".to_string());
+        }
+        let end = parse_frame_bound(tokenizer)?;
+        Ok(WindowFrame { start, end })
+    } else {
+        Ok(WindowFrame {
+            start: parse_frame_bound(tokenizer)?,
+            end: FrameBound::CurrentRow,
+        })
+    }
+}
+
+fn parse_frame_bound(tokenizer: &mut tokenizer::Tokenizer) -> Result<FrameBound, String> {
+    match tokenizer.next_token() {
+        Ok(Some(Token::Keyword(Keyword::Unbounded))) => match tokenizer.next_token() {
+            Ok(Some(Token::Keyword(Keyword::Preceding))) => Ok(FrameBound::UnboundedPreceding),
+            Ok(Some(Token::Keyword(Keyword::Following))) => Ok(FrameBound::UnboundedFollowing),
+            _ => Err("Expected PRECEDING or FOLLOWING after UNBOUNDED".to_string()),
+        },
+        Ok(Some(Token::Keyword(Keyword::Current))) => match tokenizer.next_token() {
+            Ok(Some(Token::Keyword(Keyword::Row))) => Ok(FrameBound::CurrentRow),
+            _ => Err("Expected keyword \"ROW\" after CURRENT".to_string()),
+        },
+        Ok(Some(Token::Value(Value::Integer(offset)))) if offset >= 0 => {
+            match tokenizer.next_token() {
+                Ok(Some(Token::Keyword(Keyword::Preceding))) => {
+                    Ok(FrameBound::Preceding(offset as u32))
+                }
+                Ok(Some(Token::Keyword(Keyword::Following))) => {
+                    Ok(FrameBound::Following(offset as u32))
+                }
+                _ => Err("Expected PRECEDING or FOLLOWING after frame offset".to_string()),
+            }
+        }
+        _ => Err("Expected frame bound".to_string()),
+    }
+}
+
+fn binary_operation(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+    Expression::BinaryOperation {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
     }
-    Ok(SqlStatement::Select { columns, table })
 }
 
 fn expect_keyword(
@@ -245,46 +1872,284 @@ fn expect_keyword(
 
 fn expect_punctuation(
     tokenizer: &mut tokenizer::Tokenizer,
-    _expected: token::Punctuation,
+    expected: token::Punctuation,
 ) -> Result<(), ()> {
-    if let Ok(Some(Token::Punctuation(_expected))) = tokenizer.next_token() {
-        Ok(())
-    } else {
-        Err(())
+    match tokenizer.next_token() {
+        Ok(Some(Token::Punctuation(punctuation))) if punctuation == expected => Ok(()),
+        _ => Err(()),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::net::ToSocketAddrs;
-
     use super::*;
 
+    fn single_select(select: Select) -> SqlStatement {
+        SqlStatement::Query(Query {
+            with: None,
+            body: QueryBody::Select(Box::new(select)),
+            locking: None,
+        })
+    }
+
+    fn column_item(name: &str) -> SelectItem {
+        SelectItem {
+            expression: Expression::Column(name.to_string()),
+            alias: None,
+        }
+    }
+
+    #[test]
+    fn when_create_command_is_inputed_return_correct_ast() {
+        let command =
+            "CREATE TABLE table_name (collumn1 INT, collumn2 VARCHAR, PRIMARY KEY (collumn1));"
+                .to_string();
+
+        let result = parse(command);
+
+        assert_eq!(
+            result,
+            Ok(SqlStatement::CreateTable {
+                table_name: "table_name".to_string(),
+                columns: vec![
+                    ast::ColumnDefinition {
+                        name: "collumn1".to_string(),
+                        data_type: ast::DataType::Int32,
+                        default: None,
+                        identity: None,
+                    },
+                    ast::ColumnDefinition {
+                        name: "collumn2".to_string(),
+                        data_type: ast::DataType::VarChar256,
+                        default: None,
+                        identity: None,
+                    }
+                ],
+                constraints: vec![TableConstraint {
+                    name: None,
+                    kind: TableConstraintKind::PrimaryKey(vec!["collumn1".to_string()]),
+                }],
+            })
+        );
+    }
+
     #[test]
-    fn when_create_command_is_inputed_return_correct_ast() {
-        let command =
-            "CREATE TABLE table_name (collumn1 INT, collumn2 VARCHAR, PRIMARY KEY (collumn1));"
-                .to_string();
+    fn when_create_command_has_constraints_return_them_as_table_constraints() {
+        let command = "CREATE TABLE users (id INT PRIMARY KEY, email VARCHAR UNIQUE, \
+            age INT DEFAULT 18 CONSTRAINT adult CHECK (age >= 18), CHECK (id > 0));"
+            .to_string();
 
         let result = parse(command);
 
         assert_eq!(
             result,
             Ok(SqlStatement::CreateTable {
-                table_name: "table_name".to_string(),
-                primary_key: "collumn1".to_string(),
+                table_name: "users".to_string(),
                 columns: vec![
                     ast::ColumnDefinition {
-                        name: "collumn1".to_string(),
+                        name: "id".to_string(),
                         data_type: ast::DataType::Int32,
+                        default: None,
+                        identity: None,
                     },
                     ast::ColumnDefinition {
-                        name: "collumn2".to_string(),
+                        name: "email".to_string(),
                         data_type: ast::DataType::VarChar256,
-                    }
-                ]
+                        default: None,
+                        identity: None,
+                    },
+                    ast::ColumnDefinition {
+                        name: "age".to_string(),
+                        data_type: ast::DataType::Int32,
+                        default: Some(Expression::Value(ast::Value::Int32(18))),
+                        identity: None,
+                    },
+                ],
+                constraints: vec![
+                    TableConstraint {
+                        name: None,
+                        kind: TableConstraintKind::PrimaryKey(vec!["id".to_string()]),
+                    },
+                    TableConstraint {
+                        name: None,
+                        kind: TableConstraintKind::Unique(vec!["email".to_string()]),
+                    },
+                    TableConstraint {
+                        name: Some("adult".to_string()),
+                        kind: TableConstraintKind::Check(binary_operation(
+                            Expression::Column("age".to_string()),
+                            BinaryOperator::GreaterOrEqual,
+                            Expression::Value(ast::Value::Int32(18)),
+                        )),
+                    },
+                    TableConstraint {
+                        name: None,
+                        kind: TableConstraintKind::Check(binary_operation(
+                            Expression::Column("id".to_string()),
+                            BinaryOperator::Greater,
+                            Expression::Value(ast::Value::Int32(0)),
+                        )),
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn when_update_command_is_inputed_return_correct_ast() {
+        let command = "UPDATE users SET age = age + 1, email = \"x\" WHERE id = 1;".to_string();
+
+        let result = parse(command);
+
+        assert_eq!(
+            result,
+            Ok(SqlStatement::Update {
+                table_name: "users".to_string(),
+                assignments: vec![
+                    Assignment {
+                        column: "age".to_string(),
+                        value: binary_operation(
+                            Expression::Column("age".to_string()),
+                            BinaryOperator::Plus,
+                            Expression::Value(ast::Value::Int32(1)),
+                        ),
+                    },
+                    Assignment {
+                        column: "email".to_string(),
+                        value: Expression::Value(ast::Value::VarChar256("x".to_string())),
+                    },
+                ],
+                selection: Some(binary_operation(
+                    Expression::Column("id".to_string()),
+                    BinaryOperator::Equal,
+                    Expression::Value(ast::Value::Int32(1)),
+                )),
+                returning: None,
+            })
+        );
+    }
+
+    #[test]
+    fn when_create_command_has_foreign_keys_return_them_with_actions() {
+        let command = "CREATE TABLE orders (id INT PRIMARY KEY, \
+            user_id INT REFERENCES users ON DELETE CASCADE, product INT, \
+            CONSTRAINT product_fk FOREIGN KEY (product) REFERENCES products (code) \
+            ON UPDATE SET NULL ON DELETE NO ACTION);"
+            .to_string();
+
+        let constraints = match parse(command) {
+            Ok(SqlStatement::CreateTable { constraints, .. }) => constraints,
+            other => panic!("Expected CREATE TABLE, got {:?}", other),
+        };
+
+        assert_eq!(
+            constraints[1..],
+            [
+                TableConstraint {
+                    name: None,
+                    kind: TableConstraintKind::ForeignKey(ForeignKey {
+                        columns: vec!["user_id".to_string()],
+                        referenced_table: "users".to_string(),
+                        referenced_columns: vec![],
+                        on_delete: ReferentialAction::Cascade,
+                        on_update: ReferentialAction::NoAction,
+                    }),
+                },
+                TableConstraint {
+                    name: Some("product_fk".to_string()),
+                    kind: TableConstraintKind::ForeignKey(ForeignKey {
+                        columns: vec!["product".to_string()],
+                        referenced_table: "products".to_string(),
+                        referenced_columns: vec!["code".to_string()],
+                        on_delete: ReferentialAction::NoAction,
+                        on_update: ReferentialAction::SetNull,
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn when_create_index_command_is_inputed_return_correct_ast() {
+        assert_eq!(
+            parse("CREATE UNIQUE INDEX grades_key ON grades (student, course);".to_string()),
+            Ok(SqlStatement::CreateIndex {
+                name: "grades_key".to_string(),
+                table_name: "grades".to_string(),
+                columns: vec!["student".to_string(), "course".to_string()],
+                unique: true,
+            })
+        );
+        assert!(parse("CREATE UNIQUE TABLE grades (id INT PRIMARY KEY);".to_string()).is_err());
+    }
+
+    #[test]
+    fn when_sequence_and_identity_columns_are_inputed_return_their_options() {
+        assert_eq!(
+            parse("CREATE SEQUENCE ids INCREMENT BY -1 MINVALUE -10 START 5 NO CYCLE;".to_string()),
+            Ok(SqlStatement::CreateSequence {
+                name: "ids".to_string(),
+                options: SequenceOptions {
+                    increment: Some(-1),
+                    min_value: Some(-10),
+                    max_value: None,
+                    start: Some(5),
+                    cycle: Some(false),
+                },
             })
         );
+        let result = parse(
+            "CREATE TABLE t (id SERIAL PRIMARY KEY, \
+                n INT GENERATED BY DEFAULT AS IDENTITY (START WITH 10 CYCLE));"
+                .to_string(),
+        );
+        let columns = match result {
+            Ok(SqlStatement::CreateTable { columns, .. }) => columns,
+            other => panic!("Expected CREATE TABLE, got {:?}", other),
+        };
+        assert_eq!(columns[0].data_type, ast::DataType::Int32);
+        assert_eq!(columns[0].identity, Some(ColumnIdentity::Serial));
+        assert_eq!(
+            columns[1].identity,
+            Some(ColumnIdentity::ByDefault(SequenceOptions {
+                start: Some(10),
+                cycle: Some(true),
+                ..SequenceOptions::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn when_delete_command_is_inputed_return_correct_ast() {
+        let command = "DELETE FROM users WHERE id = 1;".to_string();
+
+        let result = parse(command);
+
+        assert_eq!(
+            result,
+            Ok(SqlStatement::Delete {
+                table_name: "users".to_string(),
+                selection: Some(binary_operation(
+                    Expression::Column("id".to_string()),
+                    BinaryOperator::Equal,
+                    Expression::Value(ast::Value::Int32(1)),
+                )),
+                returning: None,
+            })
+        );
+    }
+
+    #[test]
+    fn when_expression_is_written_as_text_it_should_parse_back_to_same_expression() {
+        let text = "CASE WHEN lower(name) ILIKE \"a%\" ESCAPE \"!\" THEN CAST(-2 AS VARCHAR) \
+            ELSE coalesce(NULL, count(*), 3 - 1 * 2) END"
+            .to_string();
+        let expression = parse_expression_text(text).unwrap();
+
+        let reparsed = parse_expression_text(expression.to_string());
+
+        assert_eq!(reparsed, Ok(expression));
+        assert!(parse_expression_text("a = 1 b".to_string()).is_err());
     }
 
     #[test]
@@ -299,14 +2164,90 @@ mod tests {
             Ok(ast::SqlStatement::InsertInto {
                 table_name: "table_name".to_string(),
                 column_names: vec!["collumn1".to_string(), "collumn2".to_string()],
+                values: vec![vec![
+                    Expression::Value(ast::Value::Int32(12)),
+                    Expression::Value(ast::Value::VarChar256("value2".to_string())),
+                ]],
+                on_conflict: None,
+                returning: None,
+            })
+        );
+    }
+
+    #[test]
+    fn when_insert_has_many_rows_of_values_should_return_all_of_them() {
+        let result =
+            parse("INSERT INTO items (id, name) VALUES (1, \"a\"), (2, NULL);".to_string());
+
+        assert_eq!(
+            result,
+            Ok(ast::SqlStatement::InsertInto {
+                table_name: "items".to_string(),
+                column_names: vec!["id".to_string(), "name".to_string()],
                 values: vec![
-                    ast::Value::Int32(12),
-                    ast::Value::VarChar256("value2".to_string()),
-                ]
+                    vec![
+                        Expression::Value(ast::Value::Int32(1)),
+                        Expression::Value(ast::Value::VarChar256("a".to_string())),
+                    ],
+                    vec![
+                        Expression::Value(ast::Value::Int32(2)),
+                        Expression::Value(ast::Value::Null),
+                    ],
+                ],
+                on_conflict: None,
+                returning: None,
             })
         );
     }
 
+    #[test]
+    fn when_other_punctuation_is_found_than_expected_should_return_error() {
+        assert_eq!(
+            parse("INSERT INTO items (id) VALUES (1));".to_string()),
+            Err("Expected semicolon at the end of the command".to_string())
+        );
+        assert_eq!(
+            parse("INSERT INTO items (id) VALUES ,(1);".to_string()),
+            Err("Expected left parenthesis after \"Values keyword\"".to_string())
+        );
+        assert_eq!(
+            parse("INSERT INTO items (id) VALUES (1), (2),;".to_string()),
+            Err("Expected left parenthesis after \"Values keyword\"".to_string())
+        );
+        assert_eq!(
+            parse("INSERT INTO items (id) VALUES (1), (".to_string()),
+            Err("Unexpected end of input when tried to parse values".to_string())
+        );
+    }
+
+    #[test]
+    fn when_select_has_no_from_should_return_select_without_table() {
+        let result = parse("SELECT 1 AS one WHERE 2 > 1;".to_string());
+
+        assert_eq!(
+            result,
+            Ok(single_select(Select {
+                distinct: false,
+                columns: Columns::Specific(vec![SelectItem {
+                    expression: Expression::Value(ast::Value::Int32(1)),
+                    alias: Some("one".to_string()),
+                }]),
+                table: None,
+                alias: None,
+                joins: vec![],
+                selection: Some(binary_operation(
+                    Expression::Value(ast::Value::Int32(2)),
+                    BinaryOperator::Greater,
+                    Expression::Value(ast::Value::Int32(1)),
+                )),
+            }))
+        );
+        assert_eq!(
+            parse("SELECT *;".to_string()),
+            Err("SELECT * with no tables specified is not valid".to_string())
+        );
+    }
+
     #[test]
     fn when_select_command_is_inputed_return_correct_ast() {
         let command = "SELECT collumn1, collumn2 FROM table_name;".to_string();
@@ -315,12 +2256,14 @@ mod tests {
 
         assert_eq!(
             result,
-            Ok(ast::SqlStatement::Select {
-                columns: Columns::Specific(
-                    ["collumn1".to_string(), "collumn2".to_string()].to_vec()
-                ),
-                table: "table_name".to_string()
-            })
+            Ok(single_select(Select {
+                distinct: false,
+                columns: Columns::Specific(vec![column_item("collumn1"), column_item("collumn2")]),
+                table: Some("table_name".to_string()),
+                alias: None,
+                joins: vec![],
+                selection: None,
+            }))
         )
     }
 
@@ -332,10 +2275,559 @@ mod tests {
 
         assert_eq!(
             result,
-            Ok(ast::SqlStatement::Select {
+            Ok(single_select(Select {
+                distinct: false,
                 columns: Columns::All,
-                table: "table_name".to_string()
-            })
+                table: Some("table_name".to_string()),
+                alias: None,
+                joins: vec![],
+                selection: None,
+            }))
+        )
+    }
+
+    #[test]
+    fn when_select_command_with_join_and_where_is_inputed_return_correct_ast() {
+        let command =
+            "SELECT e.name FROM employees e JOIN teams t ON e.team_id = t.id WHERE t.size > 2 + 1;"
+                .to_string();
+
+        let result = parse(command);
+
+        assert_eq!(
+            result,
+            Ok(single_select(Select {
+                distinct: false,
+                columns: Columns::Specific(vec![column_item("e.name")]),
+                table: Some("employees".to_string()),
+                alias: Some("e".to_string()),
+                joins: vec![Join {
+                    table: "teams".to_string(),
+                    alias: Some("t".to_string()),
+                    on: binary_operation(
+                        Expression::Column("e.team_id".to_string()),
+                        BinaryOperator::Equal,
+                        Expression::Column("t.id".to_string()),
+                    ),
+                }],
+                selection: Some(binary_operation(
+                    Expression::Column("t.size".to_string()),
+                    BinaryOperator::Greater,
+                    binary_operation(
+                        Expression::Value(ast::Value::Int32(2)),
+                        BinaryOperator::Plus,
+                        Expression::Value(ast::Value::Int32(1)),
+                    ),
+                )),
+            }))
+        )
+    }
+
+    #[test]
+    fn when_recursive_with_command_is_inputed_return_correct_ast() {
+        let command =
+            "WITH RECURSIVE chart(id, name) AS (SELECT id, name FROM employees WHERE id = 1 \
+            UNION ALL SELECT e.id, e.name FROM employees e JOIN chart c ON e.manager_id = c.id) \
+            SELECT * FROM chart;"
+                .to_string();
+
+        let result = parse(command);
+
+        assert_eq!(
+            result,
+            Ok(SqlStatement::Query(Query {
+                with: Some(With {
+                    recursive: true,
+                    ctes: vec![Cte {
+                        name: "chart".to_string(),
+                        columns: vec!["id".to_string(), "name".to_string()],
+                        query: Box::new(Query {
+                            with: None,
+                            body: QueryBody::SetOperation {
+                                operator: SetOperator::Union,
+                                all: true,
+                                left: Box::new(QueryBody::Select(Box::new(Select {
+                                    distinct: false,
+                                    columns: Columns::Specific(vec![
+                                        column_item("id"),
+                                        column_item("name")
+                                    ]),
+                                    table: Some("employees".to_string()),
+                                    alias: None,
+                                    joins: vec![],
+                                    selection: Some(binary_operation(
+                                        Expression::Column("id".to_string()),
+                                        BinaryOperator::Equal,
+                                        Expression::Value(ast::Value::Int32(1)),
+                                    )),
+                                }))),
+                                right: Box::new(QueryBody::Select(Box::new(Select {
+                                    distinct: false,
+                                    columns: Columns::Specific(vec![
+                                        column_item("e.id"),
+                                        column_item("e.name")
+                                    ]),
+                                    table: Some("employees".to_string()),
+                                    alias: Some("e".to_string()),
+                                    joins: vec![Join {
+                                        table: "chart".to_string(),
+                                        alias: Some("c".to_string()),
+                                        on: binary_operation(
+                                            Expression::Column("e.manager_id".to_string()),
+                                            BinaryOperator::Equal,
+                                            Expression::Column("c.id".to_string()),
+                                        ),
+                                    }],
+                                    selection: None,
+                                }))),
+                            },
+                            locking: None,
+                        }),
+                    }],
+                }),
+                body: QueryBody::Select(Box::new(Select {
+                    distinct: false,
+                    columns: Columns::All,
+                    table: Some("chart".to_string()),
+                    alias: None,
+                    joins: vec![],
+                    selection: None,
+                })),
+                locking: None,
+            }))
+        )
+    }
+
+    #[test]
+    fn when_select_command_with_window_function_is_inputed_return_correct_ast() {
+        let command = "SELECT name, SUM(amount) OVER (PARTITION BY team ORDER BY day DESC \
+            ROWS BETWEEN 2 PRECEDING AND CURRENT ROW) AS running FROM sales;"
+            .to_string();
+
+        let result = parse(command);
+
+        assert_eq!(
+            result,
+            Ok(single_select(Select {
+                distinct: false,
+                columns: Columns::Specific(vec![
+                    column_item("name"),
+                    SelectItem {
+                        expression: Expression::Function {
+                            name: "sum".to_string(),
+                            distinct: false,
+                            arguments: vec![Expression::Column("amount".to_string())],
+                            over: Some(WindowSpecification {
+                                partition_by: vec![Expression::Column("team".to_string())],
+                                order_by: vec![OrderByExpression {
+                                    expression: Expression::Column("day".to_string()),
+                                    ascending: false,
+                                }],
+                                frame: Some(WindowFrame {
+                                    start: FrameBound::Preceding(2),
+                                    end: FrameBound::CurrentRow,
+                                }),
+                            }),
+                        },
+                        alias: Some("running".to_string()),
+                    }
+                ]),
+                table: Some("sales".to_string()),
+                alias: None,
+                joins: vec![],
+                selection: None,
+            }))
+        )
+    }
+
+    #[test]
+    fn when_set_operations_are_inputed_intersect_should_bind_tighter_than_union() {
+        let command =
+            "SELECT DISTINCT a FROM t1 UNION ALL SELECT a FROM t2 INTERSECT SELECT a FROM t3 \
+            EXCEPT SELECT COUNT(DISTINCT a) FROM t4;"
+                .to_string();
+
+        let result = parse(command);
+
+        let select = |distinct: bool, expression: Expression, table: &str| {
+            Box::new(QueryBody::Select(Box::new(Select {
+                distinct,
+                columns: Columns::Specific(vec![SelectItem {
+                    expression,
+                    alias: None,
+                }]),
+                table: Some(table.to_string()),
+                alias: None,
+                joins: vec![],
+                selection: None,
+            })))
+        };
+        let column_a = || Expression::Column("a".to_string());
+        assert_eq!(
+            result,
+            Ok(SqlStatement::Query(Query {
+                with: None,
+                body: QueryBody::SetOperation {
+                    operator: SetOperator::Except,
+                    all: false,
+                    left: Box::new(QueryBody::SetOperation {
+                        operator: SetOperator::Union,
+                        all: true,
+                        left: select(true, column_a(), "t1"),
+                        right: Box::new(QueryBody::SetOperation {
+                            operator: SetOperator::Intersect,
+                            all: false,
+                            left: select(false, column_a(), "t2"),
+                            right: select(false, column_a(), "t3"),
+                        }),
+                    }),
+                    right: select(
+                        false,
+                        Expression::Function {
+                            name: "count".to_string(),
+                            distinct: true,
+                            arguments: vec![column_a()],
+                            over: None,
+                        },
+                        "t4"
+                    ),
+                },
+                locking: None,
+            }))
+        )
+    }
+
+    #[test]
+    fn when_case_and_cast_are_inputed_should_return_case_and_cast_expressions() {
+        let command =
+            "SELECT CASE WHEN a > -1 THEN CAST(a AS VARCHAR) ELSE NULL END FROM t;".to_string();
+
+        let result = parse(command);
+
+        let case = Expression::Case {
+            operand: None,
+            conditions: vec![(
+                binary_operation(
+                    Expression::Column("a".to_string()),
+                    BinaryOperator::Greater,
+                    Expression::Value(ast::Value::Int32(-1)),
+                ),
+                Expression::Cast {
+                    expression: Box::new(Expression::Column("a".to_string())),
+                    data_type: ast::DataType::VarChar256,
+                },
+            )],
+            else_result: Some(Box::new(Expression::Value(ast::Value::Null))),
+        };
+        assert_eq!(
+            result,
+            Ok(single_select(Select {
+                distinct: false,
+                columns: Columns::Specific(vec![SelectItem {
+                    expression: case,
+                    alias: None,
+                }]),
+                table: Some("t".to_string()),
+                alias: None,
+                joins: vec![],
+                selection: None,
+            }))
+        )
+    }
+
+    #[test]
+    fn when_like_with_escape_and_regex_match_are_inputed_should_return_pattern_expressions() {
+        let command =
+            "SELECT a FROM t WHERE a ILIKE \"x!%%\" ESCAPE \"!\" OR a~\"^[0-9]+$\";".to_string();
+
+        let result = parse(command);
+
+        let like = Expression::Like {
+            expression: Box::new(Expression::Column("a".to_string())),
+            pattern: Box::new(Expression::Value(ast::Value::VarChar256(
+                "x!%%".to_string(),
+            ))),
+            escape: Some(Box::new(Expression::Value(ast::Value::VarChar256(
+                "!".to_string(),
+            )))),
+            case_insensitive: true,
+        };
+        let regex_match = binary_operation(
+            Expression::Column("a".to_string()),
+            BinaryOperator::RegexMatch,
+            Expression::Value(ast::Value::VarChar256("^[0-9]+$".to_string())),
+        );
+        assert_eq!(
+            result,
+            Ok(single_select(Select {
+                distinct: false,
+                columns: Columns::Specific(vec![column_item("a")]),
+                table: Some("t".to_string()),
+                alias: None,
+                joins: vec![],
+                selection: Some(binary_operation(like, BinaryOperator::Or, regex_match)),
+            }))
         )
     }
+
+    #[test]
+    fn when_create_view_is_inputed_should_return_view_whose_query_text_parses_back() {
+        let command = "CREATE MATERIALIZED VIEW totals (name, total) AS WITH big AS (SELECT * FROM orders WHERE amount > 10) SELECT DISTINCT u.name, SUM(b.amount) AS total FROM users AS u JOIN big AS b ON u.id = b.user_id WHERE u.name LIKE \"a%\" UNION ALL SELECT name, 0 FROM guests;".to_string();
+
+        let result = parse(command);
+
+        let query = match result {
+            Ok(SqlStatement::CreateView {
+                name,
+                columns,
+                query,
+                materialized: true,
+            }) if name == "totals" && columns == vec!["name", "total"] => query,
+            other => panic!("Expected materialized view, got {:?}", other),
+        };
+        assert_eq!(parse_query_text(query.to_string()), Ok(query));
+        assert_eq!(
+            parse("REFRESH MATERIALIZED VIEW totals;".to_string()),
+            Ok(SqlStatement::RefreshMaterializedView {
+                name: "totals".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn when_select_has_locking_clause_should_return_query_with_locking() {
+        let locking = |sql: &str| match parse(sql.to_string()) {
+            Ok(SqlStatement::Query(query)) => query.locking,
+            other => panic!("Expected query, got {:?}", other),
+        };
+
+        assert_eq!(locking("SELECT id FROM jobs;"), None);
+        assert_eq!(
+            locking("SELECT id FROM jobs WHERE done = 0 FOR UPDATE SKIP LOCKED;"),
+            Some(Locking {
+                strength: LockStrength::Update,
+                wait: LockWait::SkipLocked
+            })
+        );
+        assert_eq!(
+            locking("SELECT * FROM jobs FOR SHARE NOWAIT;"),
+            Some(Locking {
+                strength: LockStrength::Share,
+                wait: LockWait::NoWait
+            })
+        );
+        assert!(parse("SELECT * FROM jobs FOR DELETE;".to_string()).is_err());
+        assert!(parse("SELECT * FROM jobs FOR UPDATE SKIP;".to_string()).is_err());
+    }
+
+    #[test]
+    fn when_vacuum_is_inputed_should_return_vacuum_with_optional_table() {
+        assert_eq!(
+            parse("VACUUM;".to_string()),
+            Ok(SqlStatement::Vacuum { table_name: None })
+        );
+        assert_eq!(
+            parse("VACUUM sales.orders;".to_string()),
+            Ok(SqlStatement::Vacuum {
+                table_name: Some("sales.orders".to_string())
+            })
+        );
+        assert!(parse("VACUUM orders items;".to_string()).is_err());
+    }
+
+    #[test]
+    fn when_schema_commands_are_inputed_should_return_schema_statements() {
+        assert_eq!(
+            parse("CREATE SCHEMA sales;".to_string()),
+            Ok(SqlStatement::CreateSchema {
+                name: "sales".to_string()
+            })
+        );
+        assert_eq!(
+            parse("DROP SCHEMA IF EXISTS sales CASCADE;".to_string()),
+            Ok(SqlStatement::DropSchema {
+                name: "sales".to_string(),
+                if_exists: true,
+                cascade: true,
+            })
+        );
+        assert_eq!(
+            parse("SET search_path TO sales, \"public\";".to_string()),
+            Ok(SqlStatement::Set {
+                name: "search_path".to_string(),
+                values: vec!["sales".to_string(), "public".to_string()],
+            })
+        );
+        assert_eq!(
+            parse("DELETE FROM sales.orders;".to_string()),
+            Ok(SqlStatement::Delete {
+                table_name: "sales.orders".to_string(),
+                selection: None,
+                returning: None,
+            })
+        );
+        assert_eq!(
+            parse("SELECT * FROM a.b.c;".to_string()),
+            Err("Improper qualified name (too many dotted names): a.b.c".to_string())
+        );
+    }
+
+    #[test]
+    fn when_database_commands_are_inputed_should_return_database_statements() {
+        assert_eq!(
+            parse("CREATE DATABASE shop;".to_string()),
+            Ok(SqlStatement::CreateDatabase {
+                name: "shop".to_string()
+            })
+        );
+        assert_eq!(
+            parse("DROP DATABASE IF EXISTS shop;".to_string()),
+            Ok(SqlStatement::DropDatabase {
+                name: "shop".to_string(),
+                if_exists: true,
+            })
+        );
+        assert_eq!(
+            parse("USE shop;".to_string()),
+            Ok(SqlStatement::Use {
+                name: "shop".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn when_returning_is_inputed_should_return_statement_with_returning_list() {
+        let result = parse("DELETE FROM users WHERE id = 1 RETURNING id, name AS n;".to_string());
+
+        assert_eq!(
+            result,
+            Ok(SqlStatement::Delete {
+                table_name: "users".to_string(),
+                selection: Some(binary_operation(
+                    Expression::Column("id".to_string()),
+                    BinaryOperator::Equal,
+                    Expression::Value(ast::Value::Int32(1)),
+                )),
+                returning: Some(Columns::Specific(vec![
+                    column_item("id"),
+                    SelectItem {
+                        expression: Expression::Column("name".to_string()),
+                        alias: Some("n".to_string()),
+                    },
+                ])),
+            })
+        );
+        assert!(matches!(
+            parse("UPDATE users SET age = 1 RETURNING *;".to_string()),
+            Ok(SqlStatement::Update {
+                returning: Some(Columns::All),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn when_upsert_is_inputed_should_return_on_conflict_and_merge_clauses() {
+        let result = parse(
+            "INSERT INTO stock (id) VALUES (1) ON CONFLICT (id) DO UPDATE SET n = excluded.n WHERE n < 5;"
+                .to_string(),
+        );
+
+        assert!(matches!(
+            result,
+            Ok(SqlStatement::InsertInto {
+                on_conflict: Some(OnConflict {
+                    action: ConflictAction::Update { .. },
+                    ..
+                }),
+                returning: None,
+                ..
+            })
+        ));
+        assert_eq!(
+            parse(
+                "MERGE INTO stock s USING delivery d ON s.id = d.id WHEN MATCHED THEN DELETE WHEN NOT MATCHED AND d.n > 0 THEN DO NOTHING;"
+                    .to_string()
+            ),
+            Ok(SqlStatement::Merge {
+                table_name: "stock".to_string(),
+                alias: Some("s".to_string()),
+                source: "delivery".to_string(),
+                source_alias: Some("d".to_string()),
+                condition: binary_operation(
+                    Expression::Column("s.id".to_string()),
+                    BinaryOperator::Equal,
+                    Expression::Column("d.id".to_string()),
+                ),
+                clauses: vec![
+                    MergeClause {
+                        matched: true,
+                        condition: None,
+                        action: MergeAction::Delete,
+                    },
+                    MergeClause {
+                        matched: false,
+                        condition: Some(binary_operation(
+                            Expression::Column("d.n".to_string()),
+                            BinaryOperator::Greater,
+                            Expression::Value(ast::Value::Int32(0)),
+                        )),
+                        action: MergeAction::Nothing,
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            parse("MERGE INTO stock USING delivery ON stock.id = delivery.id WHEN NOT MATCHED THEN DELETE;".to_string()),
+            Err("Expected INSERT or DO NOTHING in WHEN NOT MATCHED".to_string())
+        );
+    }
+
+    #[test]
+    fn when_transaction_control_is_inputed_should_return_transaction_statement() {
+        for (sql, statement) in [
+            ("BEGIN;", SqlStatement::Begin),
+            ("START TRANSACTION;", SqlStatement::Begin),
+            ("COMMIT WORK;", SqlStatement::Commit),
+            ("END;", SqlStatement::Commit),
+            ("ROLLBACK TRANSACTION;", SqlStatement::Rollback),
+            ("ABORT;", SqlStatement::Rollback),
+            (
+                "SAVEPOINT before_load;",
+                SqlStatement::Savepoint {
+                    name: "before_load".to_string(),
+                },
+            ),
+            (
+                "ROLLBACK WORK TO SAVEPOINT before_load;",
+                SqlStatement::RollbackToSavepoint {
+                    name: "before_load".to_string(),
+                },
+            ),
+            (
+                "PREPARE TRANSACTION \"load-7\";",
+                SqlStatement::PrepareTransaction {
+                    gid: "load-7".to_string(),
+                },
+            ),
+            (
+                "ROLLBACK PREPARED \"load-7\";",
+                SqlStatement::RollbackPrepared {
+                    gid: "load-7".to_string(),
+                },
+            ),
+            (
+                "RELEASE before_load;",
+                SqlStatement::ReleaseSavepoint {
+                    name: "before_load".to_string(),
+                },
+            ),
+        ] {
+            assert_eq!(parse(sql.to_string()), Ok(statement));
+        }
+        assert_eq!(
+            parse("START;".to_string()),
+            Err("Expected keyword \"TRANSACTION\" after START".to_string())
+        );
+    }
 }
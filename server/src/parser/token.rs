@@ -6,6 +6,7 @@ pub enum Token {
     Value(Value),
     Wildcard,
     Punctuation(Punctuation),
+    Operator(Operator),
 }
 
 #[derive(Debug, PartialEq)]
@@ -31,6 +32,121 @@ pub enum Keyword {
     Key,
     Select,
     From,
+    With,
+    Recursive,
+    As,
+    Union,
+    All,
+    Where,
+    Join,
+    On,
+    And,
+    Or,
+    Over,
+    Partition,
+    By,
+    Order,
+    Asc,
+    Desc,
+    Rows,
+    Between,
+    Unbounded,
+    Preceding,
+    Following,
+    Current,
+    Row,
+    Distinct,
+    Intersect,
+    Except,
+    Case,
+    When,
+    Then,
+    Else,
+    End,
+    Cast,
+    Null,
+    Like,
+    Ilike,
+    Escape,
+    Default,
+    Check,
+    Unique,
+    Constraint,
+    Update,
+    Set,
+    Foreign,
+    References,
+    Restrict,
+    Cascade,
+    No,
+    Action,
+    Delete,
+    Index,
+    Sequence,
+    Serial,
+    Generated,
+    Always,
+    Identity,
+    Increment,
+    Minvalue,
+    Maxvalue,
+    Start,
+    Cycle,
+    View,
+    Materialized,
+    Refresh,
+    Schema,
+    Drop,
+    If,
+    Exists,
+    To,
+    Show,
+    Database,
+    Use,
+    Returning,
+    Conflict,
+    Do,
+    Nothing,
+    Merge,
+    Using,
+    Matched,
+    Not,
+    Begin,
+    Commit,
+    Rollback,
+    Transaction,
+    Work,
+    Abort,
+    Savepoint,
+    Release,
+    Prepare,
+    Prepared,
+    Isolation,
+    Level,
+    Read,
+    Committed,
+    Repeatable,
+    Serializable,
+    Vacuum,
+    For,
+    Share,
+    Nowait,
+    Skip,
+    Locked,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    LessOrEqual,
+    GreaterOrEqual,
+    Less,
+    Greater,
+    Plus,
+    Minus,
+    Divide,
+    RegexMatch,
 }
 
 #[derive(Debug, PartialEq)]
@@ -53,6 +169,107 @@ impl Keyword {
             Keyword::Key => "KEY",
             Keyword::Select => "SELECT",
             Keyword::From => "FROM",
+            Keyword::With => "WITH",
+            Keyword::Recursive => "RECURSIVE",
+            Keyword::As => "AS",
+            Keyword::Union => "UNION",
+            Keyword::All => "ALL",
+            Keyword::Where => "WHERE",
+            Keyword::Join => "JOIN",
+            Keyword::On => "ON",
+            Keyword::And => "AND",
+            Keyword::Or => "OR",
+            Keyword::Over => "OVER",
+            Keyword::Partition => "PARTITION",
+            Keyword::By => "BY",
+            Keyword::Order => "ORDER",
+            Keyword::Asc => "ASC",
+            Keyword::Desc => "DESC",
+            Keyword::Rows => "ROWS",
+            Keyword::Between => "BETWEEN",
+            Keyword::Unbounded => "UNBOUNDED",
+            Keyword::Preceding => "PRECEDING",
+            Keyword::Following => "FOLLOWING",
+            Keyword::Current => "CURRENT",
+            Keyword::Row => "ROW",
+            Keyword::Distinct => "DISTINCT",
+            Keyword::Intersect => "INTERSECT",
+            Keyword::Except => "EXCEPT",
+            Keyword::Case => "CASE",
+            Keyword::When => "WHEN",
+            Keyword::Then => "THEN",
+            Keyword::Else => "ELSE",
+            Keyword::End => "END",
+            Keyword::Cast => "CAST",
+            Keyword::Null => "NULL",
+            Keyword::Like => "LIKE",
+            Keyword::Ilike => "ILIKE",
+            Keyword::Escape => "ESCAPE",
+            Keyword::Default => "DEFAULT",
+            Keyword::Check => "CHECK",
+            Keyword::Unique => "UNIQUE",
+            Keyword::Constraint => "CONSTRAINT",
+            Keyword::Update => "UPDATE",
+            Keyword::Set => "SET",
+            Keyword::Foreign => "FOREIGN",
+            Keyword::References => "REFERENCES",
+            Keyword::Restrict => "RESTRICT",
+            Keyword::Cascade => "CASCADE",
+            Keyword::No => "NO",
+            Keyword::Action => "ACTION",
+            Keyword::Delete => "DELETE",
+            Keyword::Index => "INDEX",
+            Keyword::Sequence => "SEQUENCE",
+            Keyword::Serial => "SERIAL",
+            Keyword::Generated => "GENERATED",
+            Keyword::Always => "ALWAYS",
+            Keyword::Identity => "IDENTITY",
+            Keyword::Increment => "INCREMENT",
+            Keyword::Minvalue => "MINVALUE",
+            Keyword::Maxvalue => "MAXVALUE",
+            Keyword::Start => "START",
+            Keyword::Cycle => "CYCLE",
+            Keyword::View => "VIEW",
+            Keyword::Materialized => "MATERIALIZED",
+            Keyword::Refresh => "REFRESH",
+            Keyword::Schema => "SCHEMA",
+            Keyword::Drop => "DROP",
+            Keyword::If => "IF",
+            Keyword::Exists => "EXISTS",
+            Keyword::To => "TO",
+            Keyword::Show => "SHOW",
+            Keyword::Database => "DATABASE",
+            Keyword::Use => "USE",
+            Keyword::Returning => "RETURNING",
+            Keyword::Conflict => "CONFLICT",
+            Keyword::Do => "DO",
+            Keyword::Nothing => "NOTHING",
+            Keyword::Merge => "MERGE",
+            Keyword::Using => "USING",
+            Keyword::Matched => "MATCHED",
+            Keyword::Not => "NOT",
+            Keyword::Begin => "BEGIN",
+            Keyword::Commit => "COMMIT",
+            Keyword::Rollback => "ROLLBACK",
+            Keyword::Transaction => "TRANSACTION",
+            Keyword::Work => "WORK",
+            Keyword::Abort => "ABORT",
+            Keyword::Savepoint => "SAVEPOINT",
+            Keyword::Release => "RELEASE",
+            Keyword::Prepare => "PREPARE",
+            Keyword::Prepared => "PREPARED",
+            Keyword::Isolation => "ISOLATION",
+            Keyword::Level => "LEVEL",
+            Keyword::Read => "READ",
+            Keyword::Committed => "COMMITTED",
+            Keyword::Repeatable => "REPEATABLE",
+            Keyword::Serializable => "SERIALIZABLE",
+            Keyword::Vacuum => "VACUUM",
+            Keyword::For => "FOR",
+            Keyword::Share => "SHARE",
+            Keyword::Nowait => "NOWAIT",
+            Keyword::Skip => "SKIP",
+            Keyword::Locked => "LOCKED",
         }
     }
 }
@@ -76,3 +293,20 @@ impl Punctuation {
         }
     }
 }
+
+impl Operator {
+    pub fn value(&self) -> &'static str {
+        match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "<>",
+            Operator::LessOrEqual => "<=",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Less => "<",
+            Operator::Greater => ">",
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Divide => "/",
+            Operator::RegexMatch => "~",
+        }
+    }
+}
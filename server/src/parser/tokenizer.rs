@@ -1,5 +1,5 @@
 use crate::{
-    parser::token::{DataType, Keyword, Punctuation, Token, Value},
+    parser::token::{DataType, Keyword, Operator, Punctuation, Token, Value},
     string_helpers::StringHelpers,
 };
 
@@ -24,6 +24,7 @@ impl Tokenizer {
                 Token::DataType(data_type) => self.position += data_type.value().len(),
                 Token::Punctuation(_) => self.position += 1,
                 Token::Wildcard => self.position += 1,
+                Token::Operator(operator) => self.position += operator.value().len(),
             }
         }
         Ok(token_option)
@@ -53,7 +54,7 @@ impl Tokenizer {
                 }
             }
             _ if ch.is_numeric() => {
-                if let Some(numeric) = (&self.input[self.position..]).extract_integer() {
+                if let Some(numeric) = self.input[self.position..].extract_integer() {
                     if let Ok(parsed_int) = numeric.parse::<i32>() {
                         Ok(Some(Token::Value(Value::Integer(parsed_int))))
                     } else {
@@ -81,14 +82,27 @@ impl Tokenizer {
             ch if ch == Punctuation::Comma.value() => {
                 Ok(Some(Token::Punctuation(Punctuation::Comma)))
             }
-            ch if ch == '*' => Ok(Some(Token::Wildcard)),
+            '*' => Ok(Some(Token::Wildcard)),
+            '=' | '<' | '>' | '!' | '+' | '-' | '/' | '~' => self.peek_operator(),
             _ => {
                 let token_value = self.input[self.position..].take_until(&[
                     ' ',
+                    '\n',
+                    '\r',
+                    '\t',
                     Punctuation::Comma.value(),
                     Punctuation::LeftParen.value(),
                     Punctuation::RightParen.value(),
                     Punctuation::SemiColon.value(),
+                    '=',
+                    '<',
+                    '>',
+                    '!',
+                    '+',
+                    '-',
+                    '/',
+                    '*',
+                    '~',
                 ]);
                 match token_value.as_str() {
                     kw if kw == Keyword::Create.value() => {
@@ -110,6 +124,211 @@ impl Tokenizer {
                         Ok(Some(Token::Keyword(Keyword::Select)))
                     }
                     kw if kw == Keyword::From.value() => Ok(Some(Token::Keyword(Keyword::From))),
+                    kw if kw == Keyword::With.value() => Ok(Some(Token::Keyword(Keyword::With))),
+                    kw if kw == Keyword::Recursive.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Recursive)))
+                    }
+                    kw if kw == Keyword::As.value() => Ok(Some(Token::Keyword(Keyword::As))),
+                    kw if kw == Keyword::Union.value() => Ok(Some(Token::Keyword(Keyword::Union))),
+                    kw if kw == Keyword::All.value() => Ok(Some(Token::Keyword(Keyword::All))),
+                    kw if kw == Keyword::Where.value() => Ok(Some(Token::Keyword(Keyword::Where))),
+                    kw if kw == Keyword::Join.value() => Ok(Some(Token::Keyword(Keyword::Join))),
+                    kw if kw == Keyword::On.value() => Ok(Some(Token::Keyword(Keyword::On))),
+                    kw if kw == Keyword::And.value() => Ok(Some(Token::Keyword(Keyword::And))),
+                    kw if kw == Keyword::Or.value() => Ok(Some(Token::Keyword(Keyword::Or))),
+                    kw if kw == Keyword::Over.value() => Ok(Some(Token::Keyword(Keyword::Over))),
+                    kw if kw == Keyword::Partition.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Partition)))
+                    }
+                    kw if kw == Keyword::By.value() => Ok(Some(Token::Keyword(Keyword::By))),
+                    kw if kw == Keyword::Order.value() => Ok(Some(Token::Keyword(Keyword::Order))),
+                    kw if kw == Keyword::Asc.value() => Ok(Some(Token::Keyword(Keyword::Asc))),
+                    kw if kw == Keyword::Desc.value() => Ok(Some(Token::Keyword(Keyword::Desc))),
+                    kw if kw == Keyword::Rows.value() => Ok(Some(Token::Keyword(Keyword::Rows))),
+                    kw if kw == Keyword::Between.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Between)))
+                    }
+                    kw if kw == Keyword::Unbounded.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Unbounded)))
+                    }
+                    kw if kw == Keyword::Preceding.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Preceding)))
+                    }
+                    kw if kw == Keyword::Following.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Following)))
+                    }
+                    kw if kw == Keyword::Current.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Current)))
+                    }
+                    kw if kw == Keyword::Row.value() => Ok(Some(Token::Keyword(Keyword::Row))),
+                    kw if kw == Keyword::Distinct.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Distinct)))
+                    }
+                    kw if kw == Keyword::Intersect.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Intersect)))
+                    }
+                    kw if kw == Keyword::Except.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Except)))
+                    }
+                    kw if kw == Keyword::Case.value() => Ok(Some(Token::Keyword(Keyword::Case))),
+                    kw if kw == Keyword::When.value() => Ok(Some(Token::Keyword(Keyword::When))),
+                    kw if kw == Keyword::Then.value() => Ok(Some(Token::Keyword(Keyword::Then))),
+                    kw if kw == Keyword::Else.value() => Ok(Some(Token::Keyword(Keyword::Else))),
+                    kw if kw == Keyword::End.value() => Ok(Some(Token::Keyword(Keyword::End))),
+                    kw if kw == Keyword::Cast.value() => Ok(Some(Token::Keyword(Keyword::Cast))),
+                    kw if kw == Keyword::Null.value() => Ok(Some(Token::Keyword(Keyword::Null))),
+                    kw if kw == Keyword::Like.value() => Ok(Some(Token::Keyword(Keyword::Like))),
+                    kw if kw == Keyword::Ilike.value() => Ok(Some(Token::Keyword(Keyword::Ilike))),
+                    kw if kw == Keyword::Escape.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Escape)))
+                    }
+                    kw if kw == Keyword::Default.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Default)))
+                    }
+                    kw if kw == Keyword::Check.value() => Ok(Some(Token::Keyword(Keyword::Check))),
+                    kw if kw == Keyword::Unique.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Unique)))
+                    }
+                    kw if kw == Keyword::Constraint.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Constraint)))
+                    }
+                    kw if kw == Keyword::Update.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Update)))
+                    }
+                    kw if kw == Keyword::Set.value() => Ok(Some(Token::Keyword(Keyword::Set))),
+                    kw if kw == Keyword::Foreign.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Foreign)))
+                    }
+                    kw if kw == Keyword::References.value() => {
+                        Ok(Some(Token::Keyword(Keyword::References)))
+                    }
+                    kw if kw == Keyword::Restrict.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Restrict)))
+                    }
+                    kw if kw == Keyword::Cascade.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Cascade)))
+                    }
+                    kw if kw == Keyword::No.value() => Ok(Some(Token::Keyword(Keyword::No))),
+                    kw if kw == Keyword::Action.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Action)))
+                    }
+                    kw if kw == Keyword::Delete.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Delete)))
+                    }
+                    kw if kw == Keyword::Index.value() => Ok(Some(Token::Keyword(Keyword::Index))),
+                    kw if kw == Keyword::Sequence.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Sequence)))
+                    }
+                    kw if kw == Keyword::Serial.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Serial)))
+                    }
+                    kw if kw == Keyword::Generated.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Generated)))
+                    }
+                    kw if kw == Keyword::Always.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Always)))
+                    }
+                    kw if kw == Keyword::Identity.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Identity)))
+                    }
+                    kw if kw == Keyword::Increment.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Increment)))
+                    }
+                    kw if kw == Keyword::Minvalue.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Minvalue)))
+                    }
+                    kw if kw == Keyword::Maxvalue.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Maxvalue)))
+                    }
+                    kw if kw == Keyword::Start.value() => Ok(Some(Token::Keyword(Keyword::Start))),
+                    kw if kw == Keyword::Cycle.value() => Ok(Some(Token::Keyword(Keyword::Cycle))),
+                    kw if kw == Keyword::View.value() => Ok(Some(Token::Keyword(Keyword::View))),
+                    kw if kw == Keyword::Materialized.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Materialized)))
+                    }
+                    kw if kw == Keyword::Refresh.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Refresh)))
+                    }
+                    kw if kw == Keyword::Schema.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Schema)))
+                    }
+                    kw if kw == Keyword::Drop.value() => Ok(Some(Token::Keyword(Keyword::Drop))),
+                    kw if kw == Keyword::If.value() => Ok(Some(Token::Keyword(Keyword::If))),
+                    kw if kw == Keyword::Exists.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Exists)))
+                    }
+                    kw if kw == Keyword::To.value() => Ok(Some(Token::Keyword(Keyword::To))),
+                    kw if kw == Keyword::Show.value() => Ok(Some(Token::Keyword(Keyword::Show))),
+                    kw if kw == Keyword::Database.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Database)))
+                    }
+                    kw if kw == Keyword::Use.value() => Ok(Some(Token::Keyword(Keyword::Use))),
+                    kw if kw == Keyword::Returning.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Returning)))
+                    }
+                    kw if kw == Keyword::Conflict.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Conflict)))
+                    }
+                    kw if kw == Keyword::Do.value() => Ok(Some(Token::Keyword(Keyword::Do))),
+                    kw if kw == Keyword::Nothing.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Nothing)))
+                    }
+                    kw if kw == Keyword::Merge.value() => Ok(Some(Token::Keyword(Keyword::Merge))),
+                    kw if kw == Keyword::Using.value() => Ok(Some(Token::Keyword(Keyword::Using))),
+                    kw if kw == Keyword::Matched.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Matched)))
+                    }
+                    kw if kw == Keyword::Not.value() => Ok(Some(Token::Keyword(Keyword::Not))),
+                    kw if kw == Keyword::Begin.value() => Ok(Some(Token::Keyword(Keyword::Begin))),
+                    kw if kw == Keyword::Commit.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Commit)))
+                    }
+                    kw if kw == Keyword::Rollback.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Rollback)))
+                    }
+                    kw if kw == Keyword::Transaction.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Transaction)))
+                    }
+                    kw if kw == Keyword::Work.value() => Ok(Some(Token::Keyword(Keyword::Work))),
+                    kw if kw == Keyword::Abort.value() => Ok(Some(Token::Keyword(Keyword::Abort))),
+                    kw if kw == Keyword::Savepoint.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Savepoint)))
+                    }
+                    kw if kw == Keyword::Release.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Release)))
+                    }
+                    kw if kw == Keyword::Prepare.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Prepare)))
+                    }
+                    kw if kw == Keyword::Prepared.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Prepared)))
+                    }
+                    kw if kw == Keyword::Isolation.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Isolation)))
+                    }
+                    kw if kw == Keyword::Level.value() => Ok(Some(Token::Keyword(Keyword::Level))),
+                    kw if kw == Keyword::Read.value() => Ok(Some(Token::Keyword(Keyword::Read))),
+                    kw if kw == Keyword::Committed.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Committed)))
+                    }
+                    kw if kw == Keyword::Repeatable.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Repeatable)))
+                    }
+                    kw if kw == Keyword::Serializable.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Serializable)))
+                    }
+                    kw if kw == Keyword::Vacuum.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Vacuum)))
+                    }
+                    kw if kw == Keyword::For.value() => Ok(Some(Token::Keyword(Keyword::For))),
+                    kw if kw == Keyword::Share.value() => Ok(Some(Token::Keyword(Keyword::Share))),
+                    kw if kw == Keyword::Nowait.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Nowait)))
+                    }
+                    kw if kw == Keyword::Skip.value() => Ok(Some(Token::Keyword(Keyword::Skip))),
+                    kw if kw == Keyword::Locked.value() => {
+                        Ok(Some(Token::Keyword(Keyword::Locked)))
+                    }
                     dt if dt == DataType::Integer32.value() => {
                         Ok(Some(Token::DataType(DataType::Integer32)))
                     }
@@ -122,6 +341,34 @@ impl Tokenizer {
         }
     }
 
+    fn peek_operator(&self) -> Result<Option<Token>, String> {
+        let rest = &self.input[self.position..];
+        let operator = if rest.starts_with("<=") {
+            Operator::LessOrEqual
+        } else if rest.starts_with(">=") {
+            Operator::GreaterOrEqual
+        } else if rest.starts_with("<>") || rest.starts_with("!=") {
+            Operator::NotEqual
+        } else {
+            match self.current_char() {
+                '=' => Operator::Equal,
+                '<' => Operator::Less,
+                '>' => Operator::Greater,
+                '+' => Operator::Plus,
+                '-' => Operator::Minus,
+                '/' => Operator::Divide,
+                '~' => Operator::RegexMatch,
+                _ => {
+                    return Err(format!(
+                        "Unknown operator, starting at position {}",
+                        self.position
+                    ))
+                }
+            }
+        };
+        Ok(Some(Token::Operator(operator)))
+    }
+
     fn skip_whitespace(&mut self) {
         while self.position < self.input.len() && self.current_char().is_whitespace() {
             self.position += 1;
@@ -321,6 +568,35 @@ mod tests {
         assert_eq!(tokeinzer.position, "*".len());
     }
 
+    #[test]
+    fn when_next_token_value_is_two_char_operator_return_operator_token_type() {
+        let mut tokeinzer = Tokenizer::new("<= 5".to_string());
+
+        let next_token = tokeinzer.next_token();
+
+        assert_eq!(next_token, Ok(Some(Token::Operator(Operator::LessOrEqual))));
+        assert_eq!(tokeinzer.position, "<=".len());
+    }
+
+    #[test]
+    fn when_operator_follows_identifier_without_space_return_separate_tokens() {
+        let mut tokenizer = Tokenizer::new("e.manager_id=c.id".to_string());
+        let mut result = Vec::new();
+
+        while let Ok(Some(token)) = tokenizer.next_token() {
+            result.push(token)
+        }
+
+        assert_eq!(
+            result,
+            [
+                Token::Indentifer("e.manager_id".to_string()),
+                Token::Operator(Operator::Equal),
+                Token::Indentifer("c.id".to_string()),
+            ]
+        )
+    }
+
     #[test]
     fn when_create_command_is_inputed_return_correct_tokens() {
         let mut tokeinzer = Tokenizer::new(
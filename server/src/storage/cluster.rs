@@ -0,0 +1,324 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::constants::{DEFAULT_DATABASE, PG_CLASS_FILE};
+
+use super::Database;
+
+// open databases by name
+type Databases = HashMap<String, Arc<Mutex<Database>>>;
+
+/*
+    all databases of one server, every database has its own directory with catalog and table files,
+    connection works with one database at a time, so statements of different databases don't wait for each other
+*/
+pub struct Cluster {
+    directory: PathBuf,
+    databases: Mutex<Databases>,
+}
+
+impl Cluster {
+    // every directory inside of the data directory is a database, default database is created when it's missing
+    pub fn open(directory: &Path) -> Result<Self, String> {
+        fs::create_dir_all(directory)
+            .map_err(|e| format!("Error creating directory '{}': {}", directory.display(), e))?;
+        move_single_database(directory)?;
+        let mut databases = HashMap::new();
+        let entries = fs::read_dir(directory)
+            .map_err(|e| format!("Error reading directory '{}': {}", directory.display(), e))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Error reading directory '{}': {}", directory.display(), e))?
+                .path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                let database = Database::open(&path)?;
+                databases.insert(name.to_string(), Arc::new(Mutex::new(database)));
+            }
+        }
+        if !databases.contains_key(DEFAULT_DATABASE) {
+            let database = Database::open(&directory.join(DEFAULT_DATABASE))?;
+            databases.insert(DEFAULT_DATABASE.to_string(), Arc::new(Mutex::new(database)));
+        }
+        Ok(Cluster {
+            directory: directory.to_path_buf(),
+            databases: Mutex::new(databases),
+        })
+    }
+
+    pub fn database(&self, name: &str) -> Result<Arc<Mutex<Database>>, String> {
+        self.lock()?
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Database \"{}\" does not exist", name))
+    }
+
+    pub fn create_database(&self, name: &str) -> Result<(), String> {
+        validate_name(name)?;
+        let mut databases = self.lock()?;
+        if databases.contains_key(name) {
+            return Err(format!("Database \"{}\" already exists", name));
+        }
+        let database = Database::open(&self.directory.join(name))?;
+        databases.insert(name.to_string(), Arc::new(Mutex::new(database)));
+        Ok(())
+    }
+
+    /*
+        database can't be dropped while a connection uses it, including the connection which drops it,
+        connection holds the database from USE until it switches to another one
+    */
+    pub fn drop_database(
+        &self,
+        name: &str,
+        if_exists: bool,
+        current: &Arc<Mutex<Database>>,
+    ) -> Result<(), String> {
+        let mut databases = self.lock()?;
+        let database = match databases.get(name) {
+            Some(database) => database,
+            None if if_exists => return Ok(()),
+            None => return Err(format!("Database \"{}\" does not exist", name)),
+        };
+        if Arc::ptr_eq(database, current) {
+            return Err("Cannot drop the currently open database".to_string());
+        }
+        if Arc::strong_count(database) > 1 {
+            return Err(format!(
+                "Database \"{}\" is being accessed by other users",
+                name
+            ));
+        }
+        databases.remove(name);
+        let path = self.directory.join(name);
+        fs::remove_dir_all(&path)
+            .map_err(|e| format!("Error removing directory '{}': {}", path.display(), e))
+    }
+
+    // databases are vacuumed one at a time, each is held only while its tables are vacuumed
+    pub fn autovacuum(&self) -> Result<(), String> {
+        let databases: Vec<Arc<Mutex<Database>>> = self.lock()?.values().cloned().collect();
+        for database in databases {
+            database.lock().map_err(|e| e.to_string())?.autovacuum()?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Databases>, String> {
+        self.databases.lock().map_err(|e| e.to_string())
+    }
+}
+
+// name of database is name of its directory
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    {
+        return Err(format!("Invalid database name \"{}\"", name));
+    }
+    Ok(())
+}
+
+// files of the only database, which was saved directly in the data directory, are moved to the default database
+fn move_single_database(directory: &Path) -> Result<(), String> {
+    if !directory.join(PG_CLASS_FILE).exists() {
+        return Ok(());
+    }
+    let target = directory.join(DEFAULT_DATABASE);
+    fs::create_dir_all(&target)
+        .map_err(|e| format!("Error creating directory '{}': {}", target.display(), e))?;
+    let entries = fs::read_dir(directory)
+        .map_err(|e| format!("Error reading directory '{}': {}", directory.display(), e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Error reading directory '{}': {}", directory.display(), e))?
+            .path();
+        if let (true, Some(name)) = (path.is_file(), path.file_name()) {
+            fs::rename(&path, target.join(name))
+                .map_err(|e| format!("Error moving file '{}': {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::SqlState,
+        executor::QueryResult,
+        information_schema::SData,
+        test_support::{open_cluster, open_database, run, Connection},
+    };
+
+    use super::*;
+
+    #[test]
+    fn when_databases_have_tables_with_same_name_they_should_stay_independent() {
+        let (directory, cluster) = open_cluster();
+        let (mut first, mut second) = (Connection::new(&cluster), Connection::new(&cluster));
+        first.run(&cluster, "CREATE DATABASE shop;").unwrap();
+        first.run(&cluster, "USE shop;").unwrap();
+        for connection in [&mut first, &mut second] {
+            connection
+                .run(&cluster, "CREATE TABLE items (id INT PRIMARY KEY);")
+                .unwrap();
+        }
+        assert_eq!(
+            second.run(&cluster, "CREATE TABLE items (id INT PRIMARY KEY);"),
+            Err("Relation \"items\" already exists".to_string())
+        );
+        assert_eq!(
+            first.run(&cluster, "CREATE DATABASE shop;"),
+            Err("Database \"shop\" already exists".to_string())
+        );
+        drop(cluster);
+
+        let cluster = Cluster::open(directory.path()).unwrap();
+        let mut connection = Connection::new(&cluster);
+        connection.run(&cluster, "USE shop;").unwrap();
+        assert_eq!(
+            connection.run(&cluster, "INSERT INTO items (id) VALUES (1);"),
+            Ok(QueryResult::Command("INSERT 0 1".to_string()))
+        );
+        assert_eq!(
+            connection.run(&cluster, "USE nowhere;"),
+            Err("Database \"nowhere\" does not exist".to_string())
+        );
+    }
+
+    #[test]
+    fn when_database_is_used_by_connection_it_should_not_be_dropped() {
+        let (directory, cluster) = open_cluster();
+        let (mut first, mut second) = (Connection::new(&cluster), Connection::new(&cluster));
+        first.run(&cluster, "CREATE DATABASE shop;").unwrap();
+        second.run(&cluster, "USE shop;").unwrap();
+
+        assert_eq!(
+            second.run(&cluster, "DROP DATABASE shop;"),
+            Err("Cannot drop the currently open database".to_string())
+        );
+        assert_eq!(
+            first.run(&cluster, "DROP DATABASE shop;"),
+            Err("Database \"shop\" is being accessed by other users".to_string())
+        );
+        second.run(&cluster, "USE postgres;").unwrap();
+        first.run(&cluster, "DROP DATABASE shop;").unwrap();
+        first
+            .run(&cluster, "DROP DATABASE IF EXISTS shop;")
+            .unwrap();
+
+        assert!(!directory.path().join("shop").exists());
+        assert_eq!(
+            first.run(&cluster, "CREATE DATABASE \"bad name\";"),
+            Err("Expected database name".to_string())
+        );
+    }
+
+    #[test]
+    fn when_row_is_changed_by_open_transaction_other_writer_should_wait_for_its_end() {
+        let (_directory, cluster) = open_cluster();
+        let mut first = Connection::new(&cluster);
+        for sql in [
+            "CREATE TABLE counters (id INT PRIMARY KEY, n INT);",
+            "INSERT INTO counters (id, n) VALUES (1, 0);",
+            "BEGIN;",
+            "UPDATE counters SET n = n + 1 WHERE id = 1;",
+        ] {
+            first.run(&cluster, sql).unwrap();
+        }
+
+        std::thread::scope(|scope| {
+            let second = scope.spawn(|| {
+                Connection::new(&cluster)
+                    .run(&cluster, "UPDATE counters SET n = n + 10 WHERE id = 1;")
+            });
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            assert!(!second.is_finished());
+            first.run(&cluster, "COMMIT;").unwrap();
+            assert_eq!(
+                second.join().unwrap(),
+                Ok(QueryResult::Command("UPDATE 1".to_string()))
+            );
+        });
+        match first.run(&cluster, "SELECT n FROM counters;") {
+            Ok(QueryResult::Rows(relation)) => {
+                assert_eq!(relation.rows, vec![vec![SData::INT(11)]])
+            }
+            other => panic!("Expected rows, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn when_connections_deadlock_one_of_them_should_be_rolled_back() {
+        let (_directory, cluster) = open_cluster();
+        let (mut first, mut second) = (Connection::new(&cluster), Connection::new(&cluster));
+        for sql in [
+            "CREATE TABLE counters (id INT PRIMARY KEY, n INT);",
+            "INSERT INTO counters (id, n) VALUES (1, 0);",
+            "INSERT INTO counters (id, n) VALUES (2, 0);",
+            "BEGIN;",
+            "UPDATE counters SET n = n + 1 WHERE id = 1;",
+        ] {
+            first.run(&cluster, sql).unwrap();
+        }
+        second.run(&cluster, "BEGIN;").unwrap();
+        second
+            .run(&cluster, "UPDATE counters SET n = n + 10 WHERE id = 2;")
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            let waiting =
+                scope.spawn(|| first.run(&cluster, "UPDATE counters SET n = n + 1 WHERE id = 2;"));
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            let deadlocked = second.run(&cluster, "UPDATE counters SET n = n + 10 WHERE id = 1;");
+            assert!(deadlocked
+                .is_err_and(|e| SqlState::of(&e) == Some(SqlState::DeadlockDetected)
+                    && e.contains("Deadlock detected")));
+            assert_eq!(
+                waiting.join().unwrap(),
+                Ok(QueryResult::Command("UPDATE 1".to_string()))
+            );
+        });
+        first.run(&cluster, "COMMIT;").unwrap();
+        assert_eq!(
+            second.run(&cluster, "COMMIT;"),
+            Err("There is no transaction in progress".to_string())
+        );
+        match first.run(&cluster, "SELECT id, n FROM counters;") {
+            Ok(QueryResult::Rows(mut relation)) => {
+                relation.rows.sort();
+                assert_eq!(
+                    relation.rows,
+                    vec![
+                        vec![SData::INT(1), SData::INT(1)],
+                        vec![SData::INT(2), SData::INT(1)]
+                    ]
+                )
+            }
+            other => panic!("Expected rows, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn when_data_directory_has_single_database_it_should_become_default_database() {
+        let (directory, mut database) = open_database();
+        run(&mut database, "CREATE TABLE items (id INT PRIMARY KEY);").unwrap();
+        drop(database);
+
+        let cluster = Cluster::open(directory.path()).unwrap();
+        let mut connection = Connection::new(&cluster);
+        assert_eq!(
+            connection.run(&cluster, "INSERT INTO items (id) VALUES (1);"),
+            Ok(QueryResult::Command("INSERT 0 1".to_string()))
+        );
+        assert!(!directory.path().join(PG_CLASS_FILE).exists());
+    }
+}
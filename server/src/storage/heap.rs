@@ -0,0 +1,489 @@
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::{Read, Write},
+    path::Path,
+};
+
+use crate::{bptree::page::TuplePointer, constants::PAGE_SIZE_IN_BYTES, new_page::NewPage};
+
+use super::transaction::TransactionId;
+
+/*
+    heap page structure:
+        page_type: u8 = 0x00 [0]
+        number_of_items: u16 [1..3]
+        heap_top: u16 [3..5] - indicates start of free space
+        tuples: vec<u8> [HEAP_PAGE_HEADER_SIZE..heap_top]
+        free_space: vec<u8>
+        line_pointers: vec<(offset: u16, length: u16)> - one for every item, growing from the end of the page
+
+    tuple structure:
+        xmin: u32 [0..4] - transaction which inserted the tuple
+        xmax: u32 [4..8] - transaction which deleted it, 0 while the tuple is live
+        data: vec<u8> [TUPLE_HEADER_SIZE..length]
+
+    deleted tuple stays on the page as an old version of the row until vacuum removes it,
+    item with length 0 is removed, its index entries are removed with it, so its number is given to a new tuple,
+    space of removed tuples is reclaimed when the page is compacted
+*/
+const HEAP_PAGE_TYPE: u8 = 0x00;
+const HEAP_PAGE_HEADER_SIZE: u16 = 5;
+const LINE_POINTER_SIZE: u16 = 4;
+const TUPLE_HEADER_SIZE: usize = 8;
+
+// versions of the row are told apart by transactions which created and deleted them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TupleHeader {
+    pub xmin: TransactionId,
+    pub xmax: Option<TransactionId>,
+}
+
+impl TupleHeader {
+    fn read(tuple: &[u8]) -> TupleHeader {
+        let xmax = u32::from_le_bytes([tuple[4], tuple[5], tuple[6], tuple[7]]);
+        TupleHeader {
+            xmin: TransactionId(u32::from_le_bytes([tuple[0], tuple[1], tuple[2], tuple[3]])),
+            xmax: (xmax != 0).then_some(TransactionId(xmax)),
+        }
+    }
+}
+
+impl NewPage {
+    pub fn new_heap_page() -> NewPage {
+        let mut page = NewPage::new([0; PAGE_SIZE_IN_BYTES as usize]);
+        page.data[0] = HEAP_PAGE_TYPE;
+        page.set_heap_item_count(0);
+        page.set_heap_top(HEAP_PAGE_HEADER_SIZE);
+        page
+    }
+
+    fn heap_item_count(&self) -> u16 {
+        u16::from_le_bytes([self.data[1], self.data[2]])
+    }
+
+    fn set_heap_item_count(&mut self, count: u16) {
+        self.data[1..3].copy_from_slice(&count.to_le_bytes());
+    }
+
+    fn heap_top(&self) -> u16 {
+        u16::from_le_bytes([self.data[3], self.data[4]])
+    }
+
+    fn set_heap_top(&mut self, heap_top: u16) {
+        self.data[3..5].copy_from_slice(&heap_top.to_le_bytes());
+    }
+
+    fn line_pointer_position(item: u16) -> usize {
+        PAGE_SIZE_IN_BYTES as usize - (item as usize + 1) * LINE_POINTER_SIZE as usize
+    }
+
+    fn line_pointer(&self, item: u16) -> (u16, u16) {
+        let position = Self::line_pointer_position(item);
+        (
+            u16::from_le_bytes([self.data[position], self.data[position + 1]]),
+            u16::from_le_bytes([self.data[position + 2], self.data[position + 3]]),
+        )
+    }
+
+    fn set_line_pointer(&mut self, item: u16, offset: u16, length: u16) {
+        let position = Self::line_pointer_position(item);
+        self.data[position..position + 2].copy_from_slice(&offset.to_le_bytes());
+        self.data[position + 2..position + 4].copy_from_slice(&length.to_le_bytes());
+    }
+
+    fn heap_free_space(&self) -> usize {
+        let line_pointers_start = PAGE_SIZE_IN_BYTES as usize
+            - self.heap_item_count() as usize * LINE_POINTER_SIZE as usize;
+        line_pointers_start - self.heap_top() as usize
+    }
+
+    // returns number of the new item, None when the tuple doesn't fit, removed item is reused first
+    pub fn heap_insert(&mut self, tuple: &[u8]) -> Option<u16> {
+        let count = self.heap_item_count();
+        let removed = (0..count).find(|item| self.line_pointer(*item).1 == 0);
+        let line_pointer_size = if removed.is_some() {
+            0
+        } else {
+            LINE_POINTER_SIZE as usize
+        };
+        if tuple.is_empty() || tuple.len() + line_pointer_size > self.heap_free_space() {
+            return None;
+        }
+        let item = removed.unwrap_or(count);
+        if item == count {
+            self.set_heap_item_count(count + 1);
+        }
+        let offset = self.heap_top();
+        let end = offset as usize + tuple.len();
+        self.data[offset as usize..end].copy_from_slice(tuple);
+        self.set_heap_top(end as u16);
+        self.set_line_pointer(item, offset, tuple.len() as u16);
+        Some(item)
+    }
+
+    pub fn heap_tuple(&self, item: u16) -> Option<&[u8]> {
+        if item >= self.heap_item_count() {
+            return None;
+        }
+        match self.line_pointer(item) {
+            (_, 0) => None,
+            (offset, length) => Some(&self.data[offset as usize..(offset + length) as usize]),
+        }
+    }
+
+    // space of the removed tuple is reused after the page is compacted
+    pub fn heap_remove(&mut self, item: u16) -> Option<()> {
+        self.heap_tuple(item)?;
+        let (offset, _) = self.line_pointer(item);
+        self.set_line_pointer(item, offset, 0);
+        Some(())
+    }
+
+    pub fn heap_set_xmax(&mut self, item: u16, xmax: Option<TransactionId>) -> Option<()> {
+        self.heap_tuple(item)?;
+        let (offset, _) = self.line_pointer(item);
+        let position = offset as usize + 4;
+        let xmax = xmax.map_or(0, |xmax| xmax.0);
+        self.data[position..position + 4].copy_from_slice(&xmax.to_le_bytes());
+        Some(())
+    }
+
+    /*
+        tuples are moved to the start of the page without gaps of removed tuples, items keep their numbers,
+        removed items at the end are dropped, returns number of reclaimed bytes
+    */
+    pub fn heap_compact(&mut self) -> usize {
+        let free_space = self.heap_free_space();
+        let mut count = self.heap_item_count();
+        while count > 0 && self.line_pointer(count - 1).1 == 0 {
+            count -= 1;
+        }
+        let tuples: Vec<(u16, Vec<u8>)> = (0..count)
+            .filter_map(|item| self.heap_tuple(item).map(|tuple| (item, tuple.to_vec())))
+            .collect();
+        let mut top = HEAP_PAGE_HEADER_SIZE;
+        for (item, tuple) in tuples {
+            let end = top as usize + tuple.len();
+            self.data[top as usize..end].copy_from_slice(&tuple);
+            self.set_line_pointer(item, top, tuple.len() as u16);
+            top = end as u16;
+        }
+        self.set_heap_item_count(count);
+        self.set_heap_top(top);
+        self.heap_free_space() - free_space
+    }
+}
+
+/*
+    table rows stored in heap pages, file is sequence of pages,
+    pages where vacuum reclaimed space are filled before the last page,
+    they aren't saved, they're found by their free space when the heap is loaded
+*/
+pub struct HeapFile {
+    pages: Vec<NewPage>,
+    free_pages: BTreeSet<u32>,
+}
+
+impl HeapFile {
+    pub fn new() -> Self {
+        HeapFile {
+            pages: vec![],
+            free_pages: BTreeSet::new(),
+        }
+    }
+
+    // missing file is an empty heap
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HeapFile::new()),
+            Err(e) => return Err(format!("Error opening file '{}': {}", path.display(), e)),
+        };
+        let mut content = vec![];
+        file.read_to_end(&mut content)
+            .map_err(|e| format!("Error reading file '{}': {}", path.display(), e))?;
+        if content.len() % PAGE_SIZE_IN_BYTES as usize != 0 {
+            return Err(format!(
+                "Heap file '{}' isn't made of whole pages",
+                path.display()
+            ));
+        }
+        let pages: Vec<NewPage> = content
+            .chunks(PAGE_SIZE_IN_BYTES as usize)
+            .map(|chunk| {
+                let mut data = [0; PAGE_SIZE_IN_BYTES as usize];
+                data.copy_from_slice(chunk);
+                NewPage::new(data)
+            })
+            .collect();
+        Ok(HeapFile {
+            free_pages: free_pages(&pages),
+            pages,
+        })
+    }
+
+    /*
+        pages are written to temporary file which replaces the old one, so a crash can't leave half written heap,
+        whole file is written even when one page changed, so commit costs O(table size),
+        writing only dirty pages in place needs a write-ahead log to survive torn writes
+    */
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let temporary_path = path.with_extension("tmp");
+        let mut file = File::create(&temporary_path)
+            .map_err(|e| format!("Error creating file '{}': {}", temporary_path.display(), e))?;
+        for page in &self.pages {
+            file.write_all(&page.data)
+                .map_err(|e| format!("Error writing file '{}': {}", temporary_path.display(), e))?;
+        }
+        file.sync_all()
+            .map_err(|e| format!("Error syncing file '{}': {}", temporary_path.display(), e))?;
+        fs::rename(&temporary_path, path)
+            .map_err(|e| format!("Error replacing file '{}': {}", path.display(), e))
+    }
+
+    // new tuples go to space reclaimed by vacuum, then they're appended to the last page
+    pub fn insert(&mut self, xmin: TransactionId, data: &[u8]) -> Result<TuplePointer, String> {
+        let mut tuple = Vec::with_capacity(TUPLE_HEADER_SIZE + data.len());
+        tuple.extend(xmin.0.to_le_bytes());
+        tuple.extend(0u32.to_le_bytes());
+        tuple.extend(data);
+        while let Some(page) = self.free_pages.first().copied() {
+            if let Some(item) = self.pages[page as usize].heap_insert(&tuple) {
+                return Ok(TuplePointer { page, offset: item });
+            }
+            self.free_pages.remove(&page);
+        }
+        if let Some(item) = self
+            .pages
+            .last_mut()
+            .and_then(|page| page.heap_insert(&tuple))
+        {
+            return Ok(TuplePointer {
+                page: self.pages.len() as u32 - 1,
+                offset: item,
+            });
+        }
+        let mut page = NewPage::new_heap_page();
+        let item = page
+            .heap_insert(&tuple)
+            .ok_or_else(|| format!("Row of {} bytes doesn't fit into a page", tuple.len()))?;
+        self.pages.push(page);
+        Ok(TuplePointer {
+            page: self.pages.len() as u32 - 1,
+            offset: item,
+        })
+    }
+
+    // every version which wasn't removed, visibility is decided by the caller
+    pub fn get(&self, pointer: TuplePointer) -> Option<(TupleHeader, &[u8])> {
+        self.pages
+            .get(pointer.page as usize)
+            .and_then(|page| page.heap_tuple(pointer.offset))
+            .map(split_tuple)
+    }
+
+    // deleting transaction is written to the tuple, None undoes the delete
+    pub fn set_xmax(
+        &mut self,
+        pointer: TuplePointer,
+        xmax: Option<TransactionId>,
+    ) -> Result<(), String> {
+        self.pages
+            .get_mut(pointer.page as usize)
+            .and_then(|page| page.heap_set_xmax(pointer.offset, xmax))
+            .ok_or_else(|| format!("No tuple at {:?}", pointer))
+    }
+
+    // tuple is removed physically, when it's not visible to anybody
+    pub fn remove(&mut self, pointer: TuplePointer) -> Result<(), String> {
+        self.pages
+            .get_mut(pointer.page as usize)
+            .and_then(|page| page.heap_remove(pointer.offset))
+            .ok_or_else(|| format!("No tuple at {:?}", pointer))
+    }
+
+    // space of removed tuples on the page is reclaimed for new tuples, returns number of reclaimed bytes
+    pub fn compact(&mut self, page: u32) -> Result<usize, String> {
+        let reclaimed = self
+            .pages
+            .get_mut(page as usize)
+            .ok_or_else(|| format!("No page {} in heap", page))?
+            .heap_compact();
+        if reclaimed > 0 {
+            self.free_pages.insert(page);
+        }
+        Ok(reclaimed)
+    }
+
+    pub fn scan(&self) -> impl Iterator<Item = (TuplePointer, TupleHeader, &[u8])> {
+        self.pages
+            .iter()
+            .enumerate()
+            .flat_map(|(page_number, page)| {
+                (0..page.heap_item_count()).filter_map(move |item| {
+                    page.heap_tuple(item).map(|tuple| {
+                        let (header, data) = split_tuple(tuple);
+                        (
+                            TuplePointer {
+                                page: page_number as u32,
+                                offset: item,
+                            },
+                            header,
+                            data,
+                        )
+                    })
+                })
+            })
+    }
+}
+
+// pages before the last one with space for at least a tuple without data
+fn free_pages(pages: &[NewPage]) -> BTreeSet<u32> {
+    let minimal_tuple = TUPLE_HEADER_SIZE + LINE_POINTER_SIZE as usize;
+    (0..pages.len().saturating_sub(1))
+        .filter(|page| pages[*page].heap_free_space() > minimal_tuple)
+        .map(|page| page as u32)
+        .collect()
+}
+
+fn split_tuple(tuple: &[u8]) -> (TupleHeader, &[u8]) {
+    (TupleHeader::read(tuple), &tuple[TUPLE_HEADER_SIZE..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XMIN: TransactionId = TransactionId(1);
+
+    #[test]
+    fn when_tuples_are_deleted_and_removed_scan_should_return_versions_which_were_not_removed() {
+        let mut heap = HeapFile::new();
+        let first = heap.insert(XMIN, b"first").unwrap();
+        let second = heap.insert(XMIN, b"second").unwrap();
+        let third = heap.insert(XMIN, b"third").unwrap();
+        let live = TupleHeader {
+            xmin: XMIN,
+            xmax: None,
+        };
+        let deleted = TupleHeader {
+            xmin: XMIN,
+            xmax: Some(TransactionId(2)),
+        };
+
+        heap.set_xmax(first, Some(TransactionId(2))).unwrap();
+        assert_eq!(heap.get(first), Some((deleted, &b"first"[..])));
+        heap.set_xmax(first, None).unwrap();
+        assert_eq!(heap.get(first), Some((live, &b"first"[..])));
+        heap.set_xmax(third, Some(TransactionId(2))).unwrap();
+        heap.remove(second).unwrap();
+
+        assert_eq!(heap.get(second), None);
+        assert!(heap.remove(second).is_err());
+        assert!(heap.set_xmax(second, None).is_err());
+        assert_eq!(
+            heap.scan().collect::<Vec<_>>(),
+            vec![
+                (first, live, &b"first"[..]),
+                (third, deleted, &b"third"[..])
+            ]
+        );
+    }
+
+    #[test]
+    fn when_page_is_full_tuple_should_go_to_new_page() {
+        let mut heap = HeapFile::new();
+        let tuple = [7u8; 1000];
+        let pointers: Vec<TuplePointer> =
+            (0..9).map(|_| heap.insert(XMIN, &tuple).unwrap()).collect();
+
+        assert_eq!(pointers[7].page, 0);
+        assert_eq!(pointers[8], TuplePointer { page: 1, offset: 0 });
+        assert!(heap
+            .insert(XMIN, &[0u8; PAGE_SIZE_IN_BYTES as usize])
+            .is_err());
+    }
+
+    #[test]
+    fn when_page_is_compacted_space_of_removed_tuples_should_be_reused() {
+        let mut heap = HeapFile::new();
+        let tuple = [7u8; 1000];
+        let pointers: Vec<TuplePointer> =
+            (0..9).map(|_| heap.insert(XMIN, &tuple).unwrap()).collect();
+        heap.remove(pointers[2]).unwrap();
+        heap.remove(pointers[7]).unwrap();
+
+        let reclaimed = 2 * (TUPLE_HEADER_SIZE + tuple.len()) + LINE_POINTER_SIZE as usize;
+        assert_eq!(heap.compact(0).unwrap(), reclaimed);
+        assert_eq!(
+            heap.get(pointers[3]).map(|(_, data)| data),
+            Some(&tuple[..])
+        );
+        assert_eq!(heap.insert(XMIN, b"small").unwrap(), pointers[2]);
+        assert_eq!(heap.insert(XMIN, &tuple).unwrap(), pointers[7]);
+        assert_eq!(
+            heap.insert(XMIN, &tuple).unwrap(),
+            TuplePointer { page: 0, offset: 8 }
+        );
+        // page filled again isn't free anymore
+        assert_eq!(
+            heap.insert(XMIN, &tuple).unwrap(),
+            TuplePointer { page: 1, offset: 1 }
+        );
+    }
+
+    #[test]
+    fn when_heap_is_saved_it_should_be_loaded_with_same_tuples() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("heap.ss");
+        let mut heap = HeapFile::new();
+        let pointer = heap.insert(XMIN, b"persisted").unwrap();
+        heap.set_xmax(pointer, Some(TransactionId(2))).unwrap();
+        heap.insert(XMIN, b"removed").unwrap();
+        heap.remove(TuplePointer { page: 0, offset: 1 }).unwrap();
+
+        heap.save(&path).unwrap();
+        let loaded = HeapFile::load(&path).unwrap();
+
+        assert_eq!(
+            loaded.scan().collect::<Vec<_>>(),
+            vec![(
+                pointer,
+                TupleHeader {
+                    xmin: XMIN,
+                    xmax: Some(TransactionId(2))
+                },
+                &b"persisted"[..]
+            )]
+        );
+        assert_eq!(
+            HeapFile::load(&directory.path().join("missing.ss"))
+                .unwrap()
+                .scan()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn when_heap_with_compacted_page_is_loaded_space_of_removed_tuples_should_be_reused() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("heap.ss");
+        let mut heap = HeapFile::new();
+        let tuple = [7u8; 1000];
+        let pointers: Vec<TuplePointer> =
+            (0..9).map(|_| heap.insert(XMIN, &tuple).unwrap()).collect();
+        heap.remove(pointers[2]).unwrap();
+        heap.compact(0).unwrap();
+        heap.save(&path).unwrap();
+
+        let mut loaded = HeapFile::load(&path).unwrap();
+
+        assert_eq!(loaded.insert(XMIN, &tuple).unwrap(), pointers[2]);
+        assert_eq!(
+            loaded.insert(XMIN, &tuple).unwrap(),
+            TuplePointer { page: 1, offset: 1 }
+        );
+    }
+}
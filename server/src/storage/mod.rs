@@ -0,0 +1,1174 @@
+pub mod cluster;
+pub mod heap;
+pub mod transaction;
+pub mod tuple;
+
+use std::{
+    collections::HashMap,
+    fs,
+    ops::Bound,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bptree::{
+        index::BPlusTree,
+        page::{TupleKey, TuplePointer},
+    },
+    catalog::{
+        load_relation, save_relation, split_name,
+        tables::{Kind, PgIndex},
+        Catalog, Oid,
+    },
+    constants::{
+        AUTOVACUUM_VACUUM_THRESHOLD, INDEX_ORDER, PG_PREPARED_XACTS, PG_PREPARED_XACTS_FILE,
+        PG_XACT_FILE, PUBLIC_NAMESPACE,
+    },
+    error::SqlState,
+    executor::{Relation, TableSource, View},
+    information_schema::{SData, SType},
+    lock_manager::{
+        lock::{AdvisoryKey, LockMode, LockTarget},
+        predicate::{PredicateLocks, PredicateTarget},
+        LockManager,
+    },
+    parser::{
+        ast::{IsolationLevel, LockWait},
+        parse_query_text,
+    },
+};
+use heap::{HeapFile, TupleHeader};
+use transaction::{
+    Change, PreparedTransaction, Snapshot, Transaction, TransactionId, TransactionManager,
+};
+use tuple::{deserialize_row, serialize_row};
+
+// key of index entry is values of indexed columns, pointer makes entries of equal keys unique
+pub type IndexEntry = (TupleKey, TuplePointer);
+
+const FIRST_POINTER: TuplePointer = TuplePointer { page: 0, offset: 0 };
+
+// rows written outside of any transaction are visible to everybody right away
+const FROZEN_TRANSACTION: TransactionId = TransactionId(0);
+
+// state of one client connection, it's moved into the database while its statement is executed
+#[derive(Debug)]
+pub struct Session {
+    // last value given by nextval in this session, returned by currval
+    pub sequence_values: HashMap<Oid, i32>,
+    // schemas searched for unqualified names of relations, SET search_path changes it
+    pub search_path: Vec<String>,
+    pub transaction: Option<Transaction>,
+    // undo log of the transaction, it's kept in the session, so transactions of sessions don't mix
+    changes: Vec<Change>,
+    // timeouts in milliseconds set by SET, 0 disables them
+    pub lock_timeout: u64,
+    pub statement_timeout: u64,
+    pub idle_in_transaction_session_timeout: u64,
+    // start of the current statement, it's kept while the statement waits for a lock and is retried
+    pub statement_started: Option<Instant>,
+    // advisory locks of the session with the number of times they were taken, they outlive transactions
+    pub advisory_locks: HashMap<AdvisoryKey, u32>,
+    // transaction which holds advisory locks of the session, it's the last transaction of the session
+    advisory_owner: Option<TransactionId>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            sequence_values: HashMap::new(),
+            search_path: vec![PUBLIC_NAMESPACE.to_string()],
+            transaction: None,
+            changes: vec![],
+            lock_timeout: 0,
+            statement_timeout: 0,
+            idle_in_transaction_session_timeout: 0,
+            statement_started: None,
+            advisory_locks: HashMap::new(),
+            advisory_owner: None,
+        }
+    }
+}
+
+impl Session {
+    // the current statement is canceled when it runs longer than statement_timeout
+    pub fn statement_deadline(&self) -> Option<Instant> {
+        match self.statement_started {
+            Some(started) if self.statement_timeout > 0 => {
+                Some(started + Duration::from_millis(self.statement_timeout))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn check_statement_timeout(&self) -> Result<(), String> {
+        match self.statement_deadline() {
+            Some(deadline) if deadline <= Instant::now() => {
+                Err(SqlState::QueryCanceled.error("Canceling statement due to statement timeout"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // wait for one lock is canceled after lock_timeout
+    pub fn lock_deadline(&self) -> Option<Instant> {
+        (self.lock_timeout > 0).then(|| Instant::now() + Duration::from_millis(self.lock_timeout))
+    }
+
+    // connection idle inside of transaction block is closed, so its transaction doesn't hold locks forever
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        match &self.transaction {
+            Some(transaction)
+                if transaction.explicit && self.idle_in_transaction_session_timeout > 0 =>
+            {
+                Some(Duration::from_millis(
+                    self.idle_in_transaction_session_timeout,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/*
+    database directory contains catalog files, commit log and one heap file for every table, named by its oid,
+    indexes are kept only in memory and are rebuilt from heaps when database is opened,
+    heaps keep all versions of rows, so readers see rows as they were when their snapshot was taken,
+    index has entries of all versions, they are filtered by visibility too,
+    vacuum removes versions which no snapshot can see anymore
+*/
+pub struct Database {
+    directory: PathBuf,
+    pub catalog: Catalog,
+    heaps: HashMap<Oid, HeapFile>,
+    indexes: HashMap<Oid, BPlusTree<IndexEntry>>,
+    pub transactions: TransactionManager,
+    prepared: Vec<PreparedTransaction>,
+    // shared with sessions waiting for locks, which don't hold the database meanwhile
+    pub locks: Arc<LockManager>,
+    // reads and read-write dependencies of serializable transactions
+    predicates: PredicateLocks,
+    // last value and is_called of sequences, catalog keeps value saved ahead of them
+    pub sequence_states: HashMap<Oid, (i32, bool)>,
+    // versions deleted by committed transactions since the table was vacuumed, autovacuum is driven by it
+    dead_tuples: HashMap<Oid, usize>,
+    pub session: Session,
+}
+
+impl Database {
+    pub fn open(directory: &Path) -> Result<Self, String> {
+        fs::create_dir_all(directory)
+            .map_err(|e| format!("Error creating directory '{}': {}", directory.display(), e))?;
+        let catalog = Catalog::load(directory)?;
+        let mut database = Database {
+            directory: directory.to_path_buf(),
+            catalog,
+            heaps: HashMap::new(),
+            indexes: HashMap::new(),
+            transactions: TransactionManager::new(load_relation(&directory.join(PG_XACT_FILE))?),
+            prepared: load_relation(&directory.join(PG_PREPARED_XACTS_FILE))?,
+            locks: Arc::new(LockManager::default()),
+            predicates: PredicateLocks::default(),
+            sequence_states: HashMap::new(),
+            dead_tuples: HashMap::new(),
+            session: Session::default(),
+        };
+        let tables: Vec<Oid> = database
+            .catalog
+            .classes
+            .iter()
+            .filter(|class| class.kind.has_heap())
+            .map(|class| class.oid)
+            .collect();
+        for table in tables {
+            let heap = HeapFile::load(&database.heap_path(table))?;
+            for (_, header, _) in heap.scan() {
+                database.transactions.reserve(header.xmin);
+                database
+                    .transactions
+                    .reserve(header.xmax.unwrap_or(header.xmin));
+            }
+            database.heaps.insert(table, heap);
+        }
+        for index in database.catalog.indexes.clone() {
+            database.build_index(&index)?;
+        }
+        // rows changed by prepared transactions stay locked until their second phase
+        for prepared in &database.prepared {
+            database.transactions.restore(prepared.id);
+            for change in &prepared.changes {
+                let (table, pointer) = change.row();
+                database.locks.acquire(
+                    prepared.id,
+                    LockTarget::Table(table),
+                    LockMode::IntentionExclusive,
+                )?;
+                database.locks.acquire(
+                    prepared.id,
+                    LockTarget::Row(table, pointer),
+                    LockMode::Exclusive,
+                )?;
+            }
+        }
+        Ok(database)
+    }
+
+    fn heap_path(&self, table: Oid) -> PathBuf {
+        self.directory.join(format!("{}.ss", table.0))
+    }
+
+    pub fn save_catalog(&self) -> Result<(), String> {
+        self.catalog.save(&self.directory)
+    }
+
+    pub fn save_table(&self, table: Oid) -> Result<(), String> {
+        self.heap(table)?.save(&self.heap_path(table))
+    }
+
+    fn save_commit_log(&self) -> Result<(), String> {
+        save_relation(
+            &self.directory.join(PG_XACT_FILE),
+            self.transactions.statuses(),
+        )
+    }
+
+    pub fn create_heap(&mut self, table: Oid) {
+        self.heaps.insert(table, HeapFile::new());
+    }
+
+    // heap file and index of relation removed from the catalog are deleted
+    pub fn drop_storage(&mut self, relation: Oid) -> Result<(), String> {
+        self.indexes.remove(&relation);
+        self.sequence_states.remove(&relation);
+        self.dead_tuples.remove(&relation);
+        if self.heaps.remove(&relation).is_none() {
+            return Ok(());
+        }
+        let path = self.heap_path(relation);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Error removing file '{}': {}", path.display(), e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn heap(&self, table: Oid) -> Result<&HeapFile, String> {
+        self.heaps
+            .get(&table)
+            .ok_or_else(|| format!("Table with oid {} has no heap", table.0))
+    }
+
+    pub fn column_types(&self, table: Oid) -> Vec<SType> {
+        self.catalog
+            .attributes_of(table)
+            .iter()
+            .map(|attribute| attribute.data_type)
+            .collect()
+    }
+
+    // snapshot of the statement, outside of a transaction all committed rows are visible
+    fn snapshot(&self) -> Snapshot {
+        match &self.session.transaction {
+            Some(Transaction {
+                snapshot: Some(snapshot),
+                ..
+            }) => snapshot.clone(),
+            transaction => self
+                .transactions
+                .snapshot(transaction.as_ref().map(|transaction| transaction.id)),
+        }
+    }
+
+    /*
+        it's called before every statement, statement of READ COMMITTED transaction sees rows committed
+        before it started, other isolation levels keep the snapshot of the first statement
+    */
+    pub fn take_snapshot(&mut self) -> Result<(), String> {
+        let Some(transaction) = &mut self.session.transaction else {
+            return Ok(());
+        };
+        if transaction.snapshot.is_some() && transaction.isolation != IsolationLevel::ReadCommitted
+        {
+            return Ok(());
+        }
+        transaction.snapshot = Some(self.transactions.snapshot(Some(transaction.id)));
+        if transaction.isolation == IsolationLevel::Serializable {
+            self.predicates.register(transaction.id)?;
+        }
+        Ok(())
+    }
+
+    // own transaction when it's serializable, its reads and writes are tracked
+    fn serializable(&self) -> Option<TransactionId> {
+        self.session
+            .transaction
+            .as_ref()
+            .filter(|transaction| transaction.isolation == IsolationLevel::Serializable)
+            .map(|transaction| transaction.id)
+    }
+
+    // serializable reader which doesn't see change of a concurrent transaction depends on it
+    fn read_version(
+        &self,
+        reader: Option<TransactionId>,
+        snapshot: &Snapshot,
+        header: &TupleHeader,
+    ) -> Result<(), String> {
+        match (
+            reader,
+            self.transactions.concurrent_writer(snapshot, header),
+        ) {
+            (Some(reader), Some(writer)) => self.predicates.conflict(reader, writer),
+            _ => Ok(()),
+        }
+    }
+
+    fn read_predicate(&self, target: PredicateTarget) -> Result<(), String> {
+        match self.serializable() {
+            Some(reader) => self.predicates.read(reader, target),
+            None => Ok(()),
+        }
+    }
+
+    // serializable readers of the page which didn't see the new or deleted version depend on the writer
+    fn write_version(&self, table: Oid, pointer: TuplePointer) -> Result<(), String> {
+        match self.serializable() {
+            Some(writer) => self.predicates.write(writer, table, pointer.page),
+            None => Ok(()),
+        }
+    }
+
+    // transaction can't go on after serialization failure, it's rolled back when its statement ends
+    fn serialization_failure(&mut self, message: &str) -> String {
+        if let Some(transaction) = &mut self.session.transaction {
+            transaction.failed = true;
+        }
+        SqlState::SerializationFailure.error(message)
+    }
+
+    fn own_transaction(&self) -> Option<TransactionId> {
+        self.session
+            .transaction
+            .as_ref()
+            .map(|transaction| transaction.id)
+    }
+
+    // versions of rows visible to the snapshot of the statement, the whole table is read
+    pub fn rows(&self, table: Oid) -> Result<Vec<(TuplePointer, Vec<SData>)>, String> {
+        let types = self.column_types(table);
+        let snapshot = self.snapshot();
+        let reader = self.serializable();
+        self.read_predicate(PredicateTarget::Relation(table))?;
+        let mut rows = vec![];
+        for (pointer, header, tuple) in self.heap(table)?.scan() {
+            self.session.check_statement_timeout()?;
+            self.read_version(reader, &snapshot, &header)?;
+            if self.transactions.is_visible(&snapshot, &header) {
+                rows.push((pointer, deserialize_row(tuple, &types)?));
+            }
+        }
+        Ok(rows)
+    }
+
+    // None when the version isn't visible to the snapshot of the statement, only its page is read
+    pub fn row(&self, table: Oid, pointer: TuplePointer) -> Result<Option<Vec<SData>>, String> {
+        let (header, tuple) = match self.heap(table)?.get(pointer) {
+            Some(version) => version,
+            None => return Ok(None),
+        };
+        let snapshot = self.snapshot();
+        self.read_predicate(PredicateTarget::Page(table, pointer.page))?;
+        self.read_version(self.serializable(), &snapshot, &header)?;
+        if !self.transactions.is_visible(&snapshot, &header) {
+            return Ok(None);
+        }
+        Ok(Some(deserialize_row(tuple, &self.column_types(table))?))
+    }
+
+    // any version which wasn't removed, regardless of its visibility
+    fn version(
+        &self,
+        table: Oid,
+        pointer: TuplePointer,
+    ) -> Result<Option<(TupleHeader, Vec<SData>)>, String> {
+        match self.heap(table)?.get(pointer) {
+            Some((header, tuple)) => Ok(Some((
+                header,
+                deserialize_row(tuple, &self.column_types(table))?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    // new row is locked, so other transactions can't change it before the transaction ends
+    pub fn insert_row(&mut self, table: Oid, row: Vec<SData>) -> Result<TuplePointer, String> {
+        self.lock(LockTarget::Table(table), LockMode::IntentionExclusive)?;
+        let tuple = serialize_row(&row);
+        let xmin = self.own_transaction().unwrap_or(FROZEN_TRANSACTION);
+        let pointer = self.heap_mut(table)?.insert(xmin, &tuple)?;
+        self.write_version(table, pointer)?;
+        self.lock(LockTarget::Row(table, pointer), LockMode::Exclusive)?;
+        self.index_row(table, &row, pointer, true);
+        self.session
+            .changes
+            .push(Change::Inserted { table, pointer });
+        Ok(pointer)
+    }
+
+    /*
+        deleted version stays in the heap and in indexes for snapshots which still see it,
+        it's marked with the deleting transaction, returns deleted row
+    */
+    pub fn delete_row(&mut self, table: Oid, pointer: TuplePointer) -> Result<Vec<SData>, String> {
+        self.lock_row(table, pointer, LockMode::Exclusive)?;
+        /*
+            version seen by the snapshot was deleted by transaction which committed after it was taken,
+            xmax of aborted transaction or of one cut by a crash doesn't delete the version
+        */
+        let own = self.own_transaction();
+        let row = match self.version(table, pointer)? {
+            Some((header, _))
+                if header.xmax.is_some_and(|xmax| {
+                    Some(xmax) != own && !self.transactions.is_aborted(xmax)
+                }) =>
+            {
+                return Err(self
+                    .serialization_failure("Could not serialize access due to concurrent update"))
+            }
+            Some((_, row)) => row,
+            None => return Err(format!("No tuple at {:?}", pointer)),
+        };
+        self.write_version(table, pointer)?;
+        match self.own_transaction() {
+            Some(xmax) => {
+                self.heap_mut(table)?.set_xmax(pointer, Some(xmax))?;
+                self.session
+                    .changes
+                    .push(Change::Deleted { table, pointer });
+            }
+            None => {
+                self.index_row(table, &row, pointer, false);
+                self.heap_mut(table)?.remove(pointer)?;
+            }
+        }
+        Ok(row)
+    }
+
+    fn heap_mut(&mut self, table: Oid) -> Result<&mut HeapFile, String> {
+        self.heaps
+            .get_mut(&table)
+            .ok_or_else(|| format!("Table with oid {} has no heap", table.0))
+    }
+
+    // adds or removes entries of the row in all indexes of the table
+    fn index_row(&mut self, table: Oid, row: &[SData], pointer: TuplePointer, insert: bool) {
+        for index in self.catalog.indexes_of(table) {
+            let key = index_key(index, row);
+            if let Some(tree) = self.indexes.get_mut(&index.oid) {
+                if insert {
+                    tree.insert((key, pointer));
+                } else {
+                    tree.remove(&(key, pointer));
+                }
+            }
+        }
+    }
+
+    // transaction of the session, which lasts until commit or rollback, it takes over advisory locks of the session
+    pub fn begin(&mut self, explicit: bool) -> Result<(), String> {
+        let id = self.transactions.begin();
+        self.session.transaction = Some(Transaction::new(id, explicit));
+        match self.session.advisory_owner.replace(id) {
+            Some(previous) if !self.session.advisory_locks.is_empty() => {
+                self.locks.transfer(previous, id)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /*
+        lock is held by the transaction of the session until it ends,
+        when it isn't granted right away, the request is queued and the statement fails,
+        it's retried by its caller after the lock is granted
+    */
+    pub fn lock(&self, target: LockTarget, mode: LockMode) -> Result<(), String> {
+        let owner = match &self.session.transaction {
+            Some(transaction) => transaction.id,
+            None => return Ok(()),
+        };
+        if self.locks.acquire(owner, target, mode)? {
+            return Ok(());
+        }
+        Err(self.lock_error(target))
+    }
+
+    fn lock_error(&self, target: LockTarget) -> String {
+        let (table, object) = match target {
+            LockTarget::Table(table) => (table, "relation"),
+            LockTarget::Row(table, _) => (table, "row in relation"),
+            LockTarget::Advisory(key) => return format!("Could not obtain advisory lock {}", key),
+        };
+        let name = self
+            .catalog
+            .classes
+            .iter()
+            .find(|class| class.oid == table)
+            .map_or("", |class| class.name.as_str());
+        format!("Could not obtain lock on {} \"{}\"", object, name)
+    }
+
+    // row lock is preceded by intention lock on its table
+    pub fn lock_row(
+        &self,
+        table: Oid,
+        pointer: TuplePointer,
+        mode: LockMode,
+    ) -> Result<(), String> {
+        self.lock(LockTarget::Table(table), intention_mode(mode))?;
+        self.lock(LockTarget::Row(table, pointer), mode)
+    }
+
+    // returns false when the row or its table is locked by another transaction, nothing is queued
+    fn try_lock_row(
+        &self,
+        table: Oid,
+        pointer: TuplePointer,
+        mode: LockMode,
+    ) -> Result<bool, String> {
+        let owner = match &self.session.transaction {
+            Some(transaction) => transaction.id,
+            None => return Ok(true),
+        };
+        Ok(self
+            .locks
+            .try_acquire(owner, LockTarget::Table(table), intention_mode(mode))?
+            && self
+                .locks
+                .try_acquire(owner, LockTarget::Row(table, pointer), mode)?)
+    }
+
+    /*
+        row read by SELECT FOR UPDATE or FOR SHARE is locked as if it was going to be changed,
+        returns false when the row is locked by another transaction and it should be skipped,
+        version deleted by a transaction which committed after the snapshot can't be locked
+    */
+    pub fn lock_version(
+        &mut self,
+        table: Oid,
+        pointer: TuplePointer,
+        mode: LockMode,
+        wait: LockWait,
+    ) -> Result<bool, String> {
+        match wait {
+            LockWait::Wait => self.lock_row(table, pointer, mode)?,
+            _ if self.try_lock_row(table, pointer, mode)? => (),
+            LockWait::NoWait => return Err(self.lock_error(LockTarget::Row(table, pointer))),
+            LockWait::SkipLocked => return Ok(false),
+        }
+        let own = self.own_transaction();
+        match self.heap(table)?.get(pointer) {
+            Some((header, _))
+                if header.xmax.is_some_and(|xmax| {
+                    Some(xmax) != own && !self.transactions.is_aborted(xmax)
+                }) =>
+            {
+                Err(self
+                    .serialization_failure("Could not serialize access due to concurrent update"))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /*
+        advisory lock is taken by the transaction of the session, lock of the session is counted
+        and it's kept after the transaction ends, transaction lock is released with other its locks,
+        returns false when the lock is held by another session and the caller doesn't wait
+    */
+    pub fn advisory_lock(
+        &mut self,
+        key: AdvisoryKey,
+        session: bool,
+        wait: bool,
+    ) -> Result<bool, String> {
+        let target = LockTarget::Advisory(key);
+        let transaction = match &mut self.session.transaction {
+            Some(transaction) => transaction,
+            None => return Err("Advisory lock can be taken only in a transaction".to_string()),
+        };
+        if wait {
+            if !self
+                .locks
+                .acquire(transaction.id, target, LockMode::Exclusive)?
+            {
+                return Err(self.lock_error(target));
+            }
+        } else if !self
+            .locks
+            .try_acquire(transaction.id, target, LockMode::Exclusive)?
+        {
+            return Ok(false);
+        }
+        if session {
+            *self.session.advisory_locks.entry(key).or_default() += 1;
+        } else {
+            transaction.advisory_locks.insert(key);
+        }
+        Ok(true)
+    }
+
+    // lock of the session is released once it's unlocked as many times as it was taken
+    pub fn advisory_unlock(&mut self, key: AdvisoryKey) -> Result<bool, String> {
+        let count = match self.session.advisory_locks.get_mut(&key) {
+            Some(count) => count,
+            None => return Ok(false),
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.session.advisory_locks.remove(&key);
+            self.release_advisory_lock(key)?;
+        }
+        Ok(true)
+    }
+
+    pub fn advisory_unlock_all(&mut self) -> Result<(), String> {
+        for (key, _) in std::mem::take(&mut self.session.advisory_locks) {
+            self.release_advisory_lock(key)?;
+        }
+        Ok(())
+    }
+
+    // the same key can still be locked by the transaction itself, then it's released when the transaction ends
+    fn release_advisory_lock(&self, key: AdvisoryKey) -> Result<(), String> {
+        match (&self.session.transaction, self.session.advisory_owner) {
+            (Some(transaction), _) if transaction.advisory_locks.contains(&key) => Ok(()),
+            (_, Some(owner)) => self.locks.release(owner, LockTarget::Advisory(key)),
+            (_, None) => Ok(()),
+        }
+    }
+
+    // statement of the session failed because its lock request is queued
+    pub fn is_waiting(&self) -> Result<bool, String> {
+        match &self.session.transaction {
+            Some(transaction) => self.locks.is_waiting(transaction.id),
+            None => Ok(false),
+        }
+    }
+
+    // number of changes made so far, changes after it can be undone with rollback_to
+    pub fn savepoint(&self) -> usize {
+        self.session.changes.len()
+    }
+
+    // rows written after the savepoint, they may have been deleted since
+    pub fn inserted_since(&self, savepoint: usize) -> Vec<(Oid, TuplePointer)> {
+        self.session.changes[savepoint..]
+            .iter()
+            .filter_map(|change| match change {
+                Change::Inserted { table, pointer } => Some((*table, *pointer)),
+                Change::Deleted { .. } => None,
+            })
+            .collect()
+    }
+
+    /*
+        row inserted by the transaction may have been deleted by another session meanwhile,
+        changes of tables which were dropped meanwhile are skipped
+    */
+    pub fn rollback_to(&mut self, savepoint: usize) -> Result<(), String> {
+        while self.session.changes.len() > savepoint {
+            match self.session.changes.pop() {
+                Some(change) => self.undo(change)?,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn undo(&mut self, change: Change) -> Result<(), String> {
+        match change {
+            change if !self.heaps.contains_key(&change.table()) => (),
+            Change::Inserted { table, pointer } => {
+                if let Some((_, row)) = self.version(table, pointer)? {
+                    self.index_row(table, &row, pointer, false);
+                    self.heap_mut(table)?.remove(pointer)?;
+                }
+            }
+            Change::Deleted { table, pointer } => {
+                self.heap_mut(table)?.set_xmax(pointer, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        saves heaps of changed tables and ends the transaction, after that changes can't be undone,
+        changes become visible when the commit log is saved, transaction which didn't change anything
+        doesn't have to be in the saved commit log, it's aborted after restart
+    */
+    pub fn commit(&mut self) -> Result<(), String> {
+        self.check_serializable()?;
+        let tables = self.changed_tables(&self.session.changes);
+        for table in &tables {
+            self.save_table(*table)?;
+        }
+        count_deleted(&mut self.dead_tuples, &self.session.changes);
+        self.end_transaction(true)?;
+        if tables.is_empty() {
+            return Ok(());
+        }
+        self.save_commit_log()
+    }
+
+    /*
+        undoes all changes of the transaction and ends it,
+        changed heaps are saved again, because other session could have saved them with these changes
+    */
+    pub fn rollback(&mut self) -> Result<(), String> {
+        let tables = self.changed_tables(&self.session.changes);
+        self.rollback_to(0)?;
+        for table in tables {
+            self.save_table(table)?;
+        }
+        self.end_transaction(false)
+    }
+
+    // serializable transaction which may be a part of a dependency cycle is rolled back instead of committed
+    fn check_serializable(&mut self) -> Result<(), String> {
+        let Some(id) = self.serializable() else {
+            return Ok(());
+        };
+        if let Err(e) = self.predicates.check_commit(id) {
+            self.rollback()?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn changed_tables(&self, changes: &[Change]) -> Vec<Oid> {
+        let mut tables: Vec<Oid> = changes
+            .iter()
+            .map(Change::table)
+            .filter(|table| self.heaps.contains_key(table))
+            .collect();
+        tables.sort();
+        tables.dedup();
+        tables
+    }
+
+    /*
+        first phase of two-phase commit, the transaction is detached from the session,
+        its undo log is saved before its changes, so the changes can always be undone after restart
+    */
+    pub fn prepare(&mut self, gid: &str) -> Result<(), String> {
+        if self.prepared.iter().any(|prepared| prepared.gid == gid) {
+            return Err(format!(
+                "Transaction identifier \"{}\" is already in use",
+                gid
+            ));
+        }
+        self.check_serializable()?;
+        // locks of the session are held by its transaction, they would stay with the prepared one
+        if !self.session.advisory_locks.is_empty() {
+            return Err(
+                "Cannot PREPARE a transaction while the session holds advisory locks".to_string(),
+            );
+        }
+        let transaction = match self.session.transaction.take() {
+            Some(transaction) => transaction,
+            None => return Err("There is no transaction in progress".to_string()),
+        };
+        let tables = self.changed_tables(&self.session.changes);
+        self.prepared.push(PreparedTransaction {
+            gid: gid.to_string(),
+            id: transaction.id,
+            changes: std::mem::take(&mut self.session.changes),
+        });
+        self.save_prepared()?;
+        for table in tables {
+            self.save_table(table)?;
+        }
+        Ok(())
+    }
+
+    // second phase, changes are already saved, rollback undoes them and saves them again
+    pub fn finish_prepared(&mut self, gid: &str, commit: bool) -> Result<(), String> {
+        let position = self
+            .prepared
+            .iter()
+            .position(|prepared| prepared.gid == gid)
+            .ok_or_else(|| {
+                format!(
+                    "Prepared transaction with identifier \"{}\" does not exist",
+                    gid
+                )
+            })?;
+        let prepared = self.prepared.remove(position);
+        if commit {
+            count_deleted(&mut self.dead_tuples, &prepared.changes);
+        } else {
+            let tables = self.changed_tables(&prepared.changes);
+            for change in prepared.changes.into_iter().rev() {
+                self.undo(change)?;
+            }
+            for table in tables {
+                self.save_table(table)?;
+            }
+        }
+        self.transactions.end(prepared.id, commit);
+        self.end_predicates(prepared.id, commit)?;
+        self.save_commit_log()?;
+        self.save_prepared()?;
+        self.locks.release_all(prepared.id)
+    }
+
+    fn save_prepared(&self) -> Result<(), String> {
+        save_relation(&self.directory.join(PG_PREPARED_XACTS_FILE), &self.prepared)
+    }
+
+    // rows of system view pg_prepared_xacts, which lists transactions waiting for the second phase
+    fn prepared_transactions(&self) -> Relation {
+        let database = self
+            .directory
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Relation {
+            columns: vec![
+                "transaction".to_string(),
+                "gid".to_string(),
+                "database".to_string(),
+            ],
+            rows: self
+                .prepared
+                .iter()
+                .map(|prepared| {
+                    vec![
+                        SData::INT(prepared.id.0 as i32),
+                        SData::STRING(prepared.gid.clone()),
+                        SData::STRING(database.clone()),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn end_transaction(&mut self, committed: bool) -> Result<(), String> {
+        self.session.changes.clear();
+        if let Some(transaction) = self.session.transaction.take() {
+            self.transactions.end(transaction.id, committed);
+            self.end_predicates(transaction.id, committed)?;
+            let kept: Vec<LockTarget> = self
+                .session
+                .advisory_locks
+                .keys()
+                .map(|key| LockTarget::Advisory(*key))
+                .collect();
+            self.locks.release_except(transaction.id, &kept)?;
+        }
+        Ok(())
+    }
+
+    fn end_predicates(&self, id: TransactionId, committed: bool) -> Result<(), String> {
+        self.predicates.end(
+            id,
+            committed,
+            self.transactions.next_id(),
+            self.transactions.oldest_active(),
+        )
+    }
+
+    /*
+        removes versions which no snapshot can see anymore together with their index entries,
+        pages they were on are compacted, so new rows use their space,
+        delete which was rolled back without being undone, because the database stopped, is cleared,
+        returns number of removed versions
+    */
+    pub fn vacuum(&mut self, table: Oid) -> Result<usize, String> {
+        let types = self.column_types(table);
+        let mut dead = vec![];
+        let mut aborted_deletes = vec![];
+        for (pointer, header, tuple) in self.heap(table)?.scan() {
+            if self.transactions.is_dead(&header) {
+                dead.push((pointer, deserialize_row(tuple, &types)?));
+            } else if header
+                .xmax
+                .is_some_and(|xmax| self.transactions.is_aborted(xmax))
+            {
+                aborted_deletes.push(pointer);
+            }
+        }
+        for pointer in &aborted_deletes {
+            self.heap_mut(table)?.set_xmax(*pointer, None)?;
+        }
+        let mut pages = vec![];
+        for (pointer, row) in &dead {
+            self.index_row(table, row, *pointer, false);
+            self.heap_mut(table)?.remove(*pointer)?;
+            pages.push(pointer.page);
+        }
+        pages.dedup();
+        for page in pages {
+            self.heap_mut(table)?.compact(page)?;
+        }
+        self.dead_tuples.remove(&table);
+        if !dead.is_empty() || !aborted_deletes.is_empty() {
+            self.save_table(table)?;
+        }
+        Ok(dead.len())
+    }
+
+    // tables with enough dead versions are vacuumed, returns number of removed versions
+    pub fn autovacuum(&mut self) -> Result<usize, String> {
+        let tables: Vec<Oid> = self
+            .dead_tuples
+            .iter()
+            .filter(|(_, dead)| **dead >= AUTOVACUUM_VACUUM_THRESHOLD)
+            .map(|(table, _)| *table)
+            .collect();
+        let mut removed = 0;
+        for table in tables {
+            if self.heaps.contains_key(&table) {
+                removed += self.vacuum(table)?;
+            }
+        }
+        Ok(removed)
+    }
+
+    // fills index from all versions of rows already in the table
+    pub fn build_index(&mut self, index: &PgIndex) -> Result<(), String> {
+        let mut tree = BPlusTree::new(INDEX_ORDER);
+        let types = self.column_types(index.table);
+        for (pointer, _, tuple) in self.heap(index.table)?.scan() {
+            let row = deserialize_row(tuple, &types)?;
+            tree.insert((index_key(index, &row), pointer));
+        }
+        self.indexes.insert(index.oid, tree);
+        Ok(())
+    }
+
+    fn index_tree(&self, index: Oid) -> Result<&BPlusTree<IndexEntry>, String> {
+        self.indexes
+            .get(&index)
+            .ok_or_else(|| format!("Index with oid {} isn't built", index.0))
+    }
+
+    /*
+        pointers to versions whose value of the first indexed column is inside of the range, in order of the index,
+        they may be invisible to the snapshot
+    */
+    pub fn index_range(
+        &self,
+        index: Oid,
+        range: (Bound<&SData>, Bound<&SData>),
+    ) -> Result<Vec<TuplePointer>, String> {
+        let start = match range.0 {
+            Bound::Included(value) | Bound::Excluded(value) => {
+                Bound::Included((TupleKey(vec![value.clone()]), FIRST_POINTER))
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let first = |key: &TupleKey| key.0[0].clone();
+        Ok(self
+            .index_tree(index)?
+            .scan_from(start.as_ref(), |(key, _)| match range.1 {
+                Bound::Included(end) => first(key) <= *end,
+                Bound::Excluded(end) => first(key) < *end,
+                Bound::Unbounded => true,
+            })
+            .into_iter()
+            .filter(|(key, _)| match range.0 {
+                Bound::Excluded(start) => first(key) != *start,
+                _ => true,
+            })
+            .map(|(_, pointer)| pointer)
+            .collect())
+    }
+
+    /*
+        pointers to rows visible to the snapshot whose key starts with given values, whole key finds rows equal to it,
+        pages of found rows are read, when nothing is found, insert anywhere could change it
+    */
+    pub fn index_lookup(&self, index: Oid, prefix: &[SData]) -> Result<Vec<TuplePointer>, String> {
+        let (table, versions) = self.index_versions(index, prefix)?;
+        let snapshot = self.snapshot();
+        let reader = self.serializable();
+        let mut pointers = vec![];
+        for (pointer, header) in versions {
+            self.read_version(reader, &snapshot, &header)?;
+            if self.transactions.is_visible(&snapshot, &header) {
+                self.read_predicate(PredicateTarget::Page(table, pointer.page))?;
+                pointers.push(pointer);
+            }
+        }
+        if pointers.is_empty() {
+            self.read_predicate(PredicateTarget::Relation(table))?;
+        }
+        Ok(pointers)
+    }
+
+    /*
+        pointers to versions with given key which are live or may still become live, regardless of the snapshot,
+        unique key can't be inserted while such version exists, or until its transaction rolls back
+    */
+    pub fn index_conflicts(&self, index: Oid, key: &[SData]) -> Result<Vec<TuplePointer>, String> {
+        let own = self.own_transaction();
+        Ok(self
+            .index_versions(index, key)?
+            .1
+            .into_iter()
+            .filter(|(_, header)| self.transactions.may_be_live(own, header))
+            .map(|(pointer, _)| pointer)
+            .collect())
+    }
+
+    // all versions with the key prefix which weren't removed, with the table of the index
+    fn index_versions(
+        &self,
+        index: Oid,
+        prefix: &[SData],
+    ) -> Result<(Oid, Vec<(TuplePointer, TupleHeader)>), String> {
+        let table = self
+            .catalog
+            .indexes
+            .iter()
+            .find(|pg_index| pg_index.oid == index)
+            .ok_or_else(|| format!("Index with oid {} is missing", index.0))?
+            .table;
+        let heap = self.heap(table)?;
+        let start = (TupleKey(prefix.to_vec()), FIRST_POINTER);
+        let versions = self
+            .index_tree(index)?
+            .scan_from(Bound::Included(&start), |(key, _)| key.starts_with(prefix))
+            .into_iter()
+            .filter_map(|(_, pointer)| heap.get(pointer).map(|(header, _)| (pointer, header)))
+            .collect();
+        Ok((table, versions))
+    }
+}
+
+// lock of the table which has to be held before a row of the table is locked
+fn intention_mode(mode: LockMode) -> LockMode {
+    match mode {
+        LockMode::Shared | LockMode::IntentionShared => LockMode::IntentionShared,
+        _ => LockMode::IntentionExclusive,
+    }
+}
+
+// versions deleted by committed changes wait for vacuum
+fn count_deleted(dead_tuples: &mut HashMap<Oid, usize>, changes: &[Change]) {
+    for change in changes {
+        if let Change::Deleted { table, .. } = change {
+            *dead_tuples.entry(*table).or_default() += 1;
+        }
+    }
+}
+
+pub fn index_key(index: &PgIndex, row: &[SData]) -> TupleKey {
+    TupleKey(
+        index
+            .indkey
+            .iter()
+            .map(|num| row[*num as usize - 1].clone())
+            .collect(),
+    )
+}
+
+// rows are read from the snapshot of the statement without locks, so readers never wait for writers
+impl TableSource for Database {
+    fn scan(&self, table_name: &str) -> Result<Relation, String> {
+        if let (None | Some("pg_catalog"), PG_PREPARED_XACTS) = split_name(table_name) {
+            return Ok(self.prepared_transactions());
+        }
+        let table = self
+            .catalog
+            .relation_with_heap(table_name, &self.session.search_path)?;
+        Ok(Relation {
+            columns: self
+                .catalog
+                .attributes_of(table.oid)
+                .iter()
+                .map(|attribute| attribute.name.clone())
+                .collect(),
+            rows: self
+                .rows(table.oid)?
+                .into_iter()
+                .map(|(_, row)| row)
+                .collect(),
+        })
+    }
+
+    fn index_range_scan(
+        &self,
+        table_name: &str,
+        column: &str,
+        range: (Bound<SData>, Bound<SData>),
+    ) -> Result<Option<Relation>, String> {
+        let table = match self
+            .catalog
+            .relation_with_heap(table_name, &self.session.search_path)
+        {
+            Ok(table) => table,
+            Err(_) => return Ok(None),
+        };
+        let attributes = self.catalog.attributes_of(table.oid);
+        let attribute = match attributes.iter().find(|attribute| attribute.name == column) {
+            Some(attribute) => attribute,
+            None => return Ok(None),
+        };
+        let index = match self
+            .catalog
+            .indexes_of(table.oid)
+            .into_iter()
+            .find(|index| index.indkey[0] == attribute.num)
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        // keys of different type than the column can't be compared with the index
+        let comparable = |bound: &Bound<SData>| match bound {
+            Bound::Included(key) | Bound::Excluded(key) => matches!(
+                (key, attribute.data_type),
+                (SData::INT(_), SType::INT) | (SData::STRING(_), SType::STRING)
+            ),
+            Bound::Unbounded => true,
+        };
+        if !comparable(&range.0) || !comparable(&range.1) {
+            return Ok(None);
+        }
+        // range is a predicate, rows inserted into it later would change the result
+        self.read_predicate(PredicateTarget::Relation(table.oid))?;
+        let mut rows = vec![];
+        for pointer in self.index_range(index.oid, (range.0.as_ref(), range.1.as_ref()))? {
+            if let Some(row) = self.row(table.oid, pointer)? {
+                rows.push(row);
+            }
+        }
+        Ok(Some(Relation {
+            columns: attributes
+                .iter()
+                .map(|attribute| attribute.name.clone())
+                .collect(),
+            rows,
+        }))
+    }
+
+    fn check_timeout(&self) -> Result<(), String> {
+        self.session.check_statement_timeout()
+    }
+
+    fn view(&self, name: &str) -> Result<Option<View>, String> {
+        match self.catalog.find_relation(name, &self.session.search_path) {
+            Some(class) if class.kind == Kind::View => {
+                let view = self.catalog.view(class.oid)?;
+                Ok(Some(View {
+                    columns: view.columns.clone(),
+                    query: parse_query_text(view.definition.clone())?,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    bptree::page::TuplePointer,
+    catalog::{read_i32, read_string, read_u8, tables::CatalogRecord, write_string, Oid},
+    lock_manager::lock::AdvisoryKey,
+    parser::ast::IsolationLevel,
+};
+
+use super::heap::TupleHeader;
+
+// transactions are numbered in order in which they begin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TransactionId(pub u32);
+
+/*
+    transaction of a session, it's explicit when started by BEGIN, otherwise it lasts only for one statement,
+    rows changed after a savepoint are stamped with the id of the transaction too,
+    rollback to the savepoint removes them using the undo log
+*/
+#[derive(Debug)]
+pub struct Transaction {
+    pub id: TransactionId,
+    pub explicit: bool,
+    pub savepoints: Vec<Savepoint>,
+    pub isolation: IsolationLevel,
+    // rows are read as they were when it was taken, it's taken by the first statement
+    pub snapshot: Option<Snapshot>,
+    // serialization failure, transaction has to be retried from the start, so it's rolled back as a whole
+    pub failed: bool,
+    // keys of advisory locks taken by pg_advisory_xact_lock, they are released when the transaction ends
+    pub advisory_locks: HashSet<AdvisoryKey>,
+}
+
+/*
+    transactions whose changes are visible, those which committed before the snapshot was taken,
+    changes of the own transaction are visible too
+*/
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    own: Option<TransactionId>,
+    // transactions from this one on began after the snapshot
+    xmax: TransactionId,
+    // transactions running when the snapshot was taken
+    active: HashSet<TransactionId>,
+}
+
+// undo record of row change made by a transaction
+#[derive(Debug)]
+pub(super) enum Change {
+    Inserted { table: Oid, pointer: TuplePointer },
+    Deleted { table: Oid, pointer: TuplePointer },
+}
+
+impl Change {
+    pub(super) fn table(&self) -> Oid {
+        self.row().0
+    }
+
+    pub(super) fn row(&self) -> (Oid, TuplePointer) {
+        match self {
+            Change::Inserted { table, pointer } | Change::Deleted { table, pointer } => {
+                (*table, *pointer)
+            }
+        }
+    }
+}
+
+/*
+    transaction after PREPARE TRANSACTION, it doesn't belong to any session anymore,
+    its changes are saved in heaps and its undo log is saved next to them,
+    so it can be committed or rolled back even after restart
+*/
+#[derive(Debug)]
+pub struct PreparedTransaction {
+    pub gid: String,
+    pub id: TransactionId,
+    pub(super) changes: Vec<Change>,
+}
+
+// changes made after position in the undo log are undone by rollback to the savepoint
+#[derive(Debug)]
+pub struct Savepoint {
+    pub name: String,
+    pub position: usize,
+}
+
+impl Transaction {
+    pub fn new(id: TransactionId, explicit: bool) -> Self {
+        Transaction {
+            id,
+            explicit,
+            savepoints: vec![],
+            isolation: IsolationLevel::ReadCommitted,
+            snapshot: None,
+            failed: false,
+            advisory_locks: HashSet::new(),
+        }
+    }
+}
+
+// state of transaction in the commit log
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionStatus {
+    InProgress,
+    Committed,
+    Aborted,
+}
+
+/*
+    gives identifiers to transactions and knows which of them are still running,
+    commit log keeps status of every transaction, status of id i is at position i,
+    id 0 isn't given to any transaction, its tuples are visible to everybody
+*/
+#[derive(Debug)]
+pub struct TransactionManager {
+    statuses: Vec<TransactionStatus>,
+    // running transactions with the oldest transaction which was running when they began
+    active: HashMap<TransactionId, TransactionId>,
+}
+
+impl TransactionManager {
+    // transactions which were in progress when the database stopped are aborted, unless they're restored
+    pub fn new(mut statuses: Vec<TransactionStatus>) -> Self {
+        for status in statuses.iter_mut() {
+            if *status == TransactionStatus::InProgress {
+                *status = TransactionStatus::Aborted;
+            }
+        }
+        if statuses.is_empty() {
+            statuses.push(TransactionStatus::Committed);
+        }
+        TransactionManager {
+            statuses,
+            active: HashMap::new(),
+        }
+    }
+
+    pub fn statuses(&self) -> &[TransactionStatus] {
+        &self.statuses
+    }
+
+    pub fn begin(&mut self) -> TransactionId {
+        let id = TransactionId(self.statuses.len() as u32);
+        self.statuses.push(TransactionStatus::InProgress);
+        self.active.insert(id, self.oldest_active());
+        id
+    }
+
+    // prepared transaction loaded from disk keeps its id and stays in progress
+    pub fn restore(&mut self, id: TransactionId) {
+        self.reserve(id);
+        self.statuses[id.0 as usize] = TransactionStatus::InProgress;
+        self.active.insert(id, id);
+    }
+
+    // ids found in heaps aren't given again, commit log could have been saved before them
+    pub fn reserve(&mut self, id: TransactionId) {
+        if self.statuses.len() <= id.0 as usize {
+            self.statuses
+                .resize(id.0 as usize + 1, TransactionStatus::Aborted);
+        }
+    }
+
+    pub fn end(&mut self, id: TransactionId, committed: bool) {
+        self.active.remove(&id);
+        self.statuses[id.0 as usize] = if committed {
+            TransactionStatus::Committed
+        } else {
+            TransactionStatus::Aborted
+        };
+    }
+
+    pub fn next_id(&self) -> TransactionId {
+        TransactionId(self.statuses.len() as u32)
+    }
+
+    pub fn oldest_active(&self) -> TransactionId {
+        self.active.keys().min().copied().unwrap_or(self.next_id())
+    }
+
+    /*
+        transactions before the horizon ended before any running transaction began,
+        so every snapshot sees them as committed or aborted
+    */
+    pub fn horizon(&self) -> TransactionId {
+        self.active
+            .values()
+            .min()
+            .copied()
+            .unwrap_or(self.next_id())
+    }
+
+    fn status(&self, id: TransactionId) -> TransactionStatus {
+        self.statuses
+            .get(id.0 as usize)
+            .copied()
+            .unwrap_or(TransactionStatus::Aborted)
+    }
+
+    pub fn snapshot(&self, own: Option<TransactionId>) -> Snapshot {
+        Snapshot {
+            own,
+            xmax: self.next_id(),
+            active: self.active.keys().copied().collect(),
+        }
+    }
+
+    // version is visible when the transaction which inserted it is visible and the one which deleted it isn't
+    pub fn is_visible(&self, snapshot: &Snapshot, header: &TupleHeader) -> bool {
+        self.is_committed_for(snapshot, header.xmin)
+            && !header
+                .xmax
+                .is_some_and(|xmax| self.is_committed_for(snapshot, xmax))
+    }
+
+    fn is_committed_for(&self, snapshot: &Snapshot, id: TransactionId) -> bool {
+        snapshot.own == Some(id)
+            || (id < snapshot.xmax
+                && !snapshot.active.contains(&id)
+                && self.status(id) == TransactionStatus::Committed)
+    }
+
+    /*
+        transaction which inserted or deleted the version, when the snapshot doesn't see the change,
+        because the transaction ran concurrently with the snapshot
+    */
+    pub fn concurrent_writer(
+        &self,
+        snapshot: &Snapshot,
+        header: &TupleHeader,
+    ) -> Option<TransactionId> {
+        let concurrent = |id: TransactionId| {
+            self.status(id) != TransactionStatus::Aborted && !self.is_committed_for(snapshot, id)
+        };
+        if concurrent(header.xmin) {
+            return Some(header.xmin);
+        }
+        header.xmax.filter(|xmax| concurrent(*xmax))
+    }
+
+    /*
+        version which is the latest version of its row or may still become it,
+        regardless of the snapshot, transactions which wrote it may be still in progress
+    */
+    pub fn may_be_live(&self, own: Option<TransactionId>, header: &TupleHeader) -> bool {
+        self.status(header.xmin) != TransactionStatus::Aborted
+            && !header.xmax.is_some_and(|xmax| {
+                own == Some(xmax) || self.status(xmax) == TransactionStatus::Committed
+            })
+    }
+
+    // version which no snapshot can see anymore, it was never committed or it was deleted before the horizon
+    pub fn is_dead(&self, header: &TupleHeader) -> bool {
+        self.status(header.xmin) == TransactionStatus::Aborted
+            || header.xmax.is_some_and(|xmax| {
+                xmax < self.horizon() && self.status(xmax) == TransactionStatus::Committed
+            })
+    }
+
+    // delete of the version was rolled back, but the version still has its xmax
+    pub fn is_aborted(&self, id: TransactionId) -> bool {
+        self.status(id) == TransactionStatus::Aborted
+    }
+}
+
+impl CatalogRecord for TransactionStatus {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.push(match self {
+            TransactionStatus::InProgress => 0,
+            TransactionStatus::Committed => 1,
+            TransactionStatus::Aborted => 2,
+        });
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        match read_u8(buffer)? {
+            0 => Ok(TransactionStatus::InProgress),
+            1 => Ok(TransactionStatus::Committed),
+            2 => Ok(TransactionStatus::Aborted),
+            status => Err(format!("Unknown transaction status {}", status)),
+        }
+    }
+}
+
+impl CatalogRecord for PreparedTransaction {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        write_string(buffer, &self.gid);
+        buffer.extend(self.id.0.to_le_bytes());
+        buffer.extend((self.changes.len() as i32).to_le_bytes());
+        for change in &self.changes {
+            let (table, pointer) = change.row();
+            buffer.push(match change {
+                Change::Inserted { .. } => 0,
+                Change::Deleted { .. } => 1,
+            });
+            buffer.extend(table.0.to_le_bytes());
+            buffer.extend(pointer.page.to_le_bytes());
+            buffer.extend(pointer.offset.to_le_bytes());
+        }
+    }
+
+    fn read(buffer: &mut &[u8]) -> Result<Self, String> {
+        let gid = read_string(buffer)?;
+        let id = TransactionId(read_i32(buffer)? as u32);
+        let mut changes = vec![];
+        for _ in 0..read_i32(buffer)? {
+            let kind = read_u8(buffer)?;
+            let table = Oid(read_i32(buffer)?);
+            let pointer = TuplePointer {
+                page: read_i32(buffer)? as u32,
+                offset: read_u16(buffer)?,
+            };
+            changes.push(match kind {
+                0 => Change::Inserted { table, pointer },
+                1 => Change::Deleted { table, pointer },
+                _ => return Err(format!("Unknown kind of change {}", kind)),
+            });
+        }
+        Ok(PreparedTransaction { gid, id, changes })
+    }
+}
+
+fn read_u16(buffer: &mut &[u8]) -> Result<u16, String> {
+    Ok(u16::from_le_bytes([read_u8(buffer)?, read_u8(buffer)?]))
+}
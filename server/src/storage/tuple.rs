@@ -0,0 +1,61 @@
+use crate::information_schema::{SData, SType};
+
+/*
+    tuple structure:
+        null_bitmap: [u8; number_of_columns.div_ceil(8)] - bit i is set when value of column i is NULL
+        values: vec<u8> - values of columns which aren't NULL, in column order, written by SData::serialize
+*/
+pub fn serialize_row(row: &[SData]) -> Vec<u8> {
+    let bitmap_size = row.len().div_ceil(8);
+    let values_size: u32 = row.iter().map(|value| value.serialized_size()).sum();
+    let mut tuple = Vec::with_capacity(bitmap_size + values_size as usize);
+    tuple.resize(bitmap_size, 0);
+    for (index, value) in row.iter().enumerate() {
+        if *value == SData::NULL {
+            tuple[index / 8] |= 1 << (index % 8);
+        }
+    }
+    for value in row {
+        value.serialize(&mut tuple);
+    }
+    tuple
+}
+
+pub fn deserialize_row(mut buffer: &[u8], types: &[SType]) -> Result<Vec<SData>, String> {
+    let bitmap_size = types.len().div_ceil(8);
+    if buffer.len() < bitmap_size {
+        return Err("Tuple is shorter than its null bitmap".to_string());
+    }
+    let (bitmap, values) = buffer.split_at(bitmap_size);
+    buffer = values;
+    let mut row = Vec::with_capacity(types.len());
+    for (index, data_type) in types.iter().enumerate() {
+        if bitmap[index / 8] & (1 << (index % 8)) != 0 {
+            row.push(SData::NULL);
+        } else {
+            row.push(SData::deserialize(&mut buffer, data_type)?);
+        }
+    }
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_row_with_nulls_is_serialized_it_should_be_deserialized_to_same_row() {
+        let types = [SType::INT, SType::STRING, SType::INT, SType::STRING];
+        let row = vec![
+            SData::INT(-7),
+            SData::NULL,
+            SData::NULL,
+            SData::STRING("value".to_string()),
+        ];
+
+        let tuple = serialize_row(&row);
+
+        assert_eq!(tuple.len(), 1 + 4 + 4 + 5);
+        assert_eq!(deserialize_row(&tuple, &types), Ok(row));
+    }
+}
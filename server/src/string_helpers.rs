@@ -18,7 +18,7 @@ impl StringHelpers for str {
 
     fn extract_integer(&self) -> Option<String> {
         print!("Given string is {self}");
-        for (i, c) in self.chars().enumerate() {
+        for (i, c) in self.char_indices() {
             if !c.is_numeric() {
                 if i == 0 {
                     return None;
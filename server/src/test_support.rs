@@ -0,0 +1,82 @@
+use std::sync::{Arc, Mutex};
+
+use tempfile::TempDir;
+
+use crate::{
+    constants::DEFAULT_DATABASE,
+    executor::{execute_in_cluster, execute_statement, QueryResult, Relation},
+    information_schema::SData,
+    parser::parse,
+    storage::{cluster::Cluster, Database, Session},
+};
+
+/*
+    fixtures of tests which execute SQL statements,
+    files are kept in a temporary directory which is removed when the returned TempDir is dropped
+*/
+pub fn open_database() -> (TempDir, Database) {
+    let directory = tempfile::tempdir().unwrap();
+    let database = Database::open(directory.path()).unwrap();
+    (directory, database)
+}
+
+pub fn open_cluster() -> (TempDir, Cluster) {
+    let directory = tempfile::tempdir().unwrap();
+    let cluster = Cluster::open(directory.path()).unwrap();
+    (directory, cluster)
+}
+
+// statement executed in a new session
+pub fn run(database: &mut Database, sql: &str) -> Result<QueryResult, String> {
+    run_in(database, &mut Session::default(), sql)
+}
+
+pub fn run_in(
+    database: &mut Database,
+    session: &mut Session,
+    sql: &str,
+) -> Result<QueryResult, String> {
+    execute_statement(&parse(sql.to_string())?, database, session)
+}
+
+// rows returned by a query executed in a new session, in the order they were returned
+pub fn select(database: &mut Database, sql: &str) -> Vec<Vec<SData>> {
+    select_in(database, &mut Session::default(), sql)
+}
+
+pub fn select_in(database: &mut Database, session: &mut Session, sql: &str) -> Vec<Vec<SData>> {
+    match run_in(database, session, sql) {
+        Ok(QueryResult::Rows(Relation { rows, .. })) => rows,
+        other => panic!("Expected rows, got {:?}", other),
+    }
+}
+
+// rows of a query without ORDER BY in a deterministic order
+pub fn sorted(mut rows: Vec<Vec<SData>>) -> Vec<Vec<SData>> {
+    rows.sort();
+    rows
+}
+
+// client connected to the default database of the cluster, USE switches its database
+pub struct Connection {
+    pub database: Arc<Mutex<Database>>,
+    pub session: Session,
+}
+
+impl Connection {
+    pub fn new(cluster: &Cluster) -> Self {
+        Connection {
+            database: cluster.database(DEFAULT_DATABASE).unwrap(),
+            session: Session::default(),
+        }
+    }
+
+    pub fn run(&mut self, cluster: &Cluster, sql: &str) -> Result<QueryResult, String> {
+        execute_in_cluster(
+            &parse(sql.to_string())?,
+            cluster,
+            &mut self.database,
+            &mut self.session,
+        )
+    }
+}